@@ -1,22 +1,44 @@
 use std::ops::Deref;
 
-use napi::{bindgen_prelude::External, JsFunction};
+use napi::{
+    bindgen_prelude::{Buffer, External},
+    JsFunction,
+};
 use next_api::{
-    route::{Endpoint, WrittenEndpoint},
+    route::{write_to_disk_uncached, Endpoint, EndpointConfig, WrittenEndpoint},
     server_paths::ServerPath,
 };
 use tracing::Instrument;
 use turbo_tasks::Vc;
-use turbopack_binding::turbopack::core::error::PrettyPrintError;
+use turbopack_binding::{
+    turbo::tasks_fs::FileContent,
+    turbopack::core::{
+        error::PrettyPrintError,
+        issue::{IssueSeverity, PlainIssue, PlainIssueSource},
+    },
+};
 
-use super::utils::{
-    get_diagnostics, get_issues, subscribe, NapiDiagnostic, NapiIssue, RootTask, TurbopackResult,
-    VcArc,
+use super::{
+    project::StackFrame,
+    utils::{
+        get_diagnostics, get_issues, subscribe, NapiDiagnostic, NapiIssue, RootTask,
+        TurbopackResult, VcArc,
+    },
 };
 
 #[napi(object)]
 #[derive(Default)]
-pub struct NapiEndpointConfig {}
+pub struct NapiEndpointConfig {
+    pub max_duration: Option<u32>,
+}
+
+impl From<&EndpointConfig> for NapiEndpointConfig {
+    fn from(config: &EndpointConfig) -> Self {
+        Self {
+            max_duration: config.max_duration,
+        }
+    }
+}
 
 #[napi(object)]
 #[derive(Default)]
@@ -49,21 +71,68 @@ impl From<&WrittenEndpoint> for NapiWrittenEndpoint {
             WrittenEndpoint::NodeJs {
                 server_entry_path,
                 server_paths,
+                config,
             } => Self {
                 r#type: "nodejs".to_string(),
                 entry_path: Some(server_entry_path.clone()),
                 server_paths: Some(server_paths.iter().map(From::from).collect()),
-                ..Default::default()
+                config: config.into(),
             },
-            WrittenEndpoint::Edge { server_paths } => Self {
+            WrittenEndpoint::Edge {
+                server_paths,
+                config,
+            } => Self {
                 r#type: "edge".to_string(),
                 server_paths: Some(server_paths.iter().map(From::from).collect()),
+                config: config.into(),
                 ..Default::default()
             },
         }
     }
 }
 
+/// Where an issue originates, as a [StackFrame] of its original source, so the
+/// error overlay renders its code frame the way it does for traced runtime
+/// frames. Issues are reported against the modules' sources rather than the
+/// emitted chunks, so the position doesn't need to be traced through a source
+/// map.
+fn issue_stack_frame(issue_source: &PlainIssueSource) -> StackFrame {
+    let ident = &**issue_source.asset.ident;
+    let (line, column) = issue_source
+        .range
+        .map(|(start, _)| (start.line, start.column))
+        .unwrap_or_default();
+    StackFrame {
+        file: ident
+            .strip_prefix("[project]/")
+            .unwrap_or(ident)
+            .to_string(),
+        line: line as u32 + 1,
+        column: Some(column as u32 + 1),
+        method_name: None,
+        is_server: false,
+    }
+}
+
+/// A compilation error of an endpoint, as displayed by the error overlay.
+#[napi(object)]
+pub struct NapiEndpointError {
+    pub issue: NapiIssue,
+    /// Where the error originates, with 1-indexed line and column. For errors
+    /// in modules shared between routes, this points into the shared module
+    /// rather than the route importing it.
+    pub source_frame: Option<StackFrame>,
+}
+
+impl From<&PlainIssue> for NapiEndpointError {
+    fn from(issue: &PlainIssue) -> Self {
+        Self {
+            issue: issue.into(),
+            source_frame: issue.source.as_deref().map(issue_stack_frame),
+        }
+    }
+}
+
 // NOTE(alexkirsz) We go through an extra layer of indirection here because of
 // two factors:
 // 1. rustc currently has a bug where using a dyn trait as a type argument to
@@ -83,12 +152,37 @@ impl Deref for ExternalEndpoint {
 #[napi]
 pub async fn endpoint_write_to_disk(
     #[napi(ts_arg_type = "{ __napiType: \"Endpoint\" }")] endpoint: External<ExternalEndpoint>,
+) -> napi::Result<TurbopackResult<NapiWrittenEndpoint>> {
+    write_endpoint_to_disk(endpoint, false).await
+}
+
+/// Like [endpoint_write_to_disk], but invalidates the endpoint's
+/// `write_to_disk` task first, so that it runs again even if nothing changed.
+///
+/// The invalidation is shallow: only that task re-runs. The tasks it reads,
+/// e.g. the chunking of the route and the writes of its files, are reused from
+/// the cache when their inputs are unchanged, so files that are up to date
+/// aren't rewritten, even if they were modified or deleted on disk.
+#[napi]
+pub async fn endpoint_write_to_disk_uncached(
+    #[napi(ts_arg_type = "{ __napiType: \"Endpoint\" }")] endpoint: External<ExternalEndpoint>,
+) -> napi::Result<TurbopackResult<NapiWrittenEndpoint>> {
+    write_endpoint_to_disk(endpoint, true).await
+}
+
+async fn write_endpoint_to_disk(
+    endpoint: External<ExternalEndpoint>,
+    uncached: bool,
 ) -> napi::Result<TurbopackResult<NapiWrittenEndpoint>> {
     let turbo_tasks = endpoint.turbo_tasks().clone();
     let endpoint = ***endpoint;
     let (written, issues, diags) = turbo_tasks
         .run_once(async move {
-            let write_to_disk = endpoint.write_to_disk();
+            let write_to_disk = if uncached {
+                write_to_disk_uncached(endpoint)
+            } else {
+                endpoint.write_to_disk()
+            };
             let written = write_to_disk.strongly_consistent().await?;
             let issues = get_issues(write_to_disk).await?;
             let diags = get_diagnostics(write_to_disk).await?;
@@ -104,6 +198,94 @@ pub async fn endpoint_write_to_disk(
     })
 }
 
+/// Returns the paths of the files [endpoint_write_to_disk] writes, relative to
+/// the node root, without writing them.
+#[napi]
+pub async fn endpoint_chunks(
+    #[napi(ts_arg_type = "{ __napiType: \"Endpoint\" }")] endpoint: External<ExternalEndpoint>,
+) -> napi::Result<TurbopackResult<Vec<String>>> {
+    let turbo_tasks = endpoint.turbo_tasks().clone();
+    let endpoint = ***endpoint;
+    let (chunks, issues, diags) = turbo_tasks
+        .run_once(async move {
+            let chunks = endpoint.chunks();
+            let chunks_value = chunks.strongly_consistent().await?;
+            let issues = get_issues(chunks).await?;
+            let diags = get_diagnostics(chunks).await?;
+            Ok((chunks_value, issues, diags))
+        })
+        .await
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+    Ok(TurbopackResult {
+        result: chunks.clone_value(),
+        issues: issues.iter().map(|i| NapiIssue::from(&**i)).collect(),
+        diagnostics: diags.iter().map(|d| NapiDiagnostic::from(d)).collect(),
+    })
+}
+
+#[napi(object)]
+pub struct NapiServerOutput {
+    /// The chunks of the content in order, which JS streams one by one.
+    pub chunks: Vec<Buffer>,
+}
+
+/// Returns the concatenated server JavaScript of the endpoint without writing
+/// it, with the entry chunks last, e.g. to pipe a single route to a deploy
+/// tool. It's returned in the chunks of its rope, so that it isn't copied into
+/// a single buffer.
+#[napi]
+pub async fn endpoint_server_output(
+    #[napi(ts_arg_type = "{ __napiType: \"Endpoint\" }")] endpoint: External<ExternalEndpoint>,
+) -> napi::Result<TurbopackResult<NapiServerOutput>> {
+    let turbo_tasks = endpoint.turbo_tasks().clone();
+    let endpoint = ***endpoint;
+    let (chunks, issues, diags) = turbo_tasks
+        .run_once(async move {
+            let server_output = endpoint.server_output();
+            let chunks: Vec<Vec<u8>> = match &*server_output.strongly_consistent().await? {
+                FileContent::Content(file) => {
+                    file.content().read().map(|chunk| chunk.to_vec()).collect()
+                }
+                FileContent::NotFound => vec![],
+            };
+            let issues = get_issues(server_output).await?;
+            let diags = get_diagnostics(server_output).await?;
+            Ok((chunks, issues, diags))
+        })
+        .await
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+    Ok(TurbopackResult {
+        result: NapiServerOutput {
+            chunks: chunks.into_iter().map(Buffer::from).collect(),
+        },
+        issues: issues.iter().map(|i| NapiIssue::from(&**i)).collect(),
+        diagnostics: diags.iter().map(|d| NapiDiagnostic::from(d)).collect(),
+    })
+}
+
+/// Returns the current compilation errors of the endpoint. This writes the
+/// endpoint to disk if it wasn't written yet, as that's what surfaces them.
+#[napi]
+pub async fn endpoint_errors(
+    #[napi(ts_arg_type = "{ __napiType: \"Endpoint\" }")] endpoint: External<ExternalEndpoint>,
+) -> napi::Result<Vec<NapiEndpointError>> {
+    let turbo_tasks = endpoint.turbo_tasks().clone();
+    let endpoint = ***endpoint;
+    let issues = turbo_tasks
+        .run_once(async move {
+            let write_to_disk = endpoint.write_to_disk();
+            write_to_disk.strongly_consistent().await?;
+            get_issues(write_to_disk).await
+        })
+        .await
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+    Ok(issues
+        .iter()
+        .filter(|issue| issue.severity <= IssueSeverity::Error)
+        .map(|issue| NapiEndpointError::from(&**issue))
+        .collect())
+}
+
 #[napi(ts_return_type = "{ __napiType: \"RootTask\" }")]
 pub fn endpoint_server_changed_subscribe(
     #[napi(ts_arg_type = "{ __napiType: \"Endpoint\" }")] endpoint: External<ExternalEndpoint>,
@@ -1,4 +1,8 @@
-use std::{path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use anyhow::{anyhow, bail, Context, Result};
 use napi::{
@@ -7,9 +11,10 @@ use napi::{
     JsFunction, Status,
 };
 use next_api::{
+    cache_stats::CacheStats,
     project::{
-        DefineEnv, Instrumentation, Middleware, PartialProjectOptions, ProjectContainer,
-        ProjectOptions,
+        AppIconKind, DefineEnv, Instrumentation, Middleware, PartialProjectOptions,
+        ProjectContainer, ProjectOptions,
     },
     route::{Endpoint, Route},
 };
@@ -21,7 +26,7 @@ use tracing::Instrument;
 use tracing_subscriber::{
     prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt, EnvFilter, Registry,
 };
-use turbo_tasks::{TransientInstance, TurboTasks, UpdateInfo, Vc};
+use turbo_tasks::{TransientInstance, TryJoinIterExt, TurboTasks, UpdateInfo, Vc};
 use turbopack_binding::{
     turbo::{
         tasks_fs::{FileContent, FileSystem},
@@ -59,6 +64,14 @@ pub struct NapiEnvVar {
     pub value: String,
 }
 
+#[napi(object)]
+#[derive(Clone, Debug)]
+pub struct NapiOverlayFile {
+    /// Relative to root_path.
+    pub path: String,
+    pub content: String,
+}
+
 #[napi(object)]
 pub struct NapiProjectOptions {
     /// A root path from which all files must be nested under. Trying to access
@@ -68,6 +81,10 @@ pub struct NapiProjectOptions {
     /// A path inside the root_path which contains the app/pages directories.
     pub project_path: String,
 
+    /// The directory containing the app/pages directories, relative to
+    /// project_path, when they aren't directly inside it.
+    pub routes_dir: Option<String>,
+
     /// next.config's distDir. Project initialization occurs eariler than
     /// deserializing next.config, so passing it as separate option.
     pub dist_dir: Option<String>,
@@ -81,6 +98,9 @@ pub struct NapiProjectOptions {
     /// The contents of ts/config read by load-jsconfig, serialized to JSON.
     pub js_config: String,
 
+    /// The tsconfig to compile with, relative to project_path.
+    pub tsconfig_path: Option<String>,
+
     /// A map of environment variables to use when compiling code.
     pub env: Vec<NapiEnvVar>,
 
@@ -90,6 +110,9 @@ pub struct NapiProjectOptions {
 
     /// The address of the dev server.
     pub server_addr: String,
+
+    /// In-memory source files shadowing the ones at the same paths on disk.
+    pub overlay_files: Option<Vec<NapiOverlayFile>>,
 }
 
 /// [NapiProjectOptions] with all fields optional.
@@ -102,6 +125,10 @@ pub struct NapiPartialProjectOptions {
     /// A path inside the root_path which contains the app/pages directories.
     pub project_path: Option<String>,
 
+    /// The directory containing the app/pages directories, relative to
+    /// project_path.
+    pub routes_dir: Option<Option<String>>,
+
     /// next.config's distDir. Project initialization occurs eariler than
     /// deserializing next.config, so passing it as separate option.
     pub dist_dir: Option<Option<String>>,
@@ -115,6 +142,9 @@ pub struct NapiPartialProjectOptions {
     /// The contents of ts/config read by load-jsconfig, serialized to JSON.
     pub js_config: Option<String>,
 
+    /// The tsconfig to compile with, relative to project_path.
+    pub tsconfig_path: Option<Option<String>>,
+
     /// A map of environment variables to use when compiling code.
     pub env: Option<Vec<NapiEnvVar>>,
 
@@ -124,6 +154,9 @@ pub struct NapiPartialProjectOptions {
 
     /// The address of the dev server.
     pub server_addr: Option<String>,
+
+    /// In-memory source files shadowing the ones at the same paths on disk.
+    pub overlay_files: Option<Vec<NapiOverlayFile>>,
 }
 
 #[napi(object)]
@@ -145,9 +178,11 @@ impl From<NapiProjectOptions> for ProjectOptions {
         ProjectOptions {
             root_path: val.root_path,
             project_path: val.project_path,
+            routes_dir: val.routes_dir,
             watch: val.watch,
             next_config: val.next_config,
             js_config: val.js_config,
+            tsconfig_path: val.tsconfig_path,
             env: val
                 .env
                 .into_iter()
@@ -155,6 +190,12 @@ impl From<NapiProjectOptions> for ProjectOptions {
                 .collect(),
             define_env: val.define_env.into(),
             server_addr: val.server_addr,
+            overlay_files: val
+                .overlay_files
+                .into_iter()
+                .flatten()
+                .map(|file| (file.path, file.content))
+                .collect(),
         }
     }
 }
@@ -164,14 +205,22 @@ impl From<NapiPartialProjectOptions> for PartialProjectOptions {
         PartialProjectOptions {
             root_path: val.root_path,
             project_path: val.project_path,
+            routes_dir: val.routes_dir,
             watch: val.watch,
             next_config: val.next_config,
             js_config: val.js_config,
+            tsconfig_path: val.tsconfig_path,
             env: val
                 .env
                 .map(|env| env.into_iter().map(|var| (var.name, var.value)).collect()),
             define_env: val.define_env.map(|env| env.into()),
             server_addr: val.server_addr,
+            overlay_files: val.overlay_files.map(|files| {
+                files
+                    .into_iter()
+                    .map(|file| (file.path, file.content))
+                    .collect()
+            }),
         }
     }
 }
@@ -203,6 +252,10 @@ pub struct ProjectInstance {
     container: Vc<ProjectContainer>,
     #[allow(dead_code)]
     guard: Option<ExitGuard<TraceWriterGuard>>,
+    /// The update info of the turbo-tasks instance can only be consumed once,
+    /// so a single task consumes it for the subscribers and the cache stats.
+    update_info_subscribers: Arc<Mutex<Vec<ThreadsafeFunction<NapiUpdateInfo>>>>,
+    cache_stats: Arc<Mutex<CacheStats>>,
 }
 
 #[napi(ts_return_type = "{ __napiType: \"Project\" }")]
@@ -273,11 +326,55 @@ pub async fn project_new(
         })
         .await
         .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+    let update_info_subscribers: Arc<Mutex<Vec<ThreadsafeFunction<NapiUpdateInfo>>>> =
+        Default::default();
+    let cache_stats = Arc::new(Mutex::new(CacheStats::default()));
+    // The task only holds weak references, and ends once the project is
+    // dropped.
+    tokio::spawn({
+        let turbo_tasks = Arc::downgrade(&turbo_tasks);
+        let update_info_subscribers = Arc::downgrade(&update_info_subscribers);
+        let cache_stats = Arc::downgrade(&cache_stats);
+        async move {
+            loop {
+                let update_info = match turbo_tasks.upgrade() {
+                    Some(turbo_tasks) => {
+                        turbo_tasks
+                            .get_or_wait_aggregated_update_info(Duration::from_secs(1))
+                            .await
+                    }
+                    None => break,
+                };
+                let (Some(update_info_subscribers), Some(cache_stats)) =
+                    (update_info_subscribers.upgrade(), cache_stats.upgrade())
+                else {
+                    break;
+                };
+                cache_stats.lock().unwrap().record_update(update_info.tasks);
+
+                let update_info = NapiUpdateInfo::from(update_info);
+                update_info_subscribers.lock().unwrap().retain(|func| {
+                    let status = func.call(
+                        Ok(update_info.clone()),
+                        ThreadsafeFunctionCallMode::NonBlocking,
+                    );
+                    if !matches!(status, Status::Ok) {
+                        eprintln!("{}", anyhow!("Error calling JS function: {}", status));
+                        return false;
+                    }
+                    true
+                });
+            }
+        }
+    });
+
     Ok(External::new_with_size_hint(
         ProjectInstance {
             turbo_tasks,
             container,
             guard,
+            update_info_subscribers,
+            cache_stats,
         },
         100,
     ))
@@ -491,6 +588,201 @@ pub fn project_entrypoints_subscribe(
     )
 }
 
+/// Returns the structural issues of the app and pages directories, without
+/// compiling any routes.
+#[napi]
+pub async fn project_validate(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+) -> napi::Result<Vec<NapiIssue>> {
+    let turbo_tasks = project.turbo_tasks.clone();
+    let container = project.container;
+    let issues = turbo_tasks
+        .run_once(async move {
+            let validate = container.project().validate();
+            validate.strongly_consistent().await?;
+            get_issues(validate).await
+        })
+        .await
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+    Ok(issues
+        .iter()
+        .map(|issue| NapiIssue::from(&**issue))
+        .collect())
+}
+
+#[napi(object)]
+pub struct NapiRouteSource {
+    /// The pathname of the route, with the leading slash.
+    pub pathname: String,
+    /// The relative path from project_path to the file serving the route.
+    pub file_path: String,
+}
+
+#[napi(object)]
+pub struct NapiRouteSourcesResult {
+    pub routes: Vec<NapiRouteSource>,
+}
+
+/// Returns the source file of each route, e.g. for editors to open the file
+/// serving a url, without compiling any routes.
+#[napi]
+pub async fn project_route_sources(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+) -> napi::Result<TurbopackResult<NapiRouteSourcesResult>> {
+    let turbo_tasks = project.turbo_tasks.clone();
+    let container = project.container;
+    let (sources, issues, diags) = turbo_tasks
+        .run_once(async move {
+            let project = container.project();
+            let route_sources = project.route_sources();
+            let project_path = project.project_path().await?;
+            let project_path = &project_path;
+            let sources = route_sources
+                .strongly_consistent()
+                .await?
+                .iter()
+                .map(|source| async move {
+                    let path = source.path.await?;
+                    Ok(NapiRouteSource {
+                        pathname: source.pathname.clone(),
+                        file_path: project_path
+                            .get_path_to(&path)
+                            .unwrap_or(&path.path)
+                            .to_string(),
+                    })
+                })
+                .try_join()
+                .await?;
+            let issues = get_issues(route_sources).await?;
+            let diags = get_diagnostics(route_sources).await?;
+            Ok((sources, issues, diags))
+        })
+        .await
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+
+    Ok(TurbopackResult {
+        result: NapiRouteSourcesResult { routes: sources },
+        issues: issues.iter().map(|i| NapiIssue::from(&**i)).collect(),
+        diagnostics: diags.iter().map(|d| NapiDiagnostic::from(d)).collect(),
+    })
+}
+
+#[napi(object)]
+pub struct NapiAssetBinding {
+    pub name: String,
+    pub file_path: String,
+}
+
+#[napi(object)]
+pub struct NapiMiddlewareChunks {
+    /// The JavaScript chunks, relative to the dist directory, in load order.
+    pub files: Vec<String>,
+    pub wasm: Vec<NapiAssetBinding>,
+}
+
+#[napi(object)]
+pub struct NapiMiddlewareChunksResult {
+    /// `None` when the project has no middleware.
+    pub middleware: Option<NapiMiddlewareChunks>,
+}
+
+/// Returns the chunks an edge host needs to load to run the middleware,
+/// without writing them to disk.
+#[napi]
+pub async fn project_middleware_chunks(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+) -> napi::Result<TurbopackResult<NapiMiddlewareChunksResult>> {
+    let turbo_tasks = project.turbo_tasks.clone();
+    let container = project.container;
+    let (middleware, issues, diags) = turbo_tasks
+        .run_once(async move {
+            let middleware_chunks = container.project().middleware_chunks();
+            let middleware = match *middleware_chunks.strongly_consistent().await? {
+                Some(chunks) => Some(chunks.await?),
+                None => None,
+            };
+            let issues = get_issues(middleware_chunks).await?;
+            let diags = get_diagnostics(middleware_chunks).await?;
+            Ok((middleware, issues, diags))
+        })
+        .await
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+
+    Ok(TurbopackResult {
+        result: NapiMiddlewareChunksResult {
+            middleware: middleware.map(|chunks| NapiMiddlewareChunks {
+                files: chunks.files.clone(),
+                wasm: chunks
+                    .wasm
+                    .iter()
+                    .map(|binding| NapiAssetBinding {
+                        name: binding.name.clone(),
+                        file_path: binding.file_path.clone(),
+                    })
+                    .collect(),
+            }),
+        },
+        issues: issues.iter().map(|i| NapiIssue::from(&**i)).collect(),
+        diagnostics: diags.iter().map(|d| NapiDiagnostic::from(d)).collect(),
+    })
+}
+
+#[napi(object)]
+pub struct NapiAppIcon {
+    /// `favicon`, `icon` or `apple-icon`.
+    pub kind: &'static str,
+    pub url: String,
+    /// The endpoint generating the icon, for dynamic icons like `app/icon.tsx`.
+    pub endpoint: Option<External<ExternalEndpoint>>,
+}
+
+#[napi(object)]
+pub struct NapiAppIconsResult {
+    pub icons: Vec<NapiAppIcon>,
+}
+
+/// Writes the static favicon and icons of the app directory to the client
+/// output, and returns all icons with the urls the HTML head references them
+/// with.
+#[napi]
+pub async fn project_app_icons(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+) -> napi::Result<TurbopackResult<NapiAppIconsResult>> {
+    let turbo_tasks = project.turbo_tasks.clone();
+    let container = project.container;
+    let (icons, issues, diags) = turbo_tasks
+        .run_once(async move {
+            let app_icons = container.project().write_app_icons();
+            let icons = app_icons.strongly_consistent().await?.icons.clone();
+            let issues = get_issues(app_icons).await?;
+            let diags = get_diagnostics(app_icons).await?;
+            Ok((icons, issues, diags))
+        })
+        .await
+        .map_err(|e| napi::Error::from_reason(PrettyPrintError(&e).to_string()))?;
+
+    Ok(TurbopackResult {
+        result: NapiAppIconsResult {
+            icons: icons
+                .into_iter()
+                .map(|icon| NapiAppIcon {
+                    kind: match icon.kind {
+                        AppIconKind::Favicon => "favicon",
+                        AppIconKind::Icon => "icon",
+                        AppIconKind::AppleIcon => "apple-icon",
+                    },
+                    url: icon.url,
+                    endpoint: icon.endpoint.map(|endpoint| {
+                        External::new(ExternalEndpoint(VcArc::new(turbo_tasks.clone(), endpoint)))
+                    }),
+                })
+                .collect(),
+        },
+        issues: issues.iter().map(|i| NapiIssue::from(&**i)).collect(),
+        diagnostics: diags.iter().map(|d| NapiDiagnostic::from(d)).collect(),
+    })
+}
+
 #[napi(ts_return_type = "{ __napiType: \"RootTask\" }")]
 pub fn project_hmr_events(
     #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
@@ -569,6 +861,60 @@ pub fn project_hmr_events(
     )
 }
 
+#[napi(object)]
+struct RscRefresh {
+    /// The app page whose server components changed.
+    pub pathname: String,
+}
+
+/// Subscribes to changes of the server components of the app page `pathname`,
+/// which the client can't apply as a module update. Rendering the new flight
+/// payload needs a request, so the JS binding adds it to each event by
+/// requesting the page from the dev server with the `RSC: 1` header.
+#[napi(ts_return_type = "{ __napiType: \"RootTask\" }")]
+pub fn project_rsc_hmr_events(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+    pathname: String,
+    func: JsFunction,
+) -> napi::Result<External<RootTask>> {
+    let turbo_tasks = project.turbo_tasks.clone();
+    let container = project.container;
+    subscribe(
+        turbo_tasks.clone(),
+        func,
+        {
+            let pathname = pathname.clone();
+            move || {
+                let pathname = pathname.clone();
+                async move {
+                    let changed = container.project().rsc_changed(pathname);
+                    changed.strongly_consistent().await?;
+
+                    let issues = get_issues(changed).await?;
+                    let diags = get_diagnostics(changed).await?;
+
+                    Ok((issues, diags))
+                }
+                .instrument(tracing::info_span!("RSC HMR subscription"))
+            }
+        },
+        move |ctx| {
+            let (issues, diags) = ctx.value;
+
+            Ok(vec![TurbopackResult {
+                result: RscRefresh {
+                    pathname: pathname.clone(),
+                },
+                issues: issues
+                    .iter()
+                    .map(|issue| NapiIssue::from(&**issue))
+                    .collect(),
+                diagnostics: diags.iter().map(|d| NapiDiagnostic::from(d)).collect(),
+            }])
+        },
+    )
+}
+
 #[napi(object)]
 struct HmrIdentifiers {
     pub identifiers: Vec<String>,
@@ -614,6 +960,7 @@ pub fn project_hmr_identifiers_subscribe(
 }
 
 #[napi(object)]
+#[derive(Clone)]
 struct NapiUpdateInfo {
     pub duration: u32,
     pub tasks: u32,
@@ -633,28 +980,43 @@ pub fn project_update_info_subscribe(
     #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
     func: JsFunction,
 ) -> napi::Result<()> {
-    let func: ThreadsafeFunction<UpdateInfo> = func.create_threadsafe_function(0, |ctx| {
-        let update_info = ctx.value;
-        Ok(vec![NapiUpdateInfo::from(update_info)])
-    })?;
-    let turbo_tasks = project.turbo_tasks.clone();
-    tokio::spawn(async move {
-        loop {
-            let update_info = turbo_tasks
-                .get_or_wait_aggregated_update_info(Duration::from_secs(1))
-                .await;
-
-            let status = func.call(Ok(update_info), ThreadsafeFunctionCallMode::NonBlocking);
-            if !matches!(status, Status::Ok) {
-                let error = anyhow!("Error calling JS function: {}", status);
-                eprintln!("{}", error);
-                break;
-            }
-        }
-    });
+    let func: ThreadsafeFunction<NapiUpdateInfo> =
+        func.create_threadsafe_function(0, |ctx| Ok(vec![ctx.value]))?;
+    project.update_info_subscribers.lock().unwrap().push(func);
     Ok(())
 }
 
+#[napi(object)]
+struct NapiCacheStats {
+    /// The number of tasks executed by the operations since the previous
+    /// call.
+    pub executed_tasks: u32,
+    /// The number of tasks from before the previous call which didn't need
+    /// to be executed again.
+    pub cached_tasks: u32,
+}
+
+/// Returns how many tasks were executed vs. reused from the cache since the
+/// previous call, e.g. by the most recent rebuild. Updates are counted once
+/// the project has been idle for a second, like for
+/// `project_update_info_subscribe`, which can be used alongside.
+#[napi]
+pub async fn project_cache_stats(
+    #[napi(ts_arg_type = "{ __napiType: \"Project\" }")] project: External<ProjectInstance>,
+) -> napi::Result<NapiCacheStats> {
+    let mut total_tasks = 0;
+    project
+        .turbo_tasks
+        .backend()
+        .with_all_cached_tasks(|_| total_tasks += 1);
+    let stats = project.cache_stats.lock().unwrap().take(total_tasks);
+
+    Ok(NapiCacheStats {
+        executed_tasks: stats.executed_tasks as u32,
+        cached_tasks: stats.cached_tasks as u32,
+    })
+}
+
 #[turbo_tasks::value]
 #[derive(Debug)]
 #[napi(object)]
@@ -3,16 +3,31 @@ use std::{
     path::PathBuf,
 };
 
-use anyhow::Context;
-use napi::bindgen_prelude::*;
+use anyhow::{bail, Context};
+use napi::{
+    bindgen_prelude::*,
+    threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode},
+    Env, JsFunction, JsObject,
+};
 use next_build::{
     build as turbo_next_build,
+    build_logger::{BuildLog, BuildLogger},
     build_options::{BuildContext, DefineEnv},
+    manifest_transform::ManifestTransform,
     BuildOptions as NextBuildOptions,
 };
-use next_core::next_config::{Rewrite, Rewrites, RouteHas};
+use next_core::{
+    next_client::ClientRuntime,
+    next_config::{Rewrite, Rewrites, RouteHas},
+    resolve_conditions::ResolveConditions,
+    turbopack::core::issue::IssueSeverity,
+    virtual_modules::VirtualModuleSource,
+};
 
-use crate::{next_api::project::NapiDefineEnv, util::MapErr};
+use crate::{
+    next_api::project::{NapiDefineEnv, NapiEnvVar},
+    util::MapErr,
+};
 
 #[napi(object, object_to_js = false)]
 #[derive(Debug)]
@@ -29,7 +44,12 @@ pub struct NextBuildContext {
     /// which we passes separately here.
     pub dist_dir: Option<String>,
 
-    /// The build ID.
+    /// The directory the dist directory is written to, for a read-only project
+    /// directory. Defaults to the project directory.
+    pub output_root: Option<String>,
+
+    /// The build ID. When omitted, it's computed from next.config.js's
+    /// `generateBuildId`.
     pub build_id: Option<String>,
 
     /// The rewrites, as computed by Next.js.
@@ -41,6 +61,172 @@ pub struct NextBuildContext {
     // pub original_rewrites: Option<Rewrites>,
     // pub original_redirects: Option<Vec<Redirect>>,
     pub define_env: NapiDefineEnv,
+
+    /// Compile-time replacements for both client and server code.
+    pub defines: Option<Vec<NapiEnvVar>>,
+
+    /// Modules generated before the build, resolvable by their specifier.
+    pub virtual_modules: Option<Vec<NapiVirtualModule>>,
+
+    /// Additional `exports` conditions per layer.
+    pub resolve_conditions: Option<NapiResolveConditions>,
+
+    /// Only emit the routes depending on these files, relative to the project
+    /// directory.
+    pub changed_files: Option<Vec<String>>,
+
+    /// If set, the paths in `pages-manifest.json` are resolved against this
+    /// directory instead of being relative.
+    pub pages_manifest_base: Option<String>,
+
+    /// If set, the build output is additionally written as a tar archive to
+    /// this path.
+    pub output_tarball: Option<String>,
+
+    /// The entry of a custom server to compile, relative to the project
+    /// directory.
+    pub custom_server: Option<String>,
+
+    /// The directory of the public files, relative to the project. Defaults
+    /// to `public`.
+    pub public_dir: Option<String>,
+
+    /// The maximum number of client references to chunk concurrently.
+    pub concurrency: Option<u32>,
+
+    /// Whether to warn about internal links that don't match any route.
+    pub validate_links: Option<bool>,
+
+    /// Warn when a client component bundles more than this many bytes of a
+    /// server package.
+    pub server_package_size_limit: Option<u32>,
+
+    /// Server packages allowed in client components.
+    pub allowed_client_packages: Option<Vec<String>>,
+
+    /// The regions of functions without a `preferredRegion`.
+    pub default_preferred_region: Option<Vec<String>>,
+
+    /// The timeout of functions without a `maxDuration`, in seconds.
+    pub default_max_duration: Option<u32>,
+
+    /// Whether to fail the build on `import()`s that don't resolve.
+    pub strict_dynamic_imports: Option<bool>,
+
+    /// Whether to fail the build on relative imports without an extension.
+    pub strict_import_extensions: Option<bool>,
+
+    /// Whether to warn about circular dependencies.
+    pub detect_cycles: Option<bool>,
+
+    /// Whether to write the chunk graph in the DOT format.
+    pub chunk_graph: Option<bool>,
+
+    /// The routes included in the chunk graph. Defaults to all routes.
+    pub chunk_graph_routes: Option<Vec<String>>,
+
+    /// How many references away from the entry chunks the chunk graph
+    /// reaches.
+    pub chunk_graph_depth: Option<u32>,
+
+    /// Whether client chunks are referenced by content-hashed filenames.
+    pub content_addressed: Option<bool>,
+
+    /// The number of hex digits of the content hashes, between 6 and 16.
+    /// Requires `content_addressed`.
+    pub hash_length: Option<u32>,
+
+    /// Whether to write the manifest of the files each page should preload.
+    pub preload_manifest: Option<bool>,
+
+    /// Whether to write the manifest of the order to render each page's
+    /// stylesheets, preloads and scripts in.
+    pub load_order_manifest: Option<bool>,
+
+    /// Whether to write the manifest of the files of the build.
+    pub deploy_manifest: Option<bool>,
+
+    /// Whether to write the timings of the build's phases and routes.
+    pub diagnostics: Option<bool>,
+
+    /// Whether client chunks don't reference their source maps.
+    pub hidden_source_maps: Option<bool>,
+
+    /// Whether client chunks are lowered to ES5.
+    pub legacy_browsers: Option<bool>,
+
+    /// The path prefix of the data routes of pages, followed by the build id.
+    /// Defaults to `/_next/data`.
+    pub data_path_prefix: Option<String>,
+
+    /// The variant of the Turbopack runtime loading the client chunks,
+    /// `production` or `development`. Defaults to `production`.
+    pub client_runtime: Option<String>,
+
+    /// Whether server chunks are minified. Defaults to
+    /// `experimental.serverMinification`.
+    pub minify_server: Option<bool>,
+
+    /// A seed for the hashes of the build, for reproducible builds.
+    pub build_hash_seed: Option<String>,
+
+    /// How many times transient failures writing the build output are
+    /// retried.
+    pub write_retries: Option<u32>,
+
+    /// Whether manifests are written as compact JSON instead of
+    /// pretty-printed.
+    pub compact_manifests: Option<bool>,
+
+    /// Whether a failed build writes a diagnostics bundle to the dist
+    /// directory.
+    pub diagnostics_on_failure: Option<bool>,
+
+    /// The prefixes marking environment variables as public. Defaults to
+    /// `NEXT_PUBLIC_`.
+    pub public_env_prefixes: Option<Vec<String>>,
+
+    /// The minimum severity of issues that fails the build, e.g. `warning`.
+    /// Defaults to `fatal`.
+    pub fail_on: Option<String>,
+
+    /// Globs for files whose issues fail the build, no matter their severity.
+    pub promote_to_error: Option<Vec<String>>,
+
+    /// Whether to fail on the first route with failing issues.
+    pub fail_fast: Option<bool>,
+}
+
+#[napi(object, object_to_js = false)]
+#[derive(Debug)]
+pub struct NapiVirtualModule {
+    /// The specifier the module is imported with, e.g. `virtual:routes`.
+    pub specifier: String,
+    /// The JavaScript source of the module.
+    pub source: Option<String>,
+    /// The file the module's source is generated to, relative to the project
+    /// directory, instead of `source`. The module changes whenever the file
+    /// does.
+    pub path: Option<String>,
+}
+
+/// Keep in sync with [`next_core::resolve_conditions::ResolveConditions`]
+#[napi(object, object_to_js = false)]
+#[derive(Debug)]
+pub struct NapiResolveConditions {
+    pub client: Option<Vec<String>>,
+    pub ssr: Option<Vec<String>>,
+    pub rsc: Option<Vec<String>>,
+}
+
+impl From<NapiResolveConditions> for ResolveConditions {
+    fn from(val: NapiResolveConditions) -> Self {
+        ResolveConditions {
+            client: val.client.unwrap_or_default(),
+            ssr: val.ssr.unwrap_or_default(),
+            rsc: val.rsc.unwrap_or_default(),
+        }
+    }
 }
 
 impl TryFrom<NextBuildContext> for NextBuildOptions {
@@ -51,25 +237,140 @@ impl TryFrom<NextBuildContext> for NextBuildOptions {
             dir: value.dir.map(PathBuf::try_from).transpose()?,
             root: value.root.map(PathBuf::try_from).transpose()?,
             log_level: None,
+            fail_on: value
+                .fail_on
+                .as_deref()
+                .map(parse_issue_severity)
+                .transpose()?,
+            promote_to_error: value.promote_to_error.unwrap_or_default(),
+            fail_fast: value.fail_fast.unwrap_or_default(),
             show_all: true,
             log_detail: true,
             full_stats: true,
             memory_limit: None,
+            concurrency: value.concurrency.map(|concurrency| concurrency as usize),
             dist_dir: value.dist_dir,
-            build_context: Some(BuildContext {
-                build_id: value
-                    .build_id
-                    .context("NextBuildContext must provide a build ID")?,
-                rewrites: value
-                    .rewrites
-                    .context("NextBuildContext must provide rewrites")?
-                    .into(),
-            }),
+            output_root: value.output_root.map(PathBuf::try_from).transpose()?,
+            validate_links: value.validate_links.unwrap_or_default(),
+            server_package_size_limit: value.server_package_size_limit.map(u64::from),
+            allowed_client_packages: value.allowed_client_packages.unwrap_or_default(),
+            default_preferred_region: value.default_preferred_region,
+            default_max_duration: value.default_max_duration,
+            strict_dynamic_imports: value.strict_dynamic_imports.unwrap_or_default(),
+            strict_import_extensions: value.strict_import_extensions.unwrap_or_default(),
+            detect_cycles: value.detect_cycles.unwrap_or_default(),
+            chunk_graph: value.chunk_graph.unwrap_or_default(),
+            chunk_graph_routes: value.chunk_graph_routes,
+            chunk_graph_depth: value.chunk_graph_depth,
+            content_addressed: value.content_addressed.unwrap_or_default(),
+            hash_length: value.hash_length.map(|hash_length| hash_length as usize),
+            preload_manifest: value.preload_manifest.unwrap_or_default(),
+            load_order_manifest: value.load_order_manifest.unwrap_or_default(),
+            deploy_manifest: value.deploy_manifest.unwrap_or_default(),
+            diagnostics: value.diagnostics.unwrap_or_default(),
+            logger: None,
+            manifest_transform: None,
+            hidden_source_maps: value.hidden_source_maps.unwrap_or_default(),
+            legacy_browsers: value.legacy_browsers.unwrap_or_default(),
+            data_path_prefix: value.data_path_prefix,
+            client_runtime: value
+                .client_runtime
+                .as_deref()
+                .map(parse_client_runtime)
+                .transpose()?,
+            minify_server: value.minify_server,
+            build_hash_seed: value.build_hash_seed,
+            write_retries: value.write_retries,
+            compact_manifests: value.compact_manifests.unwrap_or_default(),
+            diagnostics_on_failure: value.diagnostics_on_failure.unwrap_or_default(),
+            public_env_prefixes: value.public_env_prefixes,
+            // Without a build ID, the build ID is computed from next.config.js's
+            // `generateBuildId`.
+            build_context: value
+                .build_id
+                .map(|build_id| {
+                    Ok::<_, napi::Error>(BuildContext {
+                        build_id,
+                        rewrites: value
+                            .rewrites
+                            .context("NextBuildContext must provide rewrites")?
+                            .into(),
+                    })
+                })
+                .transpose()?,
             define_env: value.define_env.into(),
+            defines: value
+                .defines
+                .into_iter()
+                .flatten()
+                .map(|var| (var.name, var.value))
+                .collect(),
+            virtual_modules: value
+                .virtual_modules
+                .into_iter()
+                .flatten()
+                .map(|module| {
+                    let source = match (module.path, module.source) {
+                        (Some(path), _) => VirtualModuleSource::File(path),
+                        (None, Some(source)) => VirtualModuleSource::Source(source),
+                        (None, None) => bail!(
+                            "the virtual module {} has neither a source nor a path",
+                            module.specifier
+                        ),
+                    };
+                    Ok((module.specifier, source))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+                .convert_err()?,
+            changed_files: value.changed_files,
+            pages_manifest_base: value
+                .pages_manifest_base
+                .map(PathBuf::try_from)
+                .transpose()?,
+            output_tarball: value.output_tarball.map(PathBuf::try_from).transpose()?,
+            custom_server: value.custom_server,
+            public_dir: value.public_dir,
+            resolve_conditions: value
+                .resolve_conditions
+                .map(ResolveConditions::from)
+                .unwrap_or_default(),
         })
     }
 }
 
+/// Parses the name of an [IssueSeverity], e.g. `warning`.
+fn parse_issue_severity(name: &str) -> Result<IssueSeverity> {
+    Ok(match name {
+        "bug" => IssueSeverity::Bug,
+        "fatal" => IssueSeverity::Fatal,
+        "error" => IssueSeverity::Error,
+        "warning" => IssueSeverity::Warning,
+        "hint" => IssueSeverity::Hint,
+        "note" => IssueSeverity::Note,
+        "suggestion" => IssueSeverity::Suggestion,
+        "info" => IssueSeverity::Info,
+        _ => {
+            return Err(napi::Error::from_reason(format!(
+                "unknown issue severity `{name}`, expected one of bug, fatal, error, warning, \
+                 hint, note, suggestion or info"
+            )))
+        }
+    })
+}
+
+/// Parses the name of a [ClientRuntime], e.g. `production`.
+fn parse_client_runtime(name: &str) -> Result<ClientRuntime> {
+    Ok(match name {
+        "production" => ClientRuntime::Production,
+        "development" => ClientRuntime::Development,
+        _ => {
+            return Err(napi::Error::from_reason(format!(
+                "unknown client runtime `{name}`, expected production or development"
+            )))
+        }
+    })
+}
+
 impl From<NapiDefineEnv> for DefineEnv {
     fn from(val: NapiDefineEnv) -> Self {
         DefineEnv {
@@ -202,9 +503,104 @@ impl From<NapiRouteHas> for RouteHas {
     }
 }
 
-#[napi]
-pub async fn next_build(ctx: NextBuildContext) -> napi::Result<()> {
-    turbo_next_build(ctx.try_into()?).await.convert_err()
+#[napi(object)]
+pub struct NapiBuildLog {
+    /// The phase which finished, or nothing for an issue.
+    pub phase: Option<String>,
+    pub level: String,
+    pub message: String,
+}
+
+impl From<BuildLog> for NapiBuildLog {
+    fn from(log: BuildLog) -> Self {
+        Self {
+            phase: log.phase.map(|phase| phase.as_str().to_string()),
+            level: log.level.as_str().to_string(),
+            message: log.message,
+        }
+    }
+}
+
+/// What the JS wrapper of the manifest transform returns: the transformed
+/// JSON, or the message of the error the transform threw. Errors thrown
+/// through a threadsafe function would terminate the process, so the wrapper
+/// catches them.
+#[napi(object)]
+pub struct NapiManifestTransformResult {
+    pub json: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Builds the project. The `logger` is called with a [NapiBuildLog] when each
+/// phase of the build finishes, and for each issue. The calls are queued in
+/// order.
+///
+/// The `manifest_transform` is called with the file name and JSON of each
+/// manifest before it's written, and returns the JSON to write instead. The
+/// build fails with an error naming the manifest if the transform threw.
+///
+/// Both are called Node-style, with an error or `null` first.
+#[napi(ts_return_type = "Promise<void>")]
+pub fn next_build(
+    env: Env,
+    ctx: NextBuildContext,
+    #[napi(ts_arg_type = "(err: null | Error, log: NapiBuildLog) => void")] logger: Option<
+        JsFunction,
+    >,
+    #[napi(ts_arg_type = "(err: null | Error, name: string, json: string) => \
+                          NapiManifestTransformResult")]
+    manifest_transform: Option<JsFunction>,
+) -> napi::Result<JsObject> {
+    let mut options: NextBuildOptions = ctx.try_into()?;
+    if let Some(logger) = logger {
+        let logger: ThreadsafeFunction<BuildLog, ErrorStrategy::CalleeHandled> =
+            logger.create_threadsafe_function(0, |ctx| Ok(vec![NapiBuildLog::from(ctx.value)]))?;
+        options.logger = Some(BuildLogger::new(move |log| {
+            logger.call(Ok(log), ThreadsafeFunctionCallMode::NonBlocking);
+        }));
+    }
+    if let Some(manifest_transform) = manifest_transform {
+        let manifest_transform: ThreadsafeFunction<(String, String), ErrorStrategy::CalleeHandled> =
+            manifest_transform.create_threadsafe_function(0, |ctx| {
+                let (name, json) = ctx.value;
+                Ok(vec![name, json])
+            })?;
+        options.manifest_transform = Some(ManifestTransform::new(move |name, json| {
+            let (sender, receiver) = tokio::sync::oneshot::channel();
+            let status = manifest_transform.call_with_return_value(
+                Ok((name, json)),
+                ThreadsafeFunctionCallMode::NonBlocking,
+                move |result: NapiManifestTransformResult| {
+                    let _ = sender.send(result);
+                    Ok(())
+                },
+            );
+            async move {
+                if !matches!(status, Status::Ok) {
+                    bail!("Error calling JS function: {}", status);
+                }
+                match receiver
+                    .await
+                    .context("the manifest transform didn't return")?
+                {
+                    NapiManifestTransformResult {
+                        error: Some(error), ..
+                    } => bail!("the manifest transform threw: {error}"),
+                    NapiManifestTransformResult {
+                        json: Some(json), ..
+                    } => Ok(json),
+                    _ => bail!("the manifest transform didn't return a string"),
+                }
+            }
+        }));
+    }
+    env.execute_tokio_future(
+        async move {
+            turbo_next_build(options).await.convert_err()?;
+            Ok(())
+        },
+        |env, ()| env.get_undefined(),
+    )
 }
 
 #[napi]
@@ -1,6 +1,6 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use indexmap::IndexSet;
 use next_core::{
     all_assets_from_entries,
@@ -64,9 +64,10 @@ use crate::{
         collect_chunk_group, collect_evaluated_chunk_group, collect_next_dynamic_imports,
         DynamicImportedChunks,
     },
+    edge_bundle_size::check_edge_bundle_size,
     middleware::{get_js_paths_from_root, get_wasm_paths_from_root, wasm_paths_to_bindings},
-    project::Project,
-    route::{Endpoint, Route, Routes, WrittenEndpoint},
+    project::{AppIcon, AppIconKind, AppIcons, Project},
+    route::{Endpoint, Route, RouteSource, RouteSources, Routes, WrittenEndpoint},
     server_actions::create_server_actions_manifest,
     server_paths::all_server_paths,
 };
@@ -81,6 +82,38 @@ pub struct AppProject {
 #[turbo_tasks::value(transparent)]
 pub struct OptionAppProject(Option<Vc<AppProject>>);
 
+/// A client reference of the app, see [AppProject::client_references].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub struct ClientReferenceSource {
+    pub ty: ClientReferenceType,
+    /// The path of the module marked with `"use client"`, or of the CSS file
+    /// imported from a server component, relative to the project's file
+    /// system root.
+    pub path: String,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct ClientReferenceSources(Vec<ClientReferenceSource>);
+
+#[turbo_tasks::value_impl]
+impl ClientReferenceSources {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        Vc::cell(vec![])
+    }
+}
+
+/// Returns the kind of icon the metadata file with the stem defines, if any.
+/// Icons can be numbered, e.g. `icon1.png`.
+fn app_icon_kind(stem: &str) -> Option<AppIconKind> {
+    match stem.trim_end_matches(|c: char| c.is_ascii_digit()) {
+        "favicon" => Some(AppIconKind::Favicon),
+        "icon" => Some(AppIconKind::Icon),
+        "apple-icon" => Some(AppIconKind::AppleIcon),
+        _ => None,
+    }
+}
+
 impl AppProject {
     fn client_ty(self: Vc<Self>) -> ClientContextType {
         ClientContextType::App {
@@ -398,6 +431,174 @@ impl AppProject {
                 .collect(),
         ))
     }
+
+    /// Returns the files backing the app routes: the page of app pages, and
+    /// the route handler or metadata file of app routes.
+    #[turbo_tasks::function]
+    pub async fn route_sources(self: Vc<Self>) -> Result<Vc<RouteSources>> {
+        let mut sources = vec![];
+        for (pathname, entrypoint) in self.app_entrypoints().await?.iter() {
+            let path = match entrypoint {
+                AppEntrypoint::AppPage { loader_tree, .. } => {
+                    let Some(page) = loader_tree_page(*loader_tree).await? else {
+                        continue;
+                    };
+                    page
+                }
+                AppEntrypoint::AppRoute { path, .. } => *path,
+                AppEntrypoint::AppMetadata {
+                    metadata: MetadataItem::Static { path } | MetadataItem::Dynamic { path },
+                    ..
+                } => *path,
+            };
+            sources.push(RouteSource {
+                pathname: pathname.to_string(),
+                path,
+            });
+        }
+        Ok(Vc::cell(sources))
+    }
+
+    /// Returns the client references of all app pages, i.e. the boundaries
+    /// between server and client components. A client component imported by
+    /// several server components is listed once.
+    #[turbo_tasks::function]
+    pub async fn client_references(self: Vc<Self>) -> Result<Vc<ClientReferenceSources>> {
+        let rsc_entries = self
+            .app_entrypoints()
+            .await?
+            .values()
+            .filter_map(|entrypoint| {
+                let AppEntrypoint::AppPage { page, loader_tree } = entrypoint else {
+                    return None;
+                };
+                let endpoint = AppEndpoint {
+                    ty: AppEndpointType::Page {
+                        ty: AppPageEndpointType::Html,
+                        loader_tree: *loader_tree,
+                    },
+                    app_project: self,
+                    page: page.clone(),
+                }
+                .cell();
+                Some(endpoint.app_page_entry(*loader_tree))
+            })
+            .map(|app_entry| async move { Ok(Vc::upcast(app_entry.await?.rsc_entry)) })
+            .try_join()
+            .await?;
+
+        let client_reference_types = ClientReferenceGraph::new(Vc::cell(rsc_entries))
+            .types()
+            .await?;
+        let sources = client_reference_types
+            .iter()
+            .map(|&ty| async move {
+                let module: Vc<Box<dyn Module>> = match ty {
+                    ClientReferenceType::EcmascriptClientReference(module) => Vc::upcast(module),
+                    ClientReferenceType::CssClientReference(module) => Vc::upcast(module),
+                };
+                Ok(ClientReferenceSource {
+                    ty,
+                    path: module.ident().path().await?.path.clone(),
+                })
+            })
+            .try_join()
+            .await?;
+        Ok(Vc::cell(sources))
+    }
+
+    /// Resolves the favicon and icons of the app directory. Static icons are
+    /// copied to `_next/static/media` with their content hash in the name, so
+    /// they can be cached forever. Dynamic icons are generated by the endpoint
+    /// of their metadata route.
+    #[turbo_tasks::function]
+    pub async fn icons(self: Vc<Self>) -> Result<Vc<AppIcons>> {
+        let routes = self.routes().await?;
+        let client_relative_path = self.project().client_relative_path();
+        let next_config = self.project().next_config();
+        let asset_prefix = next_config
+            .computed_asset_prefix(*self.mode().await?)
+            .await?;
+        let asset_prefix = asset_prefix.as_deref().unwrap_or("/_next/");
+        let base_path = next_config.await?.base_path.clone().unwrap_or_default();
+
+        let mut icons = vec![];
+        let mut output_assets = vec![];
+        for (pathname, entrypoint) in self.app_entrypoints().await?.iter() {
+            let AppEntrypoint::AppMetadata { metadata, .. } = entrypoint else {
+                continue;
+            };
+            let (MetadataItem::Static { path } | MetadataItem::Dynamic { path }) = *metadata;
+            let stem = path.file_stem().await?;
+            let stem = stem.as_deref().unwrap_or_default();
+            let Some(kind) = app_icon_kind(stem) else {
+                continue;
+            };
+
+            match metadata {
+                MetadataItem::Static { .. } => {
+                    let content = path.read();
+                    let hash = *content.hash().await?;
+                    let file_name = match path.await?.extension_ref() {
+                        Some(extension) => format!("{stem}.{hash:016x}.{extension}"),
+                        None => format!("{stem}.{hash:016x}"),
+                    };
+                    output_assets.push(Vc::upcast(VirtualOutputAsset::new(
+                        client_relative_path.join(format!("static/media/{file_name}")),
+                        AssetContent::file(content),
+                    )));
+                    icons.push(AppIcon {
+                        kind,
+                        url: format!("{asset_prefix}static/media/{file_name}"),
+                        endpoint: None,
+                    });
+                }
+                MetadataItem::Dynamic { .. } => {
+                    let pathname = pathname.to_string();
+                    let Some(Route::AppRoute { endpoint }) = routes.get(&pathname) else {
+                        continue;
+                    };
+                    // Without an id, the route serves the first generated icon.
+                    let url = pathname
+                        .strip_suffix("/[[...__metadata_id__]]")
+                        .unwrap_or(&pathname);
+                    icons.push(AppIcon {
+                        kind,
+                        url: format!("{base_path}{url}"),
+                        endpoint: Some(*endpoint),
+                    });
+                }
+            }
+        }
+
+        Ok(AppIcons {
+            icons,
+            output_assets: Vc::cell(output_assets),
+        }
+        .cell())
+    }
+}
+
+/// Finds the page of the loader tree, following the `children` of its
+/// segments before the parallel routes.
+async fn loader_tree_page(loader_tree: Vc<LoaderTree>) -> Result<Option<Vc<FileSystemPath>>> {
+    let mut queue = VecDeque::from([loader_tree]);
+    while let Some(tree) = queue.pop_front() {
+        let tree = tree.await?;
+        if let Some(page) = tree.components.await?.page {
+            return Ok(Some(page));
+        }
+        if let Some(&children) = tree.parallel_routes.get("children") {
+            queue.push_front(children);
+        }
+        queue.extend(
+            tree.parallel_routes
+                .iter()
+                .filter(|(name, _)| *name != "children")
+                .map(|(_, &tree)| tree),
+        );
+    }
+    Ok(None)
 }
 
 #[turbo_tasks::function]
@@ -481,8 +682,30 @@ struct AppEndpoint {
     page: AppPage,
 }
 
+/// See [crate::project::Project::rsc_changed].
+#[turbo_tasks::function]
+pub(crate) async fn app_page_rsc_changed(
+    endpoint: Vc<Box<dyn Endpoint>>,
+) -> Result<Vc<Completion>> {
+    let Some(endpoint) = Vc::try_resolve_downcast_type::<AppEndpoint>(endpoint).await? else {
+        bail!("expected an app endpoint");
+    };
+    Ok(endpoint.rsc_changed())
+}
+
 #[turbo_tasks::value_impl]
 impl AppEndpoint {
+    /// Completion when the output of the RSC layer changes. Client components
+    /// are only referenced by it, so editing them doesn't complete this.
+    #[turbo_tasks::function]
+    async fn rsc_changed(self: Vc<Self>) -> Result<Vc<Completion>> {
+        Ok(self
+            .await?
+            .app_project
+            .project()
+            .server_changed(self.output().rsc_assets()))
+    }
+
     #[turbo_tasks::function]
     fn app_page_entry(&self, loader_tree: Vc<LoaderTree>) -> Vc<AppEntry> {
         get_app_page_entry(
@@ -515,6 +738,7 @@ impl AppEndpoint {
             self.page.clone(),
             *self.app_project.mode().await?,
             metadata,
+            self.app_project.project().next_config(),
         ))
     }
 
@@ -629,6 +853,7 @@ impl AppEndpoint {
                 client_reference_types,
                 this.app_project.project().client_chunking_context(),
                 ssr_chunking_context,
+                None,
             );
             let client_references_chunks_ref = client_references_chunks.await?;
 
@@ -667,6 +892,7 @@ impl AppEndpoint {
                 pages: [(app_entry.original_name.clone(), entry_client_chunks_paths)]
                     .into_iter()
                     .collect(),
+                ..Default::default()
             };
             let manifest_path_prefix = get_asset_prefix_from_pathname(&app_entry.pathname);
             let app_build_manifest_output = Vc::upcast(VirtualOutputAsset::new(
@@ -679,10 +905,11 @@ impl AppEndpoint {
             ));
             server_assets.push(app_build_manifest_output);
 
-            let build_manifest = BuildManifest {
+            let mut build_manifest = BuildManifest {
                 root_main_files: client_shared_chunks_paths,
                 ..Default::default()
             };
+            build_manifest.compute_runtime_files();
             let build_manifest_output = Vc::upcast(VirtualOutputAsset::new(
                 node_root.join(format!(
                     "server/app{manifest_path_prefix}/{ty}/build-manifest.json",
@@ -704,7 +931,7 @@ impl AppEndpoint {
                 this.app_project
                     .project()
                     .next_config()
-                    .computed_asset_prefix(),
+                    .computed_asset_prefix(*this.app_project.mode().await?),
                 runtime,
             );
             server_assets.push(entry_manifest);
@@ -872,6 +1099,13 @@ impl AppEndpoint {
                     .extend(get_js_paths_from_root(&node_root_value, &files_value).await?);
 
                 let all_output_assets = all_assets_from_entries(files).await?;
+                check_edge_bundle_size(
+                    this.app_project.project(),
+                    app_entry.rsc_entry.ident().path(),
+                    app_entry.original_name.clone(),
+                    files,
+                )
+                .await?;
 
                 wasm_paths_from_root
                     .extend(get_wasm_paths_from_root(&node_root_value, &middleware_assets).await?);
@@ -1117,8 +1351,12 @@ impl Endpoint for AppEndpoint {
                         .context("Node.js chunk entry path must be inside the node root")?
                         .to_string(),
                     server_paths,
+                    config: Default::default(),
+                },
+                AppEndpointOutput::Edge { .. } => WrittenEndpoint::Edge {
+                    server_paths,
+                    config: Default::default(),
                 },
-                AppEndpointOutput::Edge { .. } => WrittenEndpoint::Edge { server_paths },
             };
             Ok(written_endpoint.cell())
         }
@@ -1126,6 +1364,28 @@ impl Endpoint for AppEndpoint {
         .await
     }
 
+    #[turbo_tasks::function]
+    async fn chunks(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
+        Ok(self
+            .await?
+            .app_project
+            .project()
+            .output_asset_paths(self.output_assets()))
+    }
+
+    #[turbo_tasks::function]
+    async fn server_output(self: Vc<Self>) -> Result<Vc<FileContent>> {
+        let entry_chunks = match *self.output().await? {
+            AppEndpointOutput::NodeJs { rsc_chunk, .. } => Vc::cell(vec![rsc_chunk]),
+            AppEndpointOutput::Edge { files, .. } => files,
+        };
+        Ok(self
+            .await?
+            .app_project
+            .project()
+            .server_output_content(self.output_assets(), entry_chunks))
+    }
+
     #[turbo_tasks::function]
     async fn server_changed(self: Vc<Self>) -> Result<Vc<Completion>> {
         Ok(self
@@ -1189,4 +1449,13 @@ impl AppEndpointOutput {
             | AppEndpointOutput::Edge { client_assets, .. } => client_assets,
         }
     }
+
+    /// The chunks of the RSC layer, which render the RSC payload.
+    #[turbo_tasks::function]
+    pub fn rsc_assets(&self) -> Vc<OutputAssets> {
+        match *self {
+            AppEndpointOutput::NodeJs { rsc_chunk, .. } => Vc::cell(vec![rsc_chunk]),
+            AppEndpointOutput::Edge { files, .. } => files,
+        }
+    }
 }
@@ -0,0 +1,81 @@
+/// Counts how many tasks were executed vs. reused from the cache between calls
+/// of [CacheStats::take], e.g. by a rebuild.
+///
+/// It's fed with the executed tasks of each update of the turbo-tasks
+/// instance, independently of other consumers of the updates.
+#[derive(Debug, Default)]
+pub struct CacheStats {
+    executed_tasks: usize,
+    previous_total_tasks: usize,
+}
+
+/// The tasks executed and reused since the previous [CacheStats::take].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStatsSnapshot {
+    /// The tasks executed by updates, including new tasks.
+    pub executed_tasks: usize,
+    /// The tasks that existed before and weren't executed again.
+    pub cached_tasks: usize,
+}
+
+impl CacheStats {
+    /// Records an update of the turbo-tasks instance which executed
+    /// `executed_tasks` tasks.
+    pub fn record_update(&mut self, executed_tasks: usize) {
+        self.executed_tasks += executed_tasks;
+    }
+
+    /// Returns the stats since the previous call, given the number of tasks
+    /// the backend holds now, and starts counting anew.
+    pub fn take(&mut self, total_tasks: usize) -> CacheStatsSnapshot {
+        let new_tasks = total_tasks.saturating_sub(self.previous_total_tasks);
+        let re_executed_tasks = self.executed_tasks.saturating_sub(new_tasks);
+        let snapshot = CacheStatsSnapshot {
+            executed_tasks: self.executed_tasks,
+            cached_tasks: self.previous_total_tasks.saturating_sub(re_executed_tasks),
+        };
+        self.executed_tasks = 0;
+        self.previous_total_tasks = total_tasks;
+        snapshot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CacheStats, CacheStatsSnapshot};
+
+    #[test]
+    fn counts_tasks_between_calls() {
+        let mut stats = CacheStats::default();
+
+        // The initial compilation creates and executes every task.
+        stats.record_update(100);
+        assert_eq!(
+            stats.take(100),
+            CacheStatsSnapshot {
+                executed_tasks: 100,
+                cached_tasks: 0
+            }
+        );
+
+        // A rebuild re-executing 8 tasks and adding 2, over two updates.
+        stats.record_update(6);
+        stats.record_update(4);
+        assert_eq!(
+            stats.take(102),
+            CacheStatsSnapshot {
+                executed_tasks: 10,
+                cached_tasks: 92
+            }
+        );
+
+        // Nothing changed since.
+        assert_eq!(
+            stats.take(102),
+            CacheStatsSnapshot {
+                executed_tasks: 0,
+                cached_tasks: 102
+            }
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use anyhow::Result;
+use next_core::all_assets_from_entries;
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{FileContent, FileSystemPath},
+    turbopack::core::{
+        asset::{Asset, AssetContent},
+        issue::{Issue, IssueExt, IssueSeverity, OptionStyledString, StyledString},
+        output::OutputAssets,
+    },
+};
+
+use crate::project::Project;
+
+/// The number of files listed when an edge function is too large.
+const LARGEST_FILES_COUNT: usize = 5;
+
+/// Warns when the JavaScript and WebAssembly files of the edge function `name`
+/// add up to more than `experimental.edgeBundleSizeLimit` bytes, since edge
+/// hosts refuse to deploy functions above their size cap.
+///
+/// Each function is checked on its own, so a large module shared by several
+/// functions is reported for every function it pushes over the limit.
+#[turbo_tasks::function]
+pub(crate) async fn check_edge_bundle_size(
+    project: Vc<Project>,
+    path: Vc<FileSystemPath>,
+    name: String,
+    files: Vc<OutputAssets>,
+) -> Result<Vc<()>> {
+    let Some(limit) = *project.next_config().edge_bundle_size_limit().await? else {
+        return Ok(Default::default());
+    };
+
+    let node_root = project.node_root().await?;
+    let mut sizes = all_assets_from_entries(files)
+        .await?
+        .iter()
+        .map(|&asset| async move {
+            let asset_path = asset.ident().path().await?;
+            if !matches!(asset_path.extension_ref(), Some("js" | "wasm")) {
+                return Ok(None);
+            }
+            let size = match &*asset.content().await? {
+                AssetContent::File(file) => match &*file.await? {
+                    FileContent::Content(file) => file.content().len() as u64,
+                    FileContent::NotFound => 0,
+                },
+                AssetContent::Redirect { .. } => 0,
+            };
+            let file = node_root
+                .get_path_to(&asset_path)
+                .map_or_else(|| asset_path.path.clone(), |path| path.to_string());
+            Ok(Some((file, size)))
+        })
+        .try_join()
+        .await?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+    let size = sizes.iter().map(|(_, size)| size).sum::<u64>();
+    if size <= limit {
+        return Ok(Default::default());
+    }
+
+    sizes.sort_by(|(a_file, a_size), (b_file, b_size)| {
+        b_size.cmp(a_size).then_with(|| a_file.cmp(b_file))
+    });
+    sizes.truncate(LARGEST_FILES_COUNT);
+
+    EdgeBundleSizeIssue {
+        path,
+        name,
+        size,
+        limit,
+        largest_files: sizes,
+    }
+    .cell()
+    .emit();
+
+    Ok(Default::default())
+}
+
+#[turbo_tasks::value(shared)]
+struct EdgeBundleSizeIssue {
+    path: Vc<FileSystemPath>,
+    name: String,
+    size: u64,
+    limit: u64,
+    /// The largest files of the function with their sizes, largest first.
+    largest_files: Vec<(String, u64)>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for EdgeBundleSizeIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Text("Edge function ".to_string()),
+            StyledString::Code(self.name.clone()),
+            StyledString::Text(format!(
+                " is {} bytes, which exceeds the limit of {} bytes",
+                self.size, self.limit
+            )),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("output".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        let mut lines = vec![StyledString::Text(
+            "Deploying it to an edge host may fail. The largest files are:".to_string(),
+        )];
+        lines.extend(self.largest_files.iter().map(|(file, size)| {
+            StyledString::Line(vec![
+                StyledString::Text("- ".to_string()),
+                StyledString::Code(file.clone()),
+                StyledString::Text(format!(" ({size} bytes)")),
+            ])
+        }));
+        Vc::cell(Some(StyledString::Stack(lines).cell()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use next_core::{
+        next_config::NextConfig,
+        test_utils::{issues, styled_text},
+    };
+    use turbo_tasks::Vc;
+    use turbopack_binding::{
+        turbo::tasks_fs::{File, FileContent},
+        turbopack::core::{
+            asset::AssetContent, output::OutputAsset, virtual_output::VirtualOutputAsset,
+        },
+    };
+
+    use super::check_edge_bundle_size;
+    use crate::{
+        project::{ProjectContainer, ProjectOptions},
+        test_utils::{fixture, fixture_project_options, run},
+    };
+
+    #[tokio::test]
+    async fn warns_about_an_edge_function_over_the_limit() {
+        let dir = fixture(&[("middleware.js", "export default function middleware() {}")]);
+        let root = dir.path().to_owned();
+        let messages = run(async move {
+            let mut next_config = NextConfig::default();
+            next_config.experimental.edge_bundle_size_limit = Some(100);
+            let project = ProjectContainer::new(ProjectOptions {
+                next_config: serde_json::to_string(&next_config)?,
+                ..fixture_project_options(&root)?
+            })
+            .project();
+
+            let node_root = project.node_root();
+            let file = |path: &str, size: usize| -> Vc<Box<dyn OutputAsset>> {
+                Vc::upcast(VirtualOutputAsset::new(
+                    node_root.join(path.to_string()),
+                    AssetContent::file(FileContent::Content(File::from("a".repeat(size))).cell()),
+                ))
+            };
+            let mut messages = vec![];
+            for files in [
+                vec![
+                    file("server/edge/chunks/shared.js", 80),
+                    file("server/edge/chunks/middleware.js", 40),
+                    // Source maps aren't deployed.
+                    file("server/edge/chunks/shared.js.map", 1000),
+                ],
+                vec![file("server/edge/chunks/middleware.js", 40)],
+            ] {
+                let check = check_edge_bundle_size(
+                    project,
+                    project.project_path().join("middleware.js".to_string()),
+                    "middleware".to_string(),
+                    Vc::cell(files),
+                );
+                check.await?;
+                for issue in issues(check).await? {
+                    let description = issue.description.as_ref().map(styled_text);
+                    messages.push((styled_text(&issue.title), description.unwrap_or_default()));
+                }
+            }
+            Ok(messages)
+        })
+        .await
+        .unwrap();
+
+        // Only the function over the limit is reported.
+        assert_eq!(
+            messages,
+            [(
+                "Edge function middleware is 120 bytes, which exceeds the limit of 100 bytes"
+                    .to_string(),
+                [
+                    "Deploying it to an edge host may fail. The largest files are:",
+                    "- server/edge/chunks/shared.js (80 bytes)",
+                    "- server/edge/chunks/middleware.js (40 bytes)",
+                ]
+                .join("\n")
+            )]
+        );
+    }
+}
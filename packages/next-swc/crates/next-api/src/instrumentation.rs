@@ -4,7 +4,7 @@ use next_core::{
     mode::NextMode,
     next_edge::entry::wrap_edge_entry,
     next_manifests::{InstrumentationDefinition, MiddlewaresManifestV2},
-    next_server::{get_server_chunking_context, get_server_runtime_entries, ServerContextType},
+    next_server::{get_server_runtime_entries, ServerContextType},
 };
 use tracing::Instrument;
 use turbo_tasks::{Completion, Value, Vc};
@@ -26,6 +26,7 @@ use turbopack_binding::{
 };
 
 use crate::{
+    edge_bundle_size::check_edge_bundle_size,
     middleware::{get_js_paths_from_root, get_wasm_paths_from_root, wasm_paths_to_bindings},
     project::Project,
     route::{Endpoint, WrittenEndpoint},
@@ -104,13 +105,7 @@ impl InstrumentationEndpoint {
 
     #[turbo_tasks::function]
     async fn node_chunk(&self) -> Result<Vc<Box<dyn OutputAsset>>> {
-        let chunking_context = get_server_chunking_context(
-            self.project.project_path(),
-            self.project.node_root(),
-            self.project.client_relative_path(),
-            self.project.next_config().computed_asset_prefix(),
-            self.project.server_compile_time_info().environment(),
-        );
+        let chunking_context = self.project.server_chunking_context();
 
         let userland_module = self
             .context
@@ -153,6 +148,13 @@ impl InstrumentationEndpoint {
                 get_js_paths_from_root(&node_root_value, &output_assets).await?;
 
             let all_output_assets = all_assets_from_entries(edge_files).await?;
+            check_edge_bundle_size(
+                this.project,
+                this.source.ident().path(),
+                "instrumentation".to_string(),
+                edge_files,
+            )
+            .await?;
 
             let wasm_paths_from_root =
                 get_wasm_paths_from_root(&node_root_value, &all_output_assets).await?;
@@ -202,12 +204,34 @@ impl Endpoint for InstrumentationEndpoint {
                 .await?
                 .clone_value();
 
-            Ok(WrittenEndpoint::Edge { server_paths }.cell())
+            Ok(WrittenEndpoint::Edge {
+                server_paths,
+                config: Default::default(),
+            }
+            .cell())
         }
         .instrument(span)
         .await
     }
 
+    #[turbo_tasks::function]
+    async fn chunks(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
+        Ok(self.await?.project.output_asset_paths(self.output_assets()))
+    }
+
+    #[turbo_tasks::function]
+    async fn server_output(self: Vc<Self>) -> Result<Vc<FileContent>> {
+        let this = self.await?;
+        let entry_chunks = if this.is_edge {
+            self.edge_files()
+        } else {
+            Vc::cell(vec![self.node_chunk()])
+        };
+        Ok(this
+            .project
+            .server_output_content(self.output_assets(), entry_chunks))
+    }
+
     #[turbo_tasks::function]
     async fn server_changed(self: Vc<Self>) -> Result<Vc<Completion>> {
         Ok(self.await?.project.server_changed(self.output_assets()))
@@ -218,3 +242,60 @@ impl Endpoint for InstrumentationEndpoint {
         Completion::immutable()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{bail, Context};
+
+    use crate::{
+        route::Route,
+        test_utils::{fixture, fixture_project, TestTurboTasks},
+    };
+
+    #[tokio::test]
+    async fn compiles_instrumentation_once_for_all_routes() {
+        let dir = fixture(&[
+            ("instrumentation.ts", "export function register() {}"),
+            ("pages/a.tsx", "export default function A() {}"),
+            ("pages/b.tsx", "export default function B() {}"),
+        ]);
+        let root = dir.path().to_owned();
+        let turbo_tasks = TestTurboTasks::new();
+        turbo_tasks
+            .clone()
+            .run(async move {
+                let project = fixture_project(&root)?;
+                let instrumentation = project
+                    .entrypoints()
+                    .await?
+                    .instrumentation
+                    .as_ref()
+                    .context("instrumentation.ts isn't found")?
+                    .node_js;
+                instrumentation.chunks().await?;
+
+                for pathname in ["/a", "/b"] {
+                    let entrypoints = project.entrypoints().await?;
+                    let Some(Route::Page { html_endpoint, .. }) = entrypoints.routes.get(pathname)
+                    else {
+                        bail!("{pathname} isn't a page");
+                    };
+                    html_endpoint.chunks().await?;
+
+                    // Compiling the route doesn't compile the instrumentation hook
+                    // again, so reading its chunks doesn't add any task.
+                    let cached_tasks = turbo_tasks.cached_tasks();
+                    let instrumentation = entrypoints
+                        .instrumentation
+                        .as_ref()
+                        .context("instrumentation.ts isn't found")?
+                        .node_js;
+                    instrumentation.chunks().await?;
+                    assert_eq!(turbo_tasks.cached_tasks(), cached_tasks, "for {pathname}");
+                }
+                Ok(())
+            })
+            .await
+            .unwrap();
+    }
+}
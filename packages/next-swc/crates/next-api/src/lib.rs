@@ -2,15 +2,20 @@
 #![feature(arbitrary_self_types)]
 
 mod app;
+pub mod cache_stats;
 mod dynamic_imports;
+mod edge_bundle_size;
 mod entrypoints;
 mod instrumentation;
 mod middleware;
+mod overlay_fs;
 mod pages;
 pub mod project;
 pub mod route;
 mod server_actions;
 pub mod server_paths;
+#[cfg(test)]
+mod test_utils;
 mod versioned_content_map;
 
 // Declare build-time information variables generated in build.rs
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use anyhow::{bail, Context, Result};
 use next_core::{
     all_assets_from_entries,
@@ -30,11 +32,26 @@ use turbopack_binding::{
 };
 
 use crate::{
+    edge_bundle_size::check_edge_bundle_size,
     project::Project,
     route::{Endpoint, WrittenEndpoint},
     server_paths::all_server_paths,
 };
 
+/// The files an edge host needs to load to run the middleware.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+pub struct MiddlewareChunks {
+    /// The JavaScript chunks, relative to the node root, in the order they
+    /// need to be loaded.
+    pub files: Vec<String>,
+    /// The `.wasm` files that need to be bound as globals.
+    pub wasm: Vec<AssetBinding>,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionMiddlewareChunks(Option<Vc<MiddlewareChunks>>);
+
 #[turbo_tasks::value]
 pub struct MiddlewareEndpoint {
     project: Vc<Project>,
@@ -105,6 +122,24 @@ impl MiddlewareEndpoint {
         Ok(edge_files)
     }
 
+    /// Returns the chunks of the middleware, without writing them to disk.
+    #[turbo_tasks::function]
+    pub async fn chunks(self: Vc<Self>) -> Result<Vc<MiddlewareChunks>> {
+        let edge_files = self.edge_files();
+        let node_root = self.await?.project.node_root().await?;
+
+        let files = get_js_paths_from_root(&node_root, &edge_files.await?).await?;
+
+        let all_output_assets = all_assets_from_entries(edge_files).await?;
+        let wasm_paths_from_root = get_wasm_paths_from_root(&node_root, &all_output_assets).await?;
+
+        Ok(MiddlewareChunks {
+            files,
+            wasm: wasm_paths_to_bindings(wasm_paths_from_root),
+        }
+        .cell())
+    }
+
     #[turbo_tasks::function]
     async fn output_assets(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
         let this = self.await?;
@@ -119,18 +154,17 @@ impl MiddlewareEndpoint {
 
         let config = parse_config_from_source(userland_module);
 
-        let edge_files = self.edge_files();
-        let mut output_assets = edge_files.await?.clone_value();
+        let mut output_assets = self.edge_files().await?.clone_value();
+        check_edge_bundle_size(
+            this.project,
+            this.source.ident().path(),
+            "middleware".to_string(),
+            self.edge_files(),
+        )
+        .await?;
+        let chunks = self.chunks().await?;
 
         let node_root = this.project.node_root();
-        let node_root_value = node_root.await?;
-
-        let file_paths_from_root = get_js_paths_from_root(&node_root_value, &output_assets).await?;
-
-        let all_output_assets = all_assets_from_entries(edge_files).await?;
-
-        let wasm_paths_from_root =
-            get_wasm_paths_from_root(&node_root_value, &all_output_assets).await?;
 
         let matchers = if let Some(matchers) = config.await?.matcher.as_ref() {
             matchers
@@ -149,8 +183,8 @@ impl MiddlewareEndpoint {
         };
 
         let edge_function_definition = EdgeFunctionDefinition {
-            files: file_paths_from_root,
-            wasm: wasm_paths_to_bindings(wasm_paths_from_root),
+            files: chunks.files.clone(),
+            wasm: chunks.wasm.clone(),
             name: "middleware".to_string(),
             page: "/".to_string(),
             regions: None,
@@ -195,12 +229,29 @@ impl Endpoint for MiddlewareEndpoint {
                 .await?
                 .clone_value();
 
-            Ok(WrittenEndpoint::Edge { server_paths }.cell())
+            Ok(WrittenEndpoint::Edge {
+                server_paths,
+                config: Default::default(),
+            }
+            .cell())
         }
         .instrument(span)
         .await
     }
 
+    #[turbo_tasks::function]
+    async fn chunks(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
+        Ok(self.await?.project.output_asset_paths(self.output_assets()))
+    }
+
+    #[turbo_tasks::function]
+    async fn server_output(self: Vc<Self>) -> Result<Vc<FileContent>> {
+        Ok(self
+            .await?
+            .project
+            .server_output_content(self.output_assets(), self.edge_files()))
+    }
+
     #[turbo_tasks::function]
     async fn server_changed(self: Vc<Self>) -> Result<Vc<Completion>> {
         Ok(self.await?.project.server_changed(self.output_assets()))
@@ -269,9 +320,17 @@ fn get_file_stem(path: &str) -> &str {
     }
 }
 
+/// Creates the edge function bindings for the `.wasm` files an edge function
+/// loads. The edge runtime instantiates them ahead of time and exposes them as
+/// globals, since edge functions can't read them from disk.
+///
+/// The same file can be reachable from multiple entries, so `paths` may
+/// contain duplicates. Those are bound only once.
 pub(crate) fn wasm_paths_to_bindings(paths: Vec<String>) -> Vec<AssetBinding> {
+    let mut seen = HashSet::new();
     paths
         .into_iter()
+        .filter(|path| seen.insert(path.clone()))
         .map(|path| {
             let stem = get_file_stem(&path);
 
@@ -288,3 +347,149 @@ pub(crate) fn wasm_paths_to_bindings(paths: Vec<String>) -> Vec<AssetBinding> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use indoc::indoc;
+    use turbo_tasks::Vc;
+    use turbopack_binding::{
+        turbo::tasks_fs::FileContent,
+        turbopack::core::{asset::Asset, file_source::FileSource, output::OutputAsset},
+    };
+
+    use super::wasm_paths_to_bindings;
+    use crate::{
+        route::Endpoint,
+        test_utils::{fixture, fixture_project, run},
+    };
+
+    /// The paths of the files in `dir` and its subdirectories, relative to
+    /// `root`.
+    fn files_in(root: &Path, dir: &Path, files: &mut Vec<String>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                files_in(root, &path, files);
+            } else {
+                let relative = path.strip_prefix(root).unwrap();
+                files.push(relative.to_str().unwrap().replace('\\', "/"));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_the_chunks_it_writes() {
+        let dir = fixture(&[(
+            "middleware.ts",
+            "export default function middleware() { return new Response('hello'); }",
+        )]);
+        let root = dir.path().to_owned();
+        let chunks = run(async move {
+            let project = fixture_project(&root)?;
+            let endpoint =
+                Vc::upcast::<Box<dyn Endpoint>>(project.middleware_endpoint(Vc::upcast(
+                    FileSource::new(project.project_path().join("middleware.ts".to_string())),
+                )));
+            // Listing the chunks doesn't write them.
+            let chunks = endpoint.chunks().await?.clone_value();
+            assert!(!root.join(".next").exists());
+
+            endpoint.write_to_disk().strongly_consistent().await?;
+            Ok(chunks)
+        })
+        .await
+        .unwrap();
+
+        let node_root = dir.path().join(".next");
+        let mut written = vec![];
+        files_in(&node_root, &node_root, &mut written);
+        written.sort();
+        assert!(!chunks.is_empty());
+        assert_eq!(chunks, written);
+    }
+
+    #[tokio::test]
+    async fn returns_the_middleware_chunks_in_load_order() {
+        let dir = fixture(&[
+            (
+                "middleware.ts",
+                indoc! {"
+                    import { greet } from './lib/helper';
+
+                    export default function middleware() {
+                      return new Response(greet('from the middleware'));
+                    }
+                "},
+            ),
+            (
+                "lib/helper.ts",
+                "export function greet(name: string) { return 'hello from the helper, ' + name; }",
+            ),
+        ]);
+        let root = dir.path().to_owned();
+        let (files, project_files, contents) = run(async move {
+            let project = fixture_project(&root)?;
+            let endpoint = project.middleware_endpoint(Vc::upcast(FileSource::new(
+                project.project_path().join("middleware.ts".to_string()),
+            )));
+            let files = endpoint.chunks().await?.files.clone();
+            // The project finds the same middleware.
+            let project_files = match *project.middleware_chunks().await? {
+                Some(chunks) => Some(chunks.await?.files.clone()),
+                None => None,
+            };
+
+            let node_root = project.node_root().await?;
+            let mut contents = vec![];
+            for &chunk in endpoint.edge_files().await?.iter() {
+                let path = chunk.ident().path().await?;
+                let Some(path) = node_root.get_path_to(&path) else {
+                    continue;
+                };
+                if let FileContent::Content(file) = &*chunk.content().file_content().await? {
+                    contents.push((path.to_string(), file.content().to_str()?.into_owned()));
+                }
+            }
+            Ok((files, project_files, contents))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(project_files.as_ref(), Some(&files));
+        let position = |code: &str| {
+            files.iter().position(|file| {
+                contents
+                    .iter()
+                    .any(|(path, content)| path == file && content.contains(code))
+            })
+        };
+        let helper = position("hello from the helper").expect("the helper isn't loaded");
+        let entry = position("from the middleware").expect("the middleware isn't loaded");
+        assert!(
+            helper <= entry,
+            "the helper must be loaded with or before the middleware: {files:?}"
+        );
+    }
+
+    #[test]
+    fn binds_each_wasm_file_once() {
+        let bindings = wasm_paths_to_bindings(vec![
+            "server/edge/chunks/add.0a1b2c.wasm".to_string(),
+            "server/edge/chunks/parse-url.3d4e5f.wasm".to_string(),
+            "server/edge/chunks/add.0a1b2c.wasm".to_string(),
+        ]);
+        let bindings: Vec<_> = bindings
+            .iter()
+            .map(|binding| (binding.name.as_str(), binding.file_path.as_str()))
+            .collect();
+        assert_eq!(
+            bindings,
+            [
+                ("wasm_add", "server/edge/chunks/add.0a1b2c.wasm"),
+                ("wasm_parse_url", "server/edge/chunks/parse-url.3d4e5f.wasm"),
+            ]
+        );
+    }
+}
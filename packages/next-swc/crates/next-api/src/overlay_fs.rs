@@ -0,0 +1,163 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use turbo_tasks::{Completion, ValueToString, Vc};
+use turbopack_binding::turbo::tasks_fs::{
+    DirectoryContent, DirectoryEntry, File, FileContent, FileMeta, FileSystem, FileSystemPath,
+    LinkContent,
+};
+
+/// In-memory files, from their path relative to the root of the filesystem
+/// to their content.
+#[turbo_tasks::value(transparent)]
+pub struct OverlayFiles(IndexMap<String, String>);
+
+/// Serves the `files` in place of the files at the same paths of the `base`
+/// filesystem, e.g. for tests defining a page in code. Directories only
+/// containing overlay files exist as well. Everything else, including writes,
+/// goes to the `base` filesystem.
+#[turbo_tasks::value]
+pub struct OverlayFileSystem {
+    base: Vc<Box<dyn FileSystem>>,
+    files: Vc<OverlayFiles>,
+}
+
+#[turbo_tasks::value_impl]
+impl OverlayFileSystem {
+    #[turbo_tasks::function]
+    pub fn new(base: Vc<Box<dyn FileSystem>>, files: Vc<OverlayFiles>) -> Vc<Self> {
+        OverlayFileSystem { base, files }.cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn base_path(&self, fs_path: Vc<FileSystemPath>) -> Result<Vc<FileSystemPath>> {
+        Ok(self.base.root().join(fs_path.await?.path.clone()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl FileSystem for OverlayFileSystem {
+    #[turbo_tasks::function]
+    async fn read(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Result<Vc<FileContent>> {
+        let this = self.await?;
+        if let Some(content) = this.files.await?.get(&fs_path.await?.path) {
+            return Ok(FileContent::Content(File::from(content.clone())).cell());
+        }
+        Ok(this.base.read(self.base_path(fs_path)))
+    }
+
+    #[turbo_tasks::function]
+    async fn read_link(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Result<Vc<LinkContent>> {
+        let this = self.await?;
+        if this.files.await?.contains_key(&fs_path.await?.path) {
+            return Ok(LinkContent::NotFound.cell());
+        }
+        Ok(this.base.read_link(self.base_path(fs_path)))
+    }
+
+    #[turbo_tasks::function]
+    async fn read_dir(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Result<Vc<DirectoryContent>> {
+        let this = self.await?;
+        let dir = fs_path.await?;
+
+        // The entries of the base filesystem point into it, so they're
+        // rebased onto this one.
+        let mut entries = IndexMap::new();
+        if let DirectoryContent::Entries(base_entries) =
+            &*this.base.read_dir(self.base_path(fs_path)).await?
+        {
+            for (name, entry) in base_entries.iter() {
+                let path = fs_path.join(name.clone());
+                let entry = match entry {
+                    DirectoryEntry::File(_) => DirectoryEntry::File(path),
+                    DirectoryEntry::Directory(_) => DirectoryEntry::Directory(path),
+                    DirectoryEntry::Symlink(_) => DirectoryEntry::Symlink(path),
+                    DirectoryEntry::Other(_) => DirectoryEntry::Other(path),
+                    DirectoryEntry::Error => DirectoryEntry::Error,
+                };
+                entries.insert(name.clone(), entry);
+            }
+        } else if !this
+            .files
+            .await?
+            .keys()
+            .any(|path| overlay_entry(&dir.path, path).is_some())
+        {
+            return Ok(DirectoryContent::not_found());
+        }
+
+        for path in this.files.await?.keys() {
+            let Some((name, is_file)) = overlay_entry(&dir.path, path) else {
+                continue;
+            };
+            let path = fs_path.join(name.to_string());
+            entries.insert(
+                name.to_string(),
+                if is_file {
+                    DirectoryEntry::File(path)
+                } else {
+                    DirectoryEntry::Directory(path)
+                },
+            );
+        }
+
+        Ok(DirectoryContent::new(entries.into_iter().collect()))
+    }
+
+    #[turbo_tasks::function]
+    fn track(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Vc<Completion> {
+        self.base_path(fs_path).track()
+    }
+
+    #[turbo_tasks::function]
+    async fn write(
+        self: Vc<Self>,
+        fs_path: Vc<FileSystemPath>,
+        content: Vc<FileContent>,
+    ) -> Result<Vc<Completion>> {
+        Ok(self.await?.base.write(self.base_path(fs_path), content))
+    }
+
+    #[turbo_tasks::function]
+    async fn write_link(
+        self: Vc<Self>,
+        fs_path: Vc<FileSystemPath>,
+        target: Vc<LinkContent>,
+    ) -> Result<Vc<Completion>> {
+        Ok(self.await?.base.write_link(self.base_path(fs_path), target))
+    }
+
+    #[turbo_tasks::function]
+    async fn metadata(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Result<Vc<FileMeta>> {
+        let this = self.await?;
+        if this.files.await?.contains_key(&fs_path.await?.path) {
+            return Ok(FileMeta::default().cell());
+        }
+        Ok(this.base.metadata(self.base_path(fs_path)))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for OverlayFileSystem {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<Vc<String>> {
+        Ok(Vc::cell(format!(
+            "overlay of {}",
+            self.base.to_string().await?
+        )))
+    }
+}
+
+/// The name of the entry of the directory `dir` that the overlay file at
+/// `path` creates, and whether it's the file itself rather than a directory
+/// containing it.
+fn overlay_entry<'a>(dir: &str, path: &'a str) -> Option<(&'a str, bool)> {
+    let relative = if dir.is_empty() {
+        path
+    } else {
+        path.strip_prefix(dir)?.strip_prefix('/')?
+    };
+    Some(match relative.split_once('/') {
+        Some((name, _)) => (name, false),
+        None => (relative, true),
+    })
+}
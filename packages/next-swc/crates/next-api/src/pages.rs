@@ -22,9 +22,12 @@ use next_core::{
         get_server_runtime_entries, ServerContextType,
     },
     pages_structure::{
-        find_pages_structure, PagesDirectoryStructure, PagesStructure, PagesStructureItem,
+        find_pages_structure, is_special_page, PagesDirectoryStructure, PagesStructure,
+        PagesStructureItem,
+    },
+    util::{
+        get_asset_prefix_from_pathname, parse_config_from_source, NextRuntime, NextSourceConfig,
     },
-    util::{get_asset_prefix_from_pathname, parse_config_from_source, NextRuntime},
     PageLoaderAsset,
 };
 use serde::{Deserialize, Serialize};
@@ -70,9 +73,10 @@ use crate::{
         collect_chunk_group, collect_evaluated_chunk_group, collect_next_dynamic_imports,
         DynamicImportedChunks,
     },
+    edge_bundle_size::check_edge_bundle_size,
     middleware::{get_js_paths_from_root, get_wasm_paths_from_root, wasm_paths_to_bindings},
     project::Project,
-    route::{Endpoint, Route, Routes, WrittenEndpoint},
+    route::{Endpoint, EndpointConfig, Route, RouteSource, RouteSources, Routes, WrittenEndpoint},
     server_paths::all_server_paths,
 };
 
@@ -111,6 +115,10 @@ impl PagesProject {
                 original_path,
             } = *page.await?;
             let pathname = format!("/{}", next_router_path.await?.path);
+            // `_app` and `_document` are served through the page entries.
+            if is_special_page(&pathname) {
+                return Ok(());
+            }
             let pathname_vc = Vc::cell(pathname.clone());
             let original_name = Vc::cell(format!("/{}", original_path.await?.path));
             let route = make_route(pathname_vc, original_name, project_path);
@@ -180,6 +188,37 @@ impl PagesProject {
         Ok(Vc::cell(routes))
     }
 
+    /// Returns the files backing the pages and API routes, in the order of
+    /// [PagesProject::routes].
+    #[turbo_tasks::function]
+    pub async fn route_sources(self: Vc<Self>) -> Result<Vc<RouteSources>> {
+        let PagesStructure { api, pages, .. } = &*self.pages_structure().await?;
+        let mut sources = vec![];
+        for dir in [api, pages].into_iter().flatten() {
+            let mut queue = vec![*dir];
+            while let Some(dir) = queue.pop() {
+                let dir = dir.await?;
+                for item in dir.items.iter() {
+                    let PagesStructureItem {
+                        next_router_path,
+                        project_path,
+                        ..
+                    } = *item.await?;
+                    let pathname = format!("/{}", next_router_path.await?.path);
+                    if is_special_page(&pathname) {
+                        continue;
+                    }
+                    sources.push(RouteSource {
+                        pathname,
+                        path: project_path,
+                    });
+                }
+                queue.extend(dir.children.iter().copied());
+            }
+        }
+        Ok(Vc::cell(sources))
+    }
+
     #[turbo_tasks::function]
     async fn to_endpoint(
         self: Vc<Self>,
@@ -223,6 +262,24 @@ impl PagesProject {
         Ok(self.to_endpoint(self.pages_structure().await?.error, PageEndpointType::Html))
     }
 
+    /// An endpoint serving the handler module at `path` like an API route,
+    /// for routes that don't come from the pages directory.
+    #[turbo_tasks::function]
+    pub fn api_endpoint(
+        self: Vc<Self>,
+        pathname: String,
+        path: Vc<FileSystemPath>,
+    ) -> Vc<Box<dyn Endpoint>> {
+        let pathname = Vc::cell(pathname);
+        Vc::upcast(PageEndpoint::new(
+            PageEndpointType::Api,
+            self,
+            pathname,
+            pathname,
+            path,
+        ))
+    }
+
     #[turbo_tasks::function]
     fn project(&self) -> Vc<Project> {
         self.project
@@ -233,7 +290,7 @@ impl PagesProject {
         let next_router_fs = Vc::upcast::<Box<dyn FileSystem>>(VirtualFileSystem::new());
         let next_router_root = next_router_fs.root();
         find_pages_structure(
-            self.project.project_path(),
+            self.project.routes_root(),
             next_router_root,
             self.project.next_config().page_extensions(),
         )
@@ -244,7 +301,7 @@ impl PagesProject {
         Ok(if let Some(pages) = self.pages_structure().await?.pages {
             pages.project_path()
         } else {
-            self.project().project_path().join("pages".to_string())
+            self.project().routes_root().join("pages".to_string())
         })
     }
 
@@ -787,6 +844,34 @@ impl PageEndpoint {
         ))
     }
 
+    /// The config exported by the page's source, parsed in the same module
+    /// context as [PageEndpoint::internal_ssr_chunk] does.
+    #[turbo_tasks::function]
+    async fn source_config(self: Vc<Self>) -> Result<Vc<NextSourceConfig>> {
+        let this = self.await?;
+        let (module_context, reference_type) = match this.ty {
+            PageEndpointType::Html | PageEndpointType::SsrOnly => (
+                this.pages_project.ssr_module_context(),
+                EntryReferenceSubType::Page,
+            ),
+            PageEndpointType::Data => (
+                this.pages_project.ssr_data_module_context(),
+                EntryReferenceSubType::Page,
+            ),
+            PageEndpointType::Api => (
+                this.pages_project.api_module_context(),
+                EntryReferenceSubType::PagesApi,
+            ),
+        };
+        let ssr_module = module_context
+            .process(
+                self.source(),
+                Value::new(ReferenceType::Entry(reference_type)),
+            )
+            .module();
+        Ok(parse_config_from_source(ssr_module))
+    }
+
     #[turbo_tasks::function]
     async fn pages_manifest(
         self: Vc<Self>,
@@ -1015,6 +1100,13 @@ impl PageEndpoint {
                     ..Default::default()
                 };
                 let original_name = this.original_name.await?;
+                check_edge_bundle_size(
+                    this.pages_project.project(),
+                    this.path,
+                    original_name.to_string(),
+                    files,
+                )
+                .await?;
                 let edge_function_definition = EdgeFunctionDefinition {
                     files: file_paths_from_root,
                     wasm: wasm_paths_to_bindings(wasm_paths_from_root),
@@ -1103,6 +1195,10 @@ impl Endpoint for PageEndpoint {
                 .await?
                 .clone_value();
 
+            let config = EndpointConfig {
+                max_duration: self.source_config().await?.max_duration,
+            };
+
             let node_root = &node_root.await?;
             let written_endpoint = match *output.await? {
                 PageEndpointOutput::NodeJs { entry_chunk, .. } => WrittenEndpoint::NodeJs {
@@ -1111,8 +1207,12 @@ impl Endpoint for PageEndpoint {
                         .context("ssr chunk entry path must be inside the node root")?
                         .to_string(),
                     server_paths,
+                    config,
+                },
+                PageEndpointOutput::Edge { .. } => WrittenEndpoint::Edge {
+                    server_paths,
+                    config,
                 },
-                PageEndpointOutput::Edge { .. } => WrittenEndpoint::Edge { server_paths },
             };
 
             Ok(written_endpoint.cell())
@@ -1121,6 +1221,28 @@ impl Endpoint for PageEndpoint {
         .await
     }
 
+    #[turbo_tasks::function]
+    async fn chunks(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
+        Ok(self
+            .await?
+            .pages_project
+            .project()
+            .output_asset_paths(self.output_assets()))
+    }
+
+    #[turbo_tasks::function]
+    async fn server_output(self: Vc<Self>) -> Result<Vc<FileContent>> {
+        let entry_chunks = match *self.output().await? {
+            PageEndpointOutput::NodeJs { entry_chunk, .. } => Vc::cell(vec![entry_chunk]),
+            PageEndpointOutput::Edge { files, .. } => files,
+        };
+        Ok(self
+            .await?
+            .pages_project
+            .project()
+            .server_output_content(self.output_assets(), entry_chunks))
+    }
+
     #[turbo_tasks::function]
     async fn server_changed(self: Vc<Self>) -> Result<Vc<Completion>> {
         Ok(self
@@ -1197,3 +1319,136 @@ pub enum SsrChunk {
         dynamic_import_entries: Vc<DynamicImportedChunks>,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{bail, Context};
+    use indoc::indoc;
+    use next_core::{
+        mode::NextMode,
+        test_utils::{issues, styled_text},
+    };
+    use turbo_tasks::Vc;
+
+    use super::{PageEndpoint, PagesProject};
+    use crate::{
+        route::Route,
+        test_utils::{fixture, fixture_project, run},
+    };
+
+    #[tokio::test]
+    async fn applies_app_to_pages_without_routing_to_it() {
+        let dir = fixture(&[
+            (
+                "pages/_app.tsx",
+                indoc! {"
+                    export default function App({ Component, pageProps }) {
+                      return <Component {...pageProps} />;
+                    }
+                "},
+            ),
+            (
+                "pages/_document.tsx",
+                indoc! {"
+                    import Document from 'next/document';
+
+                    export default class MyDocument extends Document {
+                      static async getInitialProps(ctx) {
+                        return Document.getInitialProps(ctx);
+                      }
+                    }
+                "},
+            ),
+            (
+                "pages/index.tsx",
+                "export default function Home() { return null; }",
+            ),
+            (
+                "pages/about.tsx",
+                "export default function About() { return null; }",
+            ),
+        ]);
+        let root = dir.path().to_owned();
+        let (routes, app, document) = run(async move {
+            let project = fixture_project(&root)?;
+            let pages_project = PagesProject::new(project, NextMode::Development);
+            let mut routes: Vec<_> = pages_project.routes().await?.keys().cloned().collect();
+            routes.sort();
+            let pages_structure = pages_project.pages_structure().await?;
+            let app = pages_structure.app.await?.project_path.await?.path.clone();
+            let document = pages_structure
+                .document
+                .await?
+                .project_path
+                .await?
+                .path
+                .clone();
+            Ok((routes, app, document))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(routes, ["/", "/about"]);
+        // Every page is rendered with them.
+        assert_eq!(app, "pages/_app.tsx");
+        assert_eq!(document, "pages/_document.tsx");
+    }
+
+    #[tokio::test]
+    async fn reads_the_max_duration_of_a_route() {
+        let dir = fixture(&[
+            (
+                "pages/api/slow.js",
+                indoc! {"
+                    export const maxDuration = 30;
+                    export default function handler() {}
+                "},
+            ),
+            (
+                "pages/api/invalid.js",
+                indoc! {"
+                    export const maxDuration = 1.5;
+                    export default function handler() {}
+                "},
+            ),
+        ]);
+        let root = dir.path().to_owned();
+        let (slow, invalid, invalid_issues) = run(async move {
+            let pages_project = PagesProject::new(fixture_project(&root)?, NextMode::Development);
+            let routes = pages_project.routes().await?;
+            let source_config = |pathname: &'static str| {
+                let route = routes.get(pathname).copied();
+                async move {
+                    let Some(Route::PageApi { endpoint }) = route else {
+                        bail!("{pathname} isn't an API route");
+                    };
+                    let endpoint = Vc::try_resolve_downcast_type::<PageEndpoint>(endpoint)
+                        .await?
+                        .context("API routes are page endpoints")?;
+                    anyhow::Ok(endpoint.source_config())
+                }
+            };
+            let slow = source_config("/api/slow").await?;
+            let invalid = source_config("/api/invalid").await?;
+            Ok((
+                slow.await?.max_duration,
+                invalid.await?.max_duration,
+                issues(invalid)
+                    .await?
+                    .iter()
+                    .map(|issue| styled_text(&issue.title))
+                    .collect::<Vec<_>>(),
+            ))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(slow, Some(30));
+        // A non-integer value isn't used, but reported.
+        assert_eq!(invalid, None);
+        assert_eq!(
+            invalid_issues,
+            ["Unable to parse config export in source file"]
+        );
+    }
+}
@@ -1,20 +1,21 @@
 use std::{net::SocketAddr, path::MAIN_SEPARATOR};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use indexmap::{map::Entry, IndexMap};
 use next_core::{
     all_assets_from_entries,
-    app_structure::find_app_dir,
+    app_structure::{find_app_dir, validate_entrypoints},
     emit_assets, get_edge_chunking_context, get_edge_compile_time_info,
     get_edge_resolve_options_context,
     instrumentation::instrumentation_files,
     middleware::middleware_files,
     mode::NextMode,
-    next_client::{get_client_chunking_context, get_client_compile_time_info},
+    next_client::{get_client_chunking_context, get_client_compile_time_info, ClientRuntime},
     next_config::{JsConfig, NextConfig},
     next_server::{
         get_server_chunking_context, get_server_compile_time_info,
-        get_server_module_options_context, get_server_resolve_options_context, ServerContextType,
+        get_server_module_options_context, get_server_resolve_options_context, server_minify,
+        ServerContextType,
     },
     next_telemetry::NextFeatureTelemetry,
 };
@@ -25,23 +26,30 @@ use turbo_tasks::{
     graph::{AdjacencyMap, GraphTraversal},
     trace::TraceRawVcs,
     Completion, Completions, IntoTraitRef, State, TaskInput, TraitRef, TransientInstance,
-    TryFlatJoinIterExt, Value, Vc,
+    TryFlatJoinIterExt, TryJoinIterExt, Value, Vc,
 };
 use turbopack_binding::{
     turbo::{
         tasks_env::{EnvMap, ProcessEnv},
-        tasks_fs::{DiskFileSystem, FileSystem, FileSystemPath, VirtualFileSystem},
+        tasks_fs::{
+            rope::RopeBuilder, DiskFileSystem, FileContent, FileSystem, FileSystemPath,
+            VirtualFileSystem,
+        },
     },
     turbopack::{
         build::BuildChunkingContext,
         core::{
+            asset::{Asset, AssetContent},
             changed::content_changed,
+            chunk::{ChunkableModule, ChunkingContextExt},
             compile_time_info::CompileTimeInfo,
             context::AssetContext,
             diagnostics::DiagnosticExt,
             environment::ServerAddr,
             file_source::FileSource,
+            issue::{Issue, IssueExt, IssueSeverity, OptionStyledString, StyledString},
             output::{OutputAsset, OutputAssets},
+            reference_type::{EntryReferenceSubType, ReferenceType},
             resolve::{find_context_file, FindContextFileResult},
             source::Source,
             version::{Update, Version, VersionState, VersionedContent},
@@ -55,13 +63,14 @@ use turbopack_binding::{
 };
 
 use crate::{
-    app::{AppProject, OptionAppProject},
+    app::{app_page_rsc_changed, AppProject, ClientReferenceSources, OptionAppProject},
     build,
     entrypoints::Entrypoints,
     instrumentation::InstrumentationEndpoint,
-    middleware::MiddlewareEndpoint,
+    middleware::{MiddlewareEndpoint, OptionMiddlewareChunks},
+    overlay_fs::{OverlayFileSystem, OverlayFiles},
     pages::PagesProject,
-    route::{Endpoint, Route},
+    route::{Endpoint, Route, RouteSource, RouteSources},
     versioned_content_map::{OutputAssetsOperation, VersionedContentMap},
 };
 
@@ -75,12 +84,22 @@ pub struct ProjectOptions {
     /// A path inside the root_path which contains the app/pages directories.
     pub project_path: String,
 
+    /// The directory containing the app/pages directories, relative to
+    /// project_path, when they aren't directly inside it. It must be inside
+    /// root_path.
+    pub routes_dir: Option<String>,
+
     /// The contents of next.config.js, serialized to JSON.
     pub next_config: String,
 
     /// The contents of ts/config read by load-jsconfig, serialized to JSON.
     pub js_config: String,
 
+    /// The tsconfig to compile with, relative to project_path, e.g.
+    /// `tsconfig.build.json`. Overrides next.config.js's
+    /// `typescript.tsconfigPath`.
+    pub tsconfig_path: Option<String>,
+
     /// A map of environment variables to use when compiling code.
     pub env: Vec<(String, String)>,
 
@@ -93,6 +112,11 @@ pub struct ProjectOptions {
 
     /// The address of the dev server.
     pub server_addr: String,
+
+    /// In-memory source files layered over the disk, from their path relative
+    /// to root_path to their content, e.g. for tests defining a page in code.
+    /// They shadow the files at the same paths on disk.
+    pub overlay_files: Vec<(String, String)>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, TaskInput, PartialEq, Eq, TraceRawVcs)]
@@ -105,12 +129,19 @@ pub struct PartialProjectOptions {
     /// A path inside the root_path which contains the app/pages directories.
     pub project_path: Option<String>,
 
+    /// The directory containing the app/pages directories, relative to
+    /// project_path.
+    pub routes_dir: Option<Option<String>>,
+
     /// The contents of next.config.js, serialized to JSON.
     pub next_config: Option<String>,
 
     /// The contents of ts/config read by load-jsconfig, serialized to JSON.
     pub js_config: Option<String>,
 
+    /// The tsconfig to compile with, relative to project_path.
+    pub tsconfig_path: Option<Option<String>>,
+
     /// A map of environment variables to use when compiling code.
     pub env: Option<Vec<(String, String)>>,
 
@@ -123,6 +154,9 @@ pub struct PartialProjectOptions {
 
     /// The address of the dev server.
     pub server_addr: Option<String>,
+
+    /// In-memory source files layered over the disk.
+    pub overlay_files: Option<Vec<(String, String)>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, TaskInput, PartialEq, Eq, TraceRawVcs)]
@@ -144,6 +178,56 @@ pub struct Instrumentation {
     pub edge: Vc<Box<dyn Endpoint>>,
 }
 
+/// The metadata file convention defining an app icon.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub enum AppIconKind {
+    /// `app/favicon.ico`.
+    Favicon,
+    /// `icon.*` in any segment, e.g. `app/icon.png` or `app/icon.tsx`.
+    Icon,
+    /// `apple-icon.*` in any segment.
+    AppleIcon,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub struct AppIcon {
+    pub kind: AppIconKind,
+    /// The url the HTML head references the icon with.
+    pub url: String,
+    /// The endpoint generating the icon, for dynamic icons like
+    /// `app/icon.tsx`. Static icons are emitted to the client output instead.
+    pub endpoint: Option<Vc<Box<dyn Endpoint>>>,
+}
+
+/// The favicon and icons of the app directory.
+#[turbo_tasks::value(shared)]
+pub struct AppIcons {
+    pub icons: Vec<AppIcon>,
+    /// The static icon files, in the client output.
+    pub output_assets: Vc<OutputAssets>,
+}
+
+#[turbo_tasks::value_impl]
+impl AppIcons {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        AppIcons {
+            icons: vec![],
+            output_assets: OutputAssets::empty(),
+        }
+        .cell()
+    }
+}
+
+/// The context an entry passed to [Project::compile_entry] is compiled in.
+#[derive(Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Debug, TaskInput, TraceRawVcs)]
+pub enum EntryKind {
+    /// Compiled for the browser, like client components and pages.
+    Client,
+    /// Compiled for Node.js, like API routes.
+    Server,
+}
+
 #[turbo_tasks::value]
 pub struct ProjectContainer {
     options_state: State<ProjectOptions>,
@@ -171,12 +255,18 @@ impl ProjectContainer {
         if let Some(project_path) = options.project_path {
             new_options.project_path = project_path;
         }
+        if let Some(routes_dir) = options.routes_dir {
+            new_options.routes_dir = routes_dir;
+        }
         if let Some(next_config) = options.next_config {
             new_options.next_config = next_config;
         }
         if let Some(js_config) = options.js_config {
             new_options.js_config = js_config;
         }
+        if let Some(tsconfig_path) = options.tsconfig_path {
+            new_options.tsconfig_path = tsconfig_path;
+        }
         if let Some(env) = options.env {
             new_options.env = env;
         }
@@ -189,6 +279,9 @@ impl ProjectContainer {
         if let Some(server_addr) = options.server_addr {
             new_options.server_addr = server_addr;
         }
+        if let Some(overlay_files) = options.overlay_files {
+            new_options.overlay_files = overlay_files;
+        }
 
         self.options_state.set(new_options);
 
@@ -199,7 +292,18 @@ impl ProjectContainer {
     pub async fn project(self: Vc<Self>) -> Result<Vc<Project>> {
         let this = self.await?;
 
-        let (env, define_env, next_config, js_config, root_path, project_path, watch, server_addr) = {
+        let (
+            env,
+            define_env,
+            next_config,
+            js_config,
+            root_path,
+            project_path,
+            routes_dir,
+            watch,
+            server_addr,
+            overlay_files,
+        ) = {
             let options = this.options_state.get();
             let env: Vc<EnvMap> = Vc::cell(options.env.iter().cloned().collect());
             let define_env: Vc<ProjectDefineEnv> = ProjectDefineEnv {
@@ -208,12 +312,18 @@ impl ProjectContainer {
                 nodejs: Vc::cell(options.define_env.nodejs.iter().cloned().collect()),
             }
             .cell();
-            let next_config = NextConfig::from_string(Vc::cell(options.next_config.clone()));
+            let mut next_config = NextConfig::from_string(Vc::cell(options.next_config.clone()));
+            if let Some(tsconfig_path) = &options.tsconfig_path {
+                next_config = next_config.with_ts_config_path(tsconfig_path.clone());
+            }
             let js_config = JsConfig::from_string(Vc::cell(options.js_config.clone()));
             let root_path = options.root_path.clone();
             let project_path = options.project_path.clone();
+            let routes_dir = options.routes_dir.clone();
             let watch = options.watch;
             let server_addr = options.server_addr.parse()?;
+            let overlay_files: Vc<OverlayFiles> =
+                Vc::cell(options.overlay_files.iter().cloned().collect());
             (
                 env,
                 define_env,
@@ -221,8 +331,10 @@ impl ProjectContainer {
                 js_config,
                 root_path,
                 project_path,
+                routes_dir,
                 watch,
                 server_addr,
+                overlay_files,
             )
         };
 
@@ -235,8 +347,10 @@ impl ProjectContainer {
         Ok(Project {
             root_path,
             project_path,
+            routes_dir,
             watch,
             server_addr,
+            overlay_files,
             next_config,
             js_config,
             dist_dir,
@@ -285,6 +399,10 @@ pub struct Project {
     /// A path inside the root_path which contains the app/pages directories.
     pub project_path: String,
 
+    /// The directory containing the app/pages directories, relative to
+    /// project_path. See [Project::routes_root].
+    routes_dir: Option<String>,
+
     /// Whether to watch the filesystem for file changes.
     watch: bool,
 
@@ -292,6 +410,9 @@ pub struct Project {
     #[turbo_tasks(trace_ignore)]
     server_addr: SocketAddr,
 
+    /// In-memory source files shadowing the ones on disk.
+    overlay_files: Vc<OverlayFiles>,
+
     /// Next config.
     next_config: Vc<NextConfig>,
 
@@ -342,7 +463,7 @@ impl Project {
     #[turbo_tasks::function]
     async fn app_project(self: Vc<Self>) -> Result<Vc<OptionAppProject>> {
         let this = self.await?;
-        let app_dir = find_app_dir(self.project_path()).await?;
+        let app_dir = find_app_dir(self.routes_root()).await?;
 
         Ok(Vc::cell(if let Some(app_dir) = &*app_dir {
             Some(AppProject::new(self, *app_dir, this.mode))
@@ -367,7 +488,13 @@ impl Project {
         if this.watch {
             disk_fs.await?.start_watching_with_invalidation_reason()?;
         }
-        Ok(Vc::upcast(disk_fs))
+        if this.overlay_files.await?.is_empty() {
+            return Ok(Vc::upcast(disk_fs));
+        }
+        Ok(Vc::upcast(OverlayFileSystem::new(
+            Vc::upcast(disk_fs),
+            this.overlay_files,
+        )))
     }
 
     #[turbo_tasks::function]
@@ -434,6 +561,27 @@ impl Project {
         Ok(root.join(project_relative))
     }
 
+    /// The directory the app and pages directories are looked up in, which is
+    /// the project path unless `routes_dir` is set.
+    #[turbo_tasks::function]
+    pub async fn routes_root(self: Vc<Self>) -> Result<Vc<FileSystemPath>> {
+        let this = self.await?;
+        let project_path = self.project_path();
+        let Some(routes_dir) = &this.routes_dir else {
+            return Ok(project_path);
+        };
+        let Some(routes_root) = *project_path
+            .try_join(routes_dir.replace(MAIN_SEPARATOR, "/"))
+            .await?
+        else {
+            bail!(
+                "the routes directory {routes_dir} must be inside the root path {}",
+                this.root_path
+            );
+        };
+        Ok(routes_root)
+    }
+
     #[turbo_tasks::function]
     pub(super) async fn env(self: Vc<Self>) -> Result<Vc<Box<dyn ProcessEnv>>> {
         Ok(self.await?.env)
@@ -507,21 +655,34 @@ impl Project {
         Ok(get_client_chunking_context(
             self.project_path(),
             self.client_relative_path(),
-            self.next_config().computed_asset_prefix(),
+            self.next_config().computed_asset_prefix(this.mode),
             self.client_compile_time_info().environment(),
-            this.mode,
+            ClientRuntime::for_mode(this.mode),
         ))
     }
 
+    /// The chunking context of the Node.js server code. Every endpoint,
+    /// including instrumentation, takes it from here rather than creating its
+    /// own, so that chunks shared between endpoints are only computed once.
+    /// This doesn't bound what a rebuild recomputes after an edit, which
+    /// depends on the modules' own tasks.
     #[turbo_tasks::function]
-    pub(super) fn server_chunking_context(self: Vc<Self>) -> Vc<BuildChunkingContext> {
-        get_server_chunking_context(
+    pub(super) async fn server_chunking_context(
+        self: Vc<Self>,
+    ) -> Result<Vc<BuildChunkingContext>> {
+        let this = self.await?;
+        Ok(get_server_chunking_context(
             self.project_path(),
             self.node_root(),
             self.client_relative_path(),
-            self.next_config().computed_asset_prefix(),
+            self.next_config().computed_asset_prefix(this.mode),
             self.server_compile_time_info().environment(),
-        )
+            server_minify(
+                this.mode,
+                *self.next_config().server_minification().await?,
+                None,
+            ),
+        ))
     }
 
     #[turbo_tasks::function]
@@ -637,6 +798,24 @@ impl Project {
             }
         }
 
+        // Routes generated by the `generateRoutes` config function are served
+        // like API routes by their handler module.
+        for (pathname, module) in self.next_config().generated_routes().await?.iter() {
+            match routes.entry(pathname.clone()) {
+                Entry::Occupied(mut entry) => {
+                    *entry.get_mut() = Route::Conflict;
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(Route::PageApi {
+                        endpoint: pages_project.api_endpoint(
+                            pathname.clone(),
+                            self.project_path().join(module.clone()),
+                        ),
+                    });
+                }
+            }
+        }
+
         let pages_document_endpoint = TraitRef::cell(
             self.pages_project()
                 .document_endpoint()
@@ -704,6 +883,102 @@ impl Project {
         .cell())
     }
 
+    /// Returns the source file of each route of [Project::entrypoints], e.g.
+    /// for editors to open the file serving a url. Dynamic routes report the
+    /// file with the brackets, like `app/blog/[slug]/page.tsx`.
+    #[turbo_tasks::function]
+    pub async fn route_sources(self: Vc<Self>) -> Result<Vc<RouteSources>> {
+        let mut sources = vec![];
+        if let Some(app_project) = &*self.app_project().await? {
+            sources.extend(app_project.route_sources().await?.iter().cloned());
+        }
+        sources.extend(self.pages_project().route_sources().await?.iter().cloned());
+        for (pathname, module) in self.next_config().generated_routes().await?.iter() {
+            sources.push(RouteSource {
+                pathname: pathname.clone(),
+                path: self.project_path().join(module.clone()),
+            });
+        }
+        Ok(Vc::cell(sources))
+    }
+
+    /// Resolves the favicon and icons of the app directory, see
+    /// [AppProject::icons].
+    #[turbo_tasks::function]
+    pub async fn app_icons(self: Vc<Self>) -> Result<Vc<AppIcons>> {
+        Ok(match *self.app_project().await? {
+            Some(app_project) => app_project.icons(),
+            None => AppIcons::empty(),
+        })
+    }
+
+    /// Returns the client references of the app directory, see
+    /// [AppProject::client_references].
+    #[turbo_tasks::function]
+    pub async fn client_references(self: Vc<Self>) -> Result<Vc<ClientReferenceSources>> {
+        Ok(match *self.app_project().await? {
+            Some(app_project) => app_project.client_references(),
+            None => ClientReferenceSources::empty(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn app_icons_output_assets(self: Vc<Self>) -> Result<Vc<OutputAssets>> {
+        Ok(self.app_icons().await?.output_assets)
+    }
+
+    /// Writes the static app icons to the client output, and returns the app
+    /// icons with their urls.
+    #[turbo_tasks::function]
+    pub async fn write_app_icons(self: Vc<Self>) -> Result<Vc<AppIcons>> {
+        self.emit_all_output_assets(Vc::cell(self.app_icons_output_assets()))
+            .await?;
+        Ok(self.app_icons())
+    }
+
+    /// Returns the chunks and wasm bindings an edge host needs to load to run
+    /// the middleware, or `None` when the project has no middleware.
+    #[turbo_tasks::function]
+    pub async fn middleware_chunks(self: Vc<Self>) -> Result<Vc<OptionMiddlewareChunks>> {
+        let middleware = find_context_file(
+            self.project_path(),
+            middleware_files(self.next_config().page_extensions()),
+        );
+        let FindContextFileResult::Found(fs_path, _) = *middleware.await? else {
+            return Ok(Vc::cell(None));
+        };
+        let source = Vc::upcast(FileSource::new(fs_path));
+        Ok(Vc::cell(Some(self.middleware_endpoint(source).chunks())))
+    }
+
+    /// Reports structural issues of the app and pages directories, like
+    /// conflicting routes, invalid dynamic segments and missing root
+    /// layouts. Unlike [Project::entrypoints], this only looks at the file
+    /// system and never compiles anything.
+    #[turbo_tasks::function]
+    pub async fn validate(self: Vc<Self>) -> Result<Vc<()>> {
+        let mut app_pathnames = vec![];
+        if let Some(app_dir) = *find_app_dir(self.routes_root()).await? {
+            validate_entrypoints(app_dir, self.next_config().page_extensions()).await?;
+        }
+        if let Some(app_project) = &*self.app_project().await? {
+            app_pathnames.extend(app_project.routes().await?.keys().cloned());
+        }
+
+        for pathname in self.pages_project().routes().await?.keys() {
+            if app_pathnames.contains(pathname) {
+                ConflictingRouteIssue {
+                    project_path: self.project_path(),
+                    pathname: pathname.clone(),
+                }
+                .cell()
+                .emit();
+            }
+        }
+
+        Ok(Default::default())
+    }
+
     #[turbo_tasks::function]
     fn middleware_context(self: Vc<Self>) -> Vc<Box<dyn AssetContext>> {
         Vc::upcast(ModuleAssetContext::new(
@@ -728,7 +1003,7 @@ impl Project {
     }
 
     #[turbo_tasks::function]
-    async fn middleware_endpoint(
+    pub(super) async fn middleware_endpoint(
         self: Vc<Self>,
         source: Vc<Box<dyn Source>>,
     ) -> Result<Vc<MiddlewareEndpoint>> {
@@ -775,6 +1050,41 @@ impl Project {
         InstrumentationEndpoint::new(self, context, source, is_edge)
     }
 
+    /// Compiles an arbitrary file that isn't part of the app or pages
+    /// directories, e.g. a worker or custom server code, and returns the chunk
+    /// group it is placed in.
+    #[turbo_tasks::function]
+    pub async fn compile_entry(
+        self: Vc<Self>,
+        path: Vc<FileSystemPath>,
+        kind: EntryKind,
+    ) -> Result<Vc<OutputAssets>> {
+        let (context, chunking_context): (Vc<Box<dyn AssetContext>>, _) = match kind {
+            EntryKind::Client => (
+                self.pages_project().client_module_context(),
+                self.client_chunking_context(),
+            ),
+            EntryKind::Server => (
+                Vc::upcast(self.pages_project().api_module_context()),
+                Vc::upcast(self.server_chunking_context()),
+            ),
+        };
+
+        let module = context
+            .process(
+                Vc::upcast(FileSource::new(path)),
+                Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+            )
+            .module();
+
+        let Some(module) = Vc::try_resolve_sidecast::<Box<dyn ChunkableModule>>(module).await?
+        else {
+            bail!("Entry module must be chunkable");
+        };
+
+        Ok(chunking_context.root_chunk_group(module))
+    }
+
     #[turbo_tasks::function]
     pub async fn emit_all_output_assets(
         self: Vc<Self>,
@@ -800,6 +1110,72 @@ impl Project {
         .await
     }
 
+    /// Returns the paths of the files [Project::emit_all_output_assets] writes
+    /// for the output assets, relative to the node root, without writing
+    /// them.
+    #[turbo_tasks::function]
+    pub async fn output_asset_paths(
+        self: Vc<Self>,
+        output_assets: Vc<OutputAssets>,
+    ) -> Result<Vc<Vec<String>>> {
+        let all_output_assets = all_assets_from_entries(output_assets).await?;
+        let node_root = &*self.node_root().await?;
+        let client_relative_path = &*self.client_relative_path().await?;
+
+        let mut paths = all_output_assets
+            .iter()
+            .map(|&asset| async move {
+                let path = asset.ident().path().await?;
+                // Client assets are rebased from the client relative path to
+                // the node root when emitting.
+                Ok(node_root
+                    .get_path_to(&path)
+                    .or_else(|| client_relative_path.get_path_to(&path))
+                    .map(|path| path.to_string()))
+            })
+            .try_flat_join()
+            .await?;
+        paths.sort();
+        Ok(Vc::cell(paths))
+    }
+
+    /// Concatenates the JavaScript files [Project::emit_all_output_assets]
+    /// writes to the node root for the output assets, without writing them.
+    /// The `entry_chunks` come last, in their order, after the chunks they
+    /// load, which are ordered by their path.
+    #[turbo_tasks::function]
+    pub async fn server_output_content(
+        self: Vc<Self>,
+        output_assets: Vc<OutputAssets>,
+        entry_chunks: Vc<OutputAssets>,
+    ) -> Result<Vc<FileContent>> {
+        let all_output_assets = all_assets_from_entries(output_assets).await?;
+        let node_root = &*self.node_root().await?;
+
+        let with_paths = |assets: Vec<Vc<Box<dyn OutputAsset>>>| {
+            assets
+                .into_iter()
+                .map(|asset| async move { Ok((asset.ident().path().await?, asset)) })
+                .try_join()
+        };
+        let entry_chunks = with_paths(entry_chunks.await?.clone_value()).await?;
+        let chunks = with_paths(all_output_assets.clone_value()).await?;
+
+        let mut content = RopeBuilder::default();
+        for (path, asset) in server_output_order(chunks, entry_chunks, |(path, _)| &path.path) {
+            if !path.is_inside_ref(node_root) || path.extension_ref() != Some("js") {
+                continue;
+            }
+            if let AssetContent::File(file) = *asset.content().await? {
+                if let FileContent::Content(file) = &*file.await? {
+                    content.concat(file.content());
+                    content.push_static_bytes(b"\n");
+                }
+            }
+        }
+        Ok(FileContent::Content(content.build().into()).cell())
+    }
+
     #[turbo_tasks::function]
     async fn hmr_content(
         self: Vc<Self>,
@@ -880,6 +1256,24 @@ impl Project {
         any_output_changed(roots, path, true)
     }
 
+    /// Completion when the server components of the app page `pathname`
+    /// change. The client needs to refetch the page's RSC payload then, instead
+    /// of applying an update to its client modules. Edits of client components
+    /// don't complete this, so a change of both server and client components
+    /// is reported here and by the HMR events of the client chunks.
+    #[turbo_tasks::function]
+    pub async fn rsc_changed(self: Vc<Self>, pathname: String) -> Result<Vc<Completion>> {
+        let Some(app_project) = &*self.app_project().await? else {
+            bail!("there is no app directory to find {pathname} in");
+        };
+        let Some(Route::AppPage { html_endpoint, .. }) =
+            app_project.routes().await?.get(&pathname).copied()
+        else {
+            bail!("{pathname} is not an app page");
+        };
+        Ok(app_page_rsc_changed(html_endpoint))
+    }
+
     /// Completion when client side changes are detected in output assets
     /// referenced from the roots
     #[turbo_tasks::function]
@@ -940,3 +1334,456 @@ fn all_assets_from_entries_operation(
 ) -> Vc<OutputAssetsOperation> {
     Vc::cell(all_assets_from_entries_operation_inner(operation))
 }
+
+#[turbo_tasks::value(shared)]
+struct ConflictingRouteIssue {
+    project_path: Vc<FileSystemPath>,
+    pathname: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ConflictingRouteIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Text("Conflicting app and page routes at ".to_string()),
+            StyledString::Code(self.pathname.clone()),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("routes".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.project_path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(
+                "Both the app directory and the pages directory define this route. Remove one of \
+                 them."
+                    .to_string(),
+            )
+            .cell(),
+        ))
+    }
+}
+
+/// Orders the chunks of [Project::server_output_content] by their `path`,
+/// followed by the `entry_chunks` in their order.
+fn server_output_order<T>(
+    chunks: Vec<T>,
+    entry_chunks: Vec<T>,
+    path: impl Fn(&T) -> &str,
+) -> Vec<T> {
+    let mut chunks: Vec<_> = chunks
+        .into_iter()
+        .filter(|chunk| !entry_chunks.iter().any(|entry| path(entry) == path(chunk)))
+        .collect();
+    chunks.sort_by(|a, b| path(a).cmp(path(b)));
+    chunks.extend(entry_chunks);
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{Context, Result};
+    use indoc::indoc;
+    use next_core::{
+        next_client_reference::ClientReferenceType,
+        test_utils::{issues, styled_text},
+    };
+    use turbopack_binding::{
+        turbo::tasks_fs::FileContent,
+        turbopack::core::{asset::Asset, output::OutputAsset},
+    };
+
+    use super::{server_output_order, AppIconKind, EntryKind, ProjectContainer, ProjectOptions};
+    use crate::test_utils::{fixture, fixture_project, fixture_project_options, run};
+
+    #[test]
+    fn test_server_output_order() {
+        let order = server_output_order(
+            vec![("c.js", 0), ("entry.js", 0), ("a.js", 0), ("b.js", 0)],
+            vec![("entry.js", 1), ("runtime.js", 1)],
+            |(path, _)| path,
+        );
+        assert_eq!(
+            order,
+            [
+                ("a.js", 0),
+                ("b.js", 0),
+                ("c.js", 0),
+                ("entry.js", 1),
+                ("runtime.js", 1)
+            ]
+        );
+    }
+
+    /// Compiles `lib/worker.ts` of a project with `kind`, and returns the
+    /// paths and contents of the JavaScript chunks of its chunk group.
+    async fn compile_worker(kind: EntryKind) -> Result<Vec<(String, String)>> {
+        let dir = fixture(&[(
+            "lib/worker.ts",
+            "export const double = (n: number) => n * 2;",
+        )]);
+        let root = dir.path().to_owned();
+        run(async move {
+            let project = fixture_project(&root)?;
+            let chunks = project
+                .compile_entry(
+                    project.project_path().join("lib/worker.ts".to_string()),
+                    kind,
+                )
+                .await?;
+
+            let mut js_chunks = vec![];
+            for &chunk in chunks.iter() {
+                let path = chunk.ident().path().await?;
+                if path.extension_ref() != Some("js") {
+                    continue;
+                }
+                if let FileContent::Content(file) = &*chunk.content().file_content().await? {
+                    js_chunks.push((path.path.clone(), file.content().to_str()?.into_owned()));
+                }
+            }
+            Ok(js_chunks)
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn compiles_a_standalone_entry() {
+        for kind in [EntryKind::Client, EntryKind::Server] {
+            let chunks = compile_worker(kind).await.unwrap();
+            assert!(
+                chunks.iter().any(|(_, content)| content.contains("double")),
+                "the {kind:?} chunk group doesn't contain lib/worker.ts: {:?}",
+                chunks.iter().map(|(path, _)| path).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_a_shared_client_reference_once() {
+        let dir = fixture(&[
+            (
+                "app/layout.tsx",
+                "export default function Layout({ children }) { return children; }",
+            ),
+            (
+                "app/page.tsx",
+                indoc! {"
+                    import Counter from './counter';
+                    export default function Home() { return <Counter />; }
+                "},
+            ),
+            (
+                "app/about/page.tsx",
+                indoc! {"
+                    import Counter from '../counter';
+                    export default function About() { return <Counter />; }
+                "},
+            ),
+            (
+                "app/counter.tsx",
+                indoc! {"
+                    'use client';
+                    export default function Counter() { return null; }
+                "},
+            ),
+        ]);
+        let root = dir.path().to_owned();
+        let client_references = run(async move {
+            let project = fixture_project(&root)?;
+            Ok(project.client_references().await?.clone_value())
+        })
+        .await
+        .unwrap();
+
+        let counters: Vec<_> = client_references
+            .iter()
+            .filter(|source| source.path == "app/counter.tsx")
+            .collect();
+        assert_eq!(counters.len(), 1, "{client_references:?}");
+        assert!(matches!(
+            counters[0].ty,
+            ClientReferenceType::EcmascriptClientReference(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn validates_the_route_structure_without_compiling() {
+        let dir = fixture(&[
+            ("app/layout.tsx", "export default function Layout() {}"),
+            ("app/about/page.tsx", "export default function About() {}"),
+            (
+                "app/posts/[id]/comments/[id]/page.tsx",
+                "export default function Comment() {}",
+            ),
+            ("pages/about.tsx", "export default function About() {}"),
+        ]);
+        let root = dir.path().to_owned();
+        let messages = run(async move {
+            let validation = fixture_project(&root)?.validate();
+            validation.await?;
+
+            let mut messages = vec![];
+            for issue in issues(validation).await? {
+                let mut message = styled_text(&issue.title);
+                if let Some(description) = &issue.description {
+                    message.push_str(&styled_text(description));
+                }
+                messages.push(message);
+            }
+            Ok(messages)
+        })
+        .await
+        .unwrap();
+
+        assert!(
+            messages
+                .iter()
+                .any(|message| message.starts_with("Conflicting app and page routes at /about")),
+            "{messages:?}"
+        );
+        assert!(
+            messages
+                .iter()
+                .any(|message| message.contains("The param name `id` is used more than once")),
+            "{messages:?}"
+        );
+        // Nothing was compiled, so nothing was written.
+        assert!(!dir.path().join(".next").exists());
+    }
+
+    #[tokio::test]
+    async fn emits_static_app_icons_and_compiles_dynamic_ones() {
+        let dir = fixture(&[
+            (
+                "app/layout.tsx",
+                "export default function Layout({ children }) { return children; }",
+            ),
+            ("app/page.tsx", "export default function Home() {}"),
+            ("app/favicon.ico", "favicon"),
+            ("app/icon.tsx", "export default function Icon() {}"),
+        ]);
+        let root = dir.path().to_owned();
+        let (icons, favicon) = run(async move {
+            let project = fixture_project(&root)?;
+            let app_icons = project.write_app_icons().strongly_consistent().await?;
+
+            let favicon = app_icons
+                .icons
+                .iter()
+                .find(|icon| icon.kind == AppIconKind::Favicon)
+                .context("app/favicon.ico isn't an icon")?;
+            let favicon_path = project.client_relative_path().join(
+                favicon
+                    .url
+                    .strip_prefix("/_next/")
+                    .context("the favicon isn't served from /_next")?
+                    .to_string(),
+            );
+            let FileContent::Content(file) = &*favicon_path.read().await? else {
+                anyhow::bail!("{} wasn't emitted", favicon.url);
+            };
+            let favicon = file.content().to_str()?.into_owned();
+
+            let icons = app_icons
+                .icons
+                .iter()
+                .map(|icon| (icon.kind, icon.url.clone(), icon.endpoint.is_some()))
+                .collect::<Vec<_>>();
+            Ok((icons, favicon))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(favicon, "favicon");
+        assert_eq!(icons.len(), 2, "{icons:?}");
+        let (_, favicon_url, favicon_endpoint) = icons
+            .iter()
+            .find(|(kind, ..)| *kind == AppIconKind::Favicon)
+            .unwrap();
+        assert!(
+            favicon_url.starts_with("/_next/static/media/favicon.")
+                && favicon_url.ends_with(".ico"),
+            "{favicon_url}"
+        );
+        assert!(!favicon_endpoint);
+        let (_, icon_url, icon_endpoint) = icons
+            .iter()
+            .find(|(kind, ..)| *kind == AppIconKind::Icon)
+            .unwrap();
+        assert!(icon_url.starts_with("/icon"), "{icon_url}");
+        // The icon is generated by the endpoint of its metadata route.
+        assert!(icon_endpoint);
+    }
+
+    #[tokio::test]
+    async fn discovers_pages_in_overlay_files() {
+        let dir = fixture(&[]);
+        let root = dir.path().to_owned();
+        let routes = run(async move {
+            let project = ProjectContainer::new(ProjectOptions {
+                overlay_files: vec![(
+                    "pages/index.js".to_string(),
+                    "export default function Home() {}".to_string(),
+                )],
+                ..fixture_project_options(&root)?
+            })
+            .project();
+            Ok(project
+                .pages_project()
+                .routes()
+                .await?
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(routes, ["/"]);
+        assert!(!dir.path().join("pages").exists());
+    }
+
+    #[tokio::test]
+    async fn shadows_files_on_disk_with_overlay_files() {
+        let dir = fixture(&[("pages/index.js", "export default function Disk() {}")]);
+        let root = dir.path().to_owned();
+        let content = run(async move {
+            let project = ProjectContainer::new(ProjectOptions {
+                overlay_files: vec![(
+                    "pages/index.js".to_string(),
+                    "export default function Overlay() {}".to_string(),
+                )],
+                ..fixture_project_options(&root)?
+            })
+            .project();
+            let content = project
+                .project_path()
+                .join("pages/index.js".to_string())
+                .read()
+                .await?;
+            let FileContent::Content(file) = &*content else {
+                anyhow::bail!("pages/index.js can't be read");
+            };
+            Ok(file.content().to_str()?.into_owned())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(content, "export default function Overlay() {}");
+    }
+
+    #[tokio::test]
+    async fn maps_routes_to_their_source_files() {
+        let dir = fixture(&[
+            ("app/layout.tsx", "export default function Layout() {}"),
+            (
+                "app/blog/[slug]/page.tsx",
+                "export default function Post() {}",
+            ),
+            ("pages/about.tsx", "export default function About() {}"),
+            ("pages/api/hello.ts", "export default function handler() {}"),
+        ]);
+        let root = dir.path().to_owned();
+        let mut sources = run(async move {
+            let project = fixture_project(&root)?;
+            let project_path = project.project_path().await?;
+            let mut sources = vec![];
+            for source in project.route_sources().await?.iter() {
+                let path = source.path.await?;
+                sources.push((
+                    source.pathname.clone(),
+                    project_path.get_path_to(&path).unwrap().to_string(),
+                ));
+            }
+            Ok(sources)
+        })
+        .await
+        .unwrap();
+        // Served by the default not-found page of Next.js.
+        sources.retain(|(pathname, _)| pathname != "/_not-found");
+        sources.sort();
+
+        assert_eq!(
+            sources,
+            [
+                ("/about", "pages/about.tsx"),
+                ("/api/hello", "pages/api/hello.ts"),
+                ("/blog/[slug]", "app/blog/[slug]/page.tsx"),
+            ]
+            .map(|(pathname, path)| (pathname.to_string(), path.to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn resolves_routes_in_the_routes_dir() {
+        let dir = fixture(&[
+            ("web/app/layout.tsx", "export default function Layout() {}"),
+            ("web/app/page.tsx", "export default function Home() {}"),
+            ("web/app/blog/page.tsx", "export default function Blog() {}"),
+            ("web/pages/about.tsx", "export default function About() {}"),
+            // Not in the routes directory.
+            (
+                "app/ignored/page.tsx",
+                "export default function Ignored() {}",
+            ),
+        ]);
+        let root = dir.path().to_owned();
+        let routes = run(async move {
+            let project = ProjectContainer::new(ProjectOptions {
+                routes_dir: Some("web".to_string()),
+                ..fixture_project_options(&root)?
+            })
+            .project();
+            let mut routes = vec![];
+            if let Some(app_project) = *project.app_project().await? {
+                routes.extend(app_project.routes().await?.keys().cloned());
+            }
+            routes.extend(project.pages_project().routes().await?.keys().cloned());
+            routes.sort();
+            Ok(routes)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(routes, ["/", "/about", "/blog"]);
+    }
+
+    #[tokio::test]
+    async fn rejects_a_routes_dir_outside_the_root_path() {
+        let dir = fixture(&[]);
+        let root = dir.path().to_owned();
+        let result = run(async move {
+            let project = ProjectContainer::new(ProjectOptions {
+                routes_dir: Some("../web".to_string()),
+                ..fixture_project_options(&root)?
+            })
+            .project();
+            project.routes_root().await?;
+            Ok(())
+        })
+        .await;
+
+        let err = result.unwrap_err();
+        assert!(
+            format!("{err:#}").contains("must be inside the root path"),
+            "unexpected error: {err:#}"
+        );
+    }
+}
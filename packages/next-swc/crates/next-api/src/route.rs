@@ -1,5 +1,7 @@
 use indexmap::IndexMap;
-use turbo_tasks::{Completion, Vc};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, Completion, RawVc, Vc};
+use turbopack_binding::turbo::tasks_fs::{FileContent, FileSystemPath};
 
 use crate::server_paths::ServerPath;
 
@@ -26,10 +28,28 @@ pub enum Route {
 #[turbo_tasks::value_trait]
 pub trait Endpoint {
     fn write_to_disk(self: Vc<Self>) -> Vc<WrittenEndpoint>;
+    /// The paths of the files [Endpoint::write_to_disk] writes, relative to
+    /// the node root, without writing them.
+    fn chunks(self: Vc<Self>) -> Vc<Vec<String>>;
+    /// The concatenated server JavaScript of the endpoint, without writing it.
+    /// The entry chunks come last. See [Project::server_output_content].
+    ///
+    /// [Project::server_output_content]: crate::project::Project::server_output_content
+    fn server_output(self: Vc<Self>) -> Vc<FileContent>;
     fn server_changed(self: Vc<Self>) -> Vc<Completion>;
     fn client_changed(self: Vc<Self>) -> Vc<Completion>;
 }
 
+/// Like [Endpoint::write_to_disk], but invalidates the task first, so that it
+/// runs again even if nothing changed. Only that task is invalidated, the ones
+/// it reads are reused from the cache when their inputs are unchanged, as are
+/// the tasks of other endpoints.
+pub fn write_to_disk_uncached(endpoint: Vc<Box<dyn Endpoint>>) -> Vc<WrittenEndpoint> {
+    let write_to_disk = endpoint.write_to_disk();
+    turbo_tasks::turbo_tasks().invalidate(RawVc::from(write_to_disk).get_task_id());
+    write_to_disk
+}
+
 #[turbo_tasks::value(shared)]
 #[derive(Debug)]
 pub enum WrittenEndpoint {
@@ -37,13 +57,102 @@ pub enum WrittenEndpoint {
         /// Relative to the root_path
         server_entry_path: String,
         server_paths: Vec<ServerPath>,
+        config: EndpointConfig,
     },
     Edge {
         server_paths: Vec<ServerPath>,
+        config: EndpointConfig,
     },
 }
 
+/// Settings of the function serving an endpoint, read from the route's source.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub struct EndpointConfig {
+    /// The timeout of the function in seconds.
+    pub max_duration: Option<u32>,
+}
+
 /// The routes as map from pathname to route. (pathname includes the leading
 /// slash)
 #[turbo_tasks::value(transparent)]
 pub struct Routes(IndexMap<String, Route>);
+
+/// The source file backing a route, e.g. `pages/blog/[slug].tsx` for
+/// `/blog/[slug]`. Conflicting app and pages routes have a source each.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub struct RouteSource {
+    /// The pathname of the route, with the leading slash.
+    pub pathname: String,
+    pub path: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct RouteSources(Vec<RouteSource>);
+
+#[cfg(test)]
+mod tests {
+    use anyhow::bail;
+    use next_core::mode::NextMode;
+
+    use super::{write_to_disk_uncached, Route};
+    use crate::{
+        pages::PagesProject,
+        test_utils::{fixture, fixture_project, TestTurboTasks},
+    };
+
+    #[tokio::test]
+    async fn rewrites_only_the_uncached_endpoint() {
+        let dir = fixture(&[
+            ("pages/a.js", "export default function A() {}"),
+            ("pages/b.js", "export default function B() {}"),
+        ]);
+        let root = dir.path().to_owned();
+        let turbo_tasks = TestTurboTasks::new();
+        let (a, b) = turbo_tasks
+            .run(async move {
+                let pages_project =
+                    PagesProject::new(fixture_project(&root)?, NextMode::Development);
+                let routes = pages_project.routes().await?;
+                let page = |pathname: &str| match routes.get(pathname) {
+                    Some(Route::Page { html_endpoint, .. }) => Ok(*html_endpoint),
+                    _ => bail!("{pathname} isn't a page"),
+                };
+                let (a, b) = (page("/a")?, page("/b")?);
+                a.write_to_disk().strongly_consistent().await?;
+                b.write_to_disk().strongly_consistent().await?;
+                Ok((a, b))
+            })
+            .await
+            .unwrap();
+        turbo_tasks.executed_tasks().await;
+
+        // Writing an unchanged endpoint again is a cache hit.
+        turbo_tasks
+            .run(async move {
+                a.write_to_disk().strongly_consistent().await?;
+                Ok(())
+            })
+            .await
+            .unwrap();
+        let cached = turbo_tasks.executed_tasks().await;
+
+        turbo_tasks
+            .run(async move {
+                write_to_disk_uncached(a).strongly_consistent().await?;
+                Ok(())
+            })
+            .await
+            .unwrap();
+        assert!(turbo_tasks.executed_tasks().await > cached);
+
+        // The other endpoint is still cached.
+        turbo_tasks
+            .run(async move {
+                b.write_to_disk().strongly_consistent().await?;
+                Ok(())
+            })
+            .await
+            .unwrap();
+        assert_eq!(turbo_tasks.executed_tasks().await, cached);
+    }
+}
@@ -0,0 +1,94 @@
+//! Helpers to compile a fixture project with turbo-tasks in unit tests, on
+//! top of the ones shared from [next_core::test_utils].
+
+use std::{future::Future, path::Path, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use next_core::{
+    next_config::NextConfig,
+    test_utils::{self, run_with, turbo_tasks_with},
+};
+use tempfile::TempDir;
+use turbo_tasks::{run_once, TurboTasks, Vc};
+use turbopack_binding::turbo::tasks_memory::MemoryBackend;
+
+use crate::project::{DefineEnv, Project, ProjectContainer, ProjectOptions};
+
+/// Runs `future` in a fresh turbo-tasks instance.
+pub(crate) async fn run<T: Send + 'static>(
+    future: impl Future<Output = Result<T>> + Send + 'static,
+) -> Result<T> {
+    run_with(crate::register, future).await
+}
+
+/// A turbo-tasks instance which can run several futures, to compare the tasks
+/// they compute.
+#[derive(Clone)]
+pub(crate) struct TestTurboTasks(Arc<TurboTasks<MemoryBackend>>);
+
+impl TestTurboTasks {
+    pub(crate) fn new() -> Self {
+        Self(turbo_tasks_with(crate::register))
+    }
+
+    /// Runs `future` in the instance.
+    pub(crate) async fn run<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = Result<T>> + Send + 'static,
+    ) -> Result<T> {
+        run_once(self.0.clone(), future).await
+    }
+
+    /// The tasks the instance has cached. A task is only added when something
+    /// is computed for the first time, so an unchanged count after reading a
+    /// value means it was reused rather than computed again.
+    pub(crate) fn cached_tasks(&self) -> usize {
+        let mut tasks = 0;
+        self.0.backend().with_all_cached_tasks(|_| tasks += 1);
+        tasks
+    }
+
+    /// The tasks executed since the previous call, once the instance is idle.
+    /// Must be called between runs.
+    pub(crate) async fn executed_tasks(&self) -> usize {
+        self.0
+            .get_or_wait_aggregated_update_info(Duration::from_millis(10))
+            .await
+            .tasks
+    }
+}
+
+/// Writes `files` to a temporary directory, along with a `next` package to
+/// resolve Next.js from. It's removed when the returned [TempDir] is dropped.
+pub(crate) fn fixture(files: &[(&str, &str)]) -> TempDir {
+    let next_package = ("node_modules/next/package.json", r#"{"name": "next"}"#);
+    test_utils::fixture(&[files, &[next_package]].concat())
+}
+
+/// The options of a project in `dir` with the default next.config.js.
+pub(crate) fn fixture_project_options(dir: &Path) -> Result<ProjectOptions> {
+    let root = dir.to_str().unwrap().to_string();
+    Ok(ProjectOptions {
+        root_path: root.clone(),
+        project_path: root,
+        routes_dir: None,
+        next_config: serde_json::to_string(&NextConfig::default())?,
+        js_config: "{}".to_string(),
+        tsconfig_path: None,
+        env: vec![],
+        define_env: DefineEnv {
+            client: vec![],
+            edge: vec![],
+            nodejs: vec![],
+        },
+        watch: false,
+        server_addr: "127.0.0.1:3000".to_string(),
+        overlay_files: vec![],
+    })
+}
+
+/// A project in `dir` with the default next.config.js. Must be called within
+/// [run].
+pub(crate) fn fixture_project(dir: &Path) -> Result<Vc<Project>> {
+    Ok(ProjectContainer::new(fixture_project_options(dir)?).project())
+}
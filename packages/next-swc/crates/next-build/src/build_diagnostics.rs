@@ -0,0 +1,184 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use next_core::turbopack::core::issue::IssueSeverity;
+use serde::Serialize;
+
+use crate::build_logger::BuildLogger;
+
+/// The phases of a build, in the order they run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildPhase {
+    /// Loading next.config.js and the environment.
+    Config,
+    /// Finding the pages and app entries and walking their module graphs.
+    Discovery,
+    PageChunking,
+    /// Chunking the client components of the app directory.
+    ClientReferences,
+    AppChunking,
+    /// Writing the manifests and the chunks, which includes generating their
+    /// code.
+    Emission,
+}
+
+impl BuildPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BuildPhase::Config => "config",
+            BuildPhase::Discovery => "discovery",
+            BuildPhase::PageChunking => "page-chunking",
+            BuildPhase::ClientReferences => "client-references",
+            BuildPhase::AppChunking => "app-chunking",
+            BuildPhase::Emission => "emission",
+        }
+    }
+}
+
+/// Measures the phases of a build and the chunking of each route with a
+/// monotonic clock. Turbo tasks run when they're first awaited, so a phase
+/// includes all work its results depend on that no earlier phase awaited.
+///
+/// The end of every phase is logged to the `logger`.
+pub(crate) struct BuildTimer {
+    logger: Option<BuildLogger>,
+    start: Instant,
+    phase_start: Instant,
+    phases: BuildPhases,
+    routes: BTreeMap<String, f64>,
+}
+
+impl BuildTimer {
+    pub(crate) fn new(logger: Option<BuildLogger>) -> Self {
+        let start = Instant::now();
+        Self {
+            logger,
+            start,
+            phase_start: start,
+            phases: BuildPhases::default(),
+            routes: BTreeMap::new(),
+        }
+    }
+
+    /// Ends `phase`, which started when the previous phase ended.
+    pub(crate) fn end_phase(&mut self, phase: BuildPhase) {
+        let now = Instant::now();
+        let duration = millis(now - self.phase_start);
+        self.phase_start = now;
+        *match phase {
+            BuildPhase::Config => &mut self.phases.config,
+            BuildPhase::Discovery => &mut self.phases.discovery,
+            BuildPhase::PageChunking => &mut self.phases.page_chunking,
+            BuildPhase::ClientReferences => &mut self.phases.client_references,
+            BuildPhase::AppChunking => &mut self.phases.app_chunking,
+            BuildPhase::Emission => &mut self.phases.emission,
+        } += duration;
+
+        if let Some(logger) = &self.logger {
+            logger.log(
+                phase,
+                IssueSeverity::Info,
+                format!("{} finished in {duration:.0}ms", phase.as_str()),
+            );
+        }
+    }
+
+    /// Records the time it took to chunk the route, e.g. `/about` for a page
+    /// or `/about/page` for an app entry. It's part of the chunking phase of
+    /// the route.
+    pub(crate) fn add_route(&mut self, route: String, duration: Duration) {
+        *self.routes.entry(route).or_default() += millis(duration);
+    }
+
+    pub(crate) fn finish(self) -> BuildDiagnostics {
+        BuildDiagnostics {
+            total: millis(self.start.elapsed()),
+            phases: self.phases,
+            routes: self.routes,
+        }
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// The content of `build-diagnostics.json`. All durations are in
+/// milliseconds.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BuildDiagnostics {
+    total: f64,
+    phases: BuildPhases,
+    routes: BTreeMap<String, f64>,
+}
+
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+struct BuildPhases {
+    config: f64,
+    discovery: f64,
+    page_chunking: f64,
+    client_references: f64,
+    app_chunking: f64,
+    emission: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        thread::sleep,
+        time::{Duration, Instant},
+    };
+
+    use super::{BuildPhase, BuildTimer};
+
+    #[test]
+    fn times_each_phase_and_route() {
+        let mut timer = BuildTimer::new(None);
+        for phase in [BuildPhase::Config, BuildPhase::Discovery] {
+            sleep(Duration::from_millis(5));
+            timer.end_phase(phase);
+        }
+        for route in ["/", "/about"] {
+            let start = Instant::now();
+            sleep(Duration::from_millis(20));
+            timer.add_route(route.to_string(), start.elapsed());
+        }
+        timer.end_phase(BuildPhase::PageChunking);
+        for phase in [
+            BuildPhase::ClientReferences,
+            BuildPhase::AppChunking,
+            BuildPhase::Emission,
+        ] {
+            sleep(Duration::from_millis(5));
+            timer.end_phase(phase);
+        }
+
+        let diagnostics = serde_json::to_value(timer.finish()).unwrap();
+        let phases = diagnostics["phases"].as_object().unwrap();
+        for phase in [
+            "config",
+            "discovery",
+            "pageChunking",
+            "clientReferences",
+            "appChunking",
+            "emission",
+        ] {
+            assert!(
+                phases[phase].as_f64().unwrap() > 0.0,
+                "{phase} took no time"
+            );
+        }
+
+        let routes = diagnostics["routes"].as_object().unwrap();
+        assert_eq!(routes.keys().collect::<Vec<_>>(), ["/", "/about"]);
+        let routes_total: f64 = routes.values().map(|time| time.as_f64().unwrap()).sum();
+        let page_chunking = phases["pageChunking"].as_f64().unwrap();
+        assert!(routes_total <= page_chunking);
+        assert!(routes_total >= page_chunking * 0.5);
+        assert!(diagnostics["total"].as_f64().unwrap() >= page_chunking);
+    }
+}
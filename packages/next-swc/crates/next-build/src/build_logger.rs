@@ -0,0 +1,242 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use next_core::turbopack::core::issue::IssueSeverity;
+use turbo_tasks::{RawVc, ReadRef, TransientInstance, TransientValue, Vc};
+use turbopack_binding::turbopack::core::issue::{CapturedIssues, IssueReporter};
+
+pub use crate::build_diagnostics::BuildPhase;
+use crate::failure_diagnostics::styled_text;
+
+/// A log line of the build, for hosts which format and route the logs
+/// themselves. Issues are logged without a phase, as they're reported when
+/// they're found.
+#[derive(Clone, Debug)]
+pub struct BuildLog {
+    pub phase: Option<BuildPhase>,
+    pub level: IssueSeverity,
+    pub message: String,
+}
+
+/// Receives the [BuildLog]s, see [BuildOptions::logger]. The build logs from a
+/// single task, so the lines arrive in the order they're logged.
+///
+/// [BuildOptions::logger]: crate::BuildOptions::logger
+#[derive(Clone)]
+pub struct BuildLogger(Arc<dyn Fn(BuildLog) + Send + Sync>);
+
+impl BuildLogger {
+    pub fn new(log: impl Fn(BuildLog) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(log))
+    }
+
+    pub(crate) fn log(&self, phase: BuildPhase, level: IssueSeverity, message: String) {
+        (self.0)(BuildLog {
+            phase: Some(phase),
+            level,
+            message,
+        });
+    }
+
+    fn log_issue(&self, level: IssueSeverity, message: String) {
+        (self.0)(BuildLog {
+            phase: None,
+            level,
+            message,
+        });
+    }
+}
+
+impl fmt::Debug for BuildLogger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BuildLogger").finish()
+    }
+}
+
+/// The [BuildLogger] issues are logged with, and the issues logged so far, as
+/// the same issues are reported again by every task that reads them.
+pub(crate) struct IssueLog {
+    logger: BuildLogger,
+    logged: Mutex<HashSet<String>>,
+}
+
+impl IssueLog {
+    pub(crate) fn new(logger: BuildLogger) -> Self {
+        Self {
+            logger,
+            logged: Default::default(),
+        }
+    }
+}
+
+/// An [IssueReporter] which reports issues with `inner`, and logs each issue
+/// once with the [BuildLogger] at its severity.
+#[turbo_tasks::value(shared, serialization = "none", eq = "manual")]
+pub(crate) struct LoggingIssueReporter {
+    inner: Vc<Box<dyn IssueReporter>>,
+    #[turbo_tasks(trace_ignore, debug_ignore)]
+    log: TransientInstance<IssueLog>,
+}
+
+#[turbo_tasks::value_impl]
+impl LoggingIssueReporter {
+    #[turbo_tasks::function]
+    pub(crate) fn new(
+        inner: Vc<Box<dyn IssueReporter>>,
+        log: TransientInstance<IssueLog>,
+    ) -> Vc<Self> {
+        LoggingIssueReporter { inner, log }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl IssueReporter for LoggingIssueReporter {
+    #[turbo_tasks::function]
+    async fn report_issues(
+        &self,
+        issues: TransientInstance<ReadRef<CapturedIssues>>,
+        source: TransientValue<RawVc>,
+        min_failing_severity: Vc<IssueSeverity>,
+    ) -> Result<Vc<bool>> {
+        for issue in issues.get_plain_issues().await?.iter() {
+            let mut message = format!("{} - {}", issue.file_path, styled_text(&issue.title));
+            if let Some(description) = &issue.description {
+                message.push('\n');
+                message.push_str(&styled_text(description));
+            }
+            let first = self
+                .log
+                .logged
+                .lock()
+                .unwrap()
+                .insert(format!("{}:{message}", issue.severity.as_str()));
+            if first {
+                self.log.logger.log_issue(issue.severity, message);
+            }
+        }
+
+        Ok(self
+            .inner
+            .report_issues(issues, source, min_failing_severity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        sync::{Arc, Mutex},
+    };
+
+    use next_core::{app_structure::get_entrypoints, turbopack::core::issue::IssueSeverity};
+    use turbo_tasks::{RawVc, TransientInstance, TransientValue, Vc};
+    use turbopack_binding::turbopack::{
+        cli_utils::issue::{ConsoleUi, LogOptions},
+        core::issue::{IssueDescriptionExt, IssueReporter},
+    };
+
+    use super::{BuildLog, BuildLogger, BuildPhase, IssueLog, LoggingIssueReporter};
+    use crate::{
+        build_diagnostics::BuildTimer,
+        test_utils::{fixture, fixture_root, run},
+    };
+
+    #[test]
+    fn logs_each_finished_phase_in_order() {
+        let logs: Arc<Mutex<Vec<BuildLog>>> = Default::default();
+        let logger = BuildLogger::new({
+            let logs = logs.clone();
+            move |log| logs.lock().unwrap().push(log)
+        });
+
+        let phases = [
+            BuildPhase::Config,
+            BuildPhase::Discovery,
+            BuildPhase::PageChunking,
+            BuildPhase::ClientReferences,
+            BuildPhase::AppChunking,
+            BuildPhase::Emission,
+        ];
+        let mut timer = BuildTimer::new(Some(logger));
+        for phase in phases {
+            timer.end_phase(phase);
+        }
+
+        let logs = logs.lock().unwrap();
+        assert_eq!(
+            logs.iter().map(|log| log.phase).collect::<Vec<_>>(),
+            phases.map(Some)
+        );
+        for log in logs.iter() {
+            assert_eq!(log.level, IssueSeverity::Info);
+            assert!(log
+                .message
+                .starts_with(&format!("{} finished in ", log.phase.unwrap().as_str())));
+        }
+    }
+
+    #[tokio::test]
+    async fn logs_each_issue_once_with_its_level() {
+        let dir = fixture(&[
+            ("app/layout.tsx", "export default function Layout() {}"),
+            ("app/page.tsx", "export default function Page() {}"),
+            (
+                "app/@modal/photo/page.tsx",
+                "export default function Photo() {}",
+            ),
+        ]);
+        let logs: Arc<Mutex<Vec<BuildLog>>> = Default::default();
+        let logger = BuildLogger::new({
+            let logs = logs.clone();
+            move |log| logs.lock().unwrap().push(log)
+        });
+
+        let root = dir.path().to_owned();
+        run(async move {
+            // The `@modal` slot has neither a page nor a default for `/`, which is a
+            // warning in `app`.
+            let entrypoints = get_entrypoints(
+                fixture_root(&root).join("app".to_string()),
+                Vc::cell(vec!["tsx".to_string()]),
+            );
+            entrypoints.await?;
+
+            let console = ConsoleUi::new(TransientInstance::new(LogOptions {
+                project_dir: root.clone(),
+                current_dir: root.clone(),
+                show_all: true,
+                log_detail: false,
+                log_level: IssueSeverity::Info,
+            }));
+            let reporter: Vc<Box<dyn IssueReporter>> = Vc::upcast(LoggingIssueReporter::new(
+                Vc::upcast(console),
+                TransientInstance::new(IssueLog::new(logger)),
+            ));
+            // The same issues are reported again by each task reading them.
+            for _ in 0..2 {
+                reporter
+                    .report_issues(
+                        TransientInstance::new(entrypoints.peek_issues_with_path().await?),
+                        TransientValue::new(RawVc::from(entrypoints)),
+                        IssueSeverity::Fatal.cell(),
+                    )
+                    .await?;
+            }
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        let logs = logs.lock().unwrap();
+        assert!(logs
+            .iter()
+            .any(|log| log.level == IssueSeverity::Warning && log.message.contains("@modal")));
+        assert!(logs.iter().all(|log| log.phase.is_none()));
+        let messages: HashSet<_> = logs.iter().map(|log| &log.message).collect();
+        assert_eq!(messages.len(), logs.len());
+    }
+}
@@ -1,8 +1,13 @@
 use std::path::PathBuf;
 
-use next_core::{next_config::Rewrites, turbopack::core::issue::IssueSeverity};
+use next_core::{
+    next_client::ClientRuntime, next_config::Rewrites, resolve_conditions::ResolveConditions,
+    turbopack::core::issue::IssueSeverity, virtual_modules::VirtualModuleSource,
+};
 
-#[derive(Clone, Debug)]
+use crate::{build_logger::BuildLogger, manifest_transform::ManifestTransform};
+
+#[derive(Clone, Debug, Default)]
 pub struct BuildOptions {
     /// The root directory of the workspace.
     pub root: Option<PathBuf>,
@@ -13,12 +18,37 @@ pub struct BuildOptions {
     /// next.config.js's distDir.
     pub dist_dir: Option<String>,
 
+    /// The directory the dist directory is written to, e.g. outside of a
+    /// project directory which is mounted read-only. It's created if it
+    /// doesn't exist. Defaults to the project directory.
+    ///
+    /// The build writes nothing but the dist directory, apart from
+    /// [BuildOptions::output_tarball], so the sources and `node_modules` may
+    /// be read-only. The dist directory mustn't be inside `node_modules`.
+    pub output_root: Option<PathBuf>,
+
     /// The maximum memory to use for the build.
     pub memory_limit: Option<usize>,
 
     /// The log level to use for the build.
     pub log_level: Option<IssueSeverity>,
 
+    /// The minimum severity of issues that fails the build. Defaults to
+    /// [IssueSeverity::Fatal].
+    pub fail_on: Option<IssueSeverity>,
+
+    /// Whether to fail on the first route with an issue at or above
+    /// [BuildOptions::fail_on], without chunking the remaining routes, e.g. to
+    /// save time in CI. Otherwise, the issues of all routes are collected
+    /// before failing.
+    pub fail_fast: bool,
+
+    /// Globs for files whose issues are treated as errors, no matter their
+    /// severity, e.g. to forbid importing a deprecated module. They fail the
+    /// build even below [BuildOptions::fail_on]. They are matched against
+    /// paths relative to the workspace root.
+    pub promote_to_error: Vec<String>,
+
     /// Whether to show all logs.
     pub show_all: bool,
 
@@ -28,10 +58,210 @@ pub struct BuildOptions {
     /// Whether to compute full stats.
     pub full_stats: bool,
 
+    /// The maximum number of client references to chunk concurrently.
+    /// Unbounded when not set.
+    pub concurrency: Option<usize>,
+
+    /// Whether to warn about internal links that don't match any route.
+    pub validate_links: bool,
+
+    /// If set, warns when a client component bundles more than this many bytes
+    /// of a package meant for the server, like `sharp`, e.g. through a shared
+    /// helper importing it.
+    pub server_package_size_limit: Option<u64>,
+
+    /// Server packages which aren't reported by
+    /// [BuildOptions::server_package_size_limit], as they're legitimately
+    /// used on the client.
+    pub allowed_client_packages: Vec<String>,
+
+    /// Whether to fail the build when an `import()` doesn't resolve, instead
+    /// of failing when it's loaded at runtime.
+    pub strict_dynamic_imports: bool,
+
+    /// Whether to fail the build on relative imports without a file
+    /// extension, which native ESM in Node.js doesn't resolve. Bare
+    /// specifiers aren't affected.
+    pub strict_import_extensions: bool,
+
+    /// Whether to warn about circular dependencies between the modules of the
+    /// project.
+    pub detect_cycles: bool,
+
+    /// Whether to write `chunk-graph.dot`, the graph of the chunks and other
+    /// output assets of the routes, in the DOT format of Graphviz.
+    pub chunk_graph: bool,
+
+    /// The routes included in `chunk-graph.dot`, e.g. `/about` for a page or
+    /// `/about/page` for an app entry. Defaults to all routes.
+    pub chunk_graph_routes: Option<Vec<String>>,
+
+    /// How many references away from the entry chunks of the routes assets
+    /// are included in `chunk-graph.dot`, to keep the graphs of large apps
+    /// readable. Defaults to no limit.
+    pub chunk_graph_depth: Option<u32>,
+
+    /// Whether client chunks listed in the build manifests are additionally
+    /// written to filenames derived from their content, which the manifests
+    /// then point to.
+    pub content_addressed: bool,
+
+    /// The number of hex digits of the hashes in content-addressed filenames,
+    /// between 6 and 16. Defaults to 16. The build fails if two files share
+    /// a hash at this length, or if it's set without `content_addressed`.
+    /// Module ids aren't hashed and are unaffected.
+    pub hash_length: Option<usize>,
+
+    /// Whether to write `server/preload-manifest.json`, which lists the files
+    /// each page should preload, e.g. its entry chunks, CSS and fonts, with
+    /// their priority.
+    pub preload_manifest: bool,
+
+    /// Whether to write `server/load-order-manifest.json`, which lists the
+    /// stylesheets, preloads and scripts of each page in the order to render
+    /// them into its `<head>`.
+    pub load_order_manifest: bool,
+
+    /// Whether to write `deploy-manifest.json`, which lists the files of the
+    /// build with their size and hash for deploy tools. It's written last, so
+    /// its presence marks a complete build.
+    pub deploy_manifest: bool,
+
+    /// Whether to write `build-diagnostics.json`, which lists how long the
+    /// build and each of its phases took, and how long each route took to
+    /// chunk.
+    pub diagnostics: bool,
+
+    /// Receives a log line when each phase of the build finishes and for each
+    /// issue at its severity, e.g. to print them in the host's format. Issues
+    /// are also reported to the console.
+    pub logger: Option<BuildLogger>,
+
+    /// Called with the name and JSON of each manifest before it's written,
+    /// e.g. to rewrite its paths for a deployment target, and returns the
+    /// JSON to write instead. The build fails if it returns invalid JSON.
+    pub manifest_transform: Option<ManifestTransform>,
+
+    /// Whether client chunks are written without their `//# sourceMappingURL`
+    /// comment. The `.map` files are still written, e.g. to upload them to an
+    /// error tracker, but browsers won't load them. Server chunks keep the
+    /// comment for local debugging.
+    pub hidden_source_maps: bool,
+
+    /// Whether client chunks are lowered to ES5 for very old browsers, e.g.
+    /// for a legacy bundle, instead of targeting recent browsers. Syntax like
+    /// arrow functions, classes and `const`/`let` is transpiled, with the
+    /// helpers it needs inlined. Server chunks are unaffected.
+    pub legacy_browsers: bool,
+
+    /// The path prefix the data routes of pages with `getStaticProps` or
+    /// `getServerSideProps` are served under, followed by the build id, e.g.
+    /// `/proxy/data` for `/proxy/data/<buildId>/about.json` behind a proxy
+    /// which rewrites `/_next/data`. Defaults to `/_next/data`. It's applied to
+    /// both the data routes of `routes-manifest.json` and `_buildManifest.js`,
+    /// which the client router fetches data with, so that they stay
+    /// consistent.
+    pub data_path_prefix: Option<String>,
+
+    /// The variant of the Turbopack runtime which loads the client chunks, e.g.
+    /// the development runtime to compare the output with `next dev`'s.
+    /// Defaults to [ClientRuntime::Production]. It's recorded in
+    /// `build-manifest.json`, and a build with
+    /// [BuildOptions::changed_files] fails if the prior build's routes it keeps
+    /// use another runtime, as all pages must share one.
+    pub client_runtime: Option<ClientRuntime>,
+
+    /// Whether server chunks are minified, independently of client chunks.
+    /// Defaults to next.config.js's `experimental.serverMinification`, which
+    /// is on unless disabled.
+    pub minify_server: Option<bool>,
+
+    /// A seed mixed into the hashes the build derives names from, i.e. the
+    /// default build id and content-addressed filenames, so that builds on
+    /// different machines produce byte-identical output. Module ids and chunk
+    /// names are already derived from paths and content.
+    pub build_hash_seed: Option<String>,
+
+    /// How many times a write of the build output that failed with a
+    /// transient error, e.g. a file locked by a virus scanner, is retried.
+    /// Defaults to 3, and is at most 10.
+    pub write_retries: Option<u32>,
+
+    /// Whether manifests are written as compact JSON instead of being
+    /// pretty-printed, to reduce the size of the dist directory for deploys.
+    /// The output stays deterministic, so reproducible builds can still be
+    /// compared byte for byte.
+    pub compact_manifests: bool,
+
+    /// Whether a failed build writes what it got to before failing to
+    /// `diagnostics` in the dist directory: a snapshot of the config, the
+    /// resolved routes, the collected issues and the modules with failing
+    /// issues. The values of `env` and `serverRuntimeConfig` are redacted, so
+    /// the bundle can be attached to bug reports.
+    pub diagnostics_on_failure: bool,
+
+    /// The prefixes marking environment variables as public, which inlines
+    /// them into client bundles. Defaults to `NEXT_PUBLIC_`.
+    pub public_env_prefixes: Option<Vec<String>>,
+
     /// The Next.js build context.
     pub build_context: Option<BuildContext>,
 
     pub define_env: DefineEnv,
+
+    /// Compile-time replacements applied to both client and server code, e.g.
+    /// `("__DEV__", "false")`. Keys are the replaced expressions and values
+    /// their JSON, like in [DefineEnv]. They take precedence over `define_env`
+    /// and are inlined before unused code is eliminated, so dead branches are
+    /// dropped. `process.env.NODE_ENV` is `"production"` unless defined here.
+    pub defines: Vec<(String, String)>,
+
+    /// Modules generated before the build, e.g. a registry of the routes, as
+    /// their specifier, like `virtual:routes`, and their JavaScript source or
+    /// the file it's generated to. They're only used for specifiers that
+    /// don't resolve otherwise.
+    pub virtual_modules: Vec<(String, VirtualModuleSource)>,
+
+    /// Additional `exports` conditions to resolve packages with in the client,
+    /// server rendering and server components layers, on top of the
+    /// conditions of each layer.
+    pub resolve_conditions: ResolveConditions,
+
+    /// The regions functions are deployed to in
+    /// `functions-config-manifest.json` when their route doesn't export a
+    /// `preferredRegion`.
+    pub default_preferred_region: Option<Vec<String>>,
+
+    /// The timeout in seconds of functions whose route doesn't export a
+    /// `maxDuration`.
+    pub default_max_duration: Option<u32>,
+
+    /// If set, only the routes depending on these files are emitted, along
+    /// with the chunks shared between routes, and the output of a prior build
+    /// is kept for the other routes. E.g. the files changed in a pull request,
+    /// relative to the project directory. A change to a build-wide file like
+    /// `next.config.js` rebuilds every route.
+    pub changed_files: Option<Vec<String>>,
+
+    /// If set, the paths of the pages in `pages-manifest.json` are resolved
+    /// against this directory, e.g. for a custom server loading the pages from
+    /// another location. By default they're relative to the manifest, which
+    /// keeps working when the dist directory is moved.
+    pub pages_manifest_base: Option<PathBuf>,
+
+    /// The entry of a custom server, e.g. `server.ts`, relative to the project
+    /// directory. If set, it's compiled to `server.js` in the dist directory,
+    /// with the files it loads listed in `server.js.nft.json`.
+    pub custom_server: Option<String>,
+
+    /// The directory of the files served from the root of the site, relative
+    /// to the project directory. They're copied to `public` in the dist
+    /// directory. Defaults to `public`, and is skipped if it doesn't exist.
+    pub public_dir: Option<String>,
+
+    /// If set, the build output is additionally written as a tar archive to
+    /// this path once the build has completed.
+    pub output_tarball: Option<PathBuf>,
 }
 
 #[derive(Clone, Debug)]
@@ -43,7 +273,7 @@ pub struct BuildContext {
     pub rewrites: Rewrites,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct DefineEnv {
     pub client: Vec<(String, String)>,
     pub edge: Vec<(String, String)>,
@@ -0,0 +1,255 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use next_core::{
+    next_manifests::{AppBuildManifest, BuildManifest},
+    pages_structure::is_special_page,
+};
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, TryFlatJoinIterExt, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{FileContent, FileSystemPath},
+    turbopack::core::{
+        asset::{Asset, AssetContent},
+        output::OutputAsset,
+    },
+};
+
+/// The client JavaScript sizes of a route, as shown in the `next build`
+/// output table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteSize {
+    /// The route's pathname for pages, or its original name for app entries.
+    pub route: String,
+    /// JavaScript only loaded by this route, in bytes.
+    pub size: u64,
+    /// All JavaScript loaded when first navigating to this route, including
+    /// the shared JavaScript, in bytes.
+    pub first_load_js: u64,
+}
+
+/// A summary of the build output.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildReport {
+    /// The sizes of all routes, sorted by route.
+    pub routes: Vec<RouteSize>,
+    /// The JavaScript files shared by all routes and their sizes in bytes.
+    pub shared_files: Vec<(String, u64)>,
+    /// The total size of the shared JavaScript, in bytes. This is included in
+    /// each route's first load size.
+    pub shared_js: u64,
+}
+
+/// Computes the size of all client JavaScript chunks within `all_chunks`,
+/// keyed by their path relative to `client_relative_path`, which is how they
+/// are referenced from the build manifests.
+async fn client_chunk_sizes(
+    all_chunks: &[Vc<Box<dyn OutputAsset>>],
+    client_relative_path: &FileSystemPath,
+) -> Result<HashMap<String, u64>> {
+    Ok(all_chunks
+        .iter()
+        .map(|&chunk| async move {
+            let path = chunk.ident().path().await?;
+            if path.extension_ref() != Some("js") {
+                return Ok(None);
+            }
+            let Some(relative_path) = client_relative_path.get_path_to(&path) else {
+                return Ok(None);
+            };
+            let size = match &*chunk.content().await? {
+                AssetContent::File(file) => match &*file.await? {
+                    FileContent::Content(file) => file.content().len() as u64,
+                    FileContent::NotFound => 0,
+                },
+                AssetContent::Redirect { .. } => 0,
+            };
+            Ok(Some((relative_path.to_string(), size)))
+        })
+        .try_flat_join()
+        .await?
+        .into_iter()
+        .collect())
+}
+
+/// Computes the first load JS of all page and app routes from the build
+/// manifests.
+///
+/// Shared JavaScript (the main files and `_app`'s chunks) is reported once in
+/// [BuildReport::shared_js], and only counted towards the first load size of
+/// each route.
+pub(crate) async fn compute_build_report(
+    all_chunks: &[Vc<Box<dyn OutputAsset>>],
+    client_relative_path: &FileSystemPath,
+    build_manifest: &BuildManifest,
+    app_build_manifest: &AppBuildManifest,
+) -> Result<BuildReport> {
+    let sizes = client_chunk_sizes(all_chunks, client_relative_path).await?;
+    let size_of = |file: &str| sizes.get(file).copied().unwrap_or_default();
+
+    let app_files = build_manifest
+        .pages
+        .get("/_app")
+        .map(|files| files.as_slice())
+        .unwrap_or_default();
+
+    let mut shared_files: Vec<&str> = vec![];
+    for file in build_manifest.root_main_files.iter().chain(app_files) {
+        if !shared_files.contains(&file.as_str()) {
+            shared_files.push(file);
+        }
+    }
+    let shared_set: HashSet<&str> = shared_files.iter().copied().collect();
+
+    let route_size = |route: &str, files: &mut dyn Iterator<Item = &String>| {
+        let files: HashSet<&str> = files.map(|file| file.as_str()).collect();
+        RouteSize {
+            route: route.to_string(),
+            size: files
+                .iter()
+                .filter(|file| !shared_set.contains(*file))
+                .map(|file| size_of(file))
+                .sum(),
+            first_load_js: files.iter().map(|file| size_of(file)).sum(),
+        }
+    };
+
+    let mut routes = vec![];
+    for (pathname, files) in &build_manifest.pages {
+        if is_special_page(pathname) {
+            continue;
+        }
+        // Pages always load `_app` alongside their own chunks.
+        routes.push(route_size(pathname, &mut files.iter().chain(app_files)));
+    }
+    for (original_name, files) in &app_build_manifest.pages {
+        // App pages already list the shared client chunks.
+        routes.push(route_size(original_name, &mut files.iter()));
+    }
+    routes.sort_by(|a, b| a.route.cmp(&b.route));
+
+    let shared_files: Vec<(String, u64)> = shared_files
+        .into_iter()
+        .map(|file| (file.to_string(), size_of(file)))
+        .collect();
+    let shared_js = shared_files.iter().map(|(_, size)| size).sum();
+
+    Ok(BuildReport {
+        routes,
+        shared_files,
+        shared_js,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use next_core::next_manifests::{AppBuildManifest, BuildManifest};
+    use turbo_tasks::Vc;
+    use turbopack_binding::{
+        turbo::tasks_fs::{File, FileSystem, VirtualFileSystem},
+        turbopack::core::{
+            asset::AssetContent, output::OutputAsset, virtual_output::VirtualOutputAsset,
+        },
+    };
+
+    use super::{compute_build_report, BuildReport, RouteSize};
+    use crate::test_utils::run;
+
+    fn files(files: &[&str]) -> Vec<String> {
+        files.iter().map(|file| file.to_string()).collect()
+    }
+
+    fn route_size(route: &str, size: u64, first_load_js: u64) -> RouteSize {
+        RouteSize {
+            route: route.to_string(),
+            size,
+            first_load_js,
+        }
+    }
+
+    #[tokio::test]
+    async fn sums_the_client_chunks_of_each_route() {
+        let report = run(async {
+            let client_relative_path = VirtualFileSystem::new().root().join(".next".to_string());
+            let all_chunks: Vec<Vc<Box<dyn OutputAsset>>> = [
+                ("static/chunks/main.js", 100),
+                ("static/chunks/pages/_app.js", 20),
+                ("static/chunks/pages/index.js", 30),
+                ("static/chunks/pages/about.js", 10),
+                ("static/chunks/main-app.js", 50),
+                ("static/chunks/app/dashboard/page.js", 40),
+                // Only JavaScript is counted.
+                ("static/css/dashboard.css", 1000),
+            ]
+            .into_iter()
+            .map(|(path, size)| {
+                Vc::upcast(VirtualOutputAsset::new(
+                    client_relative_path.join(path.to_string()),
+                    AssetContent::file(File::from("x".repeat(size)).into()),
+                ))
+            })
+            .collect();
+
+            let build_manifest = BuildManifest {
+                root_main_files: files(&["static/chunks/main.js"]),
+                pages: [
+                    ("/_app", files(&["static/chunks/pages/_app.js"])),
+                    ("/", files(&["static/chunks/pages/index.js"])),
+                    // `_app`'s chunks are shared, even when a page lists them.
+                    (
+                        "/about",
+                        files(&[
+                            "static/chunks/pages/about.js",
+                            "static/chunks/pages/_app.js",
+                        ]),
+                    ),
+                ]
+                .into_iter()
+                .map(|(pathname, files)| (pathname.to_string(), files))
+                .collect(),
+                ..Default::default()
+            };
+            let app_build_manifest = AppBuildManifest {
+                pages: [(
+                    "/dashboard/page".to_string(),
+                    files(&[
+                        "static/chunks/main-app.js",
+                        "static/chunks/app/dashboard/page.js",
+                    ]),
+                )]
+                .into_iter()
+                .collect(),
+                ..Default::default()
+            };
+
+            compute_build_report(
+                &all_chunks,
+                &*client_relative_path.await?,
+                &build_manifest,
+                &app_build_manifest,
+            )
+            .await
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            report,
+            BuildReport {
+                routes: vec![
+                    route_size("/", 30, 150),
+                    route_size("/about", 10, 130),
+                    route_size("/dashboard/page", 90, 90),
+                ],
+                shared_files: vec![
+                    ("static/chunks/main.js".to_string(), 100),
+                    ("static/chunks/pages/_app.js".to_string(), 20),
+                ],
+                shared_js: 120,
+            }
+        );
+    }
+}
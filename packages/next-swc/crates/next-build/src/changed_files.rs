@@ -0,0 +1,225 @@
+use std::{collections::HashSet, path::Path};
+
+use anyhow::Result;
+use next_core::module_analysis::all_referenced_modules;
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::core::{
+        module::{Module, Modules},
+        output::OutputAsset,
+    },
+};
+
+/// Files which configure the whole build, so that a change to them can't be
+/// attributed to some routes. They're matched by their name, in any directory.
+const GLOBAL_FILE_PREFIXES: &[&str] = &[
+    "next.config.",
+    "package.json",
+    "tsconfig.json",
+    "jsconfig.json",
+    ".env",
+    "postcss.config.",
+    "tailwind.config.",
+    ".babelrc",
+    "babel.config.",
+];
+
+/// Resolves the [BuildOptions::changed_files] to paths in the project's file
+/// system. Returns `None` when every route has to be rebuilt, i.e. when a
+/// build-wide configuration file changed.
+///
+/// [BuildOptions::changed_files]: crate::BuildOptions::changed_files
+pub(crate) async fn resolve_changed_files(
+    project_root: Vc<FileSystemPath>,
+    project_dir: &str,
+    changed_files: &[String],
+) -> Result<Option<Vc<Vec<String>>>> {
+    let mut paths = Vec::with_capacity(changed_files.len());
+    for file in changed_files {
+        let file_name = Path::new(file)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        if GLOBAL_FILE_PREFIXES
+            .iter()
+            .any(|prefix| file_name.starts_with(prefix))
+        {
+            return Ok(None);
+        }
+
+        let relative = Path::new(file)
+            .strip_prefix(project_dir)
+            .unwrap_or(Path::new(file));
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        paths.push(project_root.join(relative).await?.path.clone());
+    }
+    Ok(Some(Vc::cell(paths)))
+}
+
+/// Returns whether any module in the module graph of `entries` is one of the
+/// `changed_files`, which are paths in the project's file system.
+#[turbo_tasks::function]
+async fn depends_on_changed_files(
+    entries: Vc<Modules>,
+    changed_files: Vc<Vec<String>>,
+) -> Result<Vc<bool>> {
+    let changed_files = changed_files.await?;
+    let changed_files: HashSet<&str> = changed_files.iter().map(String::as_str).collect();
+    let modules = all_referenced_modules(entries.await?.iter().copied()).await?;
+
+    let paths = modules
+        .into_iter()
+        .map(|module| async move { Ok(module.ident().path().await?.path.clone()) })
+        .try_join()
+        .await?;
+    Ok(Vc::cell(
+        paths
+            .iter()
+            .any(|path| changed_files.contains(path.as_str())),
+    ))
+}
+
+/// Sorts the chunks of routes by whether the routes are affected by the
+/// changed files, so that only the affected ones are emitted and the output
+/// of a prior build is kept for the others.
+///
+/// The chunks of every route are still computed, as the manifests list all
+/// routes.
+pub(crate) struct RouteChunks {
+    changed_files: Option<Vc<Vec<String>>>,
+    affected: HashSet<Vc<Box<dyn OutputAsset>>>,
+    unaffected: HashSet<Vc<Box<dyn OutputAsset>>>,
+    routes: Vec<(String, Vec<Vc<Box<dyn OutputAsset>>>)>,
+}
+
+impl RouteChunks {
+    /// Every route is affected when `changed_files` is `None`.
+    pub(crate) fn new(changed_files: Option<Vc<Vec<String>>>) -> Self {
+        Self {
+            changed_files,
+            affected: Default::default(),
+            unaffected: Default::default(),
+            routes: Default::default(),
+        }
+    }
+
+    /// Records the chunks of the route with the given entry modules, e.g.
+    /// `/about` for a page or `/about/page` for an app entry. A route is
+    /// affected when any module it imports, e.g. a shared layout, changed.
+    pub(crate) async fn add_route(
+        &mut self,
+        route: String,
+        entries: Vec<Vc<Box<dyn Module>>>,
+        chunks: impl IntoIterator<Item = Vc<Box<dyn OutputAsset>>>,
+    ) -> Result<()> {
+        let chunks = chunks.into_iter().collect::<Vec<_>>();
+        let affected = match self.changed_files {
+            Some(changed_files) => {
+                *depends_on_changed_files(Vc::cell(entries), changed_files).await?
+            }
+            None => true,
+        };
+        if affected {
+            self.affected.extend(chunks.iter().copied());
+        } else {
+            self.unaffected.extend(chunks.iter().copied());
+        }
+        self.routes.push((route, chunks));
+        Ok(())
+    }
+
+    /// The entry chunks of every route, in the order they were added.
+    pub(crate) fn routes(&self) -> &[(String, Vec<Vc<Box<dyn OutputAsset>>>)] {
+        &self.routes
+    }
+
+    /// Returns whether the chunk doesn't need to be emitted. Chunks shared
+    /// with an affected route are always emitted.
+    pub(crate) fn is_unaffected(&self, chunk: &Vc<Box<dyn OutputAsset>>) -> bool {
+        self.unaffected.contains(chunk) && !self.affected.contains(chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use next_core::test_utils::client_module;
+    use turbo_tasks::Vc;
+    use turbopack_binding::{
+        turbo::tasks_fs::{File, FileContent},
+        turbopack::core::{
+            asset::AssetContent, output::OutputAsset, virtual_output::VirtualOutputAsset,
+        },
+    };
+
+    use super::{resolve_changed_files, RouteChunks};
+    use crate::test_utils::{fixture, fixture_root, run};
+
+    #[tokio::test]
+    async fn emits_only_the_routes_depending_on_a_changed_file() {
+        let dir = fixture(&[
+            ("shared.js", "export const shared = 'shared';"),
+            (
+                "a.js",
+                "import { shared } from './shared';\nconsole.log(shared);",
+            ),
+            ("b.js", "console.log('b');"),
+        ]);
+        let root = dir.path().to_owned();
+        let (a_unaffected, b_unaffected) = run(async move {
+            let project_root = fixture_root(&root);
+            let chunk = |path: &str| -> Vc<Box<dyn OutputAsset>> {
+                Vc::upcast(VirtualOutputAsset::new(
+                    project_root.join(format!(".next/{path}")),
+                    AssetContent::file(FileContent::Content(File::from("")).cell()),
+                ))
+            };
+            let changed_files = resolve_changed_files(
+                project_root,
+                root.to_str().unwrap(),
+                &["shared.js".to_string()],
+            )
+            .await?;
+            assert!(changed_files.is_some());
+
+            let mut route_chunks = RouteChunks::new(changed_files);
+            let (a, b) = (chunk("a.js"), chunk("b.js"));
+            let define_env = Vc::cell(Default::default());
+            route_chunks
+                .add_route(vec![client_module(project_root, "a.js", define_env)], [a])
+                .await?;
+            route_chunks
+                .add_route(vec![client_module(project_root, "b.js", define_env)], [b])
+                .await?;
+            Ok((
+                route_chunks.is_unaffected(&a),
+                route_chunks.is_unaffected(&b),
+            ))
+        })
+        .await
+        .unwrap();
+
+        assert!(!a_unaffected);
+        assert!(b_unaffected);
+    }
+
+    #[tokio::test]
+    async fn rebuilds_every_route_when_the_next_config_changed() {
+        let dir = fixture(&[]);
+        let root = dir.path().to_owned();
+        let changed_files = run(async move {
+            let project_root = fixture_root(&root);
+            let changed_files = resolve_changed_files(
+                project_root,
+                root.to_str().unwrap(),
+                &["app/page.js".to_string(), "next.config.js".to_string()],
+            )
+            .await?;
+            Ok(changed_files.is_none())
+        })
+        .await
+        .unwrap();
+
+        assert!(changed_files);
+    }
+}
@@ -0,0 +1,284 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Write,
+};
+
+use anyhow::Result;
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{FileContent, FileSystemPath},
+    turbopack::core::{
+        asset::{Asset, AssetContent},
+        output::OutputAsset,
+    },
+};
+
+/// A chunk or other output asset in the chunk graph.
+struct ChunkNode {
+    /// The path relative to the dist directory.
+    path: String,
+    size: usize,
+    references: Vec<Vc<Box<dyn OutputAsset>>>,
+}
+
+/// Renders the graph of the output assets of the `routes`, e.g. chunks and
+/// their source maps, in the DOT format of Graphviz. Routes are boxes linked
+/// to their entry chunks, assets are ellipses sized by their bytes, and edges
+/// are their references.
+///
+/// Only the routes listed in `route_filter` are included, if it's set. With
+/// `max_depth`, only assets reachable from the entry chunks through at most
+/// that many references are included.
+pub(crate) async fn chunk_graph_dot(
+    routes: &[(String, Vec<Vc<Box<dyn OutputAsset>>>)],
+    route_filter: Option<&[String]>,
+    max_depth: Option<u32>,
+    node_root: &FileSystemPath,
+    client_relative_path: &FileSystemPath,
+) -> Result<String> {
+    // Resolved, so that the same asset reached from different routes or
+    // references is a single node.
+    let mut routes = routes
+        .iter()
+        .filter(|(route, _)| route_filter.map_or(true, |filter| filter.contains(route)))
+        .map(|(route, chunks)| async move {
+            Ok((
+                route,
+                chunks
+                    .iter()
+                    .map(|chunk| chunk.resolve())
+                    .try_join()
+                    .await?,
+            ))
+        })
+        .try_join()
+        .await?;
+    routes.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut nodes: HashMap<Vc<Box<dyn OutputAsset>>, ChunkNode> = HashMap::new();
+    let mut order = vec![];
+    let mut queue: VecDeque<_> = routes
+        .iter()
+        .flat_map(|(_, chunks)| chunks.iter().map(|&chunk| (chunk, 0)))
+        .collect();
+    while let Some((asset, depth)) = queue.pop_front() {
+        if nodes.contains_key(&asset) {
+            continue;
+        }
+        let node = chunk_node(asset, node_root, client_relative_path).await?;
+        if max_depth.map_or(true, |max_depth| depth < max_depth) {
+            queue.extend(
+                node.references
+                    .iter()
+                    .map(|&reference| (reference, depth + 1)),
+            );
+        }
+        nodes.insert(asset, node);
+        order.push(asset);
+    }
+
+    let mut dot = String::new();
+    writeln!(dot, "digraph chunks {{")?;
+    writeln!(dot, "  rankdir=LR;")?;
+    writeln!(dot, "  node [shape=ellipse, fixedsize=false];")?;
+    for (route, _) in routes.iter() {
+        writeln!(dot, "  {} [shape=box];", quote(&format!("route {route}")))?;
+    }
+    for asset in order.iter() {
+        let node = &nodes[asset];
+        writeln!(
+            dot,
+            "  {} [label=\"{}\\n{}\", width={:.2}];",
+            quote(&node.path),
+            escape(&node.path),
+            format_size(node.size),
+            node_width(node.size)
+        )?;
+    }
+    let mut edges = HashSet::new();
+    for (route, chunks) in routes.iter() {
+        for chunk in chunks.iter() {
+            writeln!(
+                dot,
+                "  {} -> {};",
+                quote(&format!("route {route}")),
+                quote(&nodes[chunk].path)
+            )?;
+        }
+    }
+    for asset in order.iter() {
+        let node = &nodes[asset];
+        for reference in node.references.iter() {
+            // References beyond the maximum depth aren't part of the graph.
+            let Some(referenced) = nodes.get(reference) else {
+                continue;
+            };
+            if edges.insert((&node.path, &referenced.path)) {
+                writeln!(
+                    dot,
+                    "  {} -> {};",
+                    quote(&node.path),
+                    quote(&referenced.path)
+                )?;
+            }
+        }
+    }
+    writeln!(dot, "}}")?;
+    Ok(dot)
+}
+
+async fn chunk_node(
+    asset: Vc<Box<dyn OutputAsset>>,
+    node_root: &FileSystemPath,
+    client_relative_path: &FileSystemPath,
+) -> Result<ChunkNode> {
+    let path = asset.ident().path().await?;
+    let path = node_root
+        .get_path_to(&path)
+        .or_else(|| client_relative_path.get_path_to(&path))
+        .unwrap_or(&path.path)
+        .to_string();
+
+    let size = match &*asset.content().await? {
+        AssetContent::File(file) => match &*file.await? {
+            FileContent::Content(file) => file.content().len(),
+            FileContent::NotFound => 0,
+        },
+        AssetContent::Redirect { .. } => 0,
+    };
+
+    let references = asset
+        .references()
+        .await?
+        .iter()
+        .map(|reference| reference.resolve())
+        .try_join()
+        .await?;
+
+    Ok(ChunkNode {
+        path,
+        size,
+        references,
+    })
+}
+
+fn quote(id: &str) -> String {
+    format!("\"{}\"", escape(id))
+}
+
+fn escape(id: &str) -> String {
+    id.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn format_size(size: usize) -> String {
+    if size < 1024 {
+        format!("{size} B")
+    } else {
+        format!("{:.1} kB", size as f64 / 1024.0)
+    }
+}
+
+/// Scales the width of a node in inches, so that its area grows with its
+/// size.
+fn node_width(size: usize) -> f64 {
+    (size as f64 / 1024.0).sqrt().clamp(0.75, 8.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use next_core::next_client::get_client_compile_time_info;
+    use turbo_tasks::Vc;
+    use turbopack_binding::turbopack::core::asset::Asset;
+
+    use super::chunk_graph_dot;
+    use crate::test_utils::{client_chunk_group, fixture, fixture_root, run};
+
+    /// Renders the chunk graph of `/` and `/about`, which both load the chunks
+    /// of `index.js`, and returns it with the paths of these chunks.
+    async fn render(
+        route_filter: Option<Vec<String>>,
+        max_depth: Option<u32>,
+    ) -> (String, Vec<String>) {
+        let dir = fixture(&[("index.js", "console.log('index');")]);
+        let dir_path = dir.path().to_path_buf();
+        run(async move {
+            let project_root = fixture_root(&dir_path);
+            let compile_time_info = get_client_compile_time_info(
+                "last 1 Chrome versions".to_string(),
+                Vc::cell(Default::default()),
+            );
+            let chunks = client_chunk_group(project_root, "index.js", compile_time_info)
+                .await?
+                .await?
+                .clone_value();
+
+            let dist_root = project_root.join(".next".to_string()).await?;
+            let mut chunk_paths = vec![];
+            for chunk in chunks.iter() {
+                let path = chunk.ident().path().await?;
+                chunk_paths.push(dist_root.get_path_to(&path).unwrap().to_string());
+            }
+            let routes = [
+                ("/".to_string(), chunks.clone()),
+                ("/about".to_string(), chunks),
+            ];
+            let dot = chunk_graph_dot(
+                &routes,
+                route_filter.as_deref(),
+                max_depth,
+                &dist_root,
+                &dist_root,
+            )
+            .await?;
+            Ok((dot, chunk_paths))
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn declares_routes_chunks_and_their_references() {
+        let (dot, chunk_paths) = render(None, None).await;
+
+        assert!(dot.starts_with("digraph chunks {\n"), "{dot}");
+        assert!(dot.contains("  \"route /\" [shape=box];\n"), "{dot}");
+        assert!(dot.contains("  \"route /about\" [shape=box];\n"), "{dot}");
+        assert!(!chunk_paths.is_empty());
+        for path in chunk_paths.iter() {
+            assert!(
+                dot.contains(&format!("  \"{path}\" [label=\"{path}\\n")),
+                "{dot}"
+            );
+            assert!(
+                dot.contains(&format!("  \"route /\" -> \"{path}\";\n")),
+                "{dot}"
+            );
+            assert!(
+                dot.contains(&format!("  \"route /about\" -> \"{path}\";\n")),
+                "{dot}"
+            );
+        }
+        // Chunks reference their source maps.
+        let path = &chunk_paths[0];
+        assert!(
+            dot.contains(&format!("  \"{path}\" -> \"{path}.map\";\n")),
+            "{dot}"
+        );
+    }
+
+    #[tokio::test]
+    async fn prunes_the_graph_to_the_routes_and_depth() {
+        let (dot, chunk_paths) = render(Some(vec!["/".to_string()]), Some(0)).await;
+
+        assert!(dot.contains("\"route /\""), "{dot}");
+        assert!(!dot.contains("\"route /about\""), "{dot}");
+        for path in chunk_paths.iter() {
+            assert!(
+                dot.contains(&format!("  \"route /\" -> \"{path}\";\n")),
+                "{dot}"
+            );
+        }
+        // The source maps are one reference away from the entry chunks.
+        assert!(!dot.contains(".map\""), "{dot}");
+    }
+}
@@ -0,0 +1,296 @@
+use std::collections::{hash_map::Entry, BTreeMap, HashMap, HashSet};
+
+use anyhow::{bail, Result};
+use next_core::next_manifests::{AppBuildManifest, BuildManifest, ContentAddressedManifest};
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::{
+        tasks_fs::{FileContent, FileSystemPath},
+        tasks_hash::hash_xxh3_hash64,
+    },
+    turbopack::core::{asset::Asset, output::OutputAsset},
+};
+
+/// Renames the client files listed in the build manifests to
+/// `static/chunks/[hash].[ext]`, so that their URLs only change when their
+/// content does and can be cached forever.
+///
+/// Returns the mapping from the original to the hashed files, and the hashed
+/// copies to write relative to `client_output_path`. The originals are still
+/// emitted, as chunks load each other by their original names.
+///
+/// The `build_hash_seed` is mixed into the hashes when set. The hashes are
+/// truncated to `hash_length` hex digits, defaulting to all 16, and the build
+/// fails if two files share a truncated hash.
+pub(crate) async fn content_address_client_files(
+    build_manifest: &mut BuildManifest,
+    app_build_manifest: &mut AppBuildManifest,
+    chunks: &[Vc<Box<dyn OutputAsset>>],
+    client_relative_path: &FileSystemPath,
+    client_output_path: Vc<FileSystemPath>,
+    build_hash_seed: Option<&str>,
+    hash_length: Option<usize>,
+) -> Result<(
+    ContentAddressedManifest,
+    Vec<(Vc<FileSystemPath>, Vc<FileContent>)>,
+)> {
+    let listed_files: HashSet<&str> = build_manifest
+        .pages
+        .values()
+        .chain(app_build_manifest.pages.values())
+        .flatten()
+        .chain(&build_manifest.root_main_files)
+        .chain(&build_manifest.polyfill_files)
+        .map(|file| file.as_str())
+        .collect();
+
+    let mut names = HashedNames::new(hash_length.unwrap_or(MAX_HASH_LENGTH))?;
+    let mut files = BTreeMap::new();
+    let mut hashed_files = vec![];
+    for &chunk in chunks {
+        let path = chunk.ident().path().await?;
+        let Some(relative_path) = client_relative_path.get_path_to(&path) else {
+            continue;
+        };
+        if !listed_files.contains(relative_path) || files.contains_key(relative_path) {
+            continue;
+        }
+        let content = chunk.content().file_content();
+        if !matches!(&*content.await?, FileContent::Content(_)) {
+            continue;
+        }
+        let hash = seeded_hash(build_hash_seed, *content.hash().await?);
+        let hashed_path = names.hashed_path(hash, path.extension_ref())?;
+        hashed_files.push((client_output_path.join(hashed_path.clone()), content));
+        files.insert(relative_path.to_string(), hashed_path);
+    }
+
+    let rename = |file: &mut String| {
+        if let Some(hashed_path) = files.get(file.as_str()) {
+            *file = hashed_path.clone();
+        }
+    };
+    build_manifest
+        .pages
+        .values_mut()
+        .chain(app_build_manifest.pages.values_mut())
+        .flatten()
+        .chain(&mut build_manifest.root_main_files)
+        .chain(&mut build_manifest.polyfill_files)
+        .for_each(rename);
+
+    Ok((ContentAddressedManifest { files }, hashed_files))
+}
+
+/// The shortest `hash_length` accepted. Shorter hashes would collide within
+/// a few thousand files.
+const MIN_HASH_LENGTH: usize = 6;
+
+/// The hex digits of a 64-bit hash.
+const MAX_HASH_LENGTH: usize = 16;
+
+/// Derives the `static/chunks/[hash].[ext]` names from truncated hashes,
+/// erroring when two different hashes truncate to the same name.
+struct HashedNames {
+    hash_length: usize,
+    hashes: HashMap<String, u64>,
+}
+
+impl HashedNames {
+    fn new(hash_length: usize) -> Result<Self> {
+        if !(MIN_HASH_LENGTH..=MAX_HASH_LENGTH).contains(&hash_length) {
+            bail!(
+                "hash_length must be between {MIN_HASH_LENGTH} and {MAX_HASH_LENGTH}, got \
+                 {hash_length}"
+            );
+        }
+        Ok(Self {
+            hash_length,
+            hashes: HashMap::new(),
+        })
+    }
+
+    fn hashed_path(&mut self, hash: u64, extension: Option<&str>) -> Result<String> {
+        let truncated = format!("{hash:016x}")[..self.hash_length].to_string();
+        match self.hashes.entry(truncated.clone()) {
+            Entry::Occupied(entry) if *entry.get() != hash => {
+                bail!(
+                    "two client files share the content hash {truncated} at hash_length {}, use a \
+                     longer hash_length",
+                    self.hash_length
+                );
+            }
+            Entry::Occupied(_) => {}
+            Entry::Vacant(entry) => {
+                entry.insert(hash);
+            }
+        }
+        Ok(match extension {
+            Some(extension) => format!("static/chunks/{truncated}.{extension}"),
+            None => format!("static/chunks/{truncated}"),
+        })
+    }
+}
+
+/// Mixes `seed` into `hash`, so that builds with different seeds don't share
+/// filenames while builds with the same seed stay byte-identical.
+pub(crate) fn seeded_hash(seed: Option<&str>, hash: u64) -> u64 {
+    match seed {
+        Some(seed) => hash_xxh3_hash64(format!("{seed}:{hash:016x}").as_bytes()),
+        None => hash,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use turbopack_binding::{
+        turbo::tasks_fs::{File, FileSystem, VirtualFileSystem},
+        turbopack::core::{asset::AssetContent, virtual_output::VirtualOutputAsset},
+    };
+
+    use super::*;
+    use crate::test_utils::run;
+
+    fn is_hashed_chunk(file: &str, extension: &str) -> bool {
+        file.strip_prefix("static/chunks/")
+            .and_then(|name| name.strip_suffix(extension))
+            .is_some_and(|hash| hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+    }
+
+    #[tokio::test]
+    async fn maps_routes_to_content_hashed_files() {
+        let (build_manifest, app_build_manifest, manifest, hashed_paths) = run(async {
+            let client_relative_path = VirtualFileSystem::new().root().join(".next".to_string());
+            let chunks: Vec<Vc<Box<dyn OutputAsset>>> = [
+                ("static/chunks/main.js", "main"),
+                ("static/chunks/pages/index.js", "index"),
+                ("static/chunks/app/page.js", "app page"),
+                ("static/css/app/page.css", "body {}"),
+                // Files missing from the manifests aren't renamed.
+                ("static/chunks/unlisted.js", "unlisted"),
+            ]
+            .into_iter()
+            .map(|(path, content)| {
+                Vc::upcast(VirtualOutputAsset::new(
+                    client_relative_path.join(path.to_string()),
+                    AssetContent::file(File::from(content).into()),
+                ))
+            })
+            .collect();
+
+            let mut build_manifest = BuildManifest {
+                root_main_files: vec!["static/chunks/main.js".to_string()],
+                pages: [(
+                    "/".to_string(),
+                    vec!["static/chunks/pages/index.js".to_string()],
+                )]
+                .into_iter()
+                .collect(),
+                ..Default::default()
+            };
+            let mut app_build_manifest = AppBuildManifest {
+                pages: [(
+                    "/page".to_string(),
+                    vec![
+                        "static/chunks/app/page.js".to_string(),
+                        "static/css/app/page.css".to_string(),
+                    ],
+                )]
+                .into_iter()
+                .collect(),
+                ..Default::default()
+            };
+            let (manifest, hashed_files) = content_address_client_files(
+                &mut build_manifest,
+                &mut app_build_manifest,
+                &chunks,
+                &*client_relative_path.await?,
+                client_relative_path,
+                None,
+                None,
+            )
+            .await?;
+            let mut hashed_paths = vec![];
+            for (path, _) in hashed_files {
+                hashed_paths.push(path.await?.path.clone());
+            }
+            hashed_paths.sort();
+            Ok((build_manifest, app_build_manifest, manifest, hashed_paths))
+        })
+        .await
+        .unwrap();
+
+        let index = &build_manifest.pages["/"];
+        assert_eq!(index.len(), 1);
+        assert!(is_hashed_chunk(&index[0], ".js"), "{index:?}");
+        assert!(is_hashed_chunk(&build_manifest.root_main_files[0], ".js"));
+        let app_page = &app_build_manifest.pages["/page"];
+        assert!(is_hashed_chunk(&app_page[0], ".js"), "{app_page:?}");
+        assert!(is_hashed_chunk(&app_page[1], ".css"), "{app_page:?}");
+
+        assert_eq!(
+            manifest.files.keys().collect::<Vec<_>>(),
+            [
+                "static/chunks/app/page.js",
+                "static/chunks/main.js",
+                "static/chunks/pages/index.js",
+                "static/css/app/page.css",
+            ]
+        );
+        assert_eq!(manifest.files["static/chunks/pages/index.js"], index[0]);
+        // The hashed copies are written next to the originals.
+        let mut expected: Vec<_> = manifest
+            .files
+            .values()
+            .map(|file| format!(".next/{file}"))
+            .collect();
+        expected.sort();
+        assert_eq!(hashed_paths, expected);
+    }
+
+    #[test]
+    fn truncates_hashes_to_hash_length() {
+        let mut names = HashedNames::new(8).unwrap();
+        let path = names
+            .hashed_path(0x0123_4567_89ab_cdef, Some("js"))
+            .unwrap();
+        assert_eq!(path, "static/chunks/01234567.js");
+        let name = path.strip_prefix("static/chunks/").unwrap();
+        assert_eq!(name.len(), "01234567.js".len());
+
+        // The same content is deduplicated rather than reported as a collision.
+        assert_eq!(
+            names
+                .hashed_path(0x0123_4567_89ab_cdef, Some("js"))
+                .unwrap(),
+            path
+        );
+
+        let mut names = HashedNames::new(MAX_HASH_LENGTH).unwrap();
+        assert_eq!(
+            names.hashed_path(0x0123_4567_89ab_cdef, None).unwrap(),
+            "static/chunks/0123456789abcdef"
+        );
+    }
+
+    #[test]
+    fn errors_on_truncated_hash_collision() {
+        let mut names = HashedNames::new(8).unwrap();
+        names
+            .hashed_path(0x0123_4567_0000_0000, Some("js"))
+            .unwrap();
+        let err = names
+            .hashed_path(0x0123_4567_ffff_ffff, Some("css"))
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("use a longer hash_length"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_hash_length() {
+        assert!(HashedNames::new(MIN_HASH_LENGTH - 1).is_err());
+        assert!(HashedNames::new(MAX_HASH_LENGTH + 1).is_err());
+    }
+}
@@ -0,0 +1,237 @@
+use anyhow::{bail, Result};
+use next_core::{
+    mode::NextMode,
+    next_config::NextConfig,
+    next_server::{
+        get_server_module_options_context, get_server_resolve_options_context,
+        get_server_runtime_entries, ServerContextType,
+    },
+    virtual_modules::{with_virtual_modules, VirtualModules},
+};
+use serde::Serialize;
+use turbo_tasks::{Value, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::{
+        build::BuildChunkingContext,
+        core::{
+            chunk::ChunkingContext,
+            compile_time_info::CompileTimeInfo,
+            context::AssetContext,
+            file_source::FileSource,
+            output::OutputAsset,
+            reference_type::{EntryReferenceSubType, ReferenceType},
+        },
+        ecmascript::chunk::EcmascriptChunkPlaceable,
+        node::execution_context::ExecutionContext,
+        turbopack::ModuleAssetContext,
+    },
+};
+
+use crate::next_build::all_assets_from_entries;
+
+/// The trace of the files a compiled custom server loads, next to it in
+/// `server.js.nft.json`, in the format of `@vercel/nft`.
+#[derive(Serialize, Debug)]
+pub(crate) struct CustomServerTrace {
+    version: u32,
+    /// Relative to the directory of the trace.
+    files: Vec<String>,
+}
+
+/// The compiled custom server of [crate::BuildOptions::custom_server].
+pub(crate) struct CustomServer {
+    pub(crate) chunk: Vc<Box<dyn OutputAsset>>,
+    pub(crate) trace: CustomServerTrace,
+}
+
+/// Compiles the custom server `entry`, relative to the project root, to
+/// `server.js` in the dist directory, next to the manifests. It's compiled
+/// like an API route: dependencies in `node_modules` stay external, so `next`
+/// itself is required at runtime.
+///
+/// Imports are bundled, so files the build writes, like the manifests or the
+/// standalone output, must be loaded at runtime, e.g. relative to
+/// `__dirname`, rather than imported. They don't exist yet or are stale when
+/// the custom server is compiled.
+pub(crate) async fn compute_custom_server(
+    entry: &str,
+    project_root: Vc<FileSystemPath>,
+    execution_context: Vc<ExecutionContext>,
+    server_compile_time_info: Vc<CompileTimeInfo>,
+    next_config: Vc<NextConfig>,
+    virtual_modules: Vc<VirtualModules>,
+    server_chunking_context: Vc<BuildChunkingContext>,
+    node_root: Vc<FileSystemPath>,
+) -> Result<CustomServer> {
+    let mode = NextMode::Build;
+    let ty = Value::new(ServerContextType::PagesApi {
+        pages_dir: project_root.join("pages".to_string()),
+    });
+
+    let resolve_options_context = with_virtual_modules(
+        get_server_resolve_options_context(project_root, ty, mode, next_config, execution_context),
+        project_root,
+        virtual_modules,
+    );
+    let module_options_context =
+        get_server_module_options_context(project_root, execution_context, ty, mode, next_config);
+    let module_context: Vc<Box<dyn AssetContext>> = Vc::upcast(ModuleAssetContext::new(
+        Vc::cell(Default::default()),
+        server_compile_time_info,
+        module_options_context,
+        resolve_options_context,
+        Vc::cell("custom-server".to_string()),
+    ));
+
+    let entry_path = project_root.join(entry.trim_start_matches("./").to_string());
+    let module = module_context
+        .process(
+            Vc::upcast(FileSource::new(entry_path)),
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+        )
+        .module();
+    let Some(module) =
+        Vc::try_resolve_downcast::<Box<dyn EcmascriptChunkPlaceable>>(module).await?
+    else {
+        bail!("the custom server {entry} must be a JavaScript or TypeScript module");
+    };
+
+    let chunk_path = node_root.join("server.js".to_string());
+    let chunk = server_chunking_context.entry_chunk_group(
+        chunk_path,
+        Vc::upcast(module),
+        get_server_runtime_entries(ty, mode).resolve_entries(module_context),
+    );
+
+    let chunk_path = chunk_path.await?;
+    let node_root = node_root.await?;
+    let mut files = vec![];
+    for asset in all_assets_from_entries(Vc::cell(vec![chunk])).await?.iter() {
+        let path = asset.ident().path().await?;
+        if *path == *chunk_path {
+            continue;
+        }
+        if let Some(path) = node_root.get_path_to(&path) {
+            files.push(path.to_string());
+        }
+    }
+    files.sort();
+
+    Ok(CustomServer {
+        chunk,
+        trace: CustomServerTrace { version: 1, files },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use indoc::indoc;
+    use next_core::{
+        next_config::NextConfig,
+        next_server::{get_server_chunking_context, get_server_compile_time_info},
+    };
+    use turbo_tasks::Vc;
+    use turbopack_binding::turbopack::{
+        core::{asset::Asset, environment::ServerAddr},
+        dev::DevChunkingContext,
+        env::dotenv::load_env,
+        node::execution_context::ExecutionContext,
+        turbopack::evaluate_context::node_build_environment,
+    };
+
+    use super::compute_custom_server;
+    use crate::{
+        next_build::all_assets_from_entries,
+        test_utils::{fixture, fixture_root, run},
+    };
+
+    #[tokio::test]
+    async fn compiles_a_runnable_custom_server_reading_the_manifests() {
+        let dir = fixture(&[
+            (
+                "server.ts",
+                indoc! {"
+                    import { readFileSync } from 'fs';
+                    import { join } from 'path';
+
+                    const manifestPath: string = join(process.cwd(), '.next/routes-manifest.json');
+                    const routesManifest = JSON.parse(readFileSync(manifestPath, 'utf8'));
+                    console.log(`routes manifest v${routesManifest.version}`);
+                "},
+            ),
+            (".next/routes-manifest.json", r#"{"version": 3}"#),
+        ]);
+        let dir_path = dir.path().to_path_buf();
+        run(async move {
+            let project_root = fixture_root(&dir_path);
+            let node_root = project_root.join(".next".to_string());
+            let env = load_env(project_root);
+            let execution_context = ExecutionContext::new(
+                project_root,
+                Vc::upcast(
+                    DevChunkingContext::builder(
+                        project_root,
+                        node_root,
+                        node_root.join("chunks".to_string()),
+                        node_root.join("assets".to_string()),
+                        node_build_environment(),
+                    )
+                    .build(),
+                ),
+                env,
+            );
+            let server_compile_time_info = get_server_compile_time_info(
+                env,
+                ServerAddr::empty(),
+                Vc::cell(Default::default()),
+            );
+            let server_chunking_context = get_server_chunking_context(
+                project_root,
+                node_root,
+                node_root,
+                Vc::cell(None),
+                server_compile_time_info.environment(),
+                false,
+            );
+
+            let custom_server = compute_custom_server(
+                "./server.ts",
+                project_root,
+                execution_context,
+                server_compile_time_info,
+                NextConfig::default().cell(),
+                Vc::cell(Default::default()),
+                server_chunking_context,
+                node_root,
+            )
+            .await?;
+            for asset in all_assets_from_entries(Vc::cell(vec![custom_server.chunk]))
+                .await?
+                .iter()
+            {
+                asset.content().write(asset.ident().path()).await?;
+            }
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        let output = Command::new("node")
+            .arg(".next/server.js")
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim(),
+            "routes manifest v3"
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use anyhow::{bail, Result};
+use turbo_tasks::Vc;
+use turbopack_binding::turbopack::core::issue::{
+    handle_issues, IssueDescriptionExt, IssueReporter, IssueSeverity,
+};
+
+/// Checks the issues of each route as soon as it's chunked with
+/// [crate::BuildOptions::fail_fast], so the build fails on the first route
+/// with an issue at or above [crate::BuildOptions::fail_on], without
+/// chunking the remaining routes.
+///
+/// Routes are then computed one after the other instead of concurrently,
+/// which makes successful builds slower.
+pub(crate) struct FailFast {
+    pub(crate) issue_reporter: Vc<Box<dyn IssueReporter>>,
+    pub(crate) min_failing_severity: IssueSeverity,
+}
+
+impl FailFast {
+    /// Computes `source`, e.g. the chunks of `route`, reports its issues and
+    /// fails if any of them is at or above the minimum failing severity, or
+    /// if the reporter fails on them.
+    pub(crate) async fn check<T: Send>(&self, route: &str, source: Vc<T>) -> Result<()> {
+        handle_issues(
+            source,
+            self.issue_reporter,
+            self.min_failing_severity.cell(),
+            None,
+            None,
+        )
+        .await?;
+
+        // Reporters may report failing issues without failing on them.
+        let issues = source.peek_issues_with_path().await?;
+        for issue in issues.iter() {
+            if *issue.severity().await? <= self.min_failing_severity {
+                bail!(
+                    "{route} has {} issues, stopping the build as it fails fast",
+                    self.min_failing_severity.as_str()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use next_core::util::find_source_dir;
+    use turbo_tasks::{TransientInstance, Vc};
+    use turbopack_binding::turbopack::{
+        cli_utils::issue::{ConsoleUi, LogOptions},
+        core::issue::{IssueReporter, IssueSeverity},
+    };
+
+    use super::FailFast;
+    use crate::test_utils::{fixture, fixture_root, run};
+
+    /// Checks the `app` directory of each of `dirs` like routes, and returns
+    /// the ones checked until the first failure.
+    async fn check_until_failure(
+        dirs: &'static [&'static str],
+        min_failing_severity: IssueSeverity,
+    ) -> Vec<&'static str> {
+        // `find_source_dir` reports an error for `b`, which has both `app`
+        // and `src/app`.
+        let dir = fixture(&[
+            ("a/app/page.js", ""),
+            ("b/app/page.js", ""),
+            ("b/src/app/page.js", ""),
+            ("c/app/page.js", ""),
+        ]);
+        let root = dir.path().to_owned();
+        run(async move {
+            let console = ConsoleUi::new(TransientInstance::new(LogOptions {
+                project_dir: root.clone(),
+                current_dir: root.clone(),
+                show_all: true,
+                log_detail: false,
+                log_level: IssueSeverity::Info,
+            }));
+            let fail_fast = FailFast {
+                issue_reporter: Vc::upcast::<Box<dyn IssueReporter>>(console),
+                min_failing_severity,
+            };
+
+            let project_path = fixture_root(&root);
+            let mut checked = vec![];
+            for &dir in dirs {
+                let source_dir =
+                    find_source_dir(project_path.join(dir.to_string()), "app".to_string());
+                if fail_fast.check(dir, source_dir).await.is_err() {
+                    break;
+                }
+                checked.push(dir);
+            }
+            Ok(checked)
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn stops_at_the_first_failing_route() {
+        assert_eq!(
+            check_until_failure(&["a", "b", "c"], IssueSeverity::Error).await,
+            ["a"]
+        );
+        // The error doesn't fail builds failing on fatal issues only.
+        assert_eq!(
+            check_until_failure(&["a", "b", "c"], IssueSeverity::Fatal).await,
+            ["a", "b", "c"]
+        );
+    }
+}
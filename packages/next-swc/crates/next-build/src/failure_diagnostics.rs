@@ -0,0 +1,157 @@
+use std::{
+    fs::{create_dir_all, write},
+    path::Path,
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use indexmap::IndexSet;
+use next_core::next_config::NextConfig;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use turbo_tasks::{RawVc, ReadRef, TransientInstance, TransientValue, Vc};
+use turbopack_binding::turbopack::core::issue::{
+    CapturedIssues, IssueReporter, IssueSeverity, StyledString,
+};
+
+/// The config keys whose values may contain secrets, which are replaced in
+/// the config snapshot. The keys themselves are kept.
+const REDACTED_CONFIG_KEYS: &[&str] = &["env", "serverRuntimeConfig"];
+
+/// What a build got to before it failed, collected as it runs and written to
+/// `diagnostics` in the dist directory with
+/// [crate::BuildOptions::diagnostics_on_failure].
+#[derive(Default)]
+pub(crate) struct FailureDiagnostics {
+    config: Mutex<Option<JsonValue>>,
+    routes: Mutex<Vec<String>>,
+    issues: Mutex<IndexSet<DiagnosticsIssue>>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+struct DiagnosticsIssue {
+    severity: &'static str,
+    category: String,
+    file_path: String,
+    title: String,
+    description: Option<String>,
+    /// The issue is at least as severe as [crate::BuildOptions::fail_on].
+    failing: bool,
+}
+
+impl FailureDiagnostics {
+    pub(crate) fn set_config(&self, config: &NextConfig) -> Result<()> {
+        let mut config = serde_json::to_value(config)?;
+        if let JsonValue::Object(config) = &mut config {
+            for key in REDACTED_CONFIG_KEYS {
+                if let Some(JsonValue::Object(values)) = config.get_mut(*key) {
+                    for value in values.values_mut() {
+                        *value = JsonValue::String("[redacted]".to_string());
+                    }
+                }
+            }
+        }
+        *self.config.lock().unwrap() = Some(config);
+        Ok(())
+    }
+
+    pub(crate) fn set_routes(&self, routes: &[String]) {
+        *self.routes.lock().unwrap() = routes.to_vec();
+    }
+
+    /// Writes `config.json`, `routes.json`, `issues.json` and
+    /// `failing-modules.json` to `dir`, along with the `error` the build
+    /// failed with in `error.txt`.
+    pub(crate) fn write(&self, dir: &Path, error: &anyhow::Error) -> Result<()> {
+        create_dir_all(dir).context("diagnostics directory can't be created")?;
+
+        let issues = self.issues.lock().unwrap();
+        let failing_modules = issues
+            .iter()
+            .filter(|issue| issue.failing && !issue.file_path.is_empty())
+            .map(|issue| issue.file_path.as_str())
+            .collect::<IndexSet<_>>();
+
+        write(
+            dir.join("config.json"),
+            serde_json::to_string_pretty(&*self.config.lock().unwrap())?,
+        )?;
+        write(
+            dir.join("routes.json"),
+            serde_json::to_string_pretty(&*self.routes.lock().unwrap())?,
+        )?;
+        write(
+            dir.join("issues.json"),
+            serde_json::to_string_pretty(&*issues)?,
+        )?;
+        write(
+            dir.join("failing-modules.json"),
+            serde_json::to_string_pretty(&failing_modules)?,
+        )?;
+        write(dir.join("error.txt"), format!("{error:?}\n"))?;
+        Ok(())
+    }
+}
+
+/// An [IssueReporter] which reports issues with `inner` and records them in
+/// the `diagnostics` of the build.
+#[turbo_tasks::value(shared, serialization = "none", eq = "manual")]
+pub(crate) struct FailureDiagnosticsReporter {
+    inner: Vc<Box<dyn IssueReporter>>,
+    #[turbo_tasks(trace_ignore, debug_ignore)]
+    diagnostics: TransientInstance<FailureDiagnostics>,
+}
+
+#[turbo_tasks::value_impl]
+impl FailureDiagnosticsReporter {
+    #[turbo_tasks::function]
+    pub(crate) fn new(
+        inner: Vc<Box<dyn IssueReporter>>,
+        diagnostics: TransientInstance<FailureDiagnostics>,
+    ) -> Vc<Self> {
+        FailureDiagnosticsReporter { inner, diagnostics }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl IssueReporter for FailureDiagnosticsReporter {
+    #[turbo_tasks::function]
+    async fn report_issues(
+        &self,
+        issues: TransientInstance<ReadRef<CapturedIssues>>,
+        source: TransientValue<RawVc>,
+        min_failing_severity: Vc<IssueSeverity>,
+    ) -> Result<Vc<bool>> {
+        let min_severity = *min_failing_severity.await?;
+        let plain_issues = issues.get_plain_issues().await?;
+        {
+            let mut recorded = self.diagnostics.issues.lock().unwrap();
+            for issue in plain_issues.iter() {
+                recorded.insert(DiagnosticsIssue {
+                    severity: issue.severity.as_str(),
+                    category: issue.category.clone(),
+                    file_path: issue.file_path.clone(),
+                    title: styled_text(&issue.title),
+                    description: issue.description.as_ref().map(styled_text),
+                    failing: issue.severity <= min_severity,
+                });
+            }
+        }
+
+        Ok(self
+            .inner
+            .report_issues(issues, source, min_failing_severity))
+    }
+}
+
+/// The text of a styled issue title or description.
+pub(crate) fn styled_text(styled: &StyledString) -> String {
+    match styled {
+        StyledString::Line(parts) => parts.iter().map(styled_text).collect(),
+        StyledString::Stack(parts) => parts.iter().map(styled_text).collect::<Vec<_>>().join("\n"),
+        StyledString::Text(string) | StyledString::Code(string) | StyledString::Strong(string) => {
+            string.clone()
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use next_core::next_manifests::{BuildManifest, InlineCssManifest};
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::tasks_fs::{FileContent, FileSystemPath},
+    turbopack::core::{asset::Asset, output::OutputAsset},
+};
+
+/// Moves the CSS files of pages with at most `threshold` bytes of CSS from
+/// their build manifest entry to the [InlineCssManifest], so that they are
+/// inlined into the HTML instead of also being loaded with `<link>` tags.
+pub(crate) async fn inline_page_css(
+    build_manifest: &mut BuildManifest,
+    chunks: &[Vc<Box<dyn OutputAsset>>],
+    client_relative_path: &FileSystemPath,
+    threshold: u64,
+) -> Result<InlineCssManifest> {
+    let page_css_files: HashSet<&str> = build_manifest
+        .pages
+        .values()
+        .flatten()
+        .filter(|file| file.ends_with(".css"))
+        .map(|file| file.as_str())
+        .collect();
+
+    let mut css_sizes = HashMap::new();
+    for &chunk in chunks {
+        let path = chunk.ident().path().await?;
+        let Some(path) = client_relative_path.get_path_to(&path) else {
+            continue;
+        };
+        if !page_css_files.contains(path) {
+            continue;
+        }
+        if let FileContent::Content(file) = &*chunk.content().file_content().await? {
+            css_sizes.insert(path.to_string(), file.content().len() as u64);
+        }
+    }
+
+    let mut manifest = InlineCssManifest::default();
+    for (page, files) in build_manifest.pages.iter_mut() {
+        let css_files: Vec<&String> = files.iter().filter(|file| file.ends_with(".css")).collect();
+        if css_files.is_empty() {
+            continue;
+        }
+        // Pages with CSS of unknown size keep loading all of it.
+        let Some(size) = css_files
+            .iter()
+            .map(|file| css_sizes.get(file.as_str()))
+            .sum::<Option<u64>>()
+        else {
+            continue;
+        };
+        if size > threshold {
+            continue;
+        }
+
+        let (inlined, rest): (Vec<_>, Vec<_>) =
+            files.drain(..).partition(|file| file.ends_with(".css"));
+        *files = rest;
+        manifest.pages.insert(page.clone(), inlined);
+    }
+
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use next_core::next_manifests::BuildManifest;
+    use turbo_tasks::Vc;
+    use turbopack_binding::{
+        turbo::tasks_fs::{File, FileSystem, VirtualFileSystem},
+        turbopack::core::{
+            asset::AssetContent, output::OutputAsset, virtual_output::VirtualOutputAsset,
+        },
+    };
+
+    use super::inline_page_css;
+    use crate::test_utils::run;
+
+    fn files(files: &[&str]) -> Vec<String> {
+        files.iter().map(|file| file.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn inlines_the_css_of_pages_under_the_threshold() {
+        let (build_manifest, inline_css_manifest) = run(async {
+            let client_relative_path = VirtualFileSystem::new().root().join(".next".to_string());
+            let chunks: Vec<Vc<Box<dyn OutputAsset>>> = [
+                ("static/css/home.css", 40),
+                ("static/css/blog.css", 500),
+                ("static/chunks/pages/index.js", 1000),
+            ]
+            .into_iter()
+            .map(|(path, size)| {
+                Vc::upcast(VirtualOutputAsset::new(
+                    client_relative_path.join(path.to_string()),
+                    AssetContent::file(File::from("x".repeat(size)).into()),
+                ))
+            })
+            .collect();
+
+            let mut build_manifest = BuildManifest {
+                pages: [
+                    (
+                        "/",
+                        files(&["static/chunks/pages/index.js", "static/css/home.css"]),
+                    ),
+                    ("/blog", files(&["static/css/blog.css"])),
+                    ("/about", files(&["static/chunks/pages/index.js"])),
+                ]
+                .into_iter()
+                .map(|(pathname, files)| (pathname.to_string(), files))
+                .collect(),
+                ..Default::default()
+            };
+            let inline_css_manifest = inline_page_css(
+                &mut build_manifest,
+                &chunks,
+                &*client_relative_path.await?,
+                100,
+            )
+            .await?;
+            Ok((build_manifest, inline_css_manifest))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(inline_css_manifest).unwrap(),
+            serde_json::json!({ "pages": { "/": ["static/css/home.css"] } })
+        );
+        // The inlined CSS isn't loaded with a `<link>` tag too.
+        assert_eq!(build_manifest.pages["/"], ["static/chunks/pages/index.js"]);
+        assert_eq!(build_manifest.pages["/blog"], ["static/css/blog.css"]);
+    }
+}
@@ -0,0 +1,197 @@
+use anyhow::Result;
+use turbo_tasks::{
+    Completion, RawVc, ReadRef, TransientInstance, TransientValue, TryJoinIterExt, Vc,
+};
+use turbopack_binding::{
+    turbo::tasks_fs::{glob::Glob, FileSystemPath},
+    turbopack::core::issue::{
+        CapturedIssues, Issue, IssueDescriptionExt, IssueExt, IssueReporter, IssueSeverity,
+        OptionStyledString, StyledString,
+    },
+};
+
+/// An [IssueReporter] which reports issues with `inner`, but treats issues in
+/// files matching any of `patterns` as errors, no matter their severity. This
+/// allows failing the build on warnings from e.g. deprecated modules.
+///
+/// Promoted issues fail the build whatever its minimum failing severity is,
+/// and a [PromotedIssue] explaining why is reported with `inner` for each.
+#[turbo_tasks::value(shared)]
+pub(crate) struct PromotingIssueReporter {
+    inner: Vc<Box<dyn IssueReporter>>,
+    /// Globs matched against the issue's file path, relative to the
+    /// workspace root.
+    patterns: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl PromotingIssueReporter {
+    #[turbo_tasks::function]
+    pub(crate) fn new(inner: Vc<Box<dyn IssueReporter>>, patterns: Vec<String>) -> Vc<Self> {
+        PromotingIssueReporter { inner, patterns }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl IssueReporter for PromotingIssueReporter {
+    #[turbo_tasks::function]
+    async fn report_issues(
+        &self,
+        issues: TransientInstance<ReadRef<CapturedIssues>>,
+        source: TransientValue<RawVc>,
+        min_failing_severity: Vc<IssueSeverity>,
+    ) -> Result<Vc<bool>> {
+        let has_failing_issues = *self
+            .inner
+            .report_issues(issues.clone(), source, min_failing_severity)
+            .await?;
+
+        let globs = self
+            .patterns
+            .iter()
+            .map(|pattern| Glob::new(pattern.clone()))
+            .try_join()
+            .await?;
+
+        let mut has_promoted_issues = false;
+        for issue in issues.iter() {
+            if *issue.severity().await? <= IssueSeverity::Error {
+                continue;
+            }
+            let file_path = issue.file_path();
+            let path = file_path.await?;
+            let Some(pattern) = self
+                .patterns
+                .iter()
+                .zip(globs.iter())
+                .find_map(|(pattern, glob)| glob.execute(&path.path).then_some(pattern))
+            else {
+                continue;
+            };
+            let promoted = emit_promoted_issue(file_path, pattern.clone());
+            promoted.await?;
+            self.inner
+                .report_issues(
+                    TransientInstance::new(promoted.peek_issues_with_path().await?),
+                    TransientValue::new(RawVc::from(promoted)),
+                    min_failing_severity,
+                )
+                .await?;
+            has_promoted_issues = true;
+        }
+
+        Ok(Vc::cell(has_failing_issues || has_promoted_issues))
+    }
+}
+
+#[turbo_tasks::function]
+fn emit_promoted_issue(path: Vc<FileSystemPath>, pattern: String) -> Vc<Completion> {
+    PromotedIssue { path, pattern }.cell().emit();
+    Completion::new()
+}
+
+/// Explains why the build fails on an issue below the minimum failing
+/// severity.
+#[turbo_tasks::value(shared)]
+struct PromotedIssue {
+    path: Vc<FileSystemPath>,
+    pattern: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for PromotedIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("An issue is treated as an error".to_string()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("build".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Line(vec![
+                StyledString::Text(
+                    "The issues of this file fail the build because it matches ".to_string(),
+                ),
+                StyledString::Code(self.pattern.clone()),
+                StyledString::Text(" of promoteToError.".to_string()),
+            ])
+            .cell(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use next_core::app_structure::get_entrypoints;
+    use turbo_tasks::{RawVc, TransientInstance, TransientValue, Vc};
+    use turbopack_binding::turbopack::{
+        cli_utils::issue::{ConsoleUi, LogOptions},
+        core::issue::{IssueDescriptionExt, IssueReporter, IssueSeverity},
+    };
+
+    use super::PromotingIssueReporter;
+    use crate::test_utils::{fixture, fixture_root, run};
+
+    /// Whether the warnings of an app directory fail the build with the
+    /// default minimum failing severity when `patterns` are promoted.
+    async fn fails_with_promoted(patterns: Vec<String>) -> bool {
+        let dir = fixture(&[
+            ("app/layout.tsx", "export default function Layout() {}"),
+            ("app/page.tsx", "export default function Page() {}"),
+            (
+                "app/@modal/photo/page.tsx",
+                "export default function Photo() {}",
+            ),
+        ]);
+        let root = dir.path().to_owned();
+        run(async move {
+            // The `@modal` slot has neither a page nor a default for `/`, which is a
+            // warning in `app`.
+            let entrypoints = get_entrypoints(
+                fixture_root(&root).join("app".to_string()),
+                Vc::cell(vec!["tsx".to_string()]),
+            );
+            entrypoints.await?;
+
+            let console = ConsoleUi::new(TransientInstance::new(LogOptions {
+                project_dir: root.clone(),
+                current_dir: root.clone(),
+                show_all: true,
+                log_detail: false,
+                log_level: IssueSeverity::Info,
+            }));
+            let reporter: Vc<Box<dyn IssueReporter>> =
+                Vc::upcast(PromotingIssueReporter::new(Vc::upcast(console), patterns));
+            Ok(*reporter
+                .report_issues(
+                    TransientInstance::new(entrypoints.peek_issues_with_path().await?),
+                    TransientValue::new(RawVc::from(entrypoints)),
+                    IssueSeverity::Fatal.cell(),
+                )
+                .await?)
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn promotes_warnings_in_matching_files() {
+        assert!(fails_with_promoted(vec!["app".to_string()]).await);
+        assert!(!fails_with_promoted(vec!["pages/**".to_string()]).await);
+    }
+}
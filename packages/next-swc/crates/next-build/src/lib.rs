@@ -6,17 +6,40 @@ use turbopack_binding::turbo::{
     tasks_memory::MemoryBackend,
 };
 
+pub(crate) mod build_diagnostics;
+pub mod build_logger;
 pub mod build_options;
+pub mod build_report;
+pub(crate) mod changed_files;
+pub(crate) mod chunk_graph;
+pub(crate) mod content_addressing;
+pub(crate) mod custom_server;
+pub(crate) mod fail_fast;
+pub(crate) mod failure_diagnostics;
+pub(crate) mod inline_css;
+pub(crate) mod issue_promotion;
+pub mod manifest_transform;
+pub(crate) mod module_cycles;
 pub(crate) mod next_app;
 pub(crate) mod next_build;
+pub(crate) mod next_font;
 pub(crate) mod next_pages;
+pub(crate) mod preload_manifest;
+pub(crate) mod tarball;
+#[cfg(test)]
+mod test_utils;
+pub(crate) mod unemitted_assets;
 
-use anyhow::Result;
+use std::{env::current_dir, path::Path};
+
+use anyhow::{Context, Result};
+use dunce::canonicalize;
 use turbo_tasks::{StatsType, TurboTasksBackendApi};
 
-pub use self::build_options::BuildOptions;
+use self::failure_diagnostics::FailureDiagnostics;
+pub use self::{build_options::BuildOptions, build_report::BuildReport};
 
-pub async fn build(options: BuildOptions) -> Result<()> {
+pub async fn build(options: BuildOptions) -> Result<BuildReport> {
     #[cfg(feature = "tokio_console")]
     console_subscriber::init();
     register();
@@ -33,14 +56,74 @@ pub async fn build(options: BuildOptions) -> Result<()> {
     };
     tt.set_stats_type(stats_type);
 
-    run_once(tt, async move {
-        next_build::next_build(TransientInstance::new(options)).await?;
+    let output_tarball = options.output_tarball.clone();
+    let diagnostics_on_failure = options.diagnostics_on_failure;
+    let output_dir = options.output_root.clone().or_else(|| options.dir.clone());
+    let dist_dir = options
+        .dist_dir
+        .clone()
+        .unwrap_or_else(|| ".next".to_string());
+    let output_dir = || {
+        output_dir
+            .as_ref()
+            .map(canonicalize)
+            .unwrap_or_else(current_dir)
+            .context("output directory can't be found")
+    };
+
+    let failure_diagnostics = TransientInstance::new(FailureDiagnostics::default());
+    let build_report = run_once(tt, {
+        let failure_diagnostics = failure_diagnostics.clone();
+        async move {
+            let build_report =
+                next_build::next_build(TransientInstance::new(options), failure_diagnostics)
+                    .await?;
 
-        Ok(())
+            Ok(build_report.clone_value())
+        }
     })
-    .await?;
+    .await;
+    let build_report = match build_report {
+        Ok(build_report) => build_report,
+        Err(err) if diagnostics_on_failure => {
+            let diagnostics_dir = output_dir()?.join(&dist_dir).join("diagnostics");
+            return Err(write_failure_diagnostics(
+                &failure_diagnostics,
+                &diagnostics_dir,
+                err,
+            ));
+        }
+        Err(err) => return Err(err),
+    };
+
+    if let Some(output_tarball) = output_tarball {
+        let output_dir = output_dir()?;
+        tokio::task::spawn_blocking(move || {
+            tarball::write_tarball_to_file(&output_dir.join(&dist_dir), &dist_dir, &output_tarball)
+        })
+        .await??;
+    }
 
-    Ok(())
+    Ok(build_report)
+}
+
+/// Writes the diagnostics of a build which failed with `err` to `dir`, and
+/// returns `err` with where they were written to, or why they couldn't be.
+fn write_failure_diagnostics(
+    failure_diagnostics: &FailureDiagnostics,
+    dir: &Path,
+    err: anyhow::Error,
+) -> anyhow::Error {
+    match failure_diagnostics.write(dir, &err) {
+        Ok(()) => err.context(format!(
+            "diagnostics of the failed build were written to {}",
+            dir.display()
+        )),
+        Err(write_err) => err.context(format!(
+            "diagnostics of the failed build can't be written to {}: {write_err:#}",
+            dir.display()
+        )),
+    }
 }
 
 fn setup_tracing() {
@@ -67,3 +150,35 @@ pub fn register() {
     next_core::register();
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_where_failure_diagnostics_are_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let diagnostics_dir = dir.path().join("diagnostics");
+        let err = write_failure_diagnostics(
+            &FailureDiagnostics::default(),
+            &diagnostics_dir,
+            anyhow::anyhow!("build failed"),
+        );
+        assert!(format!("{err:#}").contains("were written to"));
+        assert_eq!(err.root_cause().to_string(), "build failed");
+        assert!(std::fs::read_to_string(diagnostics_dir.join("error.txt"))
+            .unwrap()
+            .starts_with("build failed"));
+
+        // A file where the directory should be can't be written into.
+        let blocked_dir = dir.path().join("blocked");
+        std::fs::write(&blocked_dir, "").unwrap();
+        let err = write_failure_diagnostics(
+            &FailureDiagnostics::default(),
+            &blocked_dir,
+            anyhow::anyhow!("build failed"),
+        );
+        assert!(format!("{err:#}").contains("can't be written"));
+        assert_eq!(err.root_cause().to_string(), "build failed");
+    }
+}
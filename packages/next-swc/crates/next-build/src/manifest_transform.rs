@@ -0,0 +1,41 @@
+use std::{fmt, future::Future, pin::Pin, sync::Arc};
+
+use anyhow::{bail, Context, Result};
+
+type TransformFuture = Pin<Box<dyn Future<Output = Result<String>> + Send>>;
+
+/// Transforms the JSON of each manifest before it's written, see
+/// [BuildOptions::manifest_transform]. It's called with the file name of the
+/// manifest, e.g. `routes-manifest.json`, and its JSON, and returns the JSON
+/// to write instead.
+///
+/// [BuildOptions::manifest_transform]: crate::BuildOptions::manifest_transform
+#[derive(Clone)]
+pub struct ManifestTransform(Arc<dyn Fn(String, String) -> TransformFuture + Send + Sync>);
+
+impl ManifestTransform {
+    pub fn new<F>(transform: impl Fn(String, String) -> F + Send + Sync + 'static) -> Self
+    where
+        F: Future<Output = Result<String>> + Send + 'static,
+    {
+        Self(Arc::new(move |name, json| Box::pin(transform(name, json))))
+    }
+
+    /// Transforms the manifest `name`, failing when the result isn't valid
+    /// JSON, as the runtime couldn't load it.
+    pub(crate) async fn transform(&self, name: &str, json: String) -> Result<String> {
+        let json = (self.0)(name.to_string(), json)
+            .await
+            .with_context(|| format!("failed to transform {name}"))?;
+        if let Err(err) = serde_json::from_str::<serde::de::IgnoredAny>(&json) {
+            bail!("the manifest transform returned invalid JSON for {name}: {err}");
+        }
+        Ok(json)
+    }
+}
+
+impl fmt::Debug for ManifestTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ManifestTransform").finish()
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+use anyhow::{Context, Result};
+use next_core::module_analysis::all_referenced_modules;
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::core::{
+        issue::{Issue, IssueExt, IssueSeverity, OptionStyledString, StyledString},
+        module::{Module, Modules},
+        reference::primary_referenced_modules,
+    },
+};
+
+/// Warns about every circular dependency in the module graph of `entries`,
+/// like `circular-dependency-plugin` does for webpack. Modules in a cycle may
+/// observe each other's exports as `undefined` while they're evaluated.
+///
+/// Each set of modules depending on each other is reported once, with one of
+/// the import chains through it. Cycles entirely within `node_modules` aren't
+/// reported, as they can't be fixed in the project.
+#[turbo_tasks::function]
+pub(crate) async fn detect_module_cycles(entries: Vc<Modules>) -> Result<Vc<()>> {
+    let modules = all_referenced_modules(entries.await?.iter().copied()).await?;
+
+    let nodes = modules
+        .iter()
+        .map(|&module| async move {
+            let ident = module.ident().to_string().await?.clone_value();
+            let references =
+                primary_referenced_modules(module)
+                    .await?
+                    .iter()
+                    .map(|reference| async move {
+                        Ok(reference.ident().to_string().await?.clone_value())
+                    })
+                    .try_join()
+                    .await?;
+            Ok((ident, module.ident().path(), references))
+        })
+        .try_join()
+        .await?;
+
+    let indices: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(index, (ident, ..))| (ident.as_str(), index))
+        .collect();
+    let edges: Vec<Vec<usize>> = nodes
+        .iter()
+        .map(|(_, _, references)| {
+            references
+                .iter()
+                .filter_map(|reference| indices.get(reference.as_str()).copied())
+                .collect()
+        })
+        .collect();
+    let paths = nodes
+        .iter()
+        .map(|(_, path, _)| async move { Ok(path.await?.path.clone()) })
+        .try_join()
+        .await?;
+
+    // The same files are part of multiple module graphs, e.g. for server and
+    // client components, so cycles are deduplicated by their files.
+    let mut reported = HashSet::new();
+    for component in strongly_connected_components(&edges) {
+        let is_cycle = component.len() > 1 || edges[component[0]].contains(&component[0]);
+        if !is_cycle
+            || component
+                .iter()
+                .all(|&node| paths[node].contains("node_modules/"))
+        {
+            continue;
+        }
+        let files: BTreeSet<&str> = component.iter().map(|&node| paths[node].as_str()).collect();
+        if !reported.insert(files) {
+            continue;
+        }
+
+        // Every node of a strongly connected component reaches every other.
+        let chain = cycle_through(&component, &edges, &paths)
+            .context("the modules of a strongly connected component form no cycle")?;
+        CircularDependencyIssue {
+            path: nodes[chain[0]].1,
+            chain: chain.iter().map(|&node| paths[node].clone()).collect(),
+        }
+        .cell()
+        .emit();
+    }
+
+    Ok(Default::default())
+}
+
+/// Computes the strongly connected components of the graph with Tarjan's
+/// algorithm, without recursing, as module graphs can be deep.
+fn strongly_connected_components(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut index = vec![usize::MAX; edges.len()];
+    let mut low_link = vec![0; edges.len()];
+    let mut on_stack = vec![false; edges.len()];
+    let mut stack = vec![];
+    let mut components = vec![];
+    let mut next_index = 0;
+
+    for root in 0..edges.len() {
+        if index[root] != usize::MAX {
+            continue;
+        }
+        index[root] = next_index;
+        low_link[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        // The nodes being visited, with the index of their next edge to follow.
+        let mut work = vec![(root, 0)];
+        while let Some((node, edge)) = work.last_mut() {
+            let node = *node;
+            if let Some(&next) = edges[node].get(*edge) {
+                *edge += 1;
+                if index[next] == usize::MAX {
+                    index[next] = next_index;
+                    low_link[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    work.push((next, 0));
+                } else if on_stack[next] {
+                    low_link[node] = low_link[node].min(index[next]);
+                }
+                continue;
+            }
+
+            work.pop();
+            if let Some(&(parent, _)) = work.last() {
+                low_link[parent] = low_link[parent].min(low_link[node]);
+            }
+            if low_link[node] == index[node] {
+                let mut component = vec![];
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+/// Finds the shortest import chain from the first file of `component` back to
+/// itself. The start is repeated at the end of the chain. Returns `None` if
+/// there's no such chain, i.e. `component` isn't a cycle.
+fn cycle_through(
+    component: &[usize],
+    edges: &[Vec<usize>],
+    paths: &[String],
+) -> Option<Vec<usize>> {
+    let members: HashSet<usize> = component.iter().copied().collect();
+    let start = *component.iter().min_by_key(|&&node| &paths[node])?;
+
+    let mut parents = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        for &next in &edges[node] {
+            if next == start {
+                // Walks back from `node` to `start`, which has no parent.
+                let mut chain = vec![node];
+                let mut current = node;
+                while let Some(&parent) = parents.get(&current) {
+                    chain.push(parent);
+                    current = parent;
+                }
+                chain.reverse();
+                chain.push(start);
+                return Some(chain);
+            }
+            if members.contains(&next) && next != start && !parents.contains_key(&next) {
+                parents.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+#[turbo_tasks::value(shared)]
+struct CircularDependencyIssue {
+    path: Vc<FileSystemPath>,
+    /// The files of the cycle in import order, starting and ending with the
+    /// same file.
+    chain: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for CircularDependencyIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text(match self.chain.len() - 1 {
+            1 => "Circular dependency of a file on itself".to_string(),
+            files => format!("Circular dependency between {files} files"),
+        })
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("module".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Line(vec![
+                StyledString::Text("Import chain: ".to_string()),
+                StyledString::Code(self.chain.join(" -> ")),
+            ])
+            .cell(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(count: usize) -> Vec<String> {
+        (0..count).map(|node| format!("{node}.js")).collect()
+    }
+
+    #[test]
+    fn finds_the_shortest_chain_through_a_cycle() {
+        // 0 -> 1 -> 2 -> 0, with a shortcut 1 -> 0 and an acyclic 3.
+        let edges = vec![vec![1], vec![2, 0], vec![0], vec![0]];
+        let components = strongly_connected_components(&edges);
+        let cycle = components
+            .iter()
+            .find(|component| component.len() > 1)
+            .unwrap();
+        assert_eq!(cycle_through(cycle, &edges, &paths(4)), Some(vec![0, 1, 0]));
+    }
+
+    #[test]
+    fn finds_no_chain_without_a_cycle() {
+        let edges = vec![vec![1], vec![]];
+        assert_eq!(cycle_through(&[0, 1], &edges, &paths(2)), None);
+        assert_eq!(cycle_through(&[], &edges, &paths(2)), None);
+    }
+}
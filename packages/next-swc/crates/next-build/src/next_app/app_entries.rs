@@ -1,26 +1,40 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Instant};
 
 use anyhow::Result;
 use next_core::{
     app_structure::{find_app_dir_if_enabled, get_entrypoints, Entrypoint},
+    csp::route_requires_nonce,
     mode::NextMode,
     next_app::{
+        app_boundaries::get_app_page_boundaries,
+        app_cache_control::{get_app_page_cache_control, get_app_route_cache_control},
+        app_ppr_shell::get_app_page_prerendering,
+        app_route_methods::get_app_route_methods,
         get_app_client_shared_chunks, get_app_page_entry, get_app_route_entry,
-        metadata::route::get_app_metadata_route_entry, AppEntry, ClientReferencesChunks,
+        metadata::{
+            route::get_app_metadata_route_entry, static_metadata::parse_metadata_from_loader_tree,
+        },
+        AppEntry, ClientReferencesChunks,
     },
     next_client::{
         get_client_module_options_context, get_client_resolve_options_context,
         get_client_runtime_entries, ClientContextType,
     },
     next_client_reference::{ClientReferenceGraph, NextEcmascriptClientReferenceTransition},
-    next_config::NextConfig,
+    next_config::{Headers, NextConfig},
     next_dynamic::NextDynamicTransition,
-    next_manifests::{AppBuildManifest, AppPathsManifest, BuildManifest, ClientReferenceManifest},
+    next_manifests::{
+        AppBoundariesManifest, AppBuildManifest, AppCacheControlManifest, AppMetadataManifest,
+        AppPathsManifest, AppRouteMethodsManifest, BuildManifest, ClientReferenceManifest,
+        NextFontManifest, PprManifest,
+    },
     next_server::{
         get_server_module_options_context, get_server_resolve_options_context,
         get_server_runtime_entries, ServerContextType,
     },
+    resolve_conditions::{with_resolve_conditions, ResolveConditions},
     util::NextRuntime,
+    virtual_modules::{with_virtual_modules, VirtualModules},
 };
 use turbo_tasks::{TryJoinIterExt, Value, Vc};
 use turbopack_binding::{
@@ -40,6 +54,11 @@ use turbopack_binding::{
     },
 };
 
+use crate::{
+    build_diagnostics::BuildTimer, changed_files::RouteChunks, fail_fast::FailFast,
+    next_font::add_font_files,
+};
+
 const ECMASCRIPT_CLIENT_TRANSITION_NAME: &str = "next-ecmascript-client-reference";
 
 #[turbo_tasks::value]
@@ -52,6 +71,8 @@ pub struct AppEntries {
     /// The client runtime entries that should be evaluated before any app entry
     /// module when client rendering.
     pub client_runtime_entries: Vc<EvaluatableAssets>,
+    /// The app directory, if the project has one.
+    pub app_dir: Option<Vc<FileSystemPath>>,
 }
 
 /// Computes all app entries found under the given project root.
@@ -62,6 +83,8 @@ pub async fn get_app_entries(
     client_compile_time_info: Vc<CompileTimeInfo>,
     server_compile_time_info: Vc<CompileTimeInfo>,
     next_config: Vc<NextConfig>,
+    virtual_modules: Vc<VirtualModules>,
+    resolve_conditions: Vc<ResolveConditions>,
 ) -> Result<Vc<AppEntries>> {
     let app_dir = find_app_dir_if_enabled(project_root);
 
@@ -70,6 +93,7 @@ pub async fn get_app_entries(
             entries: vec![],
             rsc_runtime_entries: EvaluatableAssets::empty(),
             client_runtime_entries: EvaluatableAssets::empty(),
+            app_dir: None,
         }));
     };
 
@@ -102,12 +126,19 @@ pub async fn get_app_entries(
         next_config,
     );
 
-    let client_resolve_options_context = get_client_resolve_options_context(
-        project_root,
-        client_ty,
-        mode,
-        next_config,
-        execution_context,
+    let client_resolve_options_context = with_resolve_conditions(
+        with_virtual_modules(
+            get_client_resolve_options_context(
+                project_root,
+                client_ty,
+                mode,
+                next_config,
+                execution_context,
+            ),
+            project_root,
+            virtual_modules,
+        ),
+        resolve_conditions.client(),
     );
 
     let client_transition = ContextTransition::new(
@@ -117,12 +148,19 @@ pub async fn get_app_entries(
         Vc::cell("app-client".to_string()),
     );
 
-    let ssr_resolve_options_context = get_server_resolve_options_context(
-        project_root,
-        ssr_ty,
-        mode,
-        next_config,
-        execution_context,
+    let ssr_resolve_options_context = with_resolve_conditions(
+        with_virtual_modules(
+            get_server_resolve_options_context(
+                project_root,
+                ssr_ty,
+                mode,
+                next_config,
+                execution_context,
+            ),
+            project_root,
+            virtual_modules,
+        ),
+        resolve_conditions.ssr(),
     );
 
     let ssr_module_options_context = get_server_module_options_context(
@@ -171,12 +209,19 @@ pub async fn get_app_entries(
         mode,
         next_config,
     );
-    let rsc_resolve_options_context = get_server_resolve_options_context(
-        project_root,
-        rsc_ty,
-        mode,
-        next_config,
-        execution_context,
+    let rsc_resolve_options_context = with_resolve_conditions(
+        with_virtual_modules(
+            get_server_resolve_options_context(
+                project_root,
+                rsc_ty,
+                mode,
+                next_config,
+                execution_context,
+            ),
+            project_root,
+            virtual_modules,
+        ),
+        resolve_conditions.rsc(),
     );
 
     let rsc_context = ModuleAssetContext::new(
@@ -217,6 +262,7 @@ pub async fn get_app_entries(
                     page.clone(),
                     mode,
                     *metadata,
+                    next_config,
                 ),
             })
         })
@@ -243,14 +289,162 @@ pub async fn get_app_entries(
         entries,
         rsc_runtime_entries: runtime_entries.resolve_entries(Vc::upcast(rsc_context)),
         client_runtime_entries: client_runtime_entries.resolve_entries(Vc::upcast(client_context)),
+        app_dir: Some(app_dir),
     }))
 }
 
+/// Extracts the `metadata` of the layouts and pages of all app pages found
+/// under the given project root.
+pub async fn compute_app_metadata_manifest(
+    project_root: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+) -> Result<AppMetadataManifest> {
+    let mut app_metadata_manifest = AppMetadataManifest::default();
+
+    let Some(&app_dir) = find_app_dir_if_enabled(project_root).await?.as_ref() else {
+        return Ok(app_metadata_manifest);
+    };
+
+    let entrypoints = get_entrypoints(app_dir, next_config.page_extensions());
+    for entrypoint in entrypoints.await?.values() {
+        let Entrypoint::AppPage { page, loader_tree } = entrypoint else {
+            continue;
+        };
+        app_metadata_manifest.pages.insert(
+            page.to_string(),
+            parse_metadata_from_loader_tree(*loader_tree, project_root)
+                .await?
+                .clone_value(),
+        );
+    }
+
+    Ok(app_metadata_manifest)
+}
+
+/// Classifies how each app page found under the given project root can be
+/// prerendered.
+pub async fn compute_ppr_manifest(
+    project_root: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+) -> Result<PprManifest> {
+    let mut ppr_manifest = PprManifest::default();
+
+    let Some(&app_dir) = find_app_dir_if_enabled(project_root).await?.as_ref() else {
+        return Ok(ppr_manifest);
+    };
+
+    let entrypoints = get_entrypoints(app_dir, next_config.page_extensions());
+    for entrypoint in entrypoints.await?.values() {
+        let Entrypoint::AppPage { page, loader_tree } = entrypoint else {
+            continue;
+        };
+        ppr_manifest.pages.insert(
+            page.to_string(),
+            get_app_page_prerendering(*loader_tree, project_root)
+                .await?
+                .clone_value(),
+        );
+    }
+
+    Ok(ppr_manifest)
+}
+
+/// Finds the `loading`, `error` and `not-found` files which apply to the
+/// segments of each app page found under the given project root.
+pub async fn compute_app_boundaries_manifest(
+    project_root: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+) -> Result<AppBoundariesManifest> {
+    let mut app_boundaries_manifest = AppBoundariesManifest::default();
+
+    let Some(&app_dir) = find_app_dir_if_enabled(project_root).await?.as_ref() else {
+        return Ok(app_boundaries_manifest);
+    };
+
+    let entrypoints = get_entrypoints(app_dir, next_config.page_extensions());
+    for entrypoint in entrypoints.await?.values() {
+        let Entrypoint::AppPage { page, loader_tree } = entrypoint else {
+            continue;
+        };
+        app_boundaries_manifest.pages.insert(
+            page.to_string(),
+            get_app_page_boundaries(*loader_tree, project_root)
+                .await?
+                .clone_value(),
+        );
+    }
+
+    Ok(app_boundaries_manifest)
+}
+
+/// Computes the HTTP methods exported by each app route handler.
+pub async fn compute_app_route_methods_manifest(
+    project_root: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+) -> Result<AppRouteMethodsManifest> {
+    let mut app_route_methods_manifest = AppRouteMethodsManifest::default();
+
+    let Some(&app_dir) = find_app_dir_if_enabled(project_root).await?.as_ref() else {
+        return Ok(app_route_methods_manifest);
+    };
+
+    let entrypoints = get_entrypoints(app_dir, next_config.page_extensions());
+    for entrypoint in entrypoints.await?.values() {
+        let Entrypoint::AppRoute { page, path } = entrypoint else {
+            continue;
+        };
+        app_route_methods_manifest.routes.insert(
+            page.to_string(),
+            get_app_route_methods(*path).await?.clone_value(),
+        );
+    }
+
+    Ok(app_route_methods_manifest)
+}
+
+/// Computes the `Cache-Control` of each app page and route handler from their
+/// segment configs.
+pub async fn compute_app_cache_control_manifest(
+    project_root: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+) -> Result<AppCacheControlManifest> {
+    let mut app_cache_control_manifest = AppCacheControlManifest::default();
+
+    let Some(&app_dir) = find_app_dir_if_enabled(project_root).await?.as_ref() else {
+        return Ok(app_cache_control_manifest);
+    };
+
+    let entrypoints = get_entrypoints(app_dir, next_config.page_extensions());
+    for entrypoint in entrypoints.await?.values() {
+        let (page, cache_control) = match entrypoint {
+            Entrypoint::AppPage { page, loader_tree } => {
+                (page, get_app_page_cache_control(*loader_tree, project_root))
+            }
+            Entrypoint::AppRoute { page, path } => {
+                (page, get_app_route_cache_control(*path, project_root))
+            }
+            _ => continue,
+        };
+        if let Some(cache_control) = cache_control.await?.clone_value() {
+            app_cache_control_manifest
+                .routes
+                .insert(page.to_string(), cache_control);
+        }
+    }
+
+    Ok(app_cache_control_manifest)
+}
+
 /// Computes and returns all chunks for app entries. The chunks will be appended
 /// to `all_chunks`, and the chunking information will be added to the provided
 /// manifests.
+///
+/// Pages which may be served with a Content Security Policy from `headers` are
+/// recorded in the app build manifest, so that only their inline scripts get a
+/// nonce.
 pub async fn compute_app_entries_chunks(
     next_config: Vc<NextConfig>,
+    headers: Vc<Headers>,
     app_entries: &AppEntries,
     app_client_reference_graph: Vc<ClientReferenceGraph>,
     app_client_references_chunks: Vc<ClientReferencesChunks>,
@@ -263,10 +457,17 @@ pub async fn compute_app_entries_chunks(
     app_build_manifest: &mut AppBuildManifest,
     build_manifest: &mut BuildManifest,
     app_paths_manifest: &mut AppPathsManifest,
+    next_font_manifest: &mut NextFontManifest,
     all_chunks: &mut Vec<Vc<Box<dyn OutputAsset>>>,
+    route_chunks: &mut RouteChunks,
+    build_timer: &mut BuildTimer,
+    mode: NextMode,
     runtime: NextRuntime,
+    fail_fast: Option<&FailFast>,
 ) -> Result<()> {
     let client_relative_path_ref = client_relative_path.await?;
+    let headers = headers.await?;
+    let base_path = next_config.await?.base_path.clone();
 
     let app_client_shared_chunks = get_app_client_shared_chunks(
         AssetIdent::from_path(
@@ -294,6 +495,7 @@ pub async fn compute_app_entries_chunks(
     let app_client_references_chunks_ref = app_client_references_chunks.await?;
 
     for app_entry in app_entries.entries.iter().copied() {
+        let route_start = Instant::now();
         let app_entry = app_entry.await?;
 
         let app_entry_client_references = app_client_reference_graph
@@ -310,6 +512,10 @@ pub async fn compute_app_entries_chunks(
         );
         all_chunks.push(rsc_chunk);
 
+        if let Some(fail_fast) = fail_fast {
+            fail_fast.check(&app_entry.original_name, rsc_chunk).await?;
+        }
+
         let mut app_entry_client_chunks = vec![];
         // TODO(alexkirsz) In which manifest should this go?
         let mut app_entry_ssr_chunks = vec![];
@@ -343,6 +549,27 @@ pub async fn compute_app_entries_chunks(
             app_entry.original_name.clone(),
             app_entry_client_chunks_paths,
         );
+        // Route handlers don't render any scripts.
+        if app_entry.original_name.ends_with("/page")
+            && route_requires_nonce(&headers, base_path.as_deref(), &app_entry.pathname)
+        {
+            app_build_manifest
+                .nonce_pages
+                .push(app_entry.original_name.clone());
+        }
+
+        if let Some(app_dir) = app_entries.app_dir {
+            // The runtime looks up the fonts by the path of the module being
+            // rendered, without its extension, e.g. `[project]/app/page`.
+            add_font_files(
+                &mut next_font_manifest.app,
+                &mut next_font_manifest.app_using_size_adjust,
+                format!("{}{}", app_dir.to_string().await?, app_entry.original_name),
+                app_entry_client_chunks,
+                &client_relative_path_ref,
+            )
+            .await?;
+        }
 
         app_paths_manifest.node_server_app_paths.pages.insert(
             app_entry.original_name.clone(),
@@ -360,12 +587,141 @@ pub async fn compute_app_entries_chunks(
             app_client_references_chunks,
             client_chunking_context,
             ssr_chunking_context,
-            next_config.computed_asset_prefix(),
+            next_config.computed_asset_prefix(mode),
             runtime,
         );
 
         all_chunks.push(entry_manifest);
+
+        // The chunks of client references are shared between routes.
+        route_chunks
+            .add_route(
+                app_entry.original_name.clone(),
+                vec![Vc::upcast(app_entry.rsc_entry)],
+                [rsc_chunk, entry_manifest],
+            )
+            .await?;
+
+        build_timer.add_route(app_entry.original_name.clone(), route_start.elapsed());
     }
 
+    app_build_manifest.nonce_pages.sort();
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use next_core::next_config::NextConfig;
+    use serde_json::json;
+
+    use super::{compute_app_metadata_manifest, compute_ppr_manifest};
+    use crate::test_utils::{fixture, fixture_root, run};
+
+    #[tokio::test]
+    async fn extracts_static_metadata_into_the_manifest() {
+        let dir = fixture(&[
+            (
+                "app/layout.tsx",
+                indoc! {"
+                    export const metadata = { title: 'Photos', robots: { index: false } };
+                    export default function Layout() {}
+                "},
+            ),
+            (
+                "app/page.tsx",
+                indoc! {"
+                    export async function generateMetadata() { return {}; }
+                    export default function Page() {}
+                "},
+            ),
+            ("app/about/page.tsx", "export default function About() {}"),
+        ]);
+        let root = dir.path().to_owned();
+        let manifest = run(async move {
+            let mut config = NextConfig::default();
+            config.page_extensions = vec!["tsx".to_string()];
+            let manifest =
+                compute_app_metadata_manifest(fixture_root(&root), config.cell()).await?;
+            Ok(serde_json::to_value(manifest)?)
+        })
+        .await
+        .unwrap();
+
+        let layout = json!({
+            "path": "app/layout.tsx",
+            "metadata": {
+                "type": "static",
+                "value": { "title": "Photos", "robots": { "index": false } },
+            },
+        });
+        let pages = manifest["pages"].as_object().unwrap();
+        assert_eq!(pages.len(), 2, "{manifest}");
+        // `generateMetadata` is marked dynamic rather than evaluated.
+        assert!(pages.values().any(|segments| *segments
+            == json!([layout, { "path": "app/page.tsx", "metadata": { "type": "dynamic" } }])));
+        // Pages without metadata only inherit the layout's.
+        assert!(pages.values().any(|segments| *segments == json!([layout])));
+    }
+
+    #[tokio::test]
+    async fn classifies_how_each_page_is_prerendered() {
+        let dir = fixture(&[
+            (
+                "app/layout.tsx",
+                "export default function Layout({ children }) { return children; }",
+            ),
+            (
+                "app/page.tsx",
+                "export default function Home() { return <h1>Home</h1>; }",
+            ),
+            (
+                "app/feed/loading.tsx",
+                "export default function Loading() { return null; }",
+            ),
+            (
+                "app/feed/page.tsx",
+                indoc! {"
+                    import { cookies } from 'next/headers';
+                    export default function Feed() { return cookies().get('feed')?.value; }
+                "},
+            ),
+            (
+                "app/account/page.tsx",
+                indoc! {"
+                    import { headers } from 'next/headers';
+                    export default function Account() { return headers().get('user'); }
+                "},
+            ),
+        ]);
+        let root = dir.path().to_owned();
+        let manifest = run(async move {
+            let mut config = NextConfig::default();
+            config.page_extensions = vec!["tsx".to_string()];
+            let manifest = compute_ppr_manifest(fixture_root(&root), config.cell()).await?;
+            Ok(serde_json::to_value(manifest)?)
+        })
+        .await
+        .unwrap();
+
+        let pages = manifest["pages"].as_object().unwrap();
+        let prerendering = |route: &str| {
+            pages
+                .iter()
+                .find(|(page, _)| page.trim_end_matches("/page") == route)
+                .map(|(_, prerendering)| prerendering.clone())
+                .unwrap_or_else(|| panic!("{route} isn't in {manifest}"))
+        };
+        // Without dynamic content, the page is static rather than PPR.
+        assert_eq!(prerendering(""), json!({ "type": "static" }));
+        assert_eq!(
+            prerendering("/feed"),
+            json!({
+                "type": "partialPrerendering",
+                "shellBoundaries": ["app/feed/loading.tsx"],
+            })
+        );
+        assert_eq!(prerendering("/account"), json!({ "type": "dynamic" }));
+    }
+}
@@ -1,39 +1,61 @@
 use std::{
     collections::{HashMap, HashSet},
     env::current_dir,
-    path::{PathBuf, MAIN_SEPARATOR},
+    fs::create_dir_all,
+    io::{self, Write},
+    path::{Path, PathBuf, MAIN_SEPARATOR},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use dunce::canonicalize;
+use indexmap::IndexMap;
 use next_core::{
+    client_env::{client_env_references, ClientEnvReferences},
+    import_extensions::validate_import_extensions,
+    link_validation::validate_links,
     mode::NextMode,
     next_app::get_app_client_references_chunks,
-    next_client::{get_client_chunking_context, get_client_compile_time_info},
-    next_client_reference::{ClientReferenceGraph, ClientReferenceType},
-    next_config::load_next_config,
-    next_dynamic::NextDynamicEntries,
+    next_client::{get_client_chunking_context, get_client_compile_time_info, ClientRuntime},
+    next_client_reference::{
+        check_server_packages_in_client, validate_client_references, ClientReferenceGraph,
+        ClientReferenceType,
+    },
+    next_config::{load_headers, load_next_config, load_rewrites, NextConfig},
+    next_dynamic::{validate_dynamic_imports, NextDynamicEntries},
     next_manifests::{
-        AppBuildManifest, AppPathsManifest, BuildManifest, ClientBuildManifest, FontManifest,
-        MiddlewaresManifest, NextFontManifest, PagesManifest, ReactLoadableManifest,
-        ServerReferenceManifest,
+        AppBuildManifest, AppPathsManifest, BuildManifest, ClientBuildManifest, ClientEnvManifest,
+        ClientSettingsManifest, DeployManifest, DeployManifestFile, FontManifest, FunctionConfig,
+        FunctionsConfigManifest, ImagesManifest, LocaleRoutesManifest, MiddlewaresManifest,
+        NextFontManifest, PagesManifest, ReactLoadableManifest, RoutesManifest,
+        ServerReferenceManifest, ServerSettingsManifest, DEFAULT_DATA_PATH_PREFIX,
     },
-    next_server::{get_server_chunking_context, get_server_compile_time_info},
+    next_server::{get_server_chunking_context, get_server_compile_time_info, server_minify},
+    pages_structure::is_special_page,
+    typed_routes::link_type_declarations,
     url_node::get_sorted_routes,
     util::NextRuntime,
     {self},
 };
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 use turbo_tasks::{
     graph::{AdjacencyMap, GraphTraversal},
     Completion, Completions, TransientInstance, TryJoinIterExt, Vc,
 };
 use turbopack_binding::{
-    turbo::tasks_fs::{rebase, DiskFileSystem, FileContent, FileSystem, FileSystemPath},
+    turbo::{
+        tasks_env::ProcessEnv,
+        tasks_fs::{
+            rebase, rope::RopeBuilder, DirectoryContent, DirectoryEntry, DiskFileSystem, File,
+            FileContent, FileSystem, FileSystemPath,
+        },
+        tasks_hash::hash_xxh3_hash64,
+    },
     turbopack::{
         cli_utils::issue::{ConsoleUi, LogOptions},
         core::{
-            asset::Asset,
+            asset::{Asset, AssetContent},
             environment::ServerAddr,
             ident::AssetIdent,
             issue::{handle_issues, IssueReporter, IssueSeverity},
@@ -49,16 +71,46 @@ use turbopack_binding::{
 };
 
 use crate::{
+    build_diagnostics::{BuildPhase, BuildTimer},
+    build_logger::{IssueLog, LoggingIssueReporter},
     build_options::{BuildContext, BuildOptions},
-    next_app::app_entries::{compute_app_entries_chunks, get_app_entries},
+    build_report::{compute_build_report, BuildReport},
+    changed_files::{resolve_changed_files, RouteChunks},
+    chunk_graph::chunk_graph_dot,
+    content_addressing::{content_address_client_files, seeded_hash},
+    custom_server::compute_custom_server,
+    fail_fast::FailFast,
+    failure_diagnostics::{FailureDiagnostics, FailureDiagnosticsReporter},
+    inline_css::inline_page_css,
+    issue_promotion::PromotingIssueReporter,
+    manifest_transform::ManifestTransform,
+    module_cycles::detect_module_cycles,
+    next_app::app_entries::{
+        compute_app_boundaries_manifest, compute_app_cache_control_manifest,
+        compute_app_entries_chunks, compute_app_metadata_manifest,
+        compute_app_route_methods_manifest, compute_ppr_manifest, get_app_entries,
+    },
     next_pages::page_entries::{compute_page_entries_chunks, get_page_entries},
+    preload_manifest::{compute_load_order_manifest, compute_preload_manifest},
+    unemitted_assets::report_unemitted_assets,
 };
 
 // TODO this should be Error, but we need to fix the errors happening first
+/// The default of [BuildOptions::fail_on].
 static MIN_FAILING_SEVERITY: IssueSeverity = IssueSeverity::Fatal;
 
+/// The client target of [BuildOptions::legacy_browsers]. Internet Explorer 11
+/// lacks (complete support for) all syntax newer than ES5, so all of it is
+/// lowered.
+const LEGACY_BROWSERSLIST_QUERY: &str = "ie 11";
+
 #[turbo_tasks::function]
-pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Result<Vc<Completion>> {
+pub(crate) async fn next_build(
+    options: TransientInstance<BuildOptions>,
+    failure_diagnostics: TransientInstance<FailureDiagnostics>,
+) -> Result<Vc<BuildReport>> {
+    let mut build_timer = BuildTimer::new(options.logger.clone());
+
     let project_root = options
         .dir
         .as_ref()
@@ -79,9 +131,15 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         project_root.clone()
     };
 
-    let browserslist_query = "last 1 Chrome versions, last 1 Firefox versions, last 1 Safari \
-                              versions, last 1 Edge versions"
-        .to_string();
+    let output_root = output_root(&options, &project_root)?;
+
+    let browserslist_query = if options.legacy_browsers {
+        LEGACY_BROWSERSLIST_QUERY.to_string()
+    } else {
+        "last 1 Chrome versions, last 1 Firefox versions, last 1 Safari versions, last 1 Edge \
+         versions"
+            .to_string()
+    };
 
     let log_options = LogOptions {
         project_dir: PathBuf::from(project_root.clone()),
@@ -95,12 +153,43 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         .dist_dir
         .as_ref()
         .map_or_else(|| ".next".to_string(), |d| d.to_string());
+    validate_dist_dir(&output_root, &dist_dir)?;
+    validate_hash_length(&options)?;
+    let data_path_prefix = data_path_prefix(options.data_path_prefix.as_deref())?;
+
+    let write_retries = options.write_retries.unwrap_or(3).min(MAX_WRITE_RETRIES);
+    let compact_manifests = options.compact_manifests;
+    let manifest_transform = options.manifest_transform.as_ref();
 
     let issue_reporter: Vc<Box<dyn IssueReporter>> =
         Vc::upcast(ConsoleUi::new(TransientInstance::new(log_options)));
-    let node_fs = node_fs(project_root.clone(), issue_reporter);
+    let issue_reporter = if options.promote_to_error.is_empty() {
+        issue_reporter
+    } else {
+        Vc::upcast(PromotingIssueReporter::new(
+            issue_reporter,
+            options.promote_to_error.clone(),
+        ))
+    };
+    let issue_reporter = if options.diagnostics_on_failure {
+        Vc::upcast(FailureDiagnosticsReporter::new(
+            issue_reporter,
+            failure_diagnostics.clone(),
+        ))
+    } else {
+        issue_reporter
+    };
+    let issue_reporter = match &options.logger {
+        Some(logger) => Vc::upcast(LoggingIssueReporter::new(
+            issue_reporter,
+            TransientInstance::new(IssueLog::new(logger.clone())),
+        )),
+        None => issue_reporter,
+    };
+    let min_failing_severity = options.fail_on.unwrap_or(MIN_FAILING_SEVERITY);
+    let node_fs = node_fs(output_root.clone(), issue_reporter);
     let node_root = node_fs.root().join(dist_dir.clone());
-    let client_fs = client_fs(project_root.clone(), issue_reporter);
+    let client_fs = client_fs(output_root, issue_reporter);
     let client_root = client_fs.root().join(dist_dir);
     // TODO(alexkirsz) This should accept a URL for assetPrefix.
     // let client_public_fs = VirtualFileSystem::new();
@@ -111,6 +200,7 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         .strip_prefix(MAIN_SEPARATOR)
         .unwrap_or(project_relative)
         .replace(MAIN_SEPARATOR, "/");
+    let project_dir = project_root;
     let project_root = workspace_fs.root().join(project_relative);
 
     let node_root_ref = node_root.await?;
@@ -131,17 +221,53 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     let execution_context =
         ExecutionContext::new(project_root, node_execution_chunking_context, env);
     let next_config = load_next_config(execution_context);
+    if options.diagnostics_on_failure {
+        failure_diagnostics.set_config(&*next_config.await?)?;
+    }
 
     let mode = NextMode::Build;
 
-    let client_define_env = Vc::cell(options.define_env.client.iter().cloned().collect());
+    let web_vitals_attribution = next_config.web_vitals_attribution().await?;
+
+    let public_env_prefixes = options
+        .public_env_prefixes
+        .clone()
+        .unwrap_or_else(|| vec!["NEXT_PUBLIC_".to_string()]);
+    if public_env_prefixes.iter().any(|prefix| prefix.is_empty()) {
+        bail!(
+            "public env prefixes must not be empty, as that would inline every environment \
+             variable into client bundles"
+        );
+    }
+    let client_define_env = Vc::cell(client_define_env(
+        &options,
+        mode,
+        &env.read_all().await?,
+        &public_env_prefixes,
+        &web_vitals_attribution,
+    )?);
     let client_compile_time_info =
         get_client_compile_time_info(browserslist_query, client_define_env);
 
-    let server_define_env = Vc::cell(options.define_env.nodejs.iter().cloned().collect());
+    let mut server_define_env: IndexMap<String, String> = options
+        .define_env
+        .nodejs
+        .iter()
+        .cloned()
+        .chain(options.defines.iter().cloned())
+        .collect();
+    server_define_env
+        .entry("process.env.NODE_ENV".to_string())
+        .or_insert(serde_json::to_string(mode.node_env())?);
+    let server_define_env = Vc::cell(server_define_env);
     let server_compile_time_info =
         get_server_compile_time_info(env, ServerAddr::empty(), server_define_env);
 
+    build_timer.end_phase(BuildPhase::Config);
+
+    let virtual_modules = Vc::cell(options.virtual_modules.iter().cloned().collect());
+    let resolve_conditions = options.resolve_conditions.clone().cell();
+
     // TODO(alexkirsz) Pages should build their own routes, outside of a FS.
     let next_router_fs = Vc::upcast::<Box<dyn FileSystem>>(VirtualFileSystem::new());
     let next_router_root = next_router_fs.root();
@@ -152,6 +278,8 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         client_compile_time_info,
         server_compile_time_info,
         next_config,
+        virtual_modules,
+        resolve_conditions,
     );
 
     let app_entries = get_app_entries(
@@ -160,12 +288,14 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         client_compile_time_info,
         server_compile_time_info,
         next_config,
+        virtual_modules,
+        resolve_conditions,
     );
 
     handle_issues(
         page_entries,
         issue_reporter,
-        MIN_FAILING_SEVERITY.cell(),
+        min_failing_severity.cell(),
         None,
         None,
     )
@@ -173,7 +303,7 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     handle_issues(
         app_entries,
         issue_reporter,
-        MIN_FAILING_SEVERITY.cell(),
+        min_failing_severity.cell(),
         None,
         None,
     )
@@ -222,6 +352,44 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         .try_join()
         .await?;
 
+    let app_client_entries: Vec<_> = app_client_reference_tys
+        .await?
+        .iter()
+        .map(|client_reference_ty| async move {
+            let ClientReferenceType::EcmascriptClientReference(entry) = client_reference_ty else {
+                return Ok(None);
+            };
+
+            Ok(Some(entry.await?.client_module))
+        })
+        .try_join()
+        .await?
+        .into_iter()
+        .flatten()
+        .collect();
+
+    let page_client_entries = page_entries
+        .entries
+        .iter()
+        .copied()
+        .map(|entry| async move { Ok(entry.await?.client_module) })
+        .try_join()
+        .await?;
+
+    let client_env_references = client_env_references(
+        Vc::cell(
+            page_client_entries
+                .iter()
+                .copied()
+                .map(Vc::upcast)
+                .chain(app_client_entries.iter().copied().map(Vc::upcast))
+                .collect(),
+        ),
+        client_define_env,
+        project_root,
+    )
+    .await?;
+
     let app_node_entries: Vec<_> = app_ssr_entries
         .iter()
         .copied()
@@ -234,12 +402,114 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         .chain(app_node_entries.iter().copied())
         .collect();
 
+    let mut routes = page_entries
+        .entries
+        .iter()
+        .map(|entry| async move { Ok(entry.await?.pathname.await?.clone_value()) })
+        .try_join()
+        .await?;
+    routes.extend(
+        app_entries
+            .entries
+            .iter()
+            .map(|entry| async move { Ok(entry.await?.pathname.clone()) })
+            .try_join()
+            .await?,
+    );
+    if options.diagnostics_on_failure {
+        failure_diagnostics.set_routes(&routes);
+    }
+
+    handle_issues(
+        validate_client_references(Vc::cell(
+            app_client_entries.iter().copied().map(Vc::upcast).collect(),
+        )),
+        issue_reporter,
+        min_failing_severity.cell(),
+        None,
+        None,
+    )
+    .await?;
+
+    if let Some(size_limit) = options.server_package_size_limit {
+        handle_issues(
+            check_server_packages_in_client(
+                Vc::cell(app_client_entries.iter().copied().map(Vc::upcast).collect()),
+                project_root,
+                next_config,
+                size_limit,
+                Vc::cell(options.allowed_client_packages.clone()),
+            ),
+            issue_reporter,
+            min_failing_severity.cell(),
+            None,
+            None,
+        )
+        .await?;
+    }
+
+    if options.validate_links {
+        handle_issues(
+            validate_links(
+                Vc::cell(all_node_entries.iter().copied().map(Vc::upcast).collect()),
+                Vc::cell(routes.clone()),
+            ),
+            issue_reporter,
+            min_failing_severity.cell(),
+            None,
+            None,
+        )
+        .await?;
+    }
+
     // TODO(alexkirsz) Handle dynamic entries and dynamic chunks.
     let _dynamic_entries = NextDynamicEntries::from_entries(Vc::cell(
         all_node_entries.iter().copied().map(Vc::upcast).collect(),
     ))
     .await?;
 
+    if options.strict_dynamic_imports {
+        // Unresolvable imports are errors, so make sure they fail the build.
+        handle_issues(
+            validate_dynamic_imports(Vc::cell(
+                all_node_entries.iter().copied().map(Vc::upcast).collect(),
+            )),
+            issue_reporter,
+            IssueSeverity::Error.cell(),
+            None,
+            None,
+        )
+        .await?;
+    }
+
+    if options.strict_import_extensions {
+        handle_issues(
+            validate_import_extensions(Vc::cell(
+                all_node_entries.iter().copied().map(Vc::upcast).collect(),
+            )),
+            issue_reporter,
+            IssueSeverity::Error.cell(),
+            None,
+            None,
+        )
+        .await?;
+    }
+
+    if options.detect_cycles {
+        handle_issues(
+            detect_module_cycles(Vc::cell(
+                all_node_entries.iter().copied().map(Vc::upcast).collect(),
+            )),
+            issue_reporter,
+            min_failing_severity.cell(),
+            None,
+            None,
+        )
+        .await?;
+    }
+
+    build_timer.end_phase(BuildPhase::Discovery);
+
     // TODO(alexkirsz) At this point, we have access to the whole module graph via
     // the entries. This is where we should compute unique module ids and optimized
     // chunks.
@@ -259,25 +529,52 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
     ));
     let client_relative_path_ref = client_relative_path.await?;
 
+    let client_runtime = options.client_runtime.unwrap_or(ClientRuntime::Production);
     let client_chunking_context = get_client_chunking_context(
         project_root,
         client_relative_path,
-        next_config.computed_asset_prefix(),
+        next_config.computed_asset_prefix(mode),
         client_compile_time_info.environment(),
-        mode,
+        client_runtime,
     );
 
     let server_chunking_context = get_server_chunking_context(
         project_root,
         node_root,
         client_relative_path,
-        next_config.computed_asset_prefix(),
+        next_config.computed_asset_prefix(mode),
         server_compile_time_info.environment(),
+        server_minify(
+            mode,
+            *next_config.server_minification().await?,
+            options.minify_server,
+        ),
     );
     let mut all_chunks = vec![];
-
-    let mut build_manifest: BuildManifest = Default::default();
+    let changed_files = match &options.changed_files {
+        Some(changed_files) => {
+            resolve_changed_files(project_root, &project_dir, changed_files).await?
+        }
+        None => None,
+    };
     let build_manifest_path = client_root.join("build-manifest.json".to_string());
+    // The unaffected routes keep the chunks of the prior build.
+    if changed_files.is_some() {
+        check_prior_client_runtime(build_manifest_path, client_runtime).await?;
+    }
+    let mut route_chunks = RouteChunks::new(changed_files);
+
+    let mut build_manifest = BuildManifest {
+        client_runtime: Some(client_runtime.name().to_string()),
+        ..Default::default()
+    };
+
+    let mut next_font_manifest = NextFontManifest::default();
+
+    let fail_fast = options.fail_fast.then(|| FailFast {
+        issue_reporter,
+        min_failing_severity,
+    });
 
     // PAGE CHUNKING
 
@@ -294,10 +591,16 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         &client_relative_path_ref,
         &mut pages_manifest,
         &mut build_manifest,
+        &mut next_font_manifest,
         &mut all_chunks,
+        &mut route_chunks,
+        &mut build_timer,
+        fail_fast.as_ref(),
     )
     .await?;
 
+    build_timer.end_phase(BuildPhase::PageChunking);
+
     // APP CHUNKING
 
     let mut app_build_manifest = AppBuildManifest::default();
@@ -315,7 +618,13 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         client_chunking_context,
         // TODO(WEB-1824): add edge support
         Vc::upcast(server_chunking_context),
+        options.concurrency,
     );
+    if let Some(fail_fast) = &fail_fast {
+        fail_fast
+            .check("app client references", app_client_references_chunks)
+            .await?;
+    }
     let app_client_references_chunks_ref = app_client_references_chunks.await?;
 
     for app_client_reference_chunks in app_client_references_chunks_ref.values() {
@@ -325,11 +634,14 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         all_chunks.extend(ssr_chunks.iter().copied());
     }
 
+    build_timer.end_phase(BuildPhase::ClientReferences);
+
     // APP RSC CHUNKING
     // TODO(alexkirsz) Do some of that in parallel with the above.
 
     compute_app_entries_chunks(
         next_config,
+        load_headers(execution_context),
         &app_entries,
         app_client_references,
         app_client_references_chunks,
@@ -342,155 +654,769 @@ pub(crate) async fn next_build(options: TransientInstance<BuildOptions>) -> Resu
         &mut app_build_manifest,
         &mut build_manifest,
         &mut app_paths_manifest,
+        &mut next_font_manifest,
         &mut all_chunks,
+        &mut route_chunks,
+        &mut build_timer,
+        mode,
         // TODO(WEB-1824): add edge support
         NextRuntime::NodeJs,
+        fail_fast.as_ref(),
     )
     .await?;
 
-    let mut completions = vec![];
+    let custom_server_trace = match &options.custom_server {
+        Some(entry) => {
+            let custom_server = compute_custom_server(
+                entry,
+                project_root,
+                execution_context,
+                server_compile_time_info,
+                next_config,
+                virtual_modules,
+                server_chunking_context,
+                node_root,
+            )
+            .await?;
+            all_chunks.push(custom_server.chunk);
+            Some(custom_server.trace)
+        }
+        None => None,
+    };
+
+    build_timer.end_phase(BuildPhase::AppChunking);
 
-    if let Some(build_context) = &options.build_context {
-        let BuildContext { build_id, rewrites } = build_context;
+    let inline_css_manifest = match *next_config.inline_css_threshold().await? {
+        Some(threshold) => Some(
+            inline_page_css(
+                &mut build_manifest,
+                &all_chunks,
+                &client_relative_path_ref,
+                threshold,
+            )
+            .await?,
+        ),
+        None => None,
+    };
 
-        let ssg_manifest_path = format!("static/{build_id}/_ssgManifest.js");
+    let mut output = OutputWriter {
+        files: vec![],
+        completions: vec![],
+        write_retries,
+        compact_manifests,
+        manifest_transform,
+    };
 
-        let ssg_manifest_fs_path = node_root.join(ssg_manifest_path.clone());
-        completions.push(
-            ssg_manifest_fs_path.write(
-                FileContent::Content(
-                    "self.__SSG_MANIFEST=new Set;self.__SSG_MANIFEST_CB&&self.__SSG_MANIFEST_CB()"
-                        .into(),
-                )
-                .cell(),
-            ),
-        );
+    let (build_id, rewrites) = match &options.build_context {
+        Some(BuildContext { build_id, rewrites }) => (build_id.clone(), rewrites.clone()),
+        None => {
+            let build_id =
+                generated_build_id(next_config, &all_chunks, options.build_hash_seed.as_deref())
+                    .await?;
+            let rewrites = load_rewrites(execution_context).await?.clone_value();
+            (build_id, rewrites)
+        }
+    };
+
+    let (ssg_manifest_path, client_manifest_path) = build_id_manifest_paths(&build_id);
+
+    let ssg_manifest_fs_path = node_root.join(ssg_manifest_path.clone());
+    output.write_file(
+        ssg_manifest_fs_path,
+        FileContent::Content(
+            "self.__SSG_MANIFEST=new Set;self.__SSG_MANIFEST_CB&&self.__SSG_MANIFEST_CB()".into(),
+        )
+        .cell(),
+    );
+
+    build_manifest.low_priority_files.push(ssg_manifest_path);
+
+    let sorted_pages =
+        get_sorted_routes(&pages_manifest.pages.keys().cloned().collect::<Vec<_>>())?;
 
-        build_manifest.low_priority_files.push(ssg_manifest_path);
+    let app_dependencies: HashSet<&str> = pages_manifest
+        .pages
+        .get("/_app")
+        .iter()
+        .map(|s| s.as_str())
+        .collect();
+    let mut pages = HashMap::new();
 
-        let sorted_pages =
-            get_sorted_routes(&pages_manifest.pages.keys().cloned().collect::<Vec<_>>())?;
+    for page in &sorted_pages {
+        if page == "_app" {
+            continue;
+        }
 
-        let app_dependencies: HashSet<&str> = pages_manifest
+        let dependencies = pages_manifest
             .pages
-            .get("/_app")
+            .get(page)
             .iter()
-            .map(|s| s.as_str())
-            .collect();
-        let mut pages = HashMap::new();
+            .map(|dep| dep.as_str())
+            .filter(|dep| !app_dependencies.contains(*dep))
+            .collect::<Vec<_>>();
 
-        for page in &sorted_pages {
-            if page == "_app" {
-                continue;
-            }
+        if !dependencies.is_empty() {
+            pages.insert(page.to_string(), dependencies);
+        }
+    }
 
-            let dependencies = pages_manifest
-                .pages
-                .get(page)
-                .iter()
-                .map(|dep| dep.as_str())
-                .filter(|dep| !app_dependencies.contains(*dep))
-                .collect::<Vec<_>>();
+    let client_manifest = ClientBuildManifest {
+        rewrites: &rewrites,
+        data_path_prefix: data_path_prefix.as_deref(),
+        sorted_pages: &sorted_pages,
+        pages,
+    };
 
-            if !dependencies.is_empty() {
-                pages.insert(page.to_string(), dependencies);
-            }
+    let client_manifest_fs_path = node_root.join(client_manifest_path.clone());
+    output.write_file(
+        client_manifest_fs_path,
+        FileContent::Content(
+            format!(
+                "self.__BUILD_MANIFEST={};self.__BUILD_MANIFEST_CB && self.__BUILD_MANIFEST_CB()",
+                StringifyJs(&client_manifest)
+            )
+            .into(),
+        )
+        .cell(),
+    );
+
+    build_manifest.low_priority_files.push(client_manifest_path);
+
+    let build_report = compute_build_report(
+        &all_chunks,
+        &client_relative_path_ref,
+        &build_manifest,
+        &app_build_manifest,
+    )
+    .await?;
+
+    // The build report is computed with the original names, as the hashed
+    // copies aren't part of the chunks.
+    if options.content_addressed {
+        let (content_addressed_manifest, hashed_files) = content_address_client_files(
+            &mut build_manifest,
+            &mut app_build_manifest,
+            &all_chunks,
+            &client_relative_path_ref,
+            client_root,
+            options.build_hash_seed.as_deref(),
+            options.hash_length,
+        )
+        .await?;
+        for (path, content) in hashed_files {
+            output.write_file(path, content);
         }
+        output
+            .write_manifest(
+                content_addressed_manifest,
+                node_root.join("server/content-addressed-manifest.json".to_string()),
+            )
+            .await?;
+    }
 
-        let client_manifest = ClientBuildManifest {
-            rewrites,
-            sorted_pages: &sorted_pages,
-            pages,
-        };
+    build_manifest.compute_runtime_files();
 
-        let client_manifest_path = format!("static/{build_id}/_buildManifest.js");
+    if options.preload_manifest {
+        let preload_manifest = compute_preload_manifest(
+            &build_manifest,
+            &app_build_manifest,
+            &next_font_manifest,
+            app_entries.app_dir,
+        )
+        .await?;
+        output
+            .write_manifest(
+                preload_manifest,
+                node_root.join("server/preload-manifest.json".to_string()),
+            )
+            .await?;
+    }
 
-        let client_manifest_fs_path = node_root.join(client_manifest_path.clone());
-        completions.push(
-            client_manifest_fs_path.write(
-                FileContent::Content(
-                    format!(
-                        "self.__BUILD_MANIFEST={};self.__BUILD_MANIFEST_CB && \
-                         self.__BUILD_MANIFEST_CB()",
-                        StringifyJs(&client_manifest)
-                    )
-                    .into(),
+    if options.load_order_manifest {
+        let load_order_manifest = compute_load_order_manifest(
+            &build_manifest,
+            &app_build_manifest,
+            &next_font_manifest,
+            app_entries.app_dir,
+        )
+        .await?;
+        output
+            .write_manifest(
+                load_order_manifest,
+                node_root.join("server/load-order-manifest.json".to_string()),
+            )
+            .await?;
+    }
+
+    if let Some(i18n) = &next_config_ref.i18n {
+        output
+            .write_manifest(
+                LocaleRoutesManifest::new(i18n, pages_manifest.pages.keys().map(String::as_str)),
+                node_root.join("server/locale-routes-manifest.json".to_string()),
+            )
+            .await?;
+    }
+
+    if let Some(pages_manifest_base) = &options.pages_manifest_base {
+        pages_manifest.resolve_paths(&pages_manifest_base.to_string_lossy());
+    }
+    output
+        .write_manifest(pages_manifest, pages_manifest_path)
+        .await?;
+    output
+        .write_manifest(app_build_manifest, app_build_manifest_path)
+        .await?;
+    output
+        .write_manifest(app_paths_manifest, app_paths_manifest_path)
+        .await?;
+    output
+        .write_manifest(build_manifest, build_manifest_path)
+        .await?;
+    output
+        .write_manifest(
+            next_font_manifest,
+            node_root.join("server/next-font-manifest.json".to_string()),
+        )
+        .await?;
+
+    // The manifests of features the project doesn't use aren't written, and
+    // the server falls back to its defaults.
+    if app_entries.app_dir.is_some() {
+        output
+            .write_manifest(
+                compute_app_metadata_manifest(project_root, next_config).await?,
+                node_root.join("server/app-metadata-manifest.json".to_string()),
+            )
+            .await?;
+        if *next_config.enable_ppr().await? {
+            output
+                .write_manifest(
+                    compute_ppr_manifest(project_root, next_config).await?,
+                    node_root.join("server/ppr-manifest.json".to_string()),
                 )
-                .cell(),
-            ),
+                .await?;
+        }
+        output
+            .write_manifest(
+                compute_app_boundaries_manifest(project_root, next_config).await?,
+                node_root.join("server/app-boundaries-manifest.json".to_string()),
+            )
+            .await?;
+        output
+            .write_manifest(
+                compute_app_route_methods_manifest(project_root, next_config).await?,
+                node_root.join("server/app-route-methods-manifest.json".to_string()),
+            )
+            .await?;
+        output
+            .write_manifest(
+                compute_app_cache_control_manifest(project_root, next_config).await?,
+                node_root.join("server/app-cache-control-manifest.json".to_string()),
+            )
+            .await?;
+    }
+    if let Some(client_env_manifest) =
+        client_env_manifest(&client_env_references, &public_env_prefixes)
+    {
+        output
+            .write_manifest(
+                client_env_manifest,
+                node_root.join("server/client-env-manifest.json".to_string()),
+            )
+            .await?;
+    }
+    if let Some(client_settings_manifest) = client_settings_manifest(next_config).await? {
+        output
+            .write_manifest(
+                client_settings_manifest,
+                client_root.join("client-settings-manifest.json".to_string()),
+            )
+            .await?;
+    }
+    if let Some(server_settings_manifest) = server_settings_manifest(next_config).await? {
+        output
+            .write_manifest(
+                server_settings_manifest,
+                node_root.join("server/server-settings-manifest.json".to_string()),
+            )
+            .await?;
+    }
+    if let Some(images_manifest) = images_manifest(next_config).await? {
+        output
+            .write_manifest(
+                images_manifest,
+                node_root.join("images-manifest.json".to_string()),
+            )
+            .await?;
+    }
+
+    let routes_manifest_path = node_root.join("routes-manifest.json".to_string());
+    // Without the manifest of `next build`, there are no routes to serve the
+    // header rule with, and writing a manifest of the rule alone would leave
+    // out the fields the server requires.
+    if let Some(mut routes_manifest) = read_routes_manifest(routes_manifest_path).await? {
+        routes_manifest
+            .add_immutable_static_assets_header(next_config_ref.base_path.as_deref().unwrap_or(""));
+        if let Some(data_path_prefix) = &data_path_prefix {
+            routes_manifest.set_data_path_prefix(data_path_prefix);
+        }
+        output
+            .write_manifest(routes_manifest, routes_manifest_path)
+            .await?;
+    }
+
+    if let Some(custom_server_trace) = custom_server_trace {
+        output
+            .write_manifest(
+                custom_server_trace,
+                node_root.join("server.js.nft.json".to_string()),
+            )
+            .await?;
+    }
+
+    if let Some(inline_css_manifest) = inline_css_manifest {
+        output
+            .write_manifest(
+                inline_css_manifest,
+                node_root.join("server/inline-css-manifest.json".to_string()),
+            )
+            .await?;
+    }
+
+    // The files are served from `public` in the dist directory, whatever the
+    // name of the directory they're copied from.
+    copy_public_dir(
+        &mut output,
+        public_dir(project_root, options.public_dir.as_deref()),
+        client_root.join("public".to_string()),
+    )
+    .await?;
+
+    if options.chunk_graph {
+        let chunk_graph = chunk_graph_dot(
+            route_chunks.routes(),
+            options.chunk_graph_routes.as_deref(),
+            options.chunk_graph_depth,
+            &node_root_ref,
+            &*client_relative_path.await?,
+        )
+        .await?;
+        output.write_file(
+            node_root.join("chunk-graph.dot".to_string()),
+            FileContent::Content(chunk_graph.into()).cell(),
         );
+    }
 
-        build_manifest.low_priority_files.push(client_manifest_path);
+    if *next_config.typed_routes().await? {
+        output.write_file(
+            node_root.join("types/link.d.ts".to_string()),
+            FileContent::Content(link_type_declarations(&routes).into()).cell(),
+        );
     }
 
-    completions.push(write_manifest(pages_manifest, pages_manifest_path)?);
-    completions.push(write_manifest(app_build_manifest, app_build_manifest_path)?);
-    completions.push(write_manifest(app_paths_manifest, app_paths_manifest_path)?);
-    completions.push(write_manifest(build_manifest, build_manifest_path)?);
+    // Pages are keyed by their pathname and app routes by their original name,
+    // like in the pages and app paths manifests.
+    let function_defaults = FunctionConfig {
+        regions: options.default_preferred_region.clone(),
+        max_duration: options.default_max_duration,
+        ..Default::default()
+    };
+    let mut functions_config_manifest = FunctionsConfigManifest::default();
+    for page_entry in page_entries.entries.iter() {
+        let page_entry = page_entry.await?;
+        let pathname = page_entry.pathname.await?;
+        // `_app` and `_document` are bundled into each page.
+        if is_special_page(&pathname) {
+            continue;
+        }
+        let config = page_entry.config.await?;
+        functions_config_manifest.add_function(
+            pathname.clone_value(),
+            FunctionConfig {
+                runtime: Some(config.runtime),
+                regions: config.preferred_region.clone(),
+                max_duration: config.max_duration,
+            },
+            &function_defaults,
+        );
+    }
+    for app_entry in app_entries.entries.iter() {
+        let app_entry = app_entry.await?;
+        let config = app_entry.config.await?;
+        functions_config_manifest.add_function(
+            app_entry.original_name.clone(),
+            FunctionConfig {
+                runtime: config.runtime,
+                regions: config.preferred_region.clone(),
+                max_duration: config.max_duration,
+            },
+            &function_defaults,
+        );
+    }
+    if functions_config_manifest.has_function_settings() {
+        output
+            .write_manifest(
+                functions_config_manifest,
+                node_root.join("server/functions-config-manifest.json".to_string()),
+            )
+            .await?;
+    }
 
     // Placeholder manifests.
 
     // TODO(alexkirsz) Proper middleware manifest with all (edge?) routes in it,
     // experimental-edge pages?
-    completions.push(write_manifest(
-        MiddlewaresManifest::default(),
-        node_root.join("server/middleware-manifest.json".to_string()),
-    )?);
-    completions.push(write_manifest(
-        NextFontManifest::default(),
-        node_root.join("server/next-font-manifest.json".to_string()),
-    )?);
-    completions.push(write_manifest(
-        FontManifest::default(),
-        node_root.join("server/font-manifest.json".to_string()),
-    )?);
-    completions.push(write_manifest(
-        ServerReferenceManifest::default(),
-        node_root.join("server/server-reference-manifest.json".to_string()),
-    )?);
-    completions.push(write_manifest(
-        ReactLoadableManifest::default(),
-        node_root.join("react-loadable-manifest.json".to_string()),
-    )?);
-
-    completions.push(
-        emit_all_assets(
-            all_chunks,
-            &node_root_ref,
-            client_relative_path,
-            client_root,
+    output
+        .write_manifest(
+            MiddlewaresManifest::default(),
+            node_root.join("server/middleware-manifest.json".to_string()),
         )
-        .await?,
-    );
-
-    Ok(Completions::all(completions))
-}
+        .await?;
+    output
+        .write_manifest(
+            FontManifest::default(),
+            node_root.join("server/font-manifest.json".to_string()),
+        )
+        .await?;
+    output
+        .write_manifest(
+            ServerReferenceManifest::default(),
+            node_root.join("server/server-reference-manifest.json".to_string()),
+        )
+        .await?;
+    output
+        .write_manifest(
+            ReactLoadableManifest::default(),
+            node_root.join("react-loadable-manifest.json".to_string()),
+        )
+        .await?;
 
-#[turbo_tasks::function]
-async fn workspace_fs(
-    workspace_root: String,
-    issue_reporter: Vc<Box<dyn IssueReporter>>,
-) -> Result<Vc<Box<dyn FileSystem>>> {
-    let disk_fs = DiskFileSystem::new("workspace".to_string(), workspace_root.to_string());
     handle_issues(
-        disk_fs,
+        report_unemitted_assets(
+            Vc::cell(all_chunks.clone()),
+            node_root,
+            client_relative_path,
+        ),
         issue_reporter,
-        MIN_FAILING_SEVERITY.cell(),
+        min_failing_severity.cell(),
         None,
         None,
     )
     .await?;
-    Ok(Vc::upcast(disk_fs))
-}
 
-#[turbo_tasks::function]
-async fn node_fs(
-    node_root: String,
-    issue_reporter: Vc<Box<dyn IssueReporter>>,
-) -> Result<Vc<Box<dyn FileSystem>>> {
-    let disk_fs = DiskFileSystem::new("node".to_string(), node_root.to_string());
-    handle_issues(
-        disk_fs,
+    let (emit_completion, emitted_files) = emit_all_assets(
+        all_chunks
+            .into_iter()
+            .filter(|chunk| !route_chunks.is_unaffected(chunk))
+            .collect(),
+        &node_root_ref,
+        client_relative_path,
+        client_root,
+        options.hidden_source_maps,
+        write_retries,
+    )
+    .await?;
+    output.completions.push(emit_completion);
+    output.files.extend(emitted_files);
+
+    Completions::all(output.completions).await?;
+
+    // Written last, as it describes all other files.
+    if options.deploy_manifest {
+        let deploy_manifest =
+            compute_deploy_manifest(&output.files, node_root, client_root).await?;
+        let deploy_manifest = if compact_manifests {
+            serde_json::to_string(&deploy_manifest)?
+        } else {
+            serde_json::to_string_pretty(&deploy_manifest)?
+        };
+        write_atomic(
+            node_root.join("deploy-manifest.json".to_string()),
+            FileContent::Content(deploy_manifest.into()).cell(),
+            write_retries,
+        )
+        .await?;
+    }
+
+    build_timer.end_phase(BuildPhase::Emission);
+
+    // Not listed in the deploy manifest, as it isn't deployed.
+    if options.diagnostics {
+        write_atomic(
+            node_root.join("build-diagnostics.json".to_string()),
+            FileContent::Content(serde_json::to_string_pretty(&build_timer.finish())?.into())
+                .cell(),
+            write_retries,
+        )
+        .await?;
+    }
+
+    Ok(build_report.cell())
+}
+
+/// Variables defined by Next.js itself, e.g. `process.env.NODE_ENV`, which
+/// aren't configured by the user.
+fn is_next_defined_env(name: &str) -> bool {
+    name == "NODE_ENV" || name == "NEXT_RUNTIME" || name.starts_with("__NEXT_")
+}
+
+/// Lists the environment variables inlined into client bundles, or returns
+/// nothing when the client doesn't access any.
+fn client_env_manifest(
+    references: &ClientEnvReferences,
+    public_env_prefixes: &[String],
+) -> Option<ClientEnvManifest> {
+    if references.inlined.is_empty() && references.dynamic_access.is_empty() {
+        return None;
+    }
+    Some(ClientEnvManifest {
+        variables: references.inlined.clone(),
+        non_public_variables: references
+            .inlined
+            .keys()
+            .filter(|name| {
+                !public_env_prefixes
+                    .iter()
+                    .any(|prefix| name.starts_with(prefix.as_str()))
+                    && !is_next_defined_env(name)
+            })
+            .cloned()
+            .collect(),
+        dynamic_access: references.dynamic_access.clone(),
+    })
+}
+
+/// The compile-time replacements of client code: the client `define_env` and
+/// `defines`, `process.env.NODE_ENV`, the environment variables starting with
+/// one of `public_env_prefixes`, and the web vitals to attribute. No other
+/// environment variable is inlined.
+fn client_define_env(
+    options: &BuildOptions,
+    mode: NextMode,
+    env: &IndexMap<String, String>,
+    public_env_prefixes: &[String],
+    web_vitals_attribution: &[String],
+) -> Result<IndexMap<String, String>> {
+    let is_public_env = |name: &str| {
+        public_env_prefixes
+            .iter()
+            .any(|prefix| name.starts_with(prefix.as_str()))
+    };
+
+    let mut client_define_env: IndexMap<String, String> = options
+        .define_env
+        .client
+        .iter()
+        // Next.js defines the `NEXT_PUBLIC_` variables itself. With other prefixes, these
+        // are server-only.
+        .filter(|(key, _)| match key.strip_prefix("process.env.") {
+            Some(name) if name.starts_with("NEXT_PUBLIC_") => is_public_env(name),
+            _ => true,
+        })
+        .cloned()
+        .chain(options.defines.iter().cloned())
+        .collect();
+    client_define_env
+        .entry("process.env.NODE_ENV".to_string())
+        .or_insert(serde_json::to_string(mode.node_env())?);
+    for (name, value) in env {
+        if is_public_env(name) {
+            client_define_env
+                .entry(format!("process.env.{name}"))
+                .or_insert(serde_json::to_string(value)?);
+        }
+    }
+    if !web_vitals_attribution.is_empty() {
+        // The client runtime only includes the attribution code when this is
+        // defined.
+        client_define_env
+            .entry("process.env.__NEXT_WEB_VITALS_ATTRIBUTION".to_string())
+            .or_insert(serde_json::to_string(web_vitals_attribution)?);
+    }
+    Ok(client_define_env)
+}
+
+/// The settings read by the client runtime, or nothing when none of them are
+/// configured.
+async fn client_settings_manifest(
+    next_config: Vc<NextConfig>,
+) -> Result<Option<ClientSettingsManifest>> {
+    let manifest = ClientSettingsManifest {
+        web_vitals_attribution: next_config.web_vitals_attribution().await?.clone_value(),
+        client_trace_metadata: next_config.client_trace_metadata().await?.clone_value(),
+    };
+    Ok(
+        (!manifest.web_vitals_attribution.is_empty() || !manifest.client_trace_metadata.is_empty())
+            .then_some(manifest),
+    )
+}
+
+/// The settings read by the server runtime, or nothing when they're all left
+/// at their defaults, which are on.
+async fn server_settings_manifest(
+    next_config: Vc<NextConfig>,
+) -> Result<Option<ServerSettingsManifest>> {
+    let manifest = ServerSettingsManifest {
+        powered_by_header: *next_config.powered_by_header().await?,
+        compress: *next_config.compress().await?,
+        generate_etags: *next_config.generate_etags().await?,
+    };
+    Ok(
+        (!manifest.powered_by_header || !manifest.compress || !manifest.generate_etags)
+            .then_some(manifest),
+    )
+}
+
+/// The settings of the image optimizer, or nothing when images are served
+/// unoptimized.
+async fn images_manifest(next_config: Vc<NextConfig>) -> Result<Option<ImagesManifest>> {
+    let image_config = next_config.image_config().await?;
+    Ok((!image_config.unoptimized).then(|| ImagesManifest::new(image_config.clone_value())))
+}
+
+/// The build id used when the caller doesn't provide one: the one returned by
+/// `generateBuildId`, or the [default_build_id] when it returns `null`.
+async fn generated_build_id(
+    next_config: Vc<NextConfig>,
+    chunks: &[Vc<Box<dyn OutputAsset>>],
+    build_hash_seed: Option<&str>,
+) -> Result<String> {
+    match &*next_config.generate_build_id().await? {
+        Some(build_id) => Ok(build_id.clone()),
+        None => default_build_id(chunks, build_hash_seed).await,
+    }
+}
+
+/// Validates the [BuildOptions::data_path_prefix], and returns it without a
+/// trailing slash, or `None` for the default.
+fn data_path_prefix(data_path_prefix: Option<&str>) -> Result<Option<String>> {
+    let Some(data_path_prefix) = data_path_prefix else {
+        return Ok(None);
+    };
+    if !data_path_prefix.starts_with('/') {
+        bail!("the data path prefix `{data_path_prefix}` must start with a `/`");
+    }
+    let data_path_prefix = data_path_prefix.trim_end_matches('/');
+    if data_path_prefix.is_empty() {
+        bail!("the data path prefix can't be `/`, as the data routes would shadow the pages");
+    }
+    Ok((data_path_prefix != DEFAULT_DATA_PATH_PREFIX).then(|| data_path_prefix.to_string()))
+}
+
+/// The paths of the SSG and client build manifests of `build_id`, relative to
+/// the node root.
+fn build_id_manifest_paths(build_id: &str) -> (String, String) {
+    (
+        format!("static/{build_id}/_ssgManifest.js"),
+        format!("static/{build_id}/_buildManifest.js"),
+    )
+}
+
+/// Computes the build id used when neither the caller nor `generateBuildId`
+/// provide one. When `build_hash_seed` is set, it's derived from the seed and
+/// the paths and content of the `chunks`, as the default timestamp would
+/// differ between otherwise identical builds, while builds of other sources
+/// still get other ids.
+async fn default_build_id(
+    chunks: &[Vc<Box<dyn OutputAsset>>],
+    build_hash_seed: Option<&str>,
+) -> Result<String> {
+    let Some(seed) = build_hash_seed else {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("system time is before the UNIX epoch")?;
+        return Ok(format!("{:x}", timestamp.as_millis()));
+    };
+
+    let mut files = chunks
+        .iter()
+        .map(|chunk| async move {
+            let path = chunk.ident().path().await?.path.clone();
+            let hash = *chunk.content().file_content().hash().await?;
+            Ok((path, hash))
+        })
+        .try_join()
+        .await?;
+    files.sort();
+    files.dedup();
+    let files = files
+        .iter()
+        .map(|(path, hash)| format!("{path}:{hash:016x}\n"))
+        .collect::<String>();
+    Ok(format!(
+        "{:016x}",
+        seeded_hash(Some(seed), hash_xxh3_hash64(files.as_bytes()))
+    ))
+}
+
+#[turbo_tasks::function]
+async fn workspace_fs(
+    workspace_root: String,
+    issue_reporter: Vc<Box<dyn IssueReporter>>,
+) -> Result<Vc<Box<dyn FileSystem>>> {
+    let disk_fs = DiskFileSystem::new("workspace".to_string(), workspace_root.to_string());
+    handle_issues(
+        disk_fs,
+        issue_reporter,
+        MIN_FAILING_SEVERITY.cell(),
+        None,
+        None,
+    )
+    .await?;
+    Ok(Vc::upcast(disk_fs))
+}
+
+/// The directory the dist directory is written to. Only the dist directory is
+/// written to, so the sources may be read-only when it's elsewhere.
+fn output_root(options: &BuildOptions, project_root: &str) -> Result<String> {
+    let Some(output_root) = options.output_root.as_ref() else {
+        return Ok(project_root.to_string());
+    };
+    create_dir_all(output_root).context("output directory can't be created")?;
+    Ok(canonicalize(output_root)
+        .context("output directory can't be found")?
+        .to_str()
+        .context("output directory contains invalid characters")?
+        .to_string())
+}
+
+/// Fails when the dist directory is inside of `node_modules`, as dependencies
+/// may be installed read-only, e.g. in a sandboxed CI.
+fn validate_dist_dir(output_root: &str, dist_dir: &str) -> Result<()> {
+    if Path::new(output_root)
+        .join(dist_dir)
+        .components()
+        .any(|component| component.as_os_str() == "node_modules")
+    {
+        bail!(
+            "the dist directory {dist_dir} in {output_root} is inside of node_modules, which may \
+             be read-only. Set distDir or the output directory to a writable location outside of \
+             it."
+        );
+    }
+    Ok(())
+}
+
+/// Fails when `hash_length` is set without `content_addressed`, as only the
+/// content-addressed copies of client files are named by their hash, and it
+/// would be ignored otherwise.
+fn validate_hash_length(options: &BuildOptions) -> Result<()> {
+    if let (Some(hash_length), false) = (options.hash_length, options.content_addressed) {
+        bail!(
+            "hash_length {hash_length} only applies to content-addressed client files. Enable              content_addressed or remove hash_length."
+        );
+    }
+    Ok(())
+}
+
+#[turbo_tasks::function]
+async fn node_fs(
+    node_root: String,
+    issue_reporter: Vc<Box<dyn IssueReporter>>,
+) -> Result<Vc<Box<dyn FileSystem>>> {
+    let disk_fs = DiskFileSystem::new("node".to_string(), node_root.to_string());
+    handle_issues(
+        disk_fs,
         issue_reporter,
         MIN_FAILING_SEVERITY.cell(),
         None,
@@ -519,20 +1445,29 @@ async fn client_fs(
 
 /// Emits all assets transitively reachable from the given chunks, that are
 /// inside the node root or the client root.
+///
+/// With `hidden_source_maps`, client assets are written without their
+/// `sourceMappingURL` comment.
 async fn emit_all_assets(
     chunks: Vec<Vc<Box<dyn OutputAsset>>>,
     node_root: &FileSystemPath,
     client_relative_path: Vc<FileSystemPath>,
     client_output_path: Vc<FileSystemPath>,
-) -> Result<Vc<Completion>> {
+    hidden_source_maps: bool,
+    write_retries: u32,
+) -> Result<(Vc<Completion>, Vec<OutputFile>)> {
     let all_assets = all_assets_from_entries(Vc::cell(chunks)).await?;
-    Ok(Completions::all(
-        all_assets
-            .iter()
-            .copied()
-            .map(|asset| async move {
+    let emitted = all_assets
+        .iter()
+        .copied()
+        .map(|asset| async move {
+            let (completion, path, content) =
                 if asset.ident().path().await?.is_inside_ref(node_root) {
-                    return Ok(emit(asset));
+                    (
+                        emit(asset, write_retries),
+                        asset.ident().path(),
+                        asset.content(),
+                    )
                 } else if asset
                     .ident()
                     .path()
@@ -541,19 +1476,45 @@ async fn emit_all_assets(
                 {
                     // Client assets are emitted to the client output path, which is prefixed with
                     // _next. We need to rebase them to remove that prefix.
-                    return Ok(emit_rebase(asset, client_relative_path, client_output_path));
-                }
+                    (
+                        emit_rebase(
+                            asset,
+                            client_relative_path,
+                            client_output_path,
+                            hidden_source_maps,
+                            write_retries,
+                        ),
+                        rebase(
+                            asset.ident().path(),
+                            client_relative_path,
+                            client_output_path,
+                        ),
+                        client_asset_content(asset, hidden_source_maps),
+                    )
+                } else {
+                    return Ok((Completion::immutable(), None));
+                };
+
+            // Symlinks have no content of their own to list.
+            let file = match &*content.await? {
+                AssetContent::File(file) => Some((path, *file)),
+                AssetContent::Redirect { .. } => None,
+            };
+            Ok((completion, file))
+        })
+        .try_join()
+        .await?;
 
-                Ok(Completion::immutable())
-            })
-            .try_join()
-            .await?,
+    let (completions, files): (Vec<_>, Vec<_>) = emitted.into_iter().unzip();
+    Ok((
+        Completions::all(completions),
+        files.into_iter().flatten().collect(),
     ))
 }
 
 #[turbo_tasks::function]
-fn emit(asset: Vc<Box<dyn OutputAsset>>) -> Vc<Completion> {
-    asset.content().write(asset.ident().path())
+fn emit(asset: Vc<Box<dyn OutputAsset>>, write_retries: u32) -> Vc<Completion> {
+    write_asset_content(asset.content(), asset.ident().path(), write_retries)
 }
 
 #[turbo_tasks::function]
@@ -561,16 +1522,83 @@ fn emit_rebase(
     asset: Vc<Box<dyn OutputAsset>>,
     from: Vc<FileSystemPath>,
     to: Vc<FileSystemPath>,
+    hidden_source_maps: bool,
+    write_retries: u32,
 ) -> Vc<Completion> {
-    asset
-        .content()
-        .write(rebase(asset.ident().path(), from, to))
+    write_asset_content(
+        client_asset_content(asset, hidden_source_maps),
+        rebase(asset.ident().path(), from, to),
+        write_retries,
+    )
+}
+
+/// The content of a client asset, without the `sourceMappingURL` comment of
+/// JavaScript and CSS files when `hidden_source_maps` is set.
+#[turbo_tasks::function]
+async fn client_asset_content(
+    asset: Vc<Box<dyn OutputAsset>>,
+    hidden_source_maps: bool,
+) -> Result<Vc<AssetContent>> {
+    let content = asset.content();
+    if !hidden_source_maps
+        || !matches!(
+            asset.ident().path().await?.extension_ref(),
+            Some("js" | "mjs" | "css")
+        )
+    {
+        return Ok(content);
+    }
+    let AssetContent::File(file) = &*content.await? else {
+        return Ok(content);
+    };
+    let FileContent::Content(file) = &*file.await? else {
+        return Ok(content);
+    };
+    let Some(stripped) = strip_source_mapping_url(&file.content().to_str()?) else {
+        return Ok(content);
+    };
+    Ok(AssetContent::file(
+        FileContent::Content(File::from(stripped)).cell(),
+    ))
+}
+
+/// Removes the `//# sourceMappingURL=` and `/*# sourceMappingURL= */` lines
+/// from `code`, or returns `None` when there are none.
+fn strip_source_mapping_url(code: &str) -> Option<String> {
+    let is_source_mapping_url = |line: &str| {
+        let line = line.trim_start();
+        line.starts_with("//# sourceMappingURL=") || line.starts_with("/*# sourceMappingURL=")
+    };
+    if !code.lines().any(is_source_mapping_url) {
+        return None;
+    }
+    let mut stripped = String::with_capacity(code.len());
+    for line in code.split_inclusive('\n') {
+        if !is_source_mapping_url(line) {
+            stripped.push_str(line);
+        }
+    }
+    Some(stripped)
+}
+
+/// Writes files with [write_retrying], so that transient write failures are
+/// retried. Symlinks are written directly.
+#[turbo_tasks::function]
+async fn write_asset_content(
+    content: Vc<AssetContent>,
+    path: Vc<FileSystemPath>,
+    write_retries: u32,
+) -> Result<Vc<Completion>> {
+    Ok(match &*content.await? {
+        AssetContent::File(file) => write_retrying(path, *file, write_retries),
+        AssetContent::Redirect { .. } => content.write(path),
+    })
 }
 
 /// Walks the asset graph from multiple assets and collect all referenced
 /// assets.
 #[turbo_tasks::function]
-async fn all_assets_from_entries(entries: Vc<OutputAssets>) -> Result<Vc<OutputAssets>> {
+pub(crate) async fn all_assets_from_entries(entries: Vc<OutputAssets>) -> Result<Vc<OutputAssets>> {
     Ok(Vc::cell(
         AdjacencyMap::new()
             .skip_duplicates()
@@ -599,12 +1627,1404 @@ async fn get_referenced_assets(
         .into_iter())
 }
 
-/// Writes a manifest to disk. This consumes the manifest to ensure we don't
-/// write to it afterwards.
-fn write_manifest<T>(manifest: T, manifest_path: Vc<FileSystemPath>) -> Result<Vc<Completion>>
+/// A file written by the build, listed in the deploy manifest.
+type OutputFile = (Vc<FileSystemPath>, Vc<FileContent>);
+
+/// The directory of static files to serve, `public` unless configured
+/// otherwise.
+fn public_dir(project_root: Vc<FileSystemPath>, public_dir: Option<&str>) -> Vc<FileSystemPath> {
+    project_root.join(
+        public_dir
+            .unwrap_or("public")
+            .trim_start_matches("./")
+            .to_string(),
+    )
+}
+
+/// Copies the files of `public_dir` and its subdirectories to `output_dir`.
+/// Nothing is copied if `public_dir` doesn't exist.
+async fn copy_public_dir(
+    output: &mut OutputWriter<'_>,
+    public_dir: Vc<FileSystemPath>,
+    output_dir: Vc<FileSystemPath>,
+) -> Result<()> {
+    let mut queue = vec![(public_dir, output_dir)];
+    while let Some((dir, output_dir)) = queue.pop() {
+        let DirectoryContent::Entries(entries) = &*dir.read_dir().await? else {
+            continue;
+        };
+        for (name, entry) in entries.iter() {
+            match *entry {
+                DirectoryEntry::File(file) => {
+                    output.write_file(output_dir.join(name.clone()), file.read())
+                }
+                DirectoryEntry::Directory(subdir) => {
+                    queue.push((subdir, output_dir.join(name.clone())))
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes the files of the build, and records them for the deploy manifest.
+struct OutputWriter<'a> {
+    files: Vec<OutputFile>,
+    completions: Vec<Vc<Completion>>,
+    write_retries: u32,
+    compact_manifests: bool,
+    manifest_transform: Option<&'a ManifestTransform>,
+}
+
+impl OutputWriter<'_> {
+    /// Writes a file with [write_retrying].
+    fn write_file(&mut self, path: Vc<FileSystemPath>, content: Vc<FileContent>) {
+        self.files.push((path, content));
+        self.completions
+            .push(write_retrying(path, content, self.write_retries));
+    }
+
+    /// Writes a manifest to disk. This consumes the manifest to ensure we
+    /// don't write to it afterwards.
+    ///
+    /// The manifest is serialized into chunks of [MANIFEST_CHUNK_SIZE] bytes,
+    /// so that manifests of projects with many routes don't need one large
+    /// allocation. The write is atomic, so that the Next.js server polling
+    /// manifests never observes a partially-written file.
+    ///
+    /// Unless `compact_manifests`, the JSON is pretty-printed, e.g. for
+    /// reading the manifests while debugging.
+    async fn write_manifest<T>(
+        &mut self,
+        manifest: T,
+        manifest_path: Vc<FileSystemPath>,
+    ) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let compact = self.compact_manifests;
+        let file = if let Some(manifest_transform) = self.manifest_transform {
+            let json = if compact {
+                serde_json::to_string(&manifest)?
+            } else {
+                serde_json::to_string_pretty(&manifest)?
+            };
+            let name = manifest_path.await?.file_name().to_string();
+            File::from(manifest_transform.transform(&name, json).await?)
+        } else {
+            let mut writer = ChunkedRopeWriter::default();
+            if compact {
+                serde_json::to_writer(&mut writer, &manifest)?;
+            } else {
+                serde_json::to_writer_pretty(&mut writer, &manifest)?;
+            }
+            File::from(writer.rope.build())
+        };
+        let content = FileContent::Content(file).cell();
+        self.files.push((manifest_path, content));
+        self.completions
+            .push(write_atomic(manifest_path, content, self.write_retries));
+        Ok(())
+    }
+}
+
+/// Reads the routes manifest `next build` writes before the Turbopack build,
+/// or returns `None` when the build runs on its own.
+///
+/// The file is read from disk directly, as it's rewritten by this build and
+/// must not be tracked as an input of it.
+/// Fails when the `build-manifest.json` of a prior build at `path` records
+/// another client runtime than `client_runtime`. Builds predating the record
+/// used the production runtime.
+async fn check_prior_client_runtime(
+    path: Vc<FileSystemPath>,
+    client_runtime: ClientRuntime,
+) -> Result<()> {
+    let Some(prior_build_manifest) = read_manifest::<serde_json::Value>(path).await? else {
+        return Ok(());
+    };
+    let prior_client_runtime = prior_build_manifest
+        .get("clientRuntime")
+        .and_then(|client_runtime| client_runtime.as_str())
+        .unwrap_or(ClientRuntime::Production.name());
+    if prior_client_runtime != client_runtime.name() {
+        bail!(
+            "the {} client runtime can't be mixed with the {prior_client_runtime} one of the \
+             routes kept from the prior build, build without `changed_files` to rebuild every \
+             route",
+            client_runtime.name()
+        );
+    }
+    Ok(())
+}
+
+async fn read_routes_manifest(path: Vc<FileSystemPath>) -> Result<Option<RoutesManifest>> {
+    read_manifest(path).await
+}
+
+/// Reads the manifest of a prior build at `path`, if there is one.
+async fn read_manifest<T: DeserializeOwned>(path: Vc<FileSystemPath>) -> Result<Option<T>> {
+    let Some(disk_fs) = Vc::try_resolve_downcast_type::<DiskFileSystem>(path.fs()).await? else {
+        return Ok(None);
+    };
+    let sys_path = disk_fs.await?.to_sys_path(path).await?;
+    match tokio::fs::read(&sys_path).await {
+        Ok(content) => serde_json::from_slice(&content)
+            .map(Some)
+            .with_context(|| format!("failed to parse {}", sys_path.display())),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err).with_context(|| format!("failed to read {}", sys_path.display())),
+    }
+}
+
+const MANIFEST_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Collects the written bytes into a rope, starting a new chunk every
+/// [MANIFEST_CHUNK_SIZE] bytes.
+#[derive(Default)]
+struct ChunkedRopeWriter {
+    rope: RopeBuilder,
+    uncommitted_len: usize,
+}
+
+impl Write for ChunkedRopeWriter {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.rope.write_all(bytes)?;
+        self.uncommitted_len += bytes.len();
+        if self.uncommitted_len >= MANIFEST_CHUNK_SIZE {
+            // Flushing commits the pending bytes as a chunk of the rope.
+            self.rope.flush()?;
+            self.uncommitted_len = 0;
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.rope.flush()
+    }
+}
+
+/// Lists the written files with their content hash and size. Paths are
+/// relative to the dist directory, which both the node root and the client
+/// root point to.
+async fn compute_deploy_manifest(
+    output_files: &[OutputFile],
+    node_root: Vc<FileSystemPath>,
+    client_root: Vc<FileSystemPath>,
+) -> Result<DeployManifest> {
+    let node_root = node_root.await?;
+    let client_root = client_root.await?;
+
+    let mut files = output_files
+        .iter()
+        .map(|&(path, content)| {
+            let node_root = &node_root;
+            let client_root = &client_root;
+            async move {
+                let FileContent::Content(file) = &*content.await? else {
+                    return Ok(None);
+                };
+                let path = path.await?;
+                let Some(relative_path) = node_root
+                    .get_path_to(&path)
+                    .or_else(|| client_root.get_path_to(&path))
+                else {
+                    return Ok(None);
+                };
+                Ok(Some(DeployManifestFile {
+                    path: relative_path.to_string(),
+                    hash: format!("{:x}", *content.hash().await?),
+                    size: file.content().len(),
+                }))
+            }
+        })
+        .try_join()
+        .await?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files.dedup_by(|a, b| a.path == b.path);
+    Ok(DeployManifest { files })
+}
+
+/// Returns whether a failed write may succeed when retried, e.g. because the
+/// file was temporarily locked by a virus scanner or file indexer.
+///
+/// Running out of disk space or lacking permissions won't resolve itself, so
+/// those errors are never retried.
+fn is_transient_write_error(err: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+
+    if matches!(
+        err.kind(),
+        ErrorKind::Interrupted | ErrorKind::WouldBlock | ErrorKind::TimedOut
+    ) {
+        return true;
+    }
+
+    match err.raw_os_error() {
+        // EBUSY, ETXTBSY
+        #[cfg(unix)]
+        Some(16 | 26) => true,
+        // ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION
+        #[cfg(windows)]
+        Some(32 | 33) => true,
+        _ => false,
+    }
+}
+
+/// The most times a failed write is retried, whatever
+/// [BuildOptions::write_retries] is set to.
+const MAX_WRITE_RETRIES: u32 = 10;
+
+/// How long to wait before retrying a write for the `attempt`th time. The
+/// delay doubles with each attempt from 50ms and stops growing after 10
+/// attempts, at about 51s.
+fn write_retry_backoff(attempt: u32) -> Duration {
+    Duration::from_millis(50u64.saturating_mul(1 << attempt.min(10)))
+}
+
+/// Runs the filesystem operation, retrying it up to `retries` times with an
+/// exponential backoff while it fails with a transient error.
+async fn retry_transient<F, Fut>(retries: u32, mut operation: F) -> std::io::Result<()>
 where
-    T: Serialize,
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = std::io::Result<()>>,
 {
-    let manifest_contents = serde_json::to_string_pretty(&manifest)?;
-    Ok(manifest_path.write(FileContent::Content(manifest_contents.into()).cell()))
+    let mut attempt = 0;
+    loop {
+        match operation().await {
+            Err(err) if attempt < retries && is_transient_write_error(&err) => {
+                tokio::time::sleep(write_retry_backoff(attempt)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Returns whether the error of a write through a [FileSystem] is a
+/// [transient](is_transient_write_error) IO error.
+fn is_transient_fs_error(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|err| err.downcast_ref::<io::Error>())
+        .any(is_transient_write_error)
+}
+
+/// Writes a file of the build output through its filesystem.
+///
+/// The result of the failed write is cached, so it can't be retried as is.
+/// When it fails with a transient error, the file is written again with
+/// [write_atomic] instead, which retries with a new temporary file each time.
+#[turbo_tasks::function]
+async fn write_retrying(
+    path: Vc<FileSystemPath>,
+    content: Vc<FileContent>,
+    write_retries: u32,
+) -> Result<Vc<Completion>> {
+    match path.write(content).await {
+        Ok(_) => Ok(Completion::new()),
+        Err(err) if write_retries > 0 && is_transient_fs_error(&err) => {
+            tokio::time::sleep(write_retry_backoff(0)).await;
+            Ok(write_atomic(path, content, write_retries - 1))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Distinguishes the temporary files of concurrent [write_atomic]s.
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Writes the content to a temporary file next to `path` and then renames it
+/// into place. The temporary file lives in the same directory as `path`, which
+/// keeps the rename on the same filesystem and therefore atomic. Its name is
+/// unique, so concurrent writes to the same file don't write to the same
+/// temporary file, and readers see either the old or the new file. This is
+/// used for manifests, which the Next.js server polls.
+///
+/// The temporary file is written through the [DiskFileSystem]. As a failed
+/// write can't be retried through it, each of the up to `write_retries`
+/// retries of a transient failure writes a new temporary file. The rename is
+/// the only step done with `tokio::fs`, as [DiskFileSystem] can't rename
+/// files, and is retried the same way. The temporary file is removed if either
+/// step fails.
+///
+/// Filesystems that aren't backed by a disk are written to directly.
+#[turbo_tasks::function]
+async fn write_atomic(
+    path: Vc<FileSystemPath>,
+    content: Vc<FileContent>,
+    write_retries: u32,
+) -> Result<Vc<Completion>> {
+    let Some(disk_fs) = Vc::try_resolve_downcast_type::<DiskFileSystem>(path.fs()).await? else {
+        return Ok(path.write(content));
+    };
+    if !matches!(&*content.await?, FileContent::Content(_)) {
+        return Ok(path.write(content));
+    }
+
+    let file_name = path.await?.file_name().to_string();
+    let mut attempt = 0;
+    let temp_path = loop {
+        let temp_path = path.parent().join(format!(
+            ".{file_name}.{}-{}.tmp",
+            std::process::id(),
+            TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        match temp_path.write(content).await {
+            Ok(_) => break temp_path,
+            Err(err) => {
+                let _ = temp_path.write(FileContent::NotFound.cell()).await;
+                if attempt < write_retries && is_transient_fs_error(&err) {
+                    tokio::time::sleep(write_retry_backoff(attempt)).await;
+                    attempt += 1;
+                    continue;
+                }
+                return Err(err.context(format!(
+                    "failed to write {} after {} attempts",
+                    temp_path.await?.path,
+                    attempt + 1
+                )));
+            }
+        }
+    };
+
+    let disk_fs = disk_fs.await?;
+    let temp_sys_path = disk_fs.to_sys_path(temp_path).await?;
+    let sys_path = disk_fs.to_sys_path(path).await?;
+    let renamed = retry_transient(write_retries, || async {
+        match tokio::fs::rename(&temp_sys_path, &sys_path).await {
+            // A previous attempt moved the temporary file into place, but
+            // reported an error.
+            Err(err) if err.kind() == io::ErrorKind::NotFound && sys_path.exists() => Ok(()),
+            result => result,
+        }
+    })
+    .await;
+    if renamed.is_err() {
+        let _ = temp_path.write(FileContent::NotFound.cell()).await;
+    }
+    renamed.with_context(|| {
+        format!(
+            "failed to move {} to {} after {} attempts",
+            temp_sys_path.display(),
+            sys_path.display(),
+            write_retries + 1
+        )
+    })?;
+
+    Ok(Completion::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use next_core::next_config::Rewrites;
+
+    use super::*;
+    use crate::test_utils::{
+        client_chunk_group, client_chunk_group_with_runtime, fixture, fixture_root, run,
+    };
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn retries_transient_write_errors_only() {
+        use std::sync::atomic::AtomicU32;
+
+        // EBUSY, then a successful write.
+        let attempts = &AtomicU32::new(0);
+        retry_transient(3, move || async move {
+            if attempts.fetch_add(1, Ordering::Relaxed) == 0 {
+                Err(std::io::Error::from_raw_os_error(16))
+            } else {
+                Ok(())
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(attempts.load(Ordering::Relaxed), 2);
+
+        // ENOSPC isn't retried.
+        let attempts = &AtomicU32::new(0);
+        let err = retry_transient(3, move || async move {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err(std::io::Error::from_raw_os_error(28))
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(28));
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn caps_the_write_retry_backoff() {
+        assert_eq!(write_retry_backoff(0), Duration::from_millis(50));
+        assert_eq!(write_retry_backoff(3), Duration::from_millis(400));
+        assert_eq!(write_retry_backoff(10), write_retry_backoff(u32::MAX));
+    }
+
+    #[tokio::test]
+    async fn writes_to_the_output_root_only() {
+        let source = fixture(&[("public/robots.txt", "User-agent: *")]);
+        let output_dir = tempfile::tempdir().unwrap();
+        let source_path = source.path().to_path_buf();
+        let options = BuildOptions {
+            output_root: Some(output_dir.path().join("out")),
+            ..Default::default()
+        };
+
+        #[cfg(unix)]
+        let set_readonly = |readonly: bool| {
+            use std::os::unix::fs::PermissionsExt;
+            for dir in [source.path().to_path_buf(), source.path().join("public")] {
+                let mode = if readonly { 0o555 } else { 0o755 };
+                std::fs::set_permissions(dir, std::fs::Permissions::from_mode(mode)).unwrap();
+            }
+        };
+        #[cfg(unix)]
+        set_readonly(true);
+
+        let result = run(async move {
+            let project_root = fixture_root(&source_path);
+            let output_root = output_root(&options, source_path.to_str().unwrap())?;
+            let mut output = OutputWriter {
+                files: vec![],
+                completions: vec![],
+                write_retries: 0,
+                compact_manifests: false,
+                manifest_transform: None,
+            };
+            copy_public_dir(
+                &mut output,
+                public_dir(project_root, None),
+                fixture_root(Path::new(&output_root)).join(".next/public".to_string()),
+            )
+            .await?;
+            Completions::all(output.completions).await?;
+            Ok(())
+        })
+        .await;
+
+        #[cfg(unix)]
+        set_readonly(false);
+        result.unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(output_dir.path().join("out/.next/public/robots.txt")).unwrap(),
+            "User-agent: *"
+        );
+        let mut source_entries: Vec<_> = std::fs::read_dir(source.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        source_entries.sort();
+        assert_eq!(source_entries, ["public"]);
+    }
+
+    #[tokio::test]
+    async fn writes_outside_of_a_read_only_node_modules() {
+        let dir = fixture(&[
+            ("public/robots.txt", "User-agent: *"),
+            ("node_modules/dep/package.json", r#"{"name": "dep"}"#),
+        ]);
+        let root = dir.path().to_str().unwrap().to_string();
+
+        assert!(validate_dist_dir(&root, "node_modules/.cache/next").is_err());
+        assert!(validate_dist_dir(&format!("{root}/node_modules/dep"), ".next").is_err());
+        validate_dist_dir(&root, ".next").unwrap();
+
+        #[cfg(unix)]
+        let set_readonly = |readonly: bool| {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = if readonly { 0o555 } else { 0o755 };
+            for dir in ["node_modules", "node_modules/dep"] {
+                std::fs::set_permissions(
+                    Path::new(&root).join(dir),
+                    std::fs::Permissions::from_mode(mode),
+                )
+                .unwrap();
+            }
+        };
+        #[cfg(unix)]
+        set_readonly(true);
+
+        let dir_path = dir.path().to_path_buf();
+        let result = run(async move {
+            let project_root = fixture_root(&dir_path);
+            let mut output = OutputWriter {
+                files: vec![],
+                completions: vec![],
+                write_retries: 0,
+                compact_manifests: false,
+                manifest_transform: None,
+            };
+            copy_public_dir(
+                &mut output,
+                public_dir(project_root, None),
+                project_root.join(".next/public".to_string()),
+            )
+            .await?;
+            output
+                .write_manifest(
+                    BuildManifest::default(),
+                    project_root.join(".next/build-manifest.json".to_string()),
+                )
+                .await?;
+            Completions::all(output.completions).await?;
+            Ok(())
+        })
+        .await;
+
+        #[cfg(unix)]
+        set_readonly(false);
+        result.unwrap();
+
+        assert!(dir.path().join(".next/public/robots.txt").exists());
+        assert!(dir.path().join(".next/build-manifest.json").exists());
+        let node_modules: Vec<_> = std::fs::read_dir(dir.path().join("node_modules"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(node_modules, ["dep"]);
+    }
+
+    #[tokio::test]
+    async fn copies_a_custom_public_dir_to_public() {
+        let dir = fixture(&[
+            ("static/robots.txt", "User-agent: *"),
+            ("static/img/logo.svg", "<svg />"),
+            ("public/ignored.txt", ""),
+        ]);
+        let dir_path = dir.path().to_path_buf();
+        let copied = run(async move {
+            let project_root = fixture_root(&dir_path);
+            let mut output = OutputWriter {
+                files: vec![],
+                completions: vec![],
+                write_retries: 0,
+                compact_manifests: false,
+                manifest_transform: None,
+            };
+            copy_public_dir(
+                &mut output,
+                public_dir(project_root, Some("./static")),
+                project_root.join(".next/public".to_string()),
+            )
+            .await?;
+            Completions::all(output.completions).await?;
+            Ok(output.files.len())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(copied, 2);
+        let output_dir = dir.path().join(".next/public");
+        assert_eq!(
+            std::fs::read_to_string(output_dir.join("robots.txt")).unwrap(),
+            "User-agent: *"
+        );
+        assert_eq!(
+            std::fs::read_to_string(output_dir.join("img/logo.svg")).unwrap(),
+            "<svg />"
+        );
+        assert!(!output_dir.join("ignored.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn readers_see_whole_files_during_concurrent_writes() {
+        let dir = fixture(&[]);
+        let dir_path = dir.path().to_path_buf();
+        let sys_path = dir.path().join("manifest.json");
+        let contents: Vec<_> = ('a'..='f')
+            .map(|letter| letter.to_string().repeat(1 << 20))
+            .collect();
+
+        // Reads the file until the writes are done. Each read must see one of
+        // the contents in full.
+        let done = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let reader = std::thread::spawn({
+            let done = done.clone();
+            let sys_path = sys_path.clone();
+            let contents = contents.clone();
+            move || {
+                while !done.load(Ordering::Relaxed) {
+                    match std::fs::read_to_string(&sys_path) {
+                        Ok(read) => {
+                            assert!(contents.contains(&read), "read a partially written file")
+                        }
+                        Err(err) => assert_eq!(err.kind(), io::ErrorKind::NotFound),
+                    }
+                }
+            }
+        });
+
+        run({
+            let contents = contents.clone();
+            async move {
+                let path = fixture_root(&dir_path).join("manifest.json".to_string());
+                // The writes run concurrently.
+                Completions::all(
+                    contents
+                        .iter()
+                        .map(|content| {
+                            write_atomic(
+                                path,
+                                FileContent::Content(content.clone().into()).cell(),
+                                0,
+                            )
+                        })
+                        .collect(),
+                )
+                .await?;
+                Ok(())
+            }
+        })
+        .await
+        .unwrap();
+        done.store(true, Ordering::Relaxed);
+        reader.join().unwrap();
+
+        assert!(contents.contains(&std::fs::read_to_string(&sys_path).unwrap()));
+        // Each write renamed its own temporary file into place.
+        let files: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(files, ["manifest.json"]);
+    }
+
+    #[test]
+    fn rejects_a_hash_length_without_content_addressing() {
+        let err = validate_hash_length(&BuildOptions {
+            hash_length: Some(8),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("content_addressed"), "{err}");
+
+        validate_hash_length(&BuildOptions {
+            hash_length: Some(8),
+            content_addressed: true,
+            ..Default::default()
+        })
+        .unwrap();
+        validate_hash_length(&BuildOptions::default()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn reads_the_routes_manifest_of_next_build_only() {
+        let dir = fixture(&[(
+            ".next/routes-manifest.json",
+            r#"{"version": 3, "pages404": true}"#,
+        )]);
+        let dir_path = dir.path().to_path_buf();
+        let (written, missing) = run(async move {
+            let root = fixture_root(&dir_path);
+            Ok((
+                read_routes_manifest(root.join(".next/routes-manifest.json".to_string())).await?,
+                read_routes_manifest(root.join("out/routes-manifest.json".to_string())).await?,
+            ))
+        })
+        .await
+        .unwrap();
+
+        let written = written.unwrap();
+        assert_eq!(written.version, 3);
+        assert_eq!(written.rest["pages404"], true);
+        assert!(missing.is_none());
+    }
+
+    #[tokio::test]
+    async fn lists_every_written_file_in_the_deploy_manifest() {
+        let dir = fixture(&[("index.js", "console.log('index');")]);
+        let dir_path = dir.path().to_path_buf();
+        let deploy_manifest = run(async move {
+            let project_root = fixture_root(&dir_path);
+            let node_root = project_root.join(".next".to_string());
+            let chunks = client_chunk_group(
+                project_root,
+                "index.js",
+                get_client_compile_time_info("defaults".to_string(), Vc::cell(Default::default())),
+            )
+            .await?
+            .await?
+            .clone_value();
+
+            let mut output = OutputWriter {
+                files: vec![],
+                completions: vec![],
+                write_retries: 0,
+                compact_manifests: false,
+                manifest_transform: None,
+            };
+            // Like the manifests of the `build_context` branch.
+            let (ssg_manifest_path, _) = build_id_manifest_paths("build-id");
+            output.write_file(
+                node_root.join(ssg_manifest_path),
+                FileContent::Content("self.__SSG_MANIFEST=new Set".into()).cell(),
+            );
+            output
+                .write_manifest(
+                    PagesManifest::default(),
+                    node_root.join("server/pages-manifest.json".to_string()),
+                )
+                .await?;
+            let (emit_completion, emitted_files) = emit_all_assets(
+                chunks,
+                &*node_root.await?,
+                node_root.join("static".to_string()),
+                node_root.join("static".to_string()),
+                false,
+                0,
+            )
+            .await?;
+            output.completions.push(emit_completion);
+            output.files.extend(emitted_files);
+            Completions::all(output.completions).await?;
+
+            compute_deploy_manifest(&output.files, node_root, node_root).await
+        })
+        .await
+        .unwrap();
+
+        fn written_files(root: &Path, dir: &Path, files: &mut Vec<(String, usize)>) {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    written_files(root, &path, files);
+                } else {
+                    let relative = path.strip_prefix(root).unwrap().to_str().unwrap();
+                    let size = std::fs::metadata(&path).unwrap().len() as usize;
+                    files.push((relative.replace('\\', "/"), size));
+                }
+            }
+        }
+        let node_root = dir.path().join(".next");
+        let mut written = vec![];
+        written_files(&node_root, &node_root, &mut written);
+        written.sort();
+
+        let listed: Vec<_> = deploy_manifest
+            .files
+            .iter()
+            .map(|file| (file.path.clone(), file.size))
+            .collect();
+        assert_eq!(listed, written);
+        assert!(listed
+            .iter()
+            .any(|(path, _)| path == "static/build-id/_ssgManifest.js"));
+        assert!(listed
+            .iter()
+            .any(|(path, _)| path.ends_with(".js") && path.contains("chunks")));
+        assert!(deploy_manifest
+            .files
+            .iter()
+            .all(|file| !file.hash.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn writes_hidden_source_maps_without_referencing_them() {
+        let dir = fixture(&[("index.js", "console.log('index');")]);
+        let dir_path = dir.path().to_path_buf();
+        run(async move {
+            let project_root = fixture_root(&dir_path);
+            let chunks = client_chunk_group(
+                project_root,
+                "index.js",
+                get_client_compile_time_info("defaults".to_string(), Vc::cell(Default::default())),
+            )
+            .await?
+            .await?
+            .clone_value();
+
+            for (hidden_source_maps, client_output) in [(true, "hidden"), (false, "visible")] {
+                let (completion, _) = emit_all_assets(
+                    chunks.clone(),
+                    &*project_root.join(".next/server".to_string()).await?,
+                    project_root.join(".next".to_string()),
+                    project_root.join(client_output.to_string()),
+                    hidden_source_maps,
+                    0,
+                )
+                .await?;
+                completion.await?;
+            }
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        let chunks_dir = |client_output: &str| dir.path().join(client_output).join("static/chunks");
+        let mut referenced_source_maps = 0;
+        for entry in std::fs::read_dir(chunks_dir("visible")).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().map_or(true, |extension| extension != "js") {
+                continue;
+            }
+            let visible = std::fs::read_to_string(&path).unwrap();
+            if !visible.contains("sourceMappingURL") {
+                continue;
+            }
+            referenced_source_maps += 1;
+
+            let chunk = path.file_name().unwrap();
+            let hidden = std::fs::read_to_string(chunks_dir("hidden").join(chunk)).unwrap();
+            assert!(!hidden.contains("sourceMappingURL"), "{chunk:?}: {hidden}");
+            // The source map is still written.
+            let source_map = Path::new(chunk).with_extension("js.map");
+            assert!(chunks_dir("hidden").join(source_map).exists());
+        }
+        assert!(referenced_source_maps > 0);
+    }
+
+    #[tokio::test]
+    async fn names_the_manifest_when_the_transform_throws() {
+        let manifest_transform = ManifestTransform::new(|_name, _json| async {
+            bail!("the manifest transform threw: Error: boom")
+        });
+
+        let dir = fixture(&[]);
+        let dir_path = dir.path().to_path_buf();
+        let err = run(async move {
+            let mut output = OutputWriter {
+                files: vec![],
+                completions: vec![],
+                write_retries: 0,
+                compact_manifests: false,
+                manifest_transform: Some(&manifest_transform),
+            };
+            output
+                .write_manifest(
+                    BuildManifest::default(),
+                    fixture_root(&dir_path).join("build-manifest.json".to_string()),
+                )
+                .await?;
+            Completions::all(output.completions).await?;
+            Ok(())
+        })
+        .await
+        .unwrap_err();
+
+        assert!(
+            format!("{err:#}").contains("failed to transform build-manifest.json"),
+            "{err:#}"
+        );
+        assert!(format!("{err:#}").contains("boom"), "{err:#}");
+        assert!(!dir.path().join("build-manifest.json").exists());
+    }
+
+    #[tokio::test]
+    async fn writes_compact_manifests_without_whitespace() {
+        let manifest = BuildManifest {
+            root_main_files: vec!["static/chunks/main.js".to_string()],
+            pages: [(
+                "/about".to_string(),
+                vec!["static/chunks/pages/about.js".to_string()],
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        let expected = serde_json::to_value(&manifest).unwrap();
+
+        let dir = fixture(&[]);
+        let dir_path = dir.path().to_path_buf();
+        run(async move {
+            let mut output = OutputWriter {
+                files: vec![],
+                completions: vec![],
+                write_retries: 0,
+                compact_manifests: true,
+                manifest_transform: None,
+            };
+            output
+                .write_manifest(
+                    manifest,
+                    fixture_root(&dir_path).join("build-manifest.json".to_string()),
+                )
+                .await?;
+            Completions::all(output.completions).await?;
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        let json = std::fs::read_to_string(dir.path().join("build-manifest.json")).unwrap();
+        assert!(!json.contains(char::is_whitespace), "{json}");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn writes_huge_manifests_in_chunks() {
+        // A pages manifest of a project with many routes, of a few megabytes.
+        let manifest: std::collections::BTreeMap<String, String> = (0..50_000)
+            .map(|i| (format!("/blog/post-{i}"), format!("pages/blog/post-{i}.js")))
+            .collect();
+        let expected = serde_json::to_string_pretty(&manifest).unwrap();
+
+        let dir = fixture(&[]);
+        let dir_path = dir.path().to_path_buf();
+        let chunk_lens = run(async move {
+            let mut output = OutputWriter {
+                files: vec![],
+                completions: vec![],
+                write_retries: 0,
+                compact_manifests: false,
+                manifest_transform: None,
+            };
+            output
+                .write_manifest(
+                    manifest,
+                    fixture_root(&dir_path).join("pages-manifest.json".to_string()),
+                )
+                .await?;
+            let (_, content) = output.files[0];
+            let FileContent::Content(file) = &*content.await? else {
+                anyhow::bail!("the manifest has no content");
+            };
+            let chunk_lens: Vec<_> = file.content().read().map(|chunk| chunk.len()).collect();
+            Completions::all(output.completions).await?;
+            Ok(chunk_lens)
+        })
+        .await
+        .unwrap();
+
+        // The manifest was never serialized into a single buffer.
+        assert!(chunk_lens.len() > 1);
+        assert!(chunk_lens.iter().all(|&len| len <= 2 * MANIFEST_CHUNK_SIZE));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("pages-manifest.json")).unwrap(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn fills_settings_manifests_from_next_config() {
+        let (client, server, images) = run(async {
+            let mut config = NextConfig::default();
+            config.experimental.client_trace_metadata = Some(vec!["traceparent".to_string()]);
+            config.compress = Some(false);
+            config.images.device_sizes = vec![640, 1080];
+            config.images.image_sizes = vec![16];
+            let next_config = config.cell();
+            Ok((
+                serde_json::to_value(client_settings_manifest(next_config).await?)?,
+                serde_json::to_value(server_settings_manifest(next_config).await?)?,
+                serde_json::to_value(images_manifest(next_config).await?)?,
+            ))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            client,
+            serde_json::json!({
+                "webVitalsAttribution": [],
+                "clientTraceMetadata": ["traceparent"],
+            })
+        );
+        assert_eq!(
+            server,
+            serde_json::json!({
+                "poweredByHeader": true,
+                "compress": false,
+                "generateEtags": true,
+            })
+        );
+        assert_eq!(images["version"], 1);
+        assert_eq!(
+            images["images"]["sizes"],
+            serde_json::json!([640, 1080, 16])
+        );
+        assert_eq!(images["images"]["path"], "/_next/image");
+    }
+
+    #[tokio::test]
+    async fn records_web_vitals_attribution_in_the_client_settings() {
+        let client = run(async {
+            let mut config = NextConfig::default();
+            config.experimental.web_vitals_attribution =
+                Some(vec!["CLS".to_string(), "LCP".to_string()]);
+            Ok(serde_json::to_value(
+                client_settings_manifest(config.cell()).await?,
+            )?)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            client,
+            serde_json::json!({
+                "webVitalsAttribution": ["CLS", "LCP"],
+                "clientTraceMetadata": [],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn records_disabled_server_behaviors_in_the_server_settings() {
+        let server = run(async {
+            // The flags are parsed from their next.config.js keys.
+            let mut config = serde_json::to_value(NextConfig::default())?;
+            config["poweredByHeader"] = false.into();
+            config["generateEtags"] = false.into();
+            let next_config = NextConfig::from_string(Vc::cell(config.to_string()));
+            Ok(serde_json::to_value(
+                server_settings_manifest(next_config).await?,
+            )?)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            server,
+            serde_json::json!({
+                "poweredByHeader": false,
+                "compress": true,
+                "generateEtags": false,
+            })
+        );
+    }
+
+    #[test]
+    fn inlines_env_vars_with_the_public_prefixes_only() {
+        let mut options = BuildOptions::default();
+        options.define_env.client = vec![(
+            "process.env.NEXT_PUBLIC_ANALYTICS_ID".to_string(),
+            "\"ua-1\"".to_string(),
+        )];
+        let env = [
+            ("PUBLIC_API_URL", "https://api.example.com"),
+            ("APP_NAME", "shop"),
+            ("NEXT_PUBLIC_ANALYTICS_ID", "ua-1"),
+            ("DATABASE_URL", "postgres://localhost"),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+
+        let define_env = client_define_env(
+            &options,
+            NextMode::Build,
+            &env,
+            &["PUBLIC_".to_string(), "APP_".to_string()],
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(
+            define_env,
+            IndexMap::from_iter(
+                [
+                    ("process.env.NODE_ENV", "\"production\""),
+                    ("process.env.PUBLIC_API_URL", "\"https://api.example.com\""),
+                    ("process.env.APP_NAME", "\"shop\""),
+                ]
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+            )
+        );
+    }
+
+    /// Compiles `index.js` with `source` into a client chunk group targeting
+    /// `browserslist_query`, and returns the concatenated code of its chunks.
+    async fn compile_client_chunks(
+        source: &str,
+        browserslist_query: &str,
+        options: BuildOptions,
+    ) -> String {
+        let dir = fixture(&[("index.js", source)]);
+        let dir_path = dir.path().to_path_buf();
+        let browserslist_query = browserslist_query.to_string();
+        run(async move {
+            let project_root = fixture_root(&dir_path);
+            let define_env =
+                client_define_env(&options, NextMode::Build, &IndexMap::new(), &[], &[])?;
+            let compile_time_info =
+                get_client_compile_time_info(browserslist_query, Vc::cell(define_env));
+
+            let chunks = client_chunk_group(project_root, "index.js", compile_time_info).await?;
+
+            let mut code = String::new();
+            for chunk in chunks.await?.iter() {
+                if let AssetContent::File(file) = &*chunk.content().await? {
+                    if let FileContent::Content(file) = &*file.await? {
+                        code.push_str(&file.content().to_str()?);
+                    }
+                }
+            }
+            Ok(code)
+        })
+        .await
+        .unwrap()
+    }
+
+    /// Builds `index.js` with `source` into client chunks seeded with `seed`,
+    /// in a fresh turbo-tasks instance. Returns the written files by their path
+    /// in the dist directory, and the default build id.
+    async fn build_seeded_client_chunks(
+        source: &str,
+        seed: &str,
+    ) -> (std::collections::BTreeMap<PathBuf, Vec<u8>>, String) {
+        let dir = fixture(&[("index.js", source)]);
+        let dir_path = dir.path().to_path_buf();
+        let seed = seed.to_string();
+        let build_id = run(async move {
+            let project_root = fixture_root(&dir_path);
+            let define_env = client_define_env(
+                &BuildOptions::default(),
+                NextMode::Build,
+                &IndexMap::new(),
+                &[],
+                &[],
+            )?;
+            let compile_time_info = get_client_compile_time_info(
+                "last 1 Chrome versions".to_string(),
+                Vc::cell(define_env),
+            );
+
+            let chunks = client_chunk_group(project_root, "index.js", compile_time_info)
+                .await?
+                .await?;
+            Completions::all(chunks.iter().map(|&chunk| emit(chunk, 0)).collect()).await?;
+            default_build_id(&chunks, Some(&seed)).await
+        })
+        .await
+        .unwrap();
+
+        let dist_dir = dir.path().join(".next");
+        let mut files = std::collections::BTreeMap::new();
+        let mut queue = vec![dist_dir.clone()];
+        while let Some(dir) = queue.pop() {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    queue.push(path);
+                } else {
+                    let content = std::fs::read(&path).unwrap();
+                    files.insert(path.strip_prefix(&dist_dir).unwrap().to_path_buf(), content);
+                }
+            }
+        }
+        (files, build_id)
+    }
+
+    #[tokio::test]
+    async fn names_the_build_manifests_after_the_generated_build_id() {
+        let (generated, defaulted) = run(async {
+            let config_with_build_id = |build_id: serde_json::Value| {
+                let mut config = serde_json::to_value(NextConfig::default())?;
+                config["generateBuildId"] = build_id;
+                anyhow::Ok(NextConfig::from_string(Vc::cell(config.to_string())))
+            };
+            let seed = Some("seed");
+            Ok((
+                generated_build_id(config_with_build_id("fixed-id".into())?, &[], seed).await?,
+                generated_build_id(config_with_build_id(serde_json::Value::Null)?, &[], seed)
+                    .await?,
+            ))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(generated, "fixed-id");
+        assert_eq!(
+            build_id_manifest_paths(&generated),
+            (
+                "static/fixed-id/_ssgManifest.js".to_string(),
+                "static/fixed-id/_buildManifest.js".to_string()
+            )
+        );
+        // `null` falls back to the default build id.
+        assert_ne!(defaulted, "null");
+        assert_eq!(defaulted.len(), 16);
+    }
+
+    #[tokio::test]
+    async fn builds_byte_identical_output_with_the_same_seed() {
+        let source = r#"console.log("hello");"#;
+        let (files, build_id) = build_seeded_client_chunks(source, "seed").await;
+        let (other_files, other_build_id) = build_seeded_client_chunks(source, "seed").await;
+        assert!(!files.is_empty());
+        assert_eq!(files, other_files);
+        assert_eq!(build_id, other_build_id);
+
+        // Other sources get another build id with the same seed.
+        let (_, changed_build_id) =
+            build_seeded_client_chunks(r#"console.log("bye");"#, "seed").await;
+        assert_ne!(build_id, changed_build_id);
+    }
+
+    #[tokio::test]
+    async fn eliminates_code_guarded_by_false_defines() {
+        let code = compile_client_chunks(
+            indoc! {r#"
+                if (__FEATURE_X__) {
+                    console.log("feature x enabled");
+                }
+                console.log("always");
+            "#},
+            "last 1 Chrome versions",
+            BuildOptions {
+                defines: vec![("__FEATURE_X__".to_string(), "false".to_string())],
+                ..Default::default()
+            },
+        )
+        .await;
+
+        assert!(code.contains("always"));
+        assert!(!code.contains("feature x enabled"));
+    }
+
+    #[tokio::test]
+    async fn lowers_legacy_client_chunks_to_es5() {
+        let source = indoc! {"
+            const double = (n) => n * 2;
+            let doubled = double(21);
+            console.log(doubled);
+        "};
+        let modern =
+            compile_client_chunks(source, "last 1 Chrome versions", Default::default()).await;
+        assert!(modern.contains("const double = (n)=>n * 2"), "{modern}");
+
+        // The module code is lowered. The chunk's own wrappers are generated
+        // by Turbopack and not part of it.
+        let legacy =
+            compile_client_chunks(source, LEGACY_BROWSERSLIST_QUERY, Default::default()).await;
+        assert!(legacy.contains("var double = function(n)"), "{legacy}");
+        assert!(!legacy.contains("(n)=>"), "{legacy}");
+        assert!(!legacy.contains("const double"), "{legacy}");
+        assert!(!legacy.contains("let doubled"), "{legacy}");
+    }
+
+    #[tokio::test]
+    async fn skips_settings_manifests_of_the_defaults() {
+        let (client, server, images) = run(async {
+            let mut config = NextConfig::default();
+            config.images.unoptimized = true;
+            let next_config = config.cell();
+            Ok((
+                client_settings_manifest(next_config).await?.is_none(),
+                server_settings_manifest(next_config).await?.is_none(),
+                images_manifest(next_config).await?.is_none(),
+            ))
+        })
+        .await
+        .unwrap();
+
+        assert!(client, "no client settings are configured");
+        assert!(server, "the server settings are all on by default");
+        assert!(images, "unoptimized images have no optimizer settings");
+    }
+
+    #[tokio::test]
+    async fn chunks_with_the_selected_client_runtime() {
+        let dir = fixture(&[("index.js", "console.log('index');")]);
+        let dir_path = dir.path().to_path_buf();
+        let (production, development) = run(async move {
+            let project_root = fixture_root(&dir_path);
+            let mut paths = vec![];
+            for runtime in [ClientRuntime::Production, ClientRuntime::Development] {
+                let chunks = client_chunk_group_with_runtime(
+                    project_root,
+                    "index.js",
+                    get_client_compile_time_info(
+                        "defaults".to_string(),
+                        Vc::cell(Default::default()),
+                    ),
+                    runtime,
+                )
+                .await?;
+                let mut chunk_paths = vec![];
+                for chunk in chunks.await?.iter() {
+                    chunk_paths.push(chunk.ident().path().await?.path.clone());
+                }
+                paths.push(chunk_paths);
+            }
+            Ok((paths[0].clone(), paths[1].clone()))
+        })
+        .await
+        .unwrap();
+
+        // The development runtime additionally registers the chunk list to
+        // apply updates to.
+        assert!(development.len() > production.len(), "{development:?}");
+        for path in &production {
+            assert!(development.contains(path), "{path} {development:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_mixing_client_runtimes_with_a_prior_build() {
+        let dir = fixture(&[(
+            ".next/build-manifest.json",
+            r#"{"pages": {}, "clientRuntime": "development"}"#,
+        )]);
+        let dir_path = dir.path().to_path_buf();
+        let (production, development, without_prior_build) = run(async move {
+            let project_root = fixture_root(&dir_path);
+            let prior = project_root.join(".next/build-manifest.json".to_string());
+            let missing = project_root.join("out/build-manifest.json".to_string());
+            Ok((
+                check_prior_client_runtime(prior, ClientRuntime::Production)
+                    .await
+                    .map_err(|err| err.to_string()),
+                check_prior_client_runtime(prior, ClientRuntime::Development)
+                    .await
+                    .is_ok(),
+                check_prior_client_runtime(missing, ClientRuntime::Production)
+                    .await
+                    .is_ok(),
+            ))
+        })
+        .await
+        .unwrap();
+
+        let err = production.unwrap_err();
+        assert!(
+            err.starts_with(
+                "the production client runtime can't be mixed with the development one"
+            ),
+            "{err}"
+        );
+        assert!(development);
+        assert!(without_prior_build);
+    }
+
+    #[test]
+    fn test_data_path_prefix() {
+        assert_eq!(data_path_prefix(None).unwrap(), None);
+        assert_eq!(data_path_prefix(Some("/_next/data/")).unwrap(), None);
+        assert_eq!(
+            data_path_prefix(Some("/proxy/data/")).unwrap().as_deref(),
+            Some("/proxy/data")
+        );
+        assert!(data_path_prefix(Some("proxy/data")).is_err());
+        assert!(data_path_prefix(Some("/")).is_err());
+    }
+
+    #[test]
+    fn lists_the_data_path_prefix_in_the_client_build_manifest() {
+        let rewrites = Rewrites::default();
+        let manifest = |data_path_prefix| {
+            serde_json::to_value(ClientBuildManifest {
+                rewrites: &rewrites,
+                data_path_prefix,
+                sorted_pages: &[],
+                pages: Default::default(),
+            })
+            .unwrap()
+        };
+
+        assert_eq!(
+            manifest(Some("/proxy/data"))["__dataPathPrefix"],
+            "/proxy/data"
+        );
+        assert!(manifest(None).get("__dataPathPrefix").is_none());
+    }
 }
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::core::{asset::Asset, output::OutputAsset},
+};
+
+use crate::next_build::all_assets_from_entries;
+
+const FONT_EXTENSIONS: &[&str] = &["woff", "woff2", "eot", "ttf", "otf"];
+
+/// Adds an entry for the font files referenced by the chunks to a section of
+/// the `NextFontManifest`, like `NextFontManifestPlugin` does for webpack.
+///
+/// The entry only lists the fonts to preload, which are named `[name].p.[ext]`
+/// by the font loader. It's added even when none of the fonts should be
+/// preloaded, as the runtime then adds a preconnect tag instead. Fonts with a
+/// size-adjusted fallback contain `-s` in their name.
+pub(crate) async fn add_font_files(
+    section: &mut HashMap<String, Vec<String>>,
+    using_size_adjust: &mut bool,
+    key: String,
+    chunks: Vec<Vc<Box<dyn OutputAsset>>>,
+    client_relative_path: &FileSystemPath,
+) -> Result<()> {
+    let mut font_files = vec![];
+    for asset in all_assets_from_entries(Vc::cell(chunks)).await?.iter() {
+        let path = asset.ident().path().await?;
+        if !FONT_EXTENSIONS.contains(&path.extension_ref().unwrap_or_default()) {
+            continue;
+        }
+        if let Some(path) = client_relative_path.get_path_to(&path) {
+            font_files.push(path.to_string());
+        }
+    }
+    if font_files.is_empty() {
+        return Ok(());
+    }
+
+    *using_size_adjust |= font_files.iter().any(|file| file.contains("-s"));
+
+    let mut preloaded_font_files: Vec<_> = font_files
+        .into_iter()
+        .filter(|file| {
+            FONT_EXTENSIONS
+                .iter()
+                .any(|extension| file.ends_with(&format!(".p.{extension}")))
+        })
+        .collect();
+    preloaded_font_files.sort();
+    section.insert(key, preloaded_font_files);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use next_core::next_manifests::NextFontManifest;
+    use turbo_tasks::Vc;
+    use turbopack_binding::{
+        turbo::tasks_fs::{File, FileSystem, VirtualFileSystem},
+        turbopack::core::{
+            asset::AssetContent, output::OutputAsset, virtual_output::VirtualOutputAsset,
+        },
+    };
+
+    use super::add_font_files;
+    use crate::test_utils::run;
+
+    #[tokio::test]
+    async fn lists_a_shared_font_for_app_and_pages_routes() {
+        let manifest = run(async {
+            let client_relative_path = VirtualFileSystem::new().root().join(".next".to_string());
+            let asset = |path: &str| -> Vc<Box<dyn OutputAsset>> {
+                Vc::upcast(VirtualOutputAsset::new(
+                    client_relative_path.join(path.to_string()),
+                    AssetContent::file(File::from("").into()),
+                ))
+            };
+            // The font of a component used by both routers.
+            let font = asset("static/media/inter-s.p.woff2");
+            let client_relative_path_ref = client_relative_path.await?;
+
+            let mut manifest = NextFontManifest::default();
+            add_font_files(
+                &mut manifest.app,
+                &mut manifest.app_using_size_adjust,
+                "[project]/app/page".to_string(),
+                vec![asset("static/chunks/app/page.js"), font],
+                &client_relative_path_ref,
+            )
+            .await?;
+            add_font_files(
+                &mut manifest.pages,
+                &mut manifest.pages_using_size_adjust,
+                "/about".to_string(),
+                vec![
+                    asset("static/chunks/pages/about.js"),
+                    font,
+                    // Fonts which aren't preloaded aren't listed.
+                    asset("static/media/mono.woff2"),
+                ],
+                &client_relative_path_ref,
+            )
+            .await?;
+            Ok(serde_json::to_value(manifest)?)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            manifest,
+            serde_json::json!({
+                "app": {
+                    "[project]/app/page": ["static/media/inter-s.p.woff2"],
+                },
+                "appUsingSizeAdjust": true,
+                "pages": {
+                    "/about": ["static/media/inter-s.p.woff2"],
+                },
+                "pagesUsingSizeAdjust": true,
+            })
+        );
+    }
+}
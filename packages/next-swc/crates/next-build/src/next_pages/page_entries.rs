@@ -1,3 +1,5 @@
+use std::time::Instant;
+
 use anyhow::{bail, Result};
 use next_core::{
     create_page_loader_entry_module, get_asset_path_from_pathname,
@@ -8,17 +10,20 @@ use next_core::{
     },
     next_config::NextConfig,
     next_dynamic::NextDynamicTransition,
-    next_manifests::{BuildManifest, PagesManifest},
+    next_manifests::{BuildManifest, NextFontManifest, PagesManifest},
     next_pages::create_page_ssr_entry_module,
     next_server::{
         get_server_module_options_context, get_server_resolve_options_context,
         get_server_runtime_entries, ServerContextType,
     },
     pages_structure::{
-        find_pages_structure, PagesDirectoryStructure, PagesStructure, PagesStructureItem,
+        find_pages_structure, is_special_page, PagesDirectoryStructure, PagesStructure,
+        PagesStructureItem,
     },
     pathname_for_path,
-    util::NextRuntime,
+    resolve_conditions::{with_resolve_conditions, ResolveConditions},
+    util::{parse_config_from_source, NextRuntime, NextSourceConfig},
+    virtual_modules::{with_virtual_modules, VirtualModules},
     PathType,
 };
 use turbo_tasks::Vc;
@@ -45,6 +50,11 @@ use turbopack_binding::{
     },
 };
 
+use crate::{
+    build_diagnostics::BuildTimer, changed_files::RouteChunks, fail_fast::FailFast,
+    next_font::add_font_files,
+};
+
 #[turbo_tasks::value]
 pub struct PageEntries {
     pub entries: Vec<Vc<PageEntry>>,
@@ -61,6 +71,8 @@ pub async fn get_page_entries(
     client_compile_time_info: Vc<CompileTimeInfo>,
     server_compile_time_info: Vc<CompileTimeInfo>,
     next_config: Vc<NextConfig>,
+    virtual_modules: Vc<VirtualModules>,
+    resolve_conditions: Vc<ResolveConditions>,
 ) -> Result<Vc<PageEntries>> {
     let pages_structure = find_pages_structure(
         project_root,
@@ -88,12 +100,19 @@ pub async fn get_page_entries(
         next_config,
     );
 
-    let client_resolve_options_context = get_client_resolve_options_context(
-        project_root,
-        client_ty,
-        mode,
-        next_config,
-        execution_context,
+    let client_resolve_options_context = with_resolve_conditions(
+        with_virtual_modules(
+            get_client_resolve_options_context(
+                project_root,
+                client_ty,
+                mode,
+                next_config,
+                execution_context,
+            ),
+            project_root,
+            virtual_modules,
+        ),
+        resolve_conditions.client(),
     );
 
     let client_transition = ContextTransition::new(
@@ -129,12 +148,19 @@ pub async fn get_page_entries(
     );
     let client_runtime_entries = client_runtime_entries.resolve_entries(client_module_context);
 
-    let ssr_resolve_options_context = get_server_resolve_options_context(
-        project_root,
-        ssr_ty,
-        mode,
-        next_config,
-        execution_context,
+    let ssr_resolve_options_context = with_resolve_conditions(
+        with_virtual_modules(
+            get_server_resolve_options_context(
+                project_root,
+                ssr_ty,
+                mode,
+                next_config,
+                execution_context,
+            ),
+            project_root,
+            virtual_modules,
+        ),
+        resolve_conditions.ssr(),
     );
     let ssr_module_options_context = get_server_module_options_context(
         project_root,
@@ -288,6 +314,11 @@ async fn get_page_entries_for_directory(
             next_router_path,
             original_path,
         } = *item.await?;
+        // The root `_app` and `_document` are added by
+        // `get_page_entries_for_root_directory`, don't shadow them.
+        if is_special_page(&format!("/{}", next_router_path.await?.path)) {
+            continue;
+        }
         entries.push(get_page_entry_for_file(
             ssr_module_context,
             client_module_context,
@@ -327,6 +358,8 @@ pub struct PageEntry {
     pub ssr_module: Vc<Box<dyn EcmascriptChunkPlaceable>>,
     /// The client entry module asset.
     pub client_module: Vc<EcmascriptModuleAsset>,
+    /// The config exported by the page's source, e.g. its `maxDuration`.
+    pub config: Vc<NextSourceConfig>,
 }
 
 #[turbo_tasks::function]
@@ -352,7 +385,7 @@ async fn get_page_entry_for_file(
 
     let ssr_module = create_page_ssr_entry_module(
         pathname,
-        reference_type,
+        reference_type.clone(),
         project_root,
         ssr_module_context,
         source,
@@ -361,6 +394,9 @@ async fn get_page_entry_for_file(
         next_config,
     );
 
+    let config =
+        parse_config_from_source(ssr_module_context.process(source, reference_type).module());
+
     let client_module = create_page_loader_entry_module(client_module_context, source, pathname);
 
     let Some(client_module) =
@@ -373,6 +409,7 @@ async fn get_page_entry_for_file(
         pathname,
         ssr_module,
         client_module,
+        config,
     }
     .cell())
 }
@@ -395,9 +432,14 @@ pub async fn compute_page_entries_chunks(
     client_relative_path: &FileSystemPath,
     pages_manifest: &mut PagesManifest,
     build_manifest: &mut BuildManifest,
+    next_font_manifest: &mut NextFontManifest,
     all_chunks: &mut Vec<Vc<Box<dyn OutputAsset>>>,
+    route_chunks: &mut RouteChunks,
+    build_timer: &mut BuildTimer,
+    fail_fast: Option<&FailFast>,
 ) -> Result<()> {
     for page_entry in page_entries.entries.iter() {
+        let route_start = Instant::now();
         let page_entry = page_entry.await?;
         let pathname = page_entry.pathname.await?;
         let asset_path: String = get_asset_path_from_pathname(&pathname, ".js");
@@ -423,6 +465,11 @@ pub async fn compute_page_entries_chunks(
                 .with_entry(Vc::upcast(page_entry.client_module)),
         );
 
+        if let Some(fail_fast) = fail_fast {
+            fail_fast.check(&pathname, ssr_entry_chunk).await?;
+            fail_fast.check(&pathname, client_chunks).await?;
+        }
+
         let build_manifest_pages_entry = build_manifest
             .pages
             .entry(pathname.clone_value())
@@ -435,6 +482,30 @@ pub async fn compute_page_entries_chunks(
                 build_manifest_pages_entry.push(asset_path.to_string());
             }
         }
+
+        add_font_files(
+            &mut next_font_manifest.pages,
+            &mut next_font_manifest.pages_using_size_adjust,
+            pathname.clone_value(),
+            client_chunks.await?.clone_value(),
+            client_relative_path,
+        )
+        .await?;
+
+        route_chunks
+            .add_route(
+                pathname.clone_value(),
+                vec![
+                    Vc::upcast(page_entry.ssr_module),
+                    Vc::upcast(page_entry.client_module),
+                ],
+                [ssr_entry_chunk]
+                    .into_iter()
+                    .chain(client_chunks.await?.iter().copied()),
+            )
+            .await?;
+
+        build_timer.add_route(pathname.clone_value(), route_start.elapsed());
     }
     Ok(())
 }
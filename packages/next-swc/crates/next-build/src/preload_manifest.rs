@@ -0,0 +1,125 @@
+use anyhow::Result;
+use next_core::next_manifests::{
+    AppBuildManifest, BuildManifest, LoadOrderManifest, NextFontManifest, PreloadManifest,
+    ResourceLoading,
+};
+use turbo_tasks::Vc;
+use turbopack_binding::turbo::tasks_fs::FileSystemPath;
+
+/// The files the build manifests list for a page.
+struct RouteFiles<'a> {
+    route: &'a str,
+    entry_files: &'a [String],
+    font_files: &'a [String],
+    /// Whether it's an app page, whose scripts are loaded async.
+    app: bool,
+}
+
+/// Collects the files of every page and app page from the build manifests.
+async fn route_files<'a>(
+    build_manifest: &'a BuildManifest,
+    app_build_manifest: &'a AppBuildManifest,
+    next_font_manifest: &'a NextFontManifest,
+    app_dir: Option<Vc<FileSystemPath>>,
+) -> Result<Vec<RouteFiles<'a>>> {
+    let mut routes = vec![];
+
+    for (pathname, files) in &build_manifest.pages {
+        routes.push(RouteFiles {
+            route: pathname,
+            entry_files: files,
+            font_files: next_font_manifest
+                .pages
+                .get(pathname)
+                .map(Vec::as_slice)
+                .unwrap_or_default(),
+            app: false,
+        });
+    }
+
+    if let Some(app_dir) = app_dir {
+        let app_dir = app_dir.to_string().await?;
+        for (original_name, files) in &app_build_manifest.pages {
+            // Route handlers don't render any HTML to add hints to.
+            if !original_name.ends_with("/page") {
+                continue;
+            }
+            routes.push(RouteFiles {
+                route: original_name,
+                entry_files: files,
+                font_files: next_font_manifest
+                    .app
+                    .get(&format!("{app_dir}{original_name}"))
+                    .map(Vec::as_slice)
+                    .unwrap_or_default(),
+                app: true,
+            });
+        }
+    }
+
+    Ok(routes)
+}
+
+/// Computes the [PreloadManifest] of every page from the build manifests, so
+/// it has to run once their files are final, e.g. content addressed.
+pub(crate) async fn compute_preload_manifest(
+    build_manifest: &BuildManifest,
+    app_build_manifest: &AppBuildManifest,
+    next_font_manifest: &NextFontManifest,
+    app_dir: Option<Vc<FileSystemPath>>,
+) -> Result<PreloadManifest> {
+    let mut preload_manifest = PreloadManifest::default();
+
+    for route in route_files(
+        build_manifest,
+        app_build_manifest,
+        next_font_manifest,
+        app_dir,
+    )
+    .await?
+    {
+        preload_manifest.add_route(
+            route.route.to_string(),
+            route.entry_files,
+            route.font_files,
+            &build_manifest.low_priority_files,
+        );
+    }
+
+    Ok(preload_manifest)
+}
+
+/// Computes the [LoadOrderManifest] of every page from the build manifests,
+/// like [compute_preload_manifest]. Pages load their scripts with `defer`, and
+/// app pages with `async`, like their HTML renderers do.
+pub(crate) async fn compute_load_order_manifest(
+    build_manifest: &BuildManifest,
+    app_build_manifest: &AppBuildManifest,
+    next_font_manifest: &NextFontManifest,
+    app_dir: Option<Vc<FileSystemPath>>,
+) -> Result<LoadOrderManifest> {
+    let mut load_order_manifest = LoadOrderManifest::default();
+
+    for route in route_files(
+        build_manifest,
+        app_build_manifest,
+        next_font_manifest,
+        app_dir,
+    )
+    .await?
+    {
+        load_order_manifest.add_route(
+            route.route.to_string(),
+            route.entry_files,
+            route.font_files,
+            &build_manifest.low_priority_files,
+            if route.app {
+                ResourceLoading::Async
+            } else {
+                ResourceLoading::Defer
+            },
+        );
+    }
+
+    Ok(load_order_manifest)
+}
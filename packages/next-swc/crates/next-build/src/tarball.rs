@@ -0,0 +1,88 @@
+use std::{fs::File, io::Write, path::Path};
+
+use anyhow::{Context, Result};
+
+/// Writes the contents of the build output directory `dist_dir_path` as a tar
+/// archive to `sink`.
+///
+/// Entries are stored relative to the project directory, i.e. prefixed with
+/// `dist_dir_name` (`.next/...` by default), and keep their file modes.
+pub(crate) fn write_tarball<W: Write>(
+    dist_dir_path: &Path,
+    dist_dir_name: &str,
+    sink: W,
+) -> Result<W> {
+    let mut builder = tar::Builder::new(sink);
+    builder.follow_symlinks(false);
+    builder
+        .append_dir_all(dist_dir_name, dist_dir_path)
+        .with_context(|| format!("failed to archive {}", dist_dir_path.display()))?;
+    builder
+        .into_inner()
+        .context("failed to finish the build output archive")
+}
+
+/// Writes the build output directory as a tar archive to the file at
+/// `tarball_path`.
+pub(crate) fn write_tarball_to_file(
+    dist_dir_path: &Path,
+    dist_dir_name: &str,
+    tarball_path: &Path,
+) -> Result<()> {
+    let file = File::create(tarball_path)
+        .with_context(|| format!("failed to create {}", tarball_path.display()))?;
+    write_tarball(dist_dir_path, dist_dir_name, file)?.sync_all()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write_tarball;
+    use crate::test_utils::fixture;
+
+    #[test]
+    fn archives_the_build_output() {
+        let dir = fixture(&[
+            (".next/build-manifest.json", "{}"),
+            (".next/server/pages-manifest.json", "{}"),
+            (".next/server/pages/index.js", ""),
+        ]);
+        let dist_dir_path = dir.path().join(".next");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            std::fs::set_permissions(
+                dist_dir_path.join("server/pages/index.js"),
+                std::fs::Permissions::from_mode(0o755),
+            )
+            .unwrap();
+        }
+
+        let tarball = write_tarball(&dist_dir_path, ".next", vec![]).unwrap();
+        let mut archive = tar::Archive::new(tarball.as_slice());
+        let mut files = vec![];
+        for entry in archive.entries().unwrap() {
+            let entry = entry.unwrap();
+            if entry.header().entry_type().is_file() {
+                files.push((
+                    entry.path().unwrap().to_str().unwrap().to_string(),
+                    entry.header().mode().unwrap() & 0o777,
+                ));
+            }
+        }
+        files.sort();
+
+        let paths: Vec<_> = files.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                ".next/build-manifest.json",
+                ".next/server/pages-manifest.json",
+                ".next/server/pages/index.js",
+            ]
+        );
+        #[cfg(unix)]
+        assert_eq!(files[2].1, 0o755);
+    }
+}
@@ -0,0 +1,96 @@
+//! The next-build specifics of the unit test helpers shared from
+//! [next_core::test_utils].
+
+use std::future::Future;
+
+use anyhow::{bail, Result};
+pub(crate) use next_core::test_utils::{fixture, fixture_root};
+use next_core::{
+    next_client::{get_client_chunking_context, ClientRuntime},
+    test_utils::run_with,
+};
+use turbo_tasks::{Value, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::{
+        core::{
+            chunk::{ChunkingContext, EvaluatableAsset, EvaluatableAssets},
+            compile_time_info::CompileTimeInfo,
+            context::AssetContext,
+            file_source::FileSource,
+            module::Module,
+            output::OutputAssets,
+            reference_type::{EntryReferenceSubType, ReferenceType},
+        },
+        turbopack::{
+            module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+            ModuleAssetContext,
+        },
+    },
+};
+
+/// Runs `future` in a fresh turbo-tasks instance.
+pub(crate) async fn run<T: Send + 'static>(
+    future: impl Future<Output = Result<T>> + Send + 'static,
+) -> Result<T> {
+    run_with(crate::register, future).await
+}
+
+/// Chunks the module at `entry` of `project_root` into a client chunk group
+/// written to `.next`, lowered to the environment of `compile_time_info`.
+/// Must be called within [run].
+pub(crate) async fn client_chunk_group(
+    project_root: Vc<FileSystemPath>,
+    entry: &str,
+    compile_time_info: Vc<CompileTimeInfo>,
+) -> Result<Vc<OutputAssets>> {
+    client_chunk_group_with_runtime(
+        project_root,
+        entry,
+        compile_time_info,
+        ClientRuntime::Production,
+    )
+    .await
+}
+
+/// Like [client_chunk_group], loaded by the `runtime` variant.
+pub(crate) async fn client_chunk_group_with_runtime(
+    project_root: Vc<FileSystemPath>,
+    entry: &str,
+    compile_time_info: Vc<CompileTimeInfo>,
+    runtime: ClientRuntime,
+) -> Result<Vc<OutputAssets>> {
+    let module_context = ModuleAssetContext::new(
+        Vc::cell(Default::default()),
+        compile_time_info,
+        ModuleOptionsContext {
+            preset_env_versions: Some(compile_time_info.environment()),
+            ..Default::default()
+        }
+        .cell(),
+        ResolveOptionsContext::default().cell(),
+        Vc::cell("test".to_string()),
+    );
+    let module = module_context
+        .process(
+            Vc::upcast(FileSource::new(project_root.join(entry.to_string()))),
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+        )
+        .module();
+    let Some(evaluatable) = Vc::try_resolve_sidecast::<Box<dyn EvaluatableAsset>>(module).await?
+    else {
+        bail!("{entry} must be evaluatable");
+    };
+
+    let chunking_context = get_client_chunking_context(
+        project_root,
+        project_root.join(".next".to_string()),
+        Vc::cell(None),
+        compile_time_info.environment(),
+        runtime,
+    );
+    Ok(chunking_context.evaluated_chunk_group(
+        module.ident(),
+        EvaluatableAssets::empty().with_entry(evaluatable),
+    ))
+}
@@ -0,0 +1,153 @@
+use anyhow::Result;
+use next_core::all_assets_from_entries;
+use turbo_tasks::{TryFlatJoinIterExt, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::core::{
+        issue::{Issue, IssueExt, IssueSeverity, OptionStyledString, StyledString},
+        output::OutputAssets,
+    },
+};
+
+/// Returns all assets transitively reachable from the given chunks that are
+/// neither inside the node root nor the client root. These are never emitted,
+/// so anything referencing them at runtime will fail to load them.
+///
+/// References to external URLs don't produce output assets, so they're never
+/// part of this list.
+#[turbo_tasks::function]
+pub(crate) async fn unemitted_assets(
+    chunks: Vc<OutputAssets>,
+    node_root: Vc<FileSystemPath>,
+    client_relative_path: Vc<FileSystemPath>,
+) -> Result<Vc<OutputAssets>> {
+    let node_root = &*node_root.await?;
+    let client_relative_path = &*client_relative_path.await?;
+    Ok(Vc::cell(
+        all_assets_from_entries(chunks)
+            .await?
+            .iter()
+            .map(|&asset| async move {
+                let path = asset.ident().path().await?;
+                Ok(
+                    (!path.is_inside_ref(node_root) && !path.is_inside_ref(client_relative_path))
+                        .then_some(asset),
+                )
+            })
+            .try_flat_join()
+            .await?,
+    ))
+}
+
+/// Emits an issue for each asset returned by [unemitted_assets].
+#[turbo_tasks::function]
+pub(crate) async fn report_unemitted_assets(
+    chunks: Vc<OutputAssets>,
+    node_root: Vc<FileSystemPath>,
+    client_relative_path: Vc<FileSystemPath>,
+) -> Result<Vc<()>> {
+    for asset in unemitted_assets(chunks, node_root, client_relative_path)
+        .await?
+        .iter()
+    {
+        UnemittedAssetIssue {
+            path: asset.ident().path(),
+        }
+        .cell()
+        .emit();
+    }
+
+    Ok(Default::default())
+}
+
+#[turbo_tasks::value(shared)]
+struct UnemittedAssetIssue {
+    path: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for UnemittedAssetIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("Referenced asset is not emitted".to_string()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("build".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(
+                "This asset is referenced by the build output, but lives outside of the server \
+                 and client output directories, so it won't be available at runtime."
+                    .to_string(),
+            )
+            .cell(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use next_core::test_utils::{issues, styled_text};
+    use turbo_tasks::Vc;
+    use turbopack_binding::{
+        turbo::tasks_fs::{File, FileContent, FileSystemPath},
+        turbopack::core::{
+            asset::AssetContent, output::OutputAsset, virtual_output::VirtualOutputAsset,
+        },
+    };
+
+    use super::report_unemitted_assets;
+    use crate::test_utils::{fixture, fixture_root, run};
+
+    #[tokio::test]
+    async fn reports_assets_outside_of_the_output_roots() {
+        let dir = fixture(&[]);
+        let root = dir.path().to_owned();
+        let issues = run(async move {
+            let project_root = fixture_root(&root);
+            let node_root = project_root.join(".next".to_string());
+            let asset = |path: Vc<FileSystemPath>| -> Vc<Box<dyn OutputAsset>> {
+                Vc::upcast(VirtualOutputAsset::new(
+                    path,
+                    AssetContent::file(FileContent::Content(File::from("")).cell()),
+                ))
+            };
+            let chunks = Vc::cell(vec![
+                asset(node_root.join("server/pages/index.js".to_string())),
+                asset(node_root.join("static/chunks/main.js".to_string())),
+                asset(project_root.join("assets/logo.png".to_string())),
+            ]);
+            let report =
+                report_unemitted_assets(chunks, node_root, node_root.join("static".to_string()));
+            report.await?;
+
+            Ok(issues(report)
+                .await?
+                .iter()
+                .map(|issue| (issue.file_path.clone(), styled_text(&issue.title)))
+                .collect::<Vec<_>>())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(issues.len(), 1, "{issues:?}");
+        let (file_path, title) = &issues[0];
+        assert!(file_path.ends_with("assets/logo.png"), "{file_path}");
+        assert_eq!(title, "Referenced asset is not emitted");
+    }
+}
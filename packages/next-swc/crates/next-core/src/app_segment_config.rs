@@ -21,12 +21,14 @@ use turbopack_binding::turbopack::{
     },
     ecmascript::{
         analyzer::{graph::EvalContext, ConstantNumber, ConstantValue, JsValue},
-        parse::{parse, ParseResult},
-        EcmascriptInputTransforms, EcmascriptModuleAssetType,
+        parse::ParseResult,
     },
 };
 
-use crate::{app_structure::LoaderTree, util::NextRuntime};
+use crate::{
+    app_structure::LoaderTree,
+    util::{parse_js_source, NextRuntime},
+};
 
 #[derive(Default, PartialEq, Eq, Clone, Copy, Debug, TraceRawVcs, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -70,6 +72,7 @@ pub struct NextSegmentConfig {
     pub fetch_cache: Option<NextSegmentFetchCache>,
     pub runtime: Option<NextRuntime>,
     pub preferred_region: Option<Vec<String>>,
+    pub max_duration: Option<u32>,
 }
 
 #[turbo_tasks::value_impl]
@@ -91,6 +94,7 @@ impl NextSegmentConfig {
             fetch_cache,
             runtime,
             preferred_region,
+            max_duration,
         } = self;
         *dynamic = dynamic.or(parent.dynamic);
         *dynamic_params = dynamic_params.or(parent.dynamic_params);
@@ -98,6 +102,7 @@ impl NextSegmentConfig {
         *fetch_cache = fetch_cache.or(parent.fetch_cache);
         *runtime = runtime.or(parent.runtime);
         *preferred_region = preferred_region.take().or(parent.preferred_region.clone());
+        *max_duration = max_duration.or(parent.max_duration);
     }
 
     /// Applies a config from a paralllel route to this config, returning an
@@ -131,6 +136,7 @@ impl NextSegmentConfig {
             fetch_cache,
             runtime,
             preferred_region,
+            max_duration,
         } = self;
         merge_parallel(dynamic, &parallel_config.dynamic, "dynamic")?;
         merge_parallel(
@@ -146,6 +152,7 @@ impl NextSegmentConfig {
             &parallel_config.preferred_region,
             "referredRegion",
         )?;
+        merge_parallel(max_duration, &parallel_config.max_duration, "maxDuration")?;
         Ok(())
     }
 }
@@ -215,37 +222,16 @@ impl Issue for NextSegmentConfigParsingIssue {
 pub async fn parse_segment_config_from_source(
     source: Vc<Box<dyn Source>>,
 ) -> Result<Vc<NextSegmentConfig>> {
-    let path = source.ident().path().await?;
-
-    // Don't try parsing if it's not a javascript file, otherwise it will emit an
-    // issue causing the build to "fail".
-    if !(path.path.ends_with(".js")
-        || path.path.ends_with(".jsx")
-        || path.path.ends_with(".ts")
-        || path.path.ends_with(".tsx"))
-    {
+    let Some(result) = parse_js_source(source).await? else {
         return Ok(Default::default());
-    }
-
-    let result = &*parse(
-        source,
-        turbo_tasks::Value::new(
-            if path.path.ends_with(".ts") || path.path.ends_with(".tsx") {
-                EcmascriptModuleAssetType::Typescript
-            } else {
-                EcmascriptModuleAssetType::Ecmascript
-            },
-        ),
-        EcmascriptInputTransforms::empty(),
-    )
-    .await?;
+    };
 
     let ParseResult::Ok {
         program: Program::Module(module_ast),
         eval_context,
         globals,
         ..
-    } = result
+    } = &*result
     else {
         return Ok(Default::default());
     };
@@ -414,6 +400,20 @@ fn parse_config_value(
 
             config.preferred_region = Some(preferred_region);
         }
+        "maxDuration" => {
+            let value = eval_context.eval(init);
+            match value {
+                JsValue::Constant(ConstantValue::Num(ConstantNumber(val)))
+                    if val >= 0.0 && val.fract() == 0.0 && val <= u32::MAX as f64 =>
+                {
+                    config.max_duration = Some(val as u32);
+                }
+                _ => invalid_config(
+                    "`maxDuration` needs to be a static non-negative integer",
+                    &value,
+                ),
+            }
+        }
         _ => {}
     }
 }
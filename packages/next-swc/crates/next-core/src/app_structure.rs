@@ -23,10 +23,11 @@ use crate::{
             match_global_metadata_file, match_local_metadata_file, normalize_metadata_route,
             GlobalMetadataFileMatch, MetadataFileMatch,
         },
-        AppPage, AppPath, PageType,
+        AppPage, AppPath, PageSegment, PageType,
     },
     next_config::NextConfig,
     next_import_map::get_next_package,
+    util::find_source_dir,
 };
 
 /// A final route in the app directory.
@@ -255,19 +256,9 @@ impl OptionAppDir {
 /// Finds and returns the [DirectoryTree] of the app directory if existing.
 #[turbo_tasks::function]
 pub async fn find_app_dir(project_path: Vc<FileSystemPath>) -> Result<Vc<OptionAppDir>> {
-    let app = project_path.join("app".to_string());
-    let src_app = project_path.join("src/app".to_string());
-    let app_dir = if *app.get_type().await? == FileSystemEntryType::Directory {
-        app
-    } else if *src_app.get_type().await? == FileSystemEntryType::Directory {
-        src_app
-    } else {
-        return Ok(Vc::cell(None));
-    }
-    .resolve()
-    .await?;
-
-    Ok(Vc::cell(Some(app_dir)))
+    Ok(Vc::cell(
+        *find_source_dir(project_path, "app".to_string()).await?,
+    ))
 }
 
 /// Finds and returns the [DirectoryTree] of the app directory if enabled and
@@ -652,6 +643,110 @@ pub fn get_entrypoints(
     )
 }
 
+/// Checks the app directory structure for routes Next.js can't serve, without
+/// compiling anything. Conflicting routes are already reported by
+/// [get_entrypoints], this additionally reports invalid dynamic segments and
+/// pages without a root layout.
+#[turbo_tasks::function]
+pub async fn validate_entrypoints(
+    app_dir: Vc<FileSystemPath>,
+    page_extensions: Vc<Vec<String>>,
+) -> Result<Vc<()>> {
+    let entrypoints = get_entrypoints(app_dir, page_extensions).await?;
+
+    let mut pages_without_root_layout = vec![];
+    for entrypoint in entrypoints.values() {
+        let page = match entrypoint {
+            Entrypoint::AppPage { page, .. }
+            | Entrypoint::AppRoute { page, .. }
+            | Entrypoint::AppMetadata { page, .. } => page,
+        };
+
+        if let Some(message) = invalid_dynamic_segment(page) {
+            DirectoryTreeIssue {
+                app_dir,
+                message: StyledString::Text(message).cell(),
+                severity: IssueSeverity::Error.cell(),
+            }
+            .cell()
+            .emit();
+        }
+
+        if let Entrypoint::AppPage { page, loader_tree } = entrypoint {
+            if !has_root_layout(*loader_tree).await? {
+                pages_without_root_layout.push(page.to_string());
+            }
+        }
+    }
+
+    if !pages_without_root_layout.is_empty() {
+        DirectoryTreeIssue {
+            app_dir,
+            message: StyledString::Text(format!(
+                "Missing root layout for {}. Every page needs a root layout, add a `layout` file \
+                 to the app directory or to its top level route groups.",
+                pages_without_root_layout.join(", ")
+            ))
+            .cell(),
+            severity: IssueSeverity::Error.cell(),
+        }
+        .cell()
+        .emit();
+    }
+
+    Ok(Default::default())
+}
+
+/// Returns an error message if a dynamic segment of the page has an invalid
+/// param name, or if a param name is used twice.
+fn invalid_dynamic_segment(page: &AppPage) -> Option<String> {
+    let mut param_names = vec![];
+    for segment in page.iter() {
+        let (PageSegment::Dynamic(name)
+        | PageSegment::CatchAll(name)
+        | PageSegment::OptionalCatchAll(name)) = segment
+        else {
+            continue;
+        };
+
+        if name.is_empty() || name.starts_with('.') || name.contains(['[', ']']) {
+            return Some(format!(
+                "Invalid dynamic segment `{segment}` in {page}. Dynamic segments must be named, \
+                 e.g. `[slug]`, `[...slug]` or `[[...slug]]`."
+            ));
+        }
+
+        if param_names.contains(&name) {
+            return Some(format!(
+                "The param name `{name}` is used more than once in {page}. Each dynamic segment \
+                 of a route needs a unique name."
+            ));
+        }
+        param_names.push(name);
+    }
+
+    None
+}
+
+/// Returns true if the root of the loader tree, or one of the route groups
+/// directly below it, has a layout.
+async fn has_root_layout(loader_tree: Vc<LoaderTree>) -> Result<bool> {
+    let mut tree = loader_tree.await?;
+    loop {
+        if tree.components.await?.layout.is_some() {
+            return Ok(true);
+        }
+        let Some(&children) = tree.parallel_routes.get("children") else {
+            return Ok(false);
+        };
+        let children = children.await?;
+        if !is_group_route(&children.segment) {
+            return Ok(false);
+        }
+        tree = children;
+    }
+}
+
 #[turbo_tasks::function]
 fn directory_tree_to_entrypoints(
     app_dir: Vc<FileSystemPath>,
@@ -827,6 +922,22 @@ async fn directory_tree_to_loader_tree(
             }
             .cell();
         } else if current_level_is_parallel_route {
+            // The slot can't be matched for this route and has no `default` file to
+            // render instead.
+            DirectoryTreeIssue {
+                app_dir,
+                message: StyledString::Text(format!(
+                    "The parallel route slot `{}` has no matching segment for {} and no default \
+                     file. Add a `default` file to `{}` to render a fallback when the slot is \
+                     unmatched.",
+                    directory_name, for_app_path, app_page
+                ))
+                .cell(),
+                severity: IssueSeverity::Warning.cell(),
+            }
+            .cell()
+            .emit();
+
             // default fallback component
             tree.components = Components {
                 default: Some(
@@ -1197,3 +1308,48 @@ impl Issue for DirectoryTreeIssue {
         Vc::cell(Some(self.message))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use turbo_tasks::Vc;
+    use turbopack_binding::turbopack::core::issue::IssueSeverity;
+
+    use super::get_entrypoints;
+    use crate::test_utils::{fixture, fixture_root, issues, run, styled_text};
+
+    #[tokio::test]
+    async fn warns_about_a_parallel_route_slot_without_a_default() {
+        let dir = fixture(&[
+            ("app/layout.js", "export default function Layout() {}"),
+            ("app/page.js", "export default function Page() {}"),
+            (
+                "app/@modal/photo/page.js",
+                "export default function Photo() {}",
+            ),
+            ("node_modules/next/package.json", r#"{"name": "next"}"#),
+        ]);
+        let root = dir.path().to_owned();
+        let warnings = run(async move {
+            let entrypoints = get_entrypoints(
+                fixture_root(&root).join("app".to_string()),
+                Vc::cell(vec!["js".to_string()]),
+            );
+            entrypoints.await?;
+            Ok(issues(entrypoints)
+                .await?
+                .iter()
+                .filter(|issue| issue.severity == IssueSeverity::Warning)
+                .filter_map(|issue| issue.description.as_ref().map(styled_text))
+                .collect::<Vec<_>>())
+        })
+        .await
+        .unwrap();
+
+        // `/` has no `@modal` segment, `/photo` has one.
+        assert!(
+            warnings.iter().any(|warning| warning
+                .contains("The parallel route slot `@modal` has no matching segment")),
+            "{warnings:?}"
+        );
+    }
+}
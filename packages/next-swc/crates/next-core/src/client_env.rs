@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use swc_core::ecma::{
+    ast::{Expr, Lit, MemberExpr, MemberProp},
+    visit::{Visit, VisitWith},
+};
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::{tasks_env::EnvMap, tasks_fs::FileSystemPath},
+    turbopack::core::module::{Module, Modules},
+};
+
+use crate::module_analysis::{all_referenced_modules, visit_ecmascript_module};
+
+/// The environment variables referenced by client modules.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientEnvReferences {
+    /// The `process.env.X` references that are inlined through the client
+    /// define env, keyed by the variable name, with the paths of the modules
+    /// referencing them relative to the project root.
+    pub inlined: BTreeMap<String, Vec<String>>,
+    /// The paths of the modules accessing `process.env` in a way that can't be
+    /// statically analyzed, e.g. `process.env[key]`.
+    pub dynamic_access: Vec<String>,
+}
+
+/// The environment variables referenced by a single module.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Default)]
+struct ModuleEnvReferences {
+    names: Vec<String>,
+    has_dynamic_access: bool,
+}
+
+/// Collects the environment variables referenced in the module graph of the
+/// client `entries`.
+#[turbo_tasks::function]
+pub async fn client_env_references(
+    entries: Vc<Modules>,
+    define_env: Vc<EnvMap>,
+    project_path: Vc<FileSystemPath>,
+) -> Result<Vc<ClientEnvReferences>> {
+    let define_env = define_env.await?;
+    let project_path = project_path.await?;
+
+    let modules = all_referenced_modules(entries.await?.iter().copied()).await?;
+
+    let mut references = ClientEnvReferences::default();
+    for module in modules {
+        let module_references = module_env_references(module).await?;
+        if module_references.names.is_empty() && !module_references.has_dynamic_access {
+            continue;
+        }
+
+        let path = module.ident().path().await?;
+        let path = project_path
+            .get_path_to(&path)
+            .unwrap_or(&path.path)
+            .to_string();
+
+        for name in &module_references.names {
+            if define_env.contains_key(&format!("process.env.{name}")) {
+                references
+                    .inlined
+                    .entry(name.clone())
+                    .or_default()
+                    .push(path.clone());
+            }
+        }
+        if module_references.has_dynamic_access {
+            references.dynamic_access.push(path);
+        }
+    }
+
+    for paths in references.inlined.values_mut() {
+        paths.sort();
+        paths.dedup();
+    }
+    references.dynamic_access.sort();
+    references.dynamic_access.dedup();
+
+    Ok(references.cell())
+}
+
+#[turbo_tasks::function]
+async fn module_env_references(module: Vc<Box<dyn Module>>) -> Result<Vc<ModuleEnvReferences>> {
+    let mut visitor = EnvVisitor::default();
+    visit_ecmascript_module(module, &mut visitor).await?;
+
+    visitor.names.sort();
+    visitor.names.dedup();
+    Ok(ModuleEnvReferences {
+        names: visitor.names,
+        has_dynamic_access: visitor.has_dynamic_access,
+    }
+    .cell())
+}
+
+/// Returns whether the member expression is `process.env`.
+fn is_process_env(member: &MemberExpr) -> bool {
+    matches!(&*member.obj, Expr::Ident(obj) if &*obj.sym == "process")
+        && matches!(&member.prop, MemberProp::Ident(prop) if &*prop.sym == "env")
+}
+
+#[derive(Default)]
+struct EnvVisitor {
+    names: Vec<String>,
+    has_dynamic_access: bool,
+}
+
+impl Visit for EnvVisitor {
+    fn visit_member_expr(&mut self, member: &MemberExpr) {
+        if matches!(&*member.obj, Expr::Member(obj) if is_process_env(obj)) {
+            match &member.prop {
+                MemberProp::Ident(prop) => self.names.push(prop.sym.to_string()),
+                MemberProp::Computed(prop) => match &*prop.expr {
+                    Expr::Lit(Lit::Str(str)) => self.names.push(str.value.to_string()),
+                    expr => {
+                        self.has_dynamic_access = true;
+                        expr.visit_with(self);
+                    }
+                },
+                MemberProp::PrivateName(_) => {}
+            }
+            return;
+        }
+
+        // `process.env` itself escapes, e.g. `const { KEY } = process.env`.
+        if is_process_env(member) {
+            self.has_dynamic_access = true;
+            return;
+        }
+
+        member.visit_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::indexmap;
+    use indoc::indoc;
+    use turbo_tasks::Vc;
+
+    use super::client_env_references;
+    use crate::test_utils::{client_module, fixture, fixture_root, run};
+
+    #[tokio::test]
+    async fn collects_the_inlined_and_dynamic_env_references() {
+        let dir = fixture(&[
+            (
+                "entry.js",
+                indoc! {"
+                    import { read } from './lib.js';
+                    fetch(process.env.NEXT_PUBLIC_API_URL + process.env.NOT_DEFINED);
+                    read('SECRET');
+                "},
+            ),
+            ("lib.js", "export const read = (key) => process.env[key];"),
+        ]);
+        let root = dir.path().to_owned();
+        let references = run(async move {
+            let project_root = fixture_root(&root);
+            let define_env = Vc::cell(indexmap! {
+                "process.env.NEXT_PUBLIC_API_URL".to_string() => "\"/api\"".to_string(),
+            });
+            let entry = client_module(project_root, "entry.js", define_env);
+            Ok(
+                client_env_references(Vc::cell(vec![entry]), define_env, project_root)
+                    .await?
+                    .clone_value(),
+            )
+        })
+        .await
+        .unwrap();
+
+        // Only the variables of the define env are inlined.
+        assert_eq!(
+            references.inlined.into_iter().collect::<Vec<_>>(),
+            [(
+                "NEXT_PUBLIC_API_URL".to_string(),
+                vec!["entry.js".to_string()]
+            )]
+        );
+        assert_eq!(references.dynamic_access, ["lib.js"]);
+    }
+}
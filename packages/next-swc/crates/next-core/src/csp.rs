@@ -0,0 +1,132 @@
+use crate::{
+    next_config::Header,
+    route_matching::{route_matches, UrlSegment},
+};
+
+/// The CSP directives governing inline scripts, from most to least specific.
+const SCRIPT_DIRECTIVES: &[&str] = &["script-src-elem", "script-src", "default-src"];
+
+/// Returns whether the app route, e.g. `/blog/[slug]`, may be served with a
+/// Content Security Policy from `headers` that restricts scripts. The inline
+/// RSC bootstrap scripts of such a route need a per-request nonce.
+///
+/// A header can only be matched against the route statically, so a header
+/// applies whenever its `source` could match some url of the route. `has`
+/// and `missing` conditions are assumed to be satisfied. The sources of
+/// loaded headers are prefixed with `base_path`, unless they opt out of it.
+pub fn route_requires_nonce(headers: &[Header], base_path: Option<&str>, route: &str) -> bool {
+    headers.iter().any(|header| {
+        if !header
+            .headers
+            .iter()
+            .any(|header| is_script_csp(&header.key, &header.value))
+        {
+            return false;
+        }
+        let source = match base_path {
+            Some(base_path) if header.base_path != Some(false) => {
+                let Some(source) = header.source.strip_prefix(base_path) else {
+                    return false;
+                };
+                source
+            }
+            _ => header.source.as_str(),
+        };
+        source_may_match(source, route)
+    })
+}
+
+fn is_script_csp(key: &str, value: &str) -> bool {
+    if !key.eq_ignore_ascii_case("content-security-policy")
+        && !key.eq_ignore_ascii_case("content-security-policy-report-only")
+    {
+        return false;
+    }
+    value.split(';').any(|directive| {
+        directive.split_whitespace().next().is_some_and(|name| {
+            SCRIPT_DIRECTIVES
+                .iter()
+                .any(|directive| name.eq_ignore_ascii_case(directive))
+        })
+    })
+}
+
+/// Returns whether the `path-to-regexp` source, e.g. `/blog/:slug*`, could
+/// match a url of the route.
+fn source_may_match(source: &str, route: &str) -> bool {
+    route_matches(route, &UrlSegment::from_source(source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_script_csp, route_requires_nonce, source_may_match};
+    use crate::next_config::{Header, HeaderValue};
+
+    fn csp_header(source: &str, value: &str) -> Header {
+        Header {
+            source: source.to_string(),
+            base_path: None,
+            locale: None,
+            headers: vec![HeaderValue {
+                key: "Content-Security-Policy".to_string(),
+                value: value.to_string(),
+            }],
+            has: None,
+            missing: None,
+        }
+    }
+
+    #[test]
+    fn test_is_script_csp() {
+        assert!(is_script_csp(
+            "Content-Security-Policy",
+            "default-src 'self'"
+        ));
+        assert!(is_script_csp(
+            "content-security-policy-report-only",
+            "img-src *; script-src 'self'"
+        ));
+        assert!(!is_script_csp("Content-Security-Policy", "img-src *"));
+        assert!(!is_script_csp("X-Frame-Options", "default-src 'self'"));
+    }
+
+    #[test]
+    fn test_source_may_match() {
+        assert!(source_may_match("/", "/"));
+        assert!(source_may_match("/admin", "/(dashboard)/admin"));
+        assert!(source_may_match("/blog/:slug", "/blog/[slug]"));
+        assert!(source_may_match("/blog/:slug", "/blog/hello"));
+        assert!(source_may_match("/blog/hello", "/blog/[slug]"));
+        assert!(source_may_match("/:path*", "/"));
+        assert!(source_may_match("/admin/:path*", "/admin/settings/users"));
+        assert!(source_may_match("/admin/(.*)", "/admin/settings"));
+        assert!(source_may_match("/docs/a/b", "/docs/[...slug]"));
+        assert!(source_may_match("/shop", "/shop/[[...slug]]"));
+        assert!(!source_may_match("/admin", "/blog"));
+        assert!(!source_may_match("/admin/:path", "/admin"));
+        assert!(!source_may_match("/blog", "/blog/[slug]"));
+    }
+
+    #[test]
+    fn test_route_requires_nonce() {
+        let headers = vec![
+            csp_header("/admin/:path*", "script-src 'self'"),
+            csp_header("/images/:path*", "img-src 'self'"),
+        ];
+        assert!(route_requires_nonce(&headers, None, "/admin/users"));
+        assert!(!route_requires_nonce(&headers, None, "/images/gallery"));
+        assert!(!route_requires_nonce(&headers, None, "/blog"));
+        assert!(!route_requires_nonce(&[], None, "/admin"));
+    }
+
+    #[test]
+    fn test_route_requires_nonce_with_base_path() {
+        let headers = vec![csp_header("/docs/admin/:path*", "script-src 'self'")];
+        assert!(route_requires_nonce(&headers, Some("/docs"), "/admin"));
+        assert!(!route_requires_nonce(&headers, Some("/docs"), "/blog"));
+
+        let mut header = csp_header("/admin", "script-src 'self'");
+        header.base_path = Some(false);
+        assert!(route_requires_nonce(&[header], Some("/docs"), "/admin"));
+    }
+}
@@ -0,0 +1,195 @@
+use anyhow::Result;
+use swc_core::ecma::{
+    ast::{CallExpr, Callee, ExportAll, Expr, ImportDecl, Lit, NamedExport},
+    visit::{Visit, VisitWith},
+};
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::core::{
+        ident::AssetIdent,
+        issue::{Issue, IssueExt, IssueSeverity, OptionStyledString, StyledString},
+        module::{Module, Modules},
+    },
+};
+
+use crate::module_analysis::{all_referenced_modules, visit_ecmascript_module};
+
+/// Reports an error for each relative ESM import in the module graph of
+/// `entries` without a file extension, e.g. `import './utils'`, as native ESM
+/// in Node.js doesn't resolve them. Imports of directories, which rely on
+/// their index file, are reported as well.
+///
+/// `import` declarations, `export ... from` and `import()` with a string
+/// literal are checked. Bare specifiers, e.g. packages and `#imports`,
+/// type-only imports and modules in `node_modules` are skipped.
+#[turbo_tasks::function]
+pub async fn validate_import_extensions(entries: Vc<Modules>) -> Result<Vc<()>> {
+    let modules = all_referenced_modules(entries.await?.iter().copied()).await?;
+
+    modules
+        .into_iter()
+        .map(|module| async move {
+            if module.ident().path().await?.path.contains("node_modules/") {
+                return Ok(());
+            }
+            for request in module_esm_requests(module).await?.iter() {
+                if is_relative_request(request) && !has_file_extension(request) {
+                    ExtensionlessImportIssue {
+                        ident: module.ident(),
+                        request: request.clone(),
+                    }
+                    .cell()
+                    .emit();
+                }
+            }
+            Ok(())
+        })
+        .try_join()
+        .await?;
+
+    Ok(Default::default())
+}
+
+/// Collects the literal specifiers of the ESM imports and re-exports in the
+/// module.
+#[turbo_tasks::function]
+async fn module_esm_requests(module: Vc<Box<dyn Module>>) -> Result<Vc<Vec<String>>> {
+    let mut visitor = EsmRequestVisitor::default();
+    visit_ecmascript_module(module, &mut visitor).await?;
+    Ok(Vc::cell(visitor.requests))
+}
+
+/// Whether the request is relative to the importing module, rather than a bare
+/// specifier or an absolute URL.
+fn is_relative_request(request: &str) -> bool {
+    request == "." || request == ".." || request.starts_with("./") || request.starts_with("../")
+}
+
+/// Whether the last segment of the request has a file extension, ignoring its
+/// query and fragment.
+fn has_file_extension(request: &str) -> bool {
+    let path = request
+        .split_once(['?', '#'])
+        .map_or(request, |(path, _)| path);
+    let name = path.rsplit('/').next().unwrap_or(path);
+    match name.rsplit_once('.') {
+        // `.` and `..` are directories, and `.env` is a file without one.
+        Some((stem, extension)) => {
+            !stem.is_empty() && !stem.ends_with('.') && !extension.is_empty()
+        }
+        None => false,
+    }
+}
+
+#[derive(Default)]
+struct EsmRequestVisitor {
+    requests: Vec<String>,
+}
+
+impl Visit for EsmRequestVisitor {
+    fn visit_import_decl(&mut self, import_decl: &ImportDecl) {
+        if !import_decl.type_only {
+            self.requests.push(import_decl.src.value.to_string());
+        }
+    }
+
+    fn visit_named_export(&mut self, named_export: &NamedExport) {
+        if let Some(src) = &named_export.src {
+            if !named_export.type_only {
+                self.requests.push(src.value.to_string());
+            }
+        }
+    }
+
+    fn visit_export_all(&mut self, export_all: &ExportAll) {
+        if !export_all.type_only {
+            self.requests.push(export_all.src.value.to_string());
+        }
+    }
+
+    fn visit_call_expr(&mut self, call_expr: &CallExpr) {
+        if let Callee::Import(_) = call_expr.callee {
+            if let Some(Expr::Lit(Lit::Str(str))) = call_expr.args.first().map(|arg| &*arg.expr) {
+                self.requests.push(str.value.to_string());
+            }
+        }
+
+        call_expr.visit_children_with(self);
+    }
+}
+
+#[turbo_tasks::value(shared)]
+struct ExtensionlessImportIssue {
+    ident: Vc<AssetIdent>,
+    request: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ExtensionlessImportIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Text("Relative import ".to_string()),
+            StyledString::Code(self.request.clone()),
+            StyledString::Text(" has no file extension".to_string()),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("resolve".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.ident.path()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(
+                "Native ESM doesn't add extensions or index files to relative imports, so this \
+                 import fails in Node.js. Import the file with its extension, e.g. `./utils.js`."
+                    .to_string(),
+            )
+            .cell(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{has_file_extension, is_relative_request};
+
+    #[test]
+    fn test_is_relative_request() {
+        assert!(is_relative_request("./utils"));
+        assert!(is_relative_request("../lib/utils.js"));
+        assert!(is_relative_request(".."));
+        assert!(!is_relative_request("react"));
+        assert!(!is_relative_request("@scope/pkg/utils"));
+        assert!(!is_relative_request("#internal"));
+        assert!(!is_relative_request(".prettierrc"));
+    }
+
+    #[test]
+    fn test_has_file_extension() {
+        assert!(has_file_extension("./utils.js"));
+        assert!(has_file_extension("../styles/page.module.css"));
+        assert!(has_file_extension("./data.json?raw"));
+        assert!(!has_file_extension("./utils"));
+        assert!(!has_file_extension("./components/"));
+        assert!(!has_file_extension("../components"));
+        assert!(!has_file_extension("./.env"));
+        assert!(!has_file_extension(".."));
+        assert!(!has_file_extension("./lib.d/utils#hash"));
+    }
+}
@@ -9,12 +9,17 @@ mod app_segment_config;
 pub mod app_structure;
 mod babel;
 mod bootstrap;
+pub mod client_env;
+pub mod csp;
 mod embed_js;
 mod emit;
+pub mod import_extensions;
 pub mod instrumentation;
+pub mod link_validation;
 mod loader_tree;
 pub mod middleware;
 pub mod mode;
+pub mod module_analysis;
 pub mod next_app;
 mod next_build;
 pub mod next_client;
@@ -34,11 +39,17 @@ mod next_shared;
 pub mod next_telemetry;
 mod page_loader;
 pub mod pages_structure;
+pub mod resolve_conditions;
+mod route_matching;
 mod sass;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 pub mod tracing_presets;
 mod transform_options;
+pub mod typed_routes;
 pub mod url_node;
 pub mod util;
+pub mod virtual_modules;
 
 pub use app_segment_config::{
     parse_segment_config_from_loader_tree, parse_segment_config_from_source,
@@ -0,0 +1,279 @@
+use anyhow::Result;
+use swc_core::ecma::{
+    ast::{
+        CallExpr, Callee, Expr, ImportDecl, ImportSpecifier, JSXAttrName, JSXAttrOrSpread,
+        JSXAttrValue, JSXElementName, JSXExpr, JSXOpeningElement, KeyValueProp, Lit, MemberProp,
+        ModuleExportName, PropName,
+    },
+    visit::{Visit, VisitWith},
+};
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::core::{
+        ident::AssetIdent,
+        issue::{Issue, IssueExt, IssueSeverity, OptionStyledString, StyledString},
+        module::{Module, Modules},
+    },
+};
+
+use crate::{
+    module_analysis::{all_referenced_modules, string_literal, visit_ecmascript_module},
+    route_matching::{route_matches, UrlSegment},
+};
+
+/// Emits a warning for each statically analyzable internal link in the module
+/// graph of `entries` that doesn't match any of `routes`.
+///
+/// Links are the `href` of `next/link`'s `<Link>`, and the destinations of
+/// `redirect()` from `next/navigation` and `router.push()`/`router.replace()`.
+/// Dynamic hrefs, external URLs and links to files (e.g. `/robots.txt`) are
+/// skipped, as are modules in `node_modules`.
+#[turbo_tasks::function]
+pub async fn validate_links(entries: Vc<Modules>, routes: Vc<Vec<String>>) -> Result<Vc<()>> {
+    let routes = routes.await?;
+
+    let modules = all_referenced_modules(entries.await?.iter().copied()).await?;
+
+    for module in modules {
+        if module.ident().path().await?.path.contains("node_modules/") {
+            continue;
+        }
+
+        for href in module_link_hrefs(module).await?.iter() {
+            let Some(pathname) = internal_pathname(href) else {
+                continue;
+            };
+            let segments = UrlSegment::from_pathname(pathname);
+            if !routes.iter().any(|route| route_matches(route, &segments)) {
+                UnmatchedLinkIssue {
+                    ident: module.ident(),
+                    href: href.clone(),
+                }
+                .cell()
+                .emit();
+            }
+        }
+    }
+
+    Ok(Default::default())
+}
+
+/// Collects the literal link hrefs in the module.
+#[turbo_tasks::function]
+async fn module_link_hrefs(module: Vc<Box<dyn Module>>) -> Result<Vc<Vec<String>>> {
+    let mut visitor = LinkVisitor::default();
+    visit_ecmascript_module(module, &mut visitor).await?;
+
+    Ok(Vc::cell(visitor.hrefs))
+}
+
+/// Returns the pathname of `href` if it's an absolute link to a page of this
+/// app.
+fn internal_pathname(href: &str) -> Option<&str> {
+    if !href.starts_with('/') || href.starts_with("//") {
+        return None;
+    }
+    let pathname = &href[..href.find(['?', '#']).unwrap_or(href.len())];
+    // Links to files, e.g. in `public`, don't point to routes.
+    if pathname
+        .rsplit('/')
+        .next()
+        .is_some_and(|segment| segment.contains('.'))
+    {
+        return None;
+    }
+    Some(pathname)
+}
+
+#[derive(Default)]
+struct LinkVisitor {
+    /// The local names of `next/link`'s default export.
+    link_idents: Vec<String>,
+    /// The local names of `next/navigation`'s `redirect` and
+    /// `permanentRedirect`.
+    redirect_idents: Vec<String>,
+    hrefs: Vec<String>,
+}
+
+impl LinkVisitor {
+    fn is_link_ident(&self, sym: &str) -> bool {
+        self.link_idents.iter().any(|link| link == sym)
+    }
+
+    fn is_link(&self, expr: &Expr) -> bool {
+        matches!(expr, Expr::Ident(ident) if self.is_link_ident(&ident.sym))
+    }
+}
+
+impl Visit for LinkVisitor {
+    fn visit_import_decl(&mut self, decl: &ImportDecl) {
+        match &*decl.src.value {
+            "next/link" => {
+                for specifier in &decl.specifiers {
+                    if let ImportSpecifier::Default(specifier) = specifier {
+                        self.link_idents.push(specifier.local.sym.to_string());
+                    }
+                }
+            }
+            "next/navigation" => {
+                for specifier in &decl.specifiers {
+                    let ImportSpecifier::Named(specifier) = specifier else {
+                        continue;
+                    };
+                    let imported = match &specifier.imported {
+                        Some(ModuleExportName::Ident(ident)) => &*ident.sym,
+                        Some(ModuleExportName::Str(str)) => &*str.value,
+                        None => &*specifier.local.sym,
+                    };
+                    if imported == "redirect" || imported == "permanentRedirect" {
+                        self.redirect_idents.push(specifier.local.sym.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_jsx_opening_element(&mut self, element: &JSXOpeningElement) {
+        if let JSXElementName::Ident(ident) = &element.name {
+            if self.is_link_ident(&ident.sym) {
+                for attr in &element.attrs {
+                    let JSXAttrOrSpread::JSXAttr(attr) = attr else {
+                        continue;
+                    };
+                    let JSXAttrName::Ident(name) = &attr.name else {
+                        continue;
+                    };
+                    if &*name.sym != "href" {
+                        continue;
+                    }
+                    let href = match &attr.value {
+                        Some(JSXAttrValue::Lit(Lit::Str(str))) => Some(str.value.to_string()),
+                        Some(JSXAttrValue::JSXExprContainer(container)) => match &container.expr {
+                            JSXExpr::Expr(expr) => string_literal(expr),
+                            JSXExpr::JSXEmptyExpr(_) => None,
+                        },
+                        _ => None,
+                    };
+                    self.hrefs.extend(href);
+                }
+            }
+        }
+
+        element.visit_children_with(self);
+    }
+
+    fn visit_call_expr(&mut self, call_expr: &CallExpr) {
+        if let Callee::Expr(callee) = &call_expr.callee {
+            let is_navigation = match &**callee {
+                Expr::Ident(ident) => self
+                    .redirect_idents
+                    .iter()
+                    .any(|redirect| redirect.as_str() == &*ident.sym),
+                Expr::Member(member) => {
+                    let is_router = matches!(
+                        &*member.obj,
+                        Expr::Ident(obj) if &*obj.sym == "router" || &*obj.sym == "Router"
+                    );
+                    let is_push_or_replace = matches!(
+                        &member.prop,
+                        MemberProp::Ident(prop) if &*prop.sym == "push" || &*prop.sym == "replace"
+                    );
+                    is_router && is_push_or_replace
+                }
+                _ => false,
+            };
+            if is_navigation {
+                if let Some(arg) = call_expr.args.first().filter(|arg| arg.spread.is_none()) {
+                    self.hrefs.extend(string_literal(&arg.expr));
+                }
+            }
+        }
+
+        // After the JSX transform, `<Link href="...">` becomes e.g.
+        // `jsx(Link, { href: "..." })`.
+        if let [component, props, ..] = &*call_expr.args {
+            if let (true, Expr::Object(props)) = (self.is_link(&component.expr), &*props.expr) {
+                for prop in &props.props {
+                    let Some(KeyValueProp { key, value }) =
+                        prop.as_prop().and_then(|prop| prop.as_key_value())
+                    else {
+                        continue;
+                    };
+                    let is_href = match key {
+                        PropName::Ident(ident) => &*ident.sym == "href",
+                        PropName::Str(str) => &*str.value == "href",
+                        _ => false,
+                    };
+                    if is_href {
+                        self.hrefs.extend(string_literal(value));
+                    }
+                }
+            }
+        }
+
+        call_expr.visit_children_with(self);
+    }
+}
+
+#[turbo_tasks::value(shared)]
+struct UnmatchedLinkIssue {
+    ident: Vc<AssetIdent>,
+    href: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for UnmatchedLinkIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Text("Link to ".to_string()),
+            StyledString::Code(self.href.clone()),
+            StyledString::Text(" doesn't match any route".to_string()),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("links".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.ident.path()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(
+                "There is no page or route at this path, so navigating to it will show the not \
+                 found page. The route may have been renamed or removed."
+                    .to_string(),
+            )
+            .cell(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::internal_pathname;
+
+    #[test]
+    fn test_internal_pathname() {
+        assert_eq!(internal_pathname("/blog?page=2#top"), Some("/blog"));
+        assert_eq!(internal_pathname("/"), Some("/"));
+        assert_eq!(internal_pathname("https://nextjs.org/docs"), None);
+        assert_eq!(internal_pathname("//cdn.example.com/blog"), None);
+        assert_eq!(internal_pathname("blog"), None);
+        assert_eq!(internal_pathname("/robots.txt"), None);
+    }
+}
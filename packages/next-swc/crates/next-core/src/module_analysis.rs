@@ -0,0 +1,70 @@
+use anyhow::Result;
+use swc_core::ecma::{
+    ast::{Expr, Lit},
+    visit::{Visit, VisitWith},
+};
+use turbo_tasks::{
+    graph::{GraphTraversal, NonDeterministic},
+    Vc,
+};
+use turbopack_binding::turbopack::{
+    core::{module::Module, reference::primary_referenced_modules},
+    ecmascript::{parse::ParseResult, EcmascriptModuleAsset},
+};
+
+/// Collects the modules in the module graph of `entries`, including the
+/// entries, each once. The order isn't deterministic.
+pub async fn all_referenced_modules(
+    entries: impl IntoIterator<Item = Vc<Box<dyn Module>>>,
+) -> Result<Vec<Vc<Box<dyn Module>>>> {
+    Ok(NonDeterministic::new()
+        .skip_duplicates()
+        .visit(entries, get_referenced_modules)
+        .await
+        .completed()?
+        .into_inner()
+        .into_iter()
+        .collect())
+}
+
+async fn get_referenced_modules(
+    parent: Vc<Box<dyn Module>>,
+) -> Result<impl Iterator<Item = Vc<Box<dyn Module>>> + Send> {
+    primary_referenced_modules(parent)
+        .await
+        .map(|modules| modules.clone_value().into_iter())
+}
+
+/// Visits the program of `module` with `visitor` if it's an ECMAScript module,
+/// and returns it as one. Other modules and modules which fail to parse
+/// aren't visited.
+pub async fn visit_ecmascript_module(
+    module: Vc<Box<dyn Module>>,
+    visitor: &mut (impl Visit + Send),
+) -> Result<Option<Vc<EcmascriptModuleAsset>>> {
+    let Some(ecmascript_asset) =
+        Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await?
+    else {
+        return Ok(None);
+    };
+
+    // Parse errors are already reported elsewhere.
+    let ParseResult::Ok { program, .. } = &*ecmascript_asset.parse().await? else {
+        return Ok(None);
+    };
+
+    program.visit_with(visitor);
+    Ok(Some(ecmascript_asset))
+}
+
+/// The value of a string literal or a template literal without expressions.
+pub fn string_literal(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(Lit::Str(str)) => Some(str.value.to_string()),
+        Expr::Tpl(tpl) if tpl.exprs.is_empty() => {
+            Some(tpl.quasis.first()?.cooked.as_ref()?.to_string())
+        }
+        Expr::Paren(paren) => string_literal(&paren.expr),
+        _ => None,
+    }
+}
@@ -0,0 +1,130 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, Vc};
+use turbo_tasks_fs::FileSystemPath;
+
+use crate::app_structure::LoaderTree;
+
+/// A `loading`, `error` or `not-found` file which applies to a segment.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct AppBoundary {
+    /// The path of the file, relative to the project root.
+    pub path: String,
+    /// The file belongs to a parent segment rather than the segment itself.
+    pub inherited: bool,
+}
+
+/// The boundaries which apply to a single segment of an app page.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct AppSegmentBoundaries {
+    /// The path of the segment from the app directory, e.g. `/dashboard`.
+    /// Parallel routes other than `children` are named by their slot, e.g.
+    /// `/dashboard/@modal`.
+    pub segment: String,
+    pub loading: Option<AppBoundary>,
+    pub error: Option<AppBoundary>,
+    pub not_found: Option<AppBoundary>,
+}
+
+impl AppSegmentBoundaries {
+    /// The boundaries of `segment`, nested in `parent`, given the paths of its
+    /// own boundary files. Boundaries it doesn't have itself are inherited from
+    /// the parent.
+    pub fn nested(
+        parent: Option<&AppSegmentBoundaries>,
+        segment: String,
+        loading: Option<String>,
+        error: Option<String>,
+        not_found: Option<String>,
+    ) -> Self {
+        fn boundary(own: Option<String>, parent: Option<&AppBoundary>) -> Option<AppBoundary> {
+            own.map(|path| AppBoundary {
+                path,
+                inherited: false,
+            })
+            .or_else(|| {
+                parent.map(|parent| AppBoundary {
+                    path: parent.path.clone(),
+                    inherited: true,
+                })
+            })
+        }
+
+        Self {
+            segment,
+            loading: boundary(loading, parent.and_then(|parent| parent.loading.as_ref())),
+            error: boundary(error, parent.and_then(|parent| parent.error.as_ref())),
+            not_found: boundary(
+                not_found,
+                parent.and_then(|parent| parent.not_found.as_ref()),
+            ),
+        }
+    }
+}
+
+/// The boundaries of all segments of an app page, parents before their
+/// children.
+#[turbo_tasks::value(transparent)]
+pub struct AppPageBoundaries(Vec<AppSegmentBoundaries>);
+
+/// Finds the `loading`, `error` and `not-found` files which apply to each
+/// segment of the app page of the loader tree.
+///
+/// Only files in the project are recorded, so the `not-found` page Next.js
+/// provides for the root segment when the app has none isn't.
+#[turbo_tasks::function]
+pub async fn get_app_page_boundaries(
+    loader_tree: Vc<LoaderTree>,
+    project_path: Vc<FileSystemPath>,
+) -> Result<Vc<AppPageBoundaries>> {
+    let project_path = project_path.await?;
+
+    let mut boundaries: Vec<AppSegmentBoundaries> = vec![];
+    let mut queue = vec![(loader_tree, String::new(), None)];
+    while let Some((tree, segment, parent)) = queue.pop() {
+        let tree = tree.await?;
+        if tree.segment == "__PAGE__" || tree.segment == "__DEFAULT__" {
+            continue;
+        }
+        let components = tree.components.await?;
+
+        let segment_boundaries = AppSegmentBoundaries::nested(
+            parent.map(|parent: usize| &boundaries[parent]),
+            if segment.is_empty() {
+                "/".to_string()
+            } else {
+                segment.clone()
+            },
+            relative_path(&project_path, components.loading).await?,
+            relative_path(&project_path, components.error).await?,
+            relative_path(&project_path, components.not_found).await?,
+        );
+        let index = boundaries.len();
+        boundaries.push(segment_boundaries);
+
+        for (key, &child) in tree.parallel_routes.iter().rev() {
+            let child_segment = if key == "children" {
+                let child_name = &child.await?.segment;
+                format!("{segment}/{child_name}")
+            } else {
+                format!("{segment}/@{key}")
+            };
+            queue.push((child, child_segment, Some(index)));
+        }
+    }
+
+    Ok(Vc::cell(boundaries))
+}
+
+async fn relative_path(
+    project_path: &FileSystemPath,
+    path: Option<Vc<FileSystemPath>>,
+) -> Result<Option<String>> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let path = path.await?;
+    Ok(project_path.get_path_to(&path).map(|path| path.to_string()))
+}
@@ -0,0 +1,342 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_binding::turbopack::core::{
+    file_source::FileSource,
+    issue::{Issue, IssueExt, IssueSeverity, OptionStyledString, StyledString},
+};
+
+use crate::{
+    app_segment_config::{
+        parse_segment_config_from_source, NextRevalidate, NextSegmentConfig, NextSegmentDynamic,
+    },
+    app_structure::LoaderTree,
+};
+
+/// The `Cache-Control` Next.js serves dynamically rendered responses with.
+const NO_STORE_CACHE_CONTROL: &str = "private, no-cache, no-store, max-age=0, must-revalidate";
+
+/// The `s-maxage` of responses which are never revalidated, one year.
+const MAX_S_MAXAGE: u32 = 31536000;
+
+/// The caching intent a layout, page or route handler expresses with its
+/// segment config.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SegmentCacheConfig {
+    /// The path of the file, relative to the project root.
+    pub path: String,
+    pub dynamic: Option<NextSegmentDynamic>,
+    pub revalidate: Option<NextRevalidate>,
+}
+
+impl SegmentCacheConfig {
+    fn new(path: String, config: &NextSegmentConfig) -> Self {
+        Self {
+            path,
+            dynamic: config.dynamic,
+            revalidate: config.revalidate,
+        }
+    }
+
+    /// Whether the segment forces the route to be rendered dynamically
+    /// (`Some(true)`) or statically (`Some(false)`).
+    fn forces_dynamic(&self) -> Option<bool> {
+        match (self.dynamic, self.revalidate) {
+            (Some(NextSegmentDynamic::ForceDynamic), _) => Some(true),
+            (Some(NextSegmentDynamic::ForceStatic), _) => Some(false),
+            (_, Some(NextRevalidate::Frequency { seconds: 0 })) => Some(true),
+            _ => None,
+        }
+    }
+}
+
+/// The `Cache-Control` directives of a route, resolved from the segment
+/// configs of its layouts and page.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolvedCacheControl {
+    /// `None` when no segment expresses caching intent, which leaves it to
+    /// rendering.
+    pub cache_control: Option<String>,
+    /// The paths of a segment forcing dynamic or static rendering and of a
+    /// parent segment forcing the opposite, which it overrides.
+    pub conflict: Option<(String, String)>,
+}
+
+/// Resolves the `Cache-Control` of a route from the configs of its
+/// `segments`, parents before their children.
+///
+/// The nearest segment forcing dynamic or static rendering, with
+/// `dynamic = 'force-dynamic'`, `revalidate = 0` or `dynamic =
+/// 'force-static'`, overrides its parents. The lowest `revalidate` of all
+/// segments applies, like it does when rendering.
+pub fn resolve_cache_control(segments: &[SegmentCacheConfig]) -> ResolvedCacheControl {
+    let forcing = segments
+        .iter()
+        .rev()
+        .find_map(|segment| Some((segment, segment.forces_dynamic()?)));
+    let conflict = forcing.and_then(|(nearest, is_dynamic)| {
+        segments
+            .iter()
+            .rev()
+            .find(|segment| segment.forces_dynamic() == Some(!is_dynamic))
+            .map(|overridden| (nearest.path.clone(), overridden.path.clone()))
+    });
+
+    let revalidate = segments
+        .iter()
+        .filter_map(|segment| match segment.revalidate {
+            Some(NextRevalidate::Frequency { seconds }) if seconds > 0 => Some(seconds),
+            _ => None,
+        })
+        .min();
+    let is_cached_forever = segments.iter().any(|segment| {
+        matches!(
+            segment.revalidate,
+            Some(NextRevalidate::Never | NextRevalidate::ForceCache)
+        )
+    });
+
+    let cache_control = match (forcing.map(|(_, is_dynamic)| is_dynamic), revalidate) {
+        (Some(true), _) => Some(NO_STORE_CACHE_CONTROL.to_string()),
+        (_, Some(seconds)) => Some(format!("s-maxage={seconds}, stale-while-revalidate")),
+        (Some(false), None) => Some(format!("s-maxage={MAX_S_MAXAGE}, stale-while-revalidate")),
+        (None, None) if is_cached_forever => {
+            Some(format!("s-maxage={MAX_S_MAXAGE}, stale-while-revalidate"))
+        }
+        (None, None) => None,
+    };
+
+    ResolvedCacheControl {
+        cache_control,
+        conflict,
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionCacheControl(Option<String>);
+
+/// Computes the `Cache-Control` of the app page of the loader tree from the
+/// segment configs of its layouts and pages, including the ones of parallel
+/// routes. Conflicting directives are reported as an issue.
+#[turbo_tasks::function]
+pub async fn get_app_page_cache_control(
+    loader_tree: Vc<LoaderTree>,
+    project_path: Vc<FileSystemPath>,
+) -> Result<Vc<OptionCacheControl>> {
+    let mut segments = vec![];
+    let mut files = vec![];
+    let mut queue = vec![loader_tree];
+    while let Some(tree) = queue.pop() {
+        let tree = tree.await?;
+        let components = tree.components.await?;
+        for component in [components.layout, components.page].into_iter().flatten() {
+            files.push(component);
+        }
+        queue.extend(tree.parallel_routes.values().rev().copied());
+    }
+    for file in files.iter().copied() {
+        segments.push(segment_cache_config(file, project_path).await?);
+    }
+
+    Ok(resolve(&segments, &files))
+}
+
+/// Computes the `Cache-Control` of the app route handler at `path` from its
+/// segment config.
+#[turbo_tasks::function]
+pub async fn get_app_route_cache_control(
+    path: Vc<FileSystemPath>,
+    project_path: Vc<FileSystemPath>,
+) -> Result<Vc<OptionCacheControl>> {
+    let segments = [segment_cache_config(path, project_path).await?];
+    Ok(resolve(&segments, &[path]))
+}
+
+async fn segment_cache_config(
+    path: Vc<FileSystemPath>,
+    project_path: Vc<FileSystemPath>,
+) -> Result<SegmentCacheConfig> {
+    let config = parse_segment_config_from_source(Vc::upcast(FileSource::new(path))).await?;
+    let path = path.await?;
+    let relative_path = project_path
+        .await?
+        .get_path_to(&path)
+        .unwrap_or(&path.path)
+        .to_string();
+    Ok(SegmentCacheConfig::new(relative_path, &config))
+}
+
+fn resolve(
+    segments: &[SegmentCacheConfig],
+    files: &[Vc<FileSystemPath>],
+) -> Vc<OptionCacheControl> {
+    let resolved = resolve_cache_control(segments);
+    if let Some((nearest, overridden)) = resolved.conflict {
+        let file_path = segments
+            .iter()
+            .zip(files)
+            .find(|(segment, _)| segment.path == nearest)
+            .map(|(_, &file)| file);
+        if let Some(file_path) = file_path {
+            ConflictingCacheControlIssue {
+                file_path,
+                overridden,
+            }
+            .cell()
+            .emit();
+        }
+    }
+    Vc::cell(resolved.cache_control)
+}
+
+/// A segment forces dynamic or static rendering while a parent segment forces
+/// the opposite.
+#[turbo_tasks::value(shared)]
+struct ConflictingCacheControlIssue {
+    file_path: Vc<FileSystemPath>,
+    /// The path of the parent segment, relative to the project root.
+    overridden: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ConflictingCacheControlIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text("Conflicting caching in segment configs".to_string()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("config".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.file_path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Line(vec![
+                StyledString::Text(
+                    "The `dynamic` or `revalidate` export of this segment overrides the one of "
+                        .to_string(),
+                ),
+                StyledString::Code(self.overridden.clone()),
+                StyledString::Text(
+                    ", which forces the opposite rendering. The cache-control manifest uses this \
+                     segment's."
+                        .to_string(),
+                ),
+            ])
+            .cell(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resolve_cache_control, SegmentCacheConfig, NO_STORE_CACHE_CONTROL};
+    use crate::app_segment_config::{NextRevalidate, NextSegmentDynamic};
+
+    fn segment(
+        path: &str,
+        dynamic: Option<NextSegmentDynamic>,
+        revalidate: Option<NextRevalidate>,
+    ) -> SegmentCacheConfig {
+        SegmentCacheConfig {
+            path: path.to_string(),
+            dynamic,
+            revalidate,
+        }
+    }
+
+    #[test]
+    fn test_resolve_cache_control_force_static() {
+        let resolved = resolve_cache_control(&[
+            segment("app/layout.tsx", None, None),
+            segment("app/page.tsx", Some(NextSegmentDynamic::ForceStatic), None),
+        ]);
+        assert_eq!(
+            resolved.cache_control.as_deref(),
+            Some("s-maxage=31536000, stale-while-revalidate")
+        );
+        assert_eq!(resolved.conflict, None);
+    }
+
+    #[test]
+    fn test_resolve_cache_control_lowest_revalidate() {
+        let resolved = resolve_cache_control(&[
+            segment(
+                "app/layout.tsx",
+                None,
+                Some(NextRevalidate::Frequency { seconds: 60 }),
+            ),
+            segment(
+                "app/page.tsx",
+                Some(NextSegmentDynamic::ForceStatic),
+                Some(NextRevalidate::Frequency { seconds: 3600 }),
+            ),
+        ]);
+        assert_eq!(
+            resolved.cache_control.as_deref(),
+            Some("s-maxage=60, stale-while-revalidate")
+        );
+    }
+
+    #[test]
+    fn test_resolve_cache_control_conflict() {
+        let resolved = resolve_cache_control(&[
+            segment(
+                "app/layout.tsx",
+                Some(NextSegmentDynamic::ForceStatic),
+                None,
+            ),
+            segment(
+                "app/page.tsx",
+                None,
+                Some(NextRevalidate::Frequency { seconds: 0 }),
+            ),
+        ]);
+        assert_eq!(
+            resolved.cache_control.as_deref(),
+            Some(NO_STORE_CACHE_CONTROL)
+        );
+        assert_eq!(
+            resolved.conflict,
+            Some(("app/page.tsx".to_string(), "app/layout.tsx".to_string()))
+        );
+
+        let resolved = resolve_cache_control(&[
+            segment(
+                "app/layout.tsx",
+                Some(NextSegmentDynamic::ForceDynamic),
+                None,
+            ),
+            segment("app/page.tsx", Some(NextSegmentDynamic::ForceStatic), None),
+        ]);
+        assert_eq!(
+            resolved.cache_control.as_deref(),
+            Some("s-maxage=31536000, stale-while-revalidate")
+        );
+        assert_eq!(
+            resolved.conflict,
+            Some(("app/page.tsx".to_string(), "app/layout.tsx".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_resolve_cache_control_no_intent() {
+        let resolved = resolve_cache_control(&[
+            segment("app/layout.tsx", None, None),
+            segment("app/page.tsx", Some(NextSegmentDynamic::Auto), None),
+        ]);
+        assert_eq!(resolved, Default::default());
+    }
+}
@@ -1,4 +1,7 @@
+use std::future::Future;
+
 use anyhow::Result;
+use futures::{stream, StreamExt, TryStreamExt};
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use tracing::Instrument;
@@ -27,25 +30,56 @@ pub struct ClientReferenceChunks {
 #[turbo_tasks::value(transparent)]
 pub struct ClientReferencesChunks(IndexMap<ClientReferenceType, ClientReferenceChunks>);
 
+/// Maps all `items`, e.g. client reference types, with `f`, processing at most
+/// `concurrency` of them at once, or all of them when `concurrency` is `None`.
+///
+/// The results are always in the order of `items`, independently of the order
+/// in which they complete.
+async fn map_with_concurrency<T, R, F, Fut>(
+    items: impl IntoIterator<Item = T>,
+    concurrency: Option<usize>,
+    f: F,
+) -> Result<Vec<R>>
+where
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = Result<R>>,
+{
+    let futures = items.into_iter().map(f);
+    match concurrency {
+        Some(concurrency) => {
+            stream::iter(futures)
+                .buffered(concurrency.max(1))
+                .try_collect()
+                .await
+        }
+        None => futures.try_join().await,
+    }
+}
+
 /// Computes all client references chunks.
 ///
 /// This returns a map from client reference type to the chunks that reference
 /// type needs to load.
+///
+/// `concurrency` bounds the number of client references that are processed at
+/// once. It's unbounded when `None`.
 #[turbo_tasks::function]
 pub async fn get_app_client_references_chunks(
     base_ident: Vc<AssetIdent>,
     app_client_reference_types: Vc<ClientReferenceTypes>,
     client_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
     ssr_chunking_context: Vc<Box<dyn EcmascriptChunkingContext>>,
+    concurrency: Option<usize>,
 ) -> Result<Vc<ClientReferencesChunks>> {
     async move {
         // TODO Reconsider this. Maybe it need to be true in production.
         let separate_chunk_group_per_client_reference = false;
         let app_client_reference_types = app_client_reference_types.await?;
         if separate_chunk_group_per_client_reference {
-            let app_client_references_chunks: IndexMap<_, _> = app_client_reference_types
-                .iter()
-                .map(|client_reference_ty| async move {
+            let app_client_references_chunks: IndexMap<_, _> = map_with_concurrency(
+                app_client_reference_types.iter(),
+                concurrency,
+                |client_reference_ty| async move {
                     Ok((
                         *client_reference_ty,
                         match client_reference_ty {
@@ -74,17 +108,18 @@ pub async fn get_app_client_references_chunks(
                             }
                         },
                     ))
-                })
-                .try_join()
-                .await?
-                .into_iter()
-                .collect();
+                },
+            )
+            .await?
+            .into_iter()
+            .collect();
 
             Ok(Vc::cell(app_client_references_chunks))
         } else {
-            let ssr_modules = app_client_reference_types
-                .iter()
-                .map(|client_reference_ty| async move {
+            let ssr_modules: Vec<_> = map_with_concurrency(
+                app_client_reference_types.iter(),
+                concurrency,
+                |client_reference_ty| async move {
                     Ok(match client_reference_ty {
                         ClientReferenceType::EcmascriptClientReference(
                             ecmascript_client_reference,
@@ -95,16 +130,20 @@ pub async fn get_app_client_references_chunks(
                         }
                         _ => None,
                     })
-                })
-                .try_flat_join()
-                .await?;
+                },
+            )
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
             let ssr_entry_module = IncludeModulesModule::new(
                 base_ident.with_modifier(Vc::cell("client modules ssr".to_string())),
                 Vc::cell(ssr_modules),
             );
-            let client_modules = app_client_reference_types
-                .iter()
-                .map(|client_reference_ty| async move {
+            let client_modules = map_with_concurrency(
+                app_client_reference_types.iter(),
+                concurrency,
+                |client_reference_ty| async move {
                     Ok(match client_reference_ty {
                         ClientReferenceType::EcmascriptClientReference(
                             ecmascript_client_reference,
@@ -118,9 +157,9 @@ pub async fn get_app_client_references_chunks(
                             Vc::upcast(css_client_reference_ref.client_module)
                         }
                     })
-                })
-                .try_join()
-                .await?;
+                },
+            )
+            .await?;
             let client_entry_module = IncludeModulesModule::new(
                 base_ident.with_modifier(Vc::cell("client modules".to_string())),
                 Vc::cell(client_modules),
@@ -171,3 +210,50 @@ pub async fn get_app_server_reference_modules(
             .await?,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use super::map_with_concurrency;
+
+    #[tokio::test]
+    async fn maps_in_order_with_bounded_concurrency() {
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+        let items: Vec<u64> = (0..200).collect();
+
+        let results = map_with_concurrency(items.iter(), Some(4), |&item| {
+            let running = &running;
+            let max_running = &max_running;
+            async move {
+                let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now_running, Ordering::SeqCst);
+                // Later items complete first.
+                tokio::time::sleep(Duration::from_micros(200 - item)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok(item * 2)
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            results,
+            items.iter().map(|item| item * 2).collect::<Vec<_>>()
+        );
+        assert!(max_running.load(Ordering::SeqCst) <= 4);
+    }
+
+    #[tokio::test]
+    async fn maps_all_items_at_once_without_a_bound() {
+        let items: Vec<u64> = (0..200).collect();
+        let results = map_with_concurrency(items.iter(), None, |&item| async move { Ok(item) })
+            .await
+            .unwrap();
+        assert_eq!(results, items);
+    }
+}
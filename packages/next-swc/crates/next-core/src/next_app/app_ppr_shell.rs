@@ -0,0 +1,211 @@
+use anyhow::Result;
+use swc_core::ecma::{
+    ast::{
+        Expr, ImportDecl, ImportSpecifier, JSXElementName, JSXOpeningElement, ModuleExportName,
+        Program,
+    },
+    visit::{Visit, VisitWith},
+};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_binding::turbopack::{
+    core::{file_source::FileSource, source::Source},
+    ecmascript::parse::ParseResult,
+};
+
+use crate::{
+    app_segment_config::{parse_segment_config_from_source, NextRevalidate, NextSegmentDynamic},
+    app_structure::LoaderTree,
+    util::parse_js_source,
+};
+
+/// Functions that opt a component into dynamic rendering, by the module they
+/// are imported from.
+const DYNAMIC_FUNCTIONS: &[(&str, &str)] = &[
+    ("next/headers", "cookies"),
+    ("next/headers", "headers"),
+    ("next/headers", "draftMode"),
+    ("next/cache", "unstable_noStore"),
+];
+
+/// How a layout or page renders, as far as it can be statically determined.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Default)]
+pub struct SegmentRendering {
+    /// The module calls a dynamic function or opts out of static rendering
+    /// with its segment config.
+    pub is_dynamic: bool,
+    /// The module renders a `<Suspense>` boundary. Its dynamic parts are
+    /// assumed to be rendered inside of it.
+    pub has_suspense: bool,
+}
+
+/// How an app page can be prerendered.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AppPagePrerendering {
+    /// No layout or page is dynamic, so the whole page is prerendered.
+    Static,
+    /// All dynamic content is rendered inside of `<Suspense>` boundaries, so
+    /// the page can be prerendered as a static shell, with the dynamic
+    /// content streamed in at request time.
+    #[serde(rename_all = "camelCase")]
+    PartialPrerendering {
+        /// The files providing the `<Suspense>` boundaries the static shell
+        /// ends at, relative to the project root. Either `loading` files or
+        /// layouts and pages rendering `<Suspense>` themselves.
+        shell_boundaries: Vec<String>,
+    },
+    /// Dynamic content is rendered outside of any `<Suspense>` boundary, so
+    /// the page has no static shell.
+    Dynamic,
+}
+
+#[derive(Default)]
+struct RenderingVisitor {
+    /// The local names of the imported `DYNAMIC_FUNCTIONS`.
+    dynamic_function_idents: Vec<String>,
+    is_dynamic: bool,
+    has_suspense: bool,
+}
+
+impl Visit for RenderingVisitor {
+    fn visit_import_decl(&mut self, decl: &ImportDecl) {
+        for specifier in &decl.specifiers {
+            let ImportSpecifier::Named(specifier) = specifier else {
+                continue;
+            };
+            let imported = match &specifier.imported {
+                Some(ModuleExportName::Ident(ident)) => &*ident.sym,
+                Some(ModuleExportName::Str(str)) => &*str.value,
+                None => &*specifier.local.sym,
+            };
+            if DYNAMIC_FUNCTIONS.contains(&(&*decl.src.value, imported)) {
+                self.dynamic_function_idents
+                    .push(specifier.local.sym.to_string());
+            }
+        }
+    }
+
+    fn visit_jsx_opening_element(&mut self, element: &JSXOpeningElement) {
+        let name = match &element.name {
+            JSXElementName::Ident(ident) => &*ident.sym,
+            JSXElementName::JSXMemberExpr(member) => &*member.prop.sym,
+            JSXElementName::JSXNamespacedName(_) => "",
+        };
+        if name == "Suspense" {
+            self.has_suspense = true;
+        }
+
+        element.visit_children_with(self);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let Expr::Ident(ident) = expr {
+            if self
+                .dynamic_function_idents
+                .iter()
+                .any(|dynamic| dynamic.as_str() == &*ident.sym)
+            {
+                self.is_dynamic = true;
+            }
+        }
+
+        expr.visit_children_with(self);
+    }
+}
+
+/// Returns true for `export const dynamic = 'force-dynamic'` and
+/// `export const revalidate = 0`.
+async fn has_dynamic_segment_config(source: Vc<Box<dyn Source>>) -> Result<bool> {
+    let config = parse_segment_config_from_source(source).await?;
+    Ok(
+        matches!(config.dynamic, Some(NextSegmentDynamic::ForceDynamic))
+            || matches!(
+                config.revalidate,
+                Some(NextRevalidate::Frequency { seconds: 0 })
+            ),
+    )
+}
+
+/// Analyzes whether a layout or page renders dynamically and whether it
+/// renders a `<Suspense>` boundary.
+#[turbo_tasks::function]
+pub async fn get_segment_rendering(path: Vc<FileSystemPath>) -> Result<Vc<SegmentRendering>> {
+    let source: Vc<Box<dyn Source>> = Vc::upcast(FileSource::new(path));
+    let Some(result) = parse_js_source(source).await? else {
+        return Ok(SegmentRendering::default().cell());
+    };
+
+    let ParseResult::Ok {
+        program: Program::Module(module_ast),
+        ..
+    } = &*result
+    else {
+        return Ok(SegmentRendering::default().cell());
+    };
+
+    let mut visitor = RenderingVisitor::default();
+    module_ast.visit_with(&mut visitor);
+
+    Ok(SegmentRendering {
+        is_dynamic: visitor.is_dynamic || has_dynamic_segment_config(source).await?,
+        has_suspense: visitor.has_suspense,
+    }
+    .cell())
+}
+
+/// Classifies how the app page of the loader tree can be prerendered.
+///
+/// A `loading` file wraps the page and nested segments of its directory in a
+/// `<Suspense>` boundary, but not the layout next to it.
+#[turbo_tasks::function]
+pub async fn get_app_page_prerendering(
+    loader_tree: Vc<LoaderTree>,
+    project_path: Vc<FileSystemPath>,
+) -> Result<Vc<AppPagePrerendering>> {
+    let project_path = project_path.await?;
+
+    let mut shell_boundaries = vec![];
+    let mut queue = vec![(loader_tree, None)];
+    while let Some((tree, boundary)) = queue.pop() {
+        let tree = tree.await?;
+        let components = tree.components.await?;
+
+        for (component, boundary) in [
+            (components.layout, boundary),
+            (components.page, components.loading.or(boundary)),
+        ] {
+            let Some(component) = component else {
+                continue;
+            };
+            let rendering = get_segment_rendering(component).await?;
+            if !rendering.is_dynamic {
+                continue;
+            }
+            let boundary = boundary.or(rendering.has_suspense.then_some(component));
+            let Some(boundary) = boundary else {
+                return Ok(AppPagePrerendering::Dynamic.cell());
+            };
+            let boundary = boundary.await?;
+            shell_boundaries.push(
+                project_path
+                    .get_path_to(&boundary)
+                    .unwrap_or(&boundary.path)
+                    .to_string(),
+            );
+        }
+
+        let boundary = components.loading.or(boundary);
+        queue.extend(tree.parallel_routes.values().map(|&tree| (tree, boundary)));
+    }
+
+    if shell_boundaries.is_empty() {
+        return Ok(AppPagePrerendering::Static.cell());
+    }
+
+    shell_boundaries.sort();
+    shell_boundaries.dedup();
+    Ok(AppPagePrerendering::PartialPrerendering { shell_boundaries }.cell())
+}
@@ -0,0 +1,152 @@
+use anyhow::Result;
+use swc_core::ecma::ast::{
+    Decl, ExportSpecifier, ModuleDecl, ModuleExportName, ModuleItem, Pat, Program,
+};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_binding::turbopack::{core::file_source::FileSource, ecmascript::parse::ParseResult};
+
+use crate::util::parse_js_source;
+
+/// The HTTP methods a route handler can export.
+pub const HTTP_METHODS: &[&str] = &["GET", "HEAD", "OPTIONS", "POST", "PUT", "DELETE", "PATCH"];
+
+fn all_http_methods() -> Vec<String> {
+    HTTP_METHODS
+        .iter()
+        .map(|method| method.to_string())
+        .collect()
+}
+
+fn export_name(name: &ModuleExportName) -> &str {
+    match name {
+        ModuleExportName::Ident(ident) => &ident.sym,
+        ModuleExportName::Str(str) => &str.value,
+    }
+}
+
+/// Returns the HTTP methods exported by a route handler module, in the order
+/// of [HTTP_METHODS].
+///
+/// Exports that can't be statically determined, like `export * from "..."`
+/// or destructured exports, could define any method, so all methods are
+/// returned for them.
+fn exported_http_methods(body: &[ModuleItem]) -> Vec<String> {
+    let mut names = vec![];
+
+    for item in body {
+        let Some(decl) = item.as_module_decl() else {
+            continue;
+        };
+
+        match decl {
+            ModuleDecl::ExportDecl(export_decl) => match &export_decl.decl {
+                Decl::Fn(decl) => names.push(decl.ident.sym.to_string()),
+                Decl::Var(decl) => {
+                    for decl in &decl.decls {
+                        match &decl.name {
+                            Pat::Ident(ident) => names.push(ident.id.sym.to_string()),
+                            _ => return all_http_methods(),
+                        }
+                    }
+                }
+                _ => {}
+            },
+            ModuleDecl::ExportNamed(named_export) => {
+                for specifier in &named_export.specifiers {
+                    if let ExportSpecifier::Named(specifier) = specifier {
+                        names.push(
+                            export_name(specifier.exported.as_ref().unwrap_or(&specifier.orig))
+                                .to_string(),
+                        );
+                    }
+                }
+            }
+            ModuleDecl::ExportAll(_) => return all_http_methods(),
+            _ => {}
+        }
+    }
+
+    HTTP_METHODS
+        .iter()
+        .filter(|method| names.iter().any(|name| name == *method))
+        .map(|method| method.to_string())
+        .collect()
+}
+
+/// Analyzes which HTTP methods an app route handler exports, so that other
+/// methods can be answered with a 405.
+#[turbo_tasks::function]
+pub async fn get_app_route_methods(path: Vc<FileSystemPath>) -> Result<Vc<Vec<String>>> {
+    let Some(result) = parse_js_source(Vc::upcast(FileSource::new(path))).await? else {
+        return Ok(Vc::cell(all_http_methods()));
+    };
+
+    let ParseResult::Ok {
+        program: Program::Module(module_ast),
+        ..
+    } = &*result
+    else {
+        return Ok(Vc::cell(all_http_methods()));
+    };
+
+    Ok(Vc::cell(exported_http_methods(&module_ast.body)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_app_route_methods, HTTP_METHODS};
+    use crate::test_utils::{fixture, fixture_root, run};
+
+    async fn route_methods(files: &[(&str, &str)]) -> Vec<String> {
+        let dir = fixture(files);
+        let root = dir.path().to_owned();
+        run(async move {
+            let path = fixture_root(&root).join("app/api/route.ts".to_string());
+            Ok(get_app_route_methods(path).await?.clone_value())
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn lists_the_exported_methods() {
+        assert_eq!(
+            route_methods(&[(
+                "app/api/route.ts",
+                "export async function GET() { return Response.json([]) }\nexport const dynamic = \
+                 'force-dynamic'",
+            )])
+            .await,
+            ["GET"]
+        );
+        assert_eq!(
+            route_methods(&[(
+                "app/api/route.ts",
+                "async function handler() {}\nexport { handler as POST, handler as GET }",
+            )])
+            .await,
+            ["GET", "POST"]
+        );
+    }
+
+    #[tokio::test]
+    async fn assumes_all_methods_for_dynamic_exports() {
+        assert_eq!(
+            route_methods(&[
+                ("app/api/route.ts", "export * from './handlers'"),
+                ("app/api/handlers.ts", "export function GET() {}"),
+            ])
+            .await,
+            HTTP_METHODS
+        );
+        assert_eq!(
+            route_methods(&[(
+                "app/api/route.ts",
+                "export const { GET, ...rest } = createHandlers()",
+            )])
+            .await,
+            HTTP_METHODS
+        );
+    }
+}
@@ -9,6 +9,7 @@ use crate::next_app::{AppPage, PageSegment, PageType};
 
 pub mod image;
 pub mod route;
+pub mod static_metadata;
 
 pub static STATIC_LOCAL_METADATA: Lazy<HashMap<&'static str, &'static [&'static str]>> =
     Lazy::new(|| {
@@ -20,6 +20,7 @@ use crate::{
     app_structure::MetadataItem,
     mode::NextMode,
     next_app::{app_entry::AppEntry, app_route_entry::get_app_route_entry, AppPage, PageSegment},
+    next_config::NextConfig,
 };
 
 /// Computes the route source for a Next.js metadata file.
@@ -28,6 +29,7 @@ pub async fn get_app_metadata_route_source(
     page: AppPage,
     mode: NextMode,
     metadata: MetadataItem,
+    next_config: Vc<NextConfig>,
 ) -> Result<Vc<Box<dyn Source>>> {
     Ok(match metadata {
         MetadataItem::Static { path } => static_route_source(mode, path),
@@ -38,7 +40,7 @@ pub async fn get_app_metadata_route_source(
             if stem == "robots" || stem == "manifest" {
                 dynamic_text_route_source(path)
             } else if stem == "sitemap" {
-                dynamic_site_map_route_source(mode, path, page)
+                dynamic_site_map_route_source(mode, path, page, next_config)
             } else {
                 dynamic_image_route_source(path)
             }
@@ -54,11 +56,12 @@ pub fn get_app_metadata_route_entry(
     page: AppPage,
     mode: NextMode,
     metadata: MetadataItem,
+    next_config: Vc<NextConfig>,
 ) -> Vc<AppEntry> {
     get_app_route_entry(
         nodejs_context,
         edge_context,
-        get_app_metadata_route_source(page.clone(), mode, metadata),
+        get_app_metadata_route_source(page.clone(), mode, metadata, next_config),
         page,
         project_root,
     )
@@ -186,6 +189,7 @@ async fn dynamic_site_map_route_source(
     mode: NextMode,
     path: Vc<FileSystemPath>,
     page: AppPage,
+    next_config: Vc<NextConfig>,
 ) -> Result<Vc<Box<dyn Source>>> {
     let stem = path.file_stem().await?;
     let stem = stem.as_deref().unwrap_or_default();
@@ -213,11 +217,19 @@ async fn dynamic_site_map_route_source(
         };
     }
 
+    // With i18n, every url gets alternates for the locales it's available in.
+    let i18n = next_config.await?.i18n.as_ref().map(|i18n| {
+        serde_json::json!({
+            "locales": i18n.locales,
+            "defaultLocale": i18n.default_locale,
+        })
+    });
+
     let code = formatdoc! {
         r#"
             import {{ NextResponse }} from 'next/server'
             import * as _sitemapModule from {resource_path}
-            import {{ resolveRouteData }} from 'next/dist/build/webpack/loaders/metadata/resolve-route-data'
+            import {{ resolveRouteData, resolveSitemapLocaleAlternates }} from 'next/dist/build/webpack/loaders/metadata/resolve-route-data'
 
             const sitemapModule = {{ ..._sitemapModule }}
             const handler = sitemapModule.default
@@ -225,6 +237,7 @@ async fn dynamic_site_map_route_source(
             const contentType = {content_type}
             const cacheControl = {cache_control}
             const fileType = {file_type}
+            const i18n = {i18n}
 
             export async function GET(_, ctx) {{
                 const {{ __metadata_id__ = [], ...params }} = ctx.params || {{}}
@@ -249,7 +262,10 @@ async fn dynamic_site_map_route_source(
                     }}
                 }}
 
-                const data = await handler({{ id }})
+                let data = await handler({{ id }})
+                if (i18n) {{
+                    data = resolveSitemapLocaleAlternates(data, i18n)
+                }}
                 const content = resolveRouteData(data, fileType)
 
                 return new NextResponse(content, {{
@@ -266,6 +282,7 @@ async fn dynamic_site_map_route_source(
         content_type = StringifyJs(&content_type),
         file_type = StringifyJs(&stem),
         cache_control = StringifyJs(CACHE_HEADER_REVALIDATE),
+        i18n = StringifyJs(&i18n),
         static_generation_code = static_generation_code,
     };
 
@@ -0,0 +1,209 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use swc_core::ecma::ast::{
+    Decl, ExportSpecifier, Expr, ExprOrSpread, KeyValueProp, Lit, ModuleDecl, ModuleExportName,
+    ModuleItem, Program, Prop, PropName, PropOrSpread,
+};
+use turbo_tasks::{trace::TraceRawVcs, TryJoinIterExt, Vc};
+use turbo_tasks_fs::FileSystemPath;
+use turbopack_binding::turbopack::{
+    core::{file_source::FileSource, source::Source},
+    ecmascript::parse::ParseResult,
+};
+
+use crate::{app_structure::LoaderTree, util::parse_js_source};
+
+/// The metadata exported by a layout or page.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum SegmentMetadata {
+    /// Neither `metadata` nor `generateMetadata` is exported.
+    None,
+    /// `export const metadata = { ... }`, with a value that can be evaluated
+    /// at build time.
+    Static(Value),
+    /// `generateMetadata`, or a `metadata` export that can only be evaluated
+    /// at runtime.
+    Dynamic,
+}
+
+/// The metadata of a single layout or page of an app route.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TraceRawVcs)]
+#[serde(rename_all = "camelCase")]
+pub struct AppSegmentMetadata {
+    /// The path of the layout or page, relative to the project root.
+    pub path: String,
+    pub metadata: SegmentMetadata,
+}
+
+/// The metadata of all layouts and pages of an app route, from the root
+/// layout down to the page. Layouts and pages without any metadata are
+/// omitted.
+#[turbo_tasks::value(transparent)]
+pub struct AppRouteMetadata(Vec<AppSegmentMetadata>);
+
+/// Converts an expression to JSON if it only consists of literals, arrays and
+/// plain objects.
+fn static_value(expr: &Expr) -> Option<Value> {
+    Some(match expr {
+        Expr::Lit(Lit::Str(str)) => Value::String(str.value.to_string()),
+        Expr::Lit(Lit::Bool(bool)) => Value::Bool(bool.value),
+        Expr::Lit(Lit::Null(_)) => Value::Null,
+        Expr::Lit(Lit::Num(num)) => Value::Number(Number::from_f64(num.value)?),
+        Expr::Tpl(tpl) if tpl.exprs.is_empty() => {
+            Value::String(tpl.quasis.first()?.cooked.as_ref()?.to_string())
+        }
+        Expr::Array(array) => Value::Array(
+            array
+                .elems
+                .iter()
+                .map(|elem| match elem {
+                    Some(ExprOrSpread { spread: None, expr }) => static_value(expr),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?,
+        ),
+        Expr::Object(object) => {
+            let mut map = Map::new();
+            for prop in &object.props {
+                let PropOrSpread::Prop(prop) = prop else {
+                    return None;
+                };
+                let Prop::KeyValue(KeyValueProp { key, value }) = &**prop else {
+                    return None;
+                };
+                let key = match key {
+                    PropName::Ident(ident) => ident.sym.to_string(),
+                    PropName::Str(str) => str.value.to_string(),
+                    PropName::Num(num) => num.value.to_string(),
+                    _ => return None,
+                };
+                map.insert(key, static_value(value)?);
+            }
+            Value::Object(map)
+        }
+        Expr::Paren(paren) => static_value(&paren.expr),
+        Expr::TsAs(ts_as) => static_value(&ts_as.expr),
+        Expr::TsConstAssertion(ts_const) => static_value(&ts_const.expr),
+        Expr::TsSatisfies(ts_satisfies) => static_value(&ts_satisfies.expr),
+        _ => return None,
+    })
+}
+
+fn metadata_from_module(body: &[ModuleItem]) -> SegmentMetadata {
+    let mut metadata = SegmentMetadata::None;
+
+    for item in body {
+        match item.as_module_decl() {
+            Some(ModuleDecl::ExportDecl(export_decl)) => match &export_decl.decl {
+                Decl::Fn(decl) if &*decl.ident.sym == "generateMetadata" => {
+                    return SegmentMetadata::Dynamic;
+                }
+                Decl::Var(decl) => {
+                    for decl in &decl.decls {
+                        let Some(ident) = decl.name.as_ident() else {
+                            continue;
+                        };
+                        match &*ident.sym {
+                            "generateMetadata" => return SegmentMetadata::Dynamic,
+                            "metadata" => {
+                                metadata = decl
+                                    .init
+                                    .as_deref()
+                                    .and_then(static_value)
+                                    .map_or(SegmentMetadata::Dynamic, SegmentMetadata::Static);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            },
+            // `export { metadata }` can't be analyzed without following the binding, so
+            // it's treated like a dynamic export.
+            Some(ModuleDecl::ExportNamed(named_export)) => {
+                for specifier in &named_export.specifiers {
+                    let ExportSpecifier::Named(specifier) = specifier else {
+                        continue;
+                    };
+                    let name = match specifier.exported.as_ref().unwrap_or(&specifier.orig) {
+                        ModuleExportName::Ident(ident) => &*ident.sym,
+                        ModuleExportName::Str(str) => &*str.value,
+                    };
+                    if name == "metadata" || name == "generateMetadata" {
+                        return SegmentMetadata::Dynamic;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    metadata
+}
+
+/// Extracts the `metadata` export of a layout or page.
+#[turbo_tasks::function]
+pub async fn parse_segment_metadata_from_source(
+    source: Vc<Box<dyn Source>>,
+) -> Result<Vc<SegmentMetadata>> {
+    // Metadata can only be exported from JavaScript files.
+    let Some(result) = parse_js_source(source).await? else {
+        return Ok(SegmentMetadata::None.cell());
+    };
+
+    let ParseResult::Ok {
+        program: Program::Module(module_ast),
+        ..
+    } = &*result
+    else {
+        return Ok(SegmentMetadata::None.cell());
+    };
+
+    Ok(metadata_from_module(&module_ast.body).cell())
+}
+
+/// Collects the metadata of all layouts and pages in the loader tree, from the
+/// root layout down to the page.
+#[turbo_tasks::function]
+pub async fn parse_metadata_from_loader_tree(
+    loader_tree: Vc<LoaderTree>,
+    project_path: Vc<FileSystemPath>,
+) -> Result<Vc<AppRouteMetadata>> {
+    let project_path_ref = project_path.await?;
+    let loader_tree = loader_tree.await?;
+    let components = loader_tree.components.await?;
+
+    let mut metadata = vec![];
+    for component in [components.layout, components.page].into_iter().flatten() {
+        let segment_metadata =
+            parse_segment_metadata_from_source(Vc::upcast(FileSource::new(component)))
+                .await?
+                .clone_value();
+        if segment_metadata == SegmentMetadata::None {
+            continue;
+        }
+        let component = component.await?;
+        metadata.push(AppSegmentMetadata {
+            path: project_path_ref
+                .get_path_to(&component)
+                .unwrap_or(&component.path)
+                .to_string(),
+            metadata: segment_metadata,
+        });
+    }
+
+    let parallel_routes_metadata = loader_tree
+        .parallel_routes
+        .values()
+        .map(|&tree| parse_metadata_from_loader_tree(tree, project_path))
+        .try_join()
+        .await?;
+    for parallel_route_metadata in parallel_routes_metadata {
+        metadata.extend(parallel_route_metadata.iter().cloned());
+    }
+
+    Ok(Vc::cell(metadata))
+}
@@ -1,8 +1,12 @@
+pub mod app_boundaries;
+pub mod app_cache_control;
 pub mod app_client_references_chunks;
 pub mod app_client_shared_chunks;
 pub mod app_entry;
 pub mod app_page_entry;
+pub mod app_ppr_shell;
 pub mod app_route_entry;
+pub mod app_route_methods;
 pub mod include_modules_module;
 pub mod metadata;
 
@@ -2,7 +2,7 @@ use core::result::Result::Ok;
 
 use anyhow::Result;
 use indexmap::IndexMap;
-use turbo_tasks::{Value, Vc};
+use turbo_tasks::{TaskInput, Value, Vc};
 use turbo_tasks_fs::FileSystem;
 use turbopack_binding::{
     turbo::{tasks_env::EnvMap, tasks_fs::FileSystemPath},
@@ -57,7 +57,7 @@ use crate::{
     },
     sass::maybe_add_sass_loader,
     transform_options::{
-        get_decorators_transform_options, get_jsx_transform_options,
+        get_decorators_transform_options, get_jsx_transform_options, get_tsconfig_path,
         get_typescript_transform_options,
     },
     util::foreign_code_context_condition,
@@ -162,6 +162,7 @@ pub async fn get_client_resolve_options_context(
     };
     Ok(ResolveOptionsContext {
         enable_typescript: true,
+        tsconfig_path: get_tsconfig_path(project_path, next_config).await?,
         enable_react: true,
         enable_mjs_extension: true,
         rules: vec![(
@@ -186,8 +187,8 @@ pub async fn get_client_module_options_context(
     let resolve_options_context =
         get_client_resolve_options_context(project_path, ty, mode, next_config, execution_context);
 
-    let tsconfig = get_typescript_transform_options(project_path);
-    let decorators_options = get_decorators_transform_options(project_path);
+    let tsconfig = get_typescript_transform_options(project_path, next_config);
+    let decorators_options = get_decorators_transform_options(project_path, next_config);
     let enable_mdx_rs = if *next_config.mdx_rs().await? {
         Some(
             MdxTransformModuleOptions {
@@ -245,7 +246,7 @@ pub async fn get_client_module_options_context(
         *get_relay_transform_plugin(next_config).await?,
         *get_emotion_transform_plugin(next_config).await?,
         *get_styled_components_transform_plugin(next_config).await?,
-        *get_styled_jsx_transform_plugin(use_lightningcss).await?,
+        *get_styled_jsx_transform_plugin(next_config, use_lightningcss).await?,
     ]
     .into_iter()
     .flatten()
@@ -314,13 +315,43 @@ pub async fn get_client_module_options_context(
     Ok(module_options_context)
 }
 
+/// The variant of the Turbopack runtime which loads the client chunks.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Copy, Clone, TaskInput, Hash)]
+pub enum ClientRuntime {
+    /// The runtime of `next build`.
+    Production,
+    /// The runtime of `next dev`, which registers the chunk list of every
+    /// chunk group to apply hot module replacement updates.
+    Development,
+}
+
+impl ClientRuntime {
+    /// Returns the runtime Next.js uses in `mode`.
+    pub fn for_mode(mode: NextMode) -> Self {
+        match mode {
+            NextMode::Development => ClientRuntime::Development,
+            NextMode::Build => ClientRuntime::Production,
+        }
+    }
+
+    /// Returns the name of the runtime, e.g. as recorded in
+    /// `build-manifest.json`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClientRuntime::Production => "production",
+            ClientRuntime::Development => "development",
+        }
+    }
+}
+
 #[turbo_tasks::function]
 pub async fn get_client_chunking_context(
     project_path: Vc<FileSystemPath>,
     client_root: Vc<FileSystemPath>,
     asset_prefix: Vc<Option<String>>,
     environment: Vc<Environment>,
-    mode: NextMode,
+    runtime: ClientRuntime,
 ) -> Result<Vc<Box<dyn EcmascriptChunkingContext>>> {
     let mut builder = DevChunkingContext::builder(
         project_path,
@@ -332,7 +363,7 @@ pub async fn get_client_chunking_context(
     .chunk_base_path(asset_prefix)
     .asset_base_path(asset_prefix);
 
-    if matches!(mode, NextMode::Development) {
+    if matches!(runtime, ClientRuntime::Development) {
         builder = builder.hot_module_replacement();
     }
 
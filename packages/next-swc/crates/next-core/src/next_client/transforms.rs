@@ -8,6 +8,7 @@ use crate::{
     next_client::context::ClientContextType,
     next_config::NextConfig,
     next_shared::transforms::{
+        get_css_module_named_imports_rule, get_mdx_frontmatter_rule,
         get_next_dynamic_transform_rule, get_next_font_transform_rule, get_next_image_rule,
         get_next_modularize_imports_rule, get_next_pages_transforms_rule,
         get_server_actions_transform_rule, server_actions::ActionsTransform,
@@ -25,14 +26,29 @@ pub async fn get_next_client_transforms_rules(
 
     let modularize_imports_config = &next_config.await?.modularize_imports;
     if let Some(modularize_imports_config) = modularize_imports_config {
-        rules.push(get_next_modularize_imports_rule(
-            modularize_imports_config,
-            *next_config.mdx_rs().await?,
-        ));
+        if !*next_config
+            .is_transform_disabled("modularize-imports".to_string())
+            .await?
+        {
+            rules.push(get_next_modularize_imports_rule(
+                modularize_imports_config,
+                *next_config.mdx_rs().await?,
+            ));
+        }
     }
 
     let mdx_rs = *next_config.mdx_rs().await?;
     rules.push(get_next_font_transform_rule(mdx_rs));
+    if mdx_rs
+        && !*next_config
+            .is_transform_disabled("mdx-frontmatter".to_string())
+            .await?
+    {
+        rules.push(get_mdx_frontmatter_rule());
+    }
+    if *next_config.css_modules_named_exports().await? {
+        rules.push(get_css_module_named_imports_rule(mdx_rs));
+    }
 
     let pages_dir = match context_ty {
         ClientContextType::Pages { pages_dir } => {
@@ -1,5 +1,7 @@
 pub(crate) mod css_client_reference;
 pub(crate) mod ecmascript_client_reference;
+pub(crate) mod server_packages_in_client;
+pub(crate) mod validate_client_references;
 pub(crate) mod visit_client_reference;
 
 pub use css_client_reference::css_client_reference_module::CssClientReferenceModule;
@@ -7,6 +9,8 @@ pub use ecmascript_client_reference::{
     ecmascript_client_reference_module::EcmascriptClientReferenceModule,
     ecmascript_client_reference_transition::NextEcmascriptClientReferenceTransition,
 };
+pub use server_packages_in_client::check_server_packages_in_client;
+pub use validate_client_references::validate_client_references;
 pub use visit_client_reference::{
     ClientReference, ClientReferenceGraph, ClientReferenceType, ClientReferenceTypes,
     ClientReferences,
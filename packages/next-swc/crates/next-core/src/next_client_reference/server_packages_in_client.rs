@@ -0,0 +1,196 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::Result;
+use turbo_tasks::{TryJoinIterExt, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{FileContent, FileSystemPath},
+    turbopack::core::{
+        asset::{Asset, AssetContent},
+        issue::{Issue, IssueExt, IssueSeverity, OptionStyledString, StyledString},
+        module::{Module, Modules},
+    },
+};
+
+use crate::{
+    module_analysis::all_referenced_modules, next_config::NextConfig, util::load_next_js_templateon,
+};
+
+/// Warns when the module graph of a client component, i.e. a module marked
+/// with `"use client"`, includes more than `size_limit` bytes of a package
+/// meant for the server, which likely got there through a shared helper
+/// importing it and bloats the client bundle.
+///
+/// Packages are considered server packages when Next.js keeps them external
+/// for server components, i.e. the ones of `server-external-packages.json`,
+/// like `sharp`, and `experimental.serverComponentsExternalPackages`. The size
+/// is the size of their sources before minification. Packages in
+/// `allowed_packages` aren't reported, e.g. for libraries which are
+/// legitimately used on the client.
+///
+/// Each package is reported once, with the first client component bundling
+/// too much of it.
+#[turbo_tasks::function]
+pub async fn check_server_packages_in_client(
+    client_entries: Vc<Modules>,
+    project_path: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+    size_limit: u64,
+    allowed_packages: Vc<Vec<String>>,
+) -> Result<Vc<()>> {
+    let mut server_packages: HashSet<String> = load_next_js_templateon::<Vec<String>>(
+        project_path,
+        "dist/lib/server-external-packages.json".to_string(),
+    )
+    .await?
+    .into_iter()
+    .collect();
+    server_packages.extend(
+        next_config
+            .server_component_externals()
+            .await?
+            .iter()
+            .cloned(),
+    );
+    for package in allowed_packages.await?.iter() {
+        server_packages.remove(package);
+    }
+    let server_packages = &server_packages;
+
+    let mut reported = HashSet::new();
+    for &client_entry in client_entries.await?.iter() {
+        let modules = all_referenced_modules([client_entry]).await?;
+
+        let sizes = modules
+            .into_iter()
+            .map(|module| async move {
+                let path = module.ident().path().await?;
+                let Some(package) = package_name(&path.path) else {
+                    return Ok(None);
+                };
+                if !server_packages.contains(package) {
+                    return Ok(None);
+                }
+                Ok(Some((package.to_string(), module_size(module).await?)))
+            })
+            .try_join()
+            .await?;
+
+        let mut package_sizes = BTreeMap::new();
+        for (package, size) in sizes.into_iter().flatten() {
+            *package_sizes.entry(package).or_insert(0) += size;
+        }
+
+        for (package, size) in package_sizes {
+            if size <= size_limit || !reported.insert(package.clone()) {
+                continue;
+            }
+            ServerPackageInClientIssue {
+                client_component: client_entry.ident().path(),
+                package,
+                size,
+            }
+            .cell()
+            .emit();
+        }
+    }
+
+    Ok(Default::default())
+}
+
+async fn module_size(module: Vc<Box<dyn Module>>) -> Result<u64> {
+    Ok(match &*module.content().await? {
+        AssetContent::File(file) => match &*file.await? {
+            FileContent::Content(file) => file.content().len() as u64,
+            FileContent::NotFound => 0,
+        },
+        AssetContent::Redirect { .. } => 0,
+    })
+}
+
+/// The name of the package the file at `path` belongs to, e.g. `@aws-sdk/core`
+/// for `node_modules/@aws-sdk/core/dist/index.js`. For nested `node_modules`,
+/// it's the innermost package.
+fn package_name(path: &str) -> Option<&str> {
+    let (_, package_path) = path.rsplit_once("node_modules/")?;
+    let mut segments = package_path.split('/');
+    let name = segments.next()?;
+    if !name.starts_with('@') {
+        return Some(name);
+    }
+    let scoped_name = segments.next()?;
+    Some(&package_path[..name.len() + 1 + scoped_name.len()])
+}
+
+#[turbo_tasks::value(shared)]
+struct ServerPackageInClientIssue {
+    client_component: Vc<FileSystemPath>,
+    package: String,
+    size: u64,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ServerPackageInClientIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Text("Server package ".to_string()),
+            StyledString::Code(self.package.clone()),
+            StyledString::Text(format!(" adds {} bytes to the client bundle", self.size)),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("output".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.client_component
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Line(vec![
+                StyledString::Code(self.package.clone()),
+                StyledString::Text(
+                    " is meant to run on the server, but this client component bundles it, e.g. \
+                     through a module it imports. Move the code using it to a server component, \
+                     or allow the package if it's meant for the client."
+                        .to_string(),
+                ),
+            ])
+            .cell(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::package_name;
+
+    #[test]
+    fn test_package_name() {
+        assert_eq!(
+            package_name("node_modules/sharp/lib/index.js"),
+            Some("sharp")
+        );
+        assert_eq!(
+            package_name("node_modules/@aws-sdk/client-s3/dist/index.js"),
+            Some("@aws-sdk/client-s3")
+        );
+        assert_eq!(
+            package_name("node_modules/a/node_modules/@prisma/client/index.js"),
+            Some("@prisma/client")
+        );
+        assert_eq!(package_name("app/page.tsx"), None);
+        assert_eq!(package_name("node_modules/@scope"), None);
+    }
+}
@@ -0,0 +1,578 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use swc_core::ecma::{
+    ast::{
+        CallExpr, Callee, DefaultDecl, ExportAll, ExportDefaultDecl, ExportDefaultExpr, Expr,
+        FnDecl, ImportDecl, ImportSpecifier, JSXElement, JSXFragment, Lit, ModuleExportName,
+        NamedExport, Pat, VarDeclarator,
+    },
+    visit::{Visit, VisitWith},
+};
+use turbo_tasks::{trace::TraceRawVcs, TryJoinIterExt, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::core::{
+        issue::{Issue, IssueExt, IssueSeverity, OptionStyledString, StyledString},
+        module::{Module, Modules},
+    },
+};
+
+use crate::module_analysis::{all_referenced_modules, visit_ecmascript_module};
+
+/// Modules which only work in server components, e.g. because they read the
+/// request like `cookies()` and `headers()` do.
+const SERVER_ONLY_REQUESTS: &[&str] = &["next/headers", "server-only"];
+
+/// The Node.js builtins without a browser polyfill. The others, like `path`,
+/// are aliased to polyfills for client code, see `NEXT_ALIASES`.
+const NODE_ONLY_BUILTINS: &[&str] = &[
+    "async_hooks",
+    "child_process",
+    "cluster",
+    "dgram",
+    "diagnostics_channel",
+    "dns",
+    "fs",
+    "http2",
+    "inspector",
+    "module",
+    "net",
+    "perf_hooks",
+    "readline",
+    "repl",
+    "tls",
+    "trace_events",
+    "v8",
+    "wasi",
+    "worker_threads",
+];
+
+/// Reports errors for code in the module graphs of the client components
+/// `client_entries`, i.e. the modules marked with `"use client"`, which only
+/// works in server components:
+/// - imports of a server-only module, like `next/headers` or the `fs` builtin.
+///   Every import is checked, including `require()`s behind `typeof window`
+///   checks, as they're still bundled.
+/// - async components, which React can't render on the client. A function is
+///   considered a component when it returns JSX and is named like one, or is
+///   the default export, so that async helpers and route handlers like `GET`
+///   aren't reported.
+///
+/// Only the client side is checked, so a module shared with server components
+/// may import `next/headers` as long as no client component imports it.
+/// Modules in `node_modules` are skipped.
+#[turbo_tasks::function]
+pub async fn validate_client_references(client_entries: Vc<Modules>) -> Result<Vc<()>> {
+    // A module imported by several client components is reported once, with
+    // the first client component importing it.
+    let mut reported = HashSet::new();
+    for &client_entry in client_entries.await?.iter() {
+        let modules = all_referenced_modules([client_entry])
+            .await?
+            .into_iter()
+            .filter(|module| reported.insert(*module))
+            .collect::<Vec<_>>();
+
+        let results = modules
+            .iter()
+            .map(|&module| async move {
+                if module.ident().path().await?.path.contains("node_modules/") {
+                    return Ok(None);
+                }
+                Ok(Some((
+                    module,
+                    server_only_imports(module).await?,
+                    async_components(module).await?,
+                )))
+            })
+            .try_join()
+            .await?;
+
+        for (module, imports, components) in results.into_iter().flatten() {
+            for import in imports.iter() {
+                ServerOnlyImportIssue {
+                    path: module.ident().path(),
+                    client_component: client_entry.ident().path(),
+                    import: import.clone(),
+                }
+                .cell()
+                .emit();
+            }
+            for component in components.iter() {
+                AsyncClientComponentIssue {
+                    path: module.ident().path(),
+                    client_component: client_entry.ident().path(),
+                    component: component.clone(),
+                }
+                .cell()
+                .emit();
+            }
+        }
+    }
+
+    Ok(Default::default())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+enum ServerOnlyKind {
+    /// A module like `next/headers`.
+    Module,
+    /// A Node.js builtin like `fs`.
+    NodeBuiltin,
+}
+
+/// Returns why the request can't be imported from a client component, if it
+/// can't.
+fn server_only_kind(request: &str) -> Option<ServerOnlyKind> {
+    if SERVER_ONLY_REQUESTS.contains(&request) {
+        return Some(ServerOnlyKind::Module);
+    }
+    // Subpaths like `fs/promises` aren't polyfilled either.
+    let builtin = request.strip_prefix("node:").unwrap_or(request);
+    let builtin = builtin.split('/').next().unwrap_or(builtin);
+    NODE_ONLY_BUILTINS
+        .contains(&builtin)
+        .then_some(ServerOnlyKind::NodeBuiltin)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+struct ServerOnlyImport {
+    request: String,
+    kind: ServerOnlyKind,
+    /// The imported names, empty for side effect and namespace imports.
+    names: Vec<String>,
+}
+
+#[turbo_tasks::value(transparent)]
+struct ServerOnlyImports(Vec<ServerOnlyImport>);
+
+#[turbo_tasks::function]
+async fn server_only_imports(module: Vc<Box<dyn Module>>) -> Result<Vc<ServerOnlyImports>> {
+    let mut visitor = ServerOnlyImportVisitor::default();
+    visit_ecmascript_module(module, &mut visitor).await?;
+    Ok(Vc::cell(visitor.imports))
+}
+
+#[derive(Default)]
+struct ServerOnlyImportVisitor {
+    imports: Vec<ServerOnlyImport>,
+}
+
+impl ServerOnlyImportVisitor {
+    fn add(&mut self, request: &str, names: Vec<String>) {
+        if let Some(kind) = server_only_kind(request) {
+            self.imports.push(ServerOnlyImport {
+                request: request.to_string(),
+                kind,
+                names,
+            });
+        }
+    }
+}
+
+fn export_name(name: &ModuleExportName) -> String {
+    match name {
+        ModuleExportName::Ident(ident) => ident.sym.to_string(),
+        ModuleExportName::Str(str) => str.value.to_string(),
+    }
+}
+
+impl Visit for ServerOnlyImportVisitor {
+    fn visit_import_decl(&mut self, decl: &ImportDecl) {
+        if decl.type_only {
+            return;
+        }
+        let names = decl
+            .specifiers
+            .iter()
+            .filter_map(|specifier| match specifier {
+                ImportSpecifier::Named(named) if !named.is_type_only => Some(
+                    named
+                        .imported
+                        .as_ref()
+                        .map_or_else(|| named.local.sym.to_string(), export_name),
+                ),
+                ImportSpecifier::Default(_) => Some("default".to_string()),
+                _ => None,
+            })
+            .collect();
+        self.add(&decl.src.value, names);
+    }
+
+    fn visit_export_all(&mut self, export: &ExportAll) {
+        if !export.type_only {
+            self.add(&export.src.value, vec![]);
+        }
+    }
+
+    fn visit_named_export(&mut self, export: &NamedExport) {
+        if let Some(src) = &export.src {
+            if !export.type_only {
+                self.add(&src.value, vec![]);
+            }
+        }
+    }
+
+    fn visit_call_expr(&mut self, call_expr: &CallExpr) {
+        let is_require = matches!(
+            &call_expr.callee,
+            Callee::Expr(callee) if matches!(&**callee, Expr::Ident(ident) if &*ident.sym == "require")
+        );
+        if is_require || matches!(call_expr.callee, Callee::Import(_)) {
+            if let Some(Expr::Lit(Lit::Str(request))) = call_expr.args.first().map(|arg| &*arg.expr)
+            {
+                self.add(&request.value, vec![]);
+            }
+        }
+
+        call_expr.visit_children_with(self);
+    }
+}
+
+/// Returns whether the function name is a component's, i.e. capitalized.
+fn is_component_name(name: &str) -> bool {
+    name.starts_with(|char: char| char.is_ascii_uppercase())
+}
+
+/// Returns the names of the async components in the module. An anonymous
+/// default export is named `default`.
+#[turbo_tasks::function]
+async fn async_components(module: Vc<Box<dyn Module>>) -> Result<Vc<Vec<String>>> {
+    let mut visitor = AsyncComponentVisitor::default();
+    visit_ecmascript_module(module, &mut visitor).await?;
+    Ok(Vc::cell(visitor.components))
+}
+
+#[derive(Default)]
+struct AsyncComponentVisitor {
+    components: Vec<String>,
+}
+
+impl AsyncComponentVisitor {
+    fn check<N: VisitWith<JsxFinder>>(
+        &mut self,
+        name: &str,
+        is_default_export: bool,
+        is_async: bool,
+        body: &N,
+    ) {
+        if is_async && (is_default_export || is_component_name(name)) && contains_jsx(body) {
+            self.components.push(name.to_string());
+        }
+    }
+}
+
+impl Visit for AsyncComponentVisitor {
+    fn visit_fn_decl(&mut self, decl: &FnDecl) {
+        self.check(
+            &decl.ident.sym,
+            false,
+            decl.function.is_async,
+            &*decl.function,
+        );
+        decl.visit_children_with(self);
+    }
+
+    fn visit_export_default_decl(&mut self, decl: &ExportDefaultDecl) {
+        if let DefaultDecl::Fn(fn_expr) = &decl.decl {
+            let name = fn_expr
+                .ident
+                .as_ref()
+                .map_or("default", |ident| &*ident.sym);
+            self.check(name, true, fn_expr.function.is_async, &*fn_expr.function);
+        }
+        decl.visit_children_with(self);
+    }
+
+    fn visit_export_default_expr(&mut self, expr: &ExportDefaultExpr) {
+        if let Expr::Arrow(arrow) = &*expr.expr {
+            self.check("default", true, arrow.is_async, &*arrow.body);
+        }
+        expr.visit_children_with(self);
+    }
+
+    fn visit_var_declarator(&mut self, declarator: &VarDeclarator) {
+        if let (Pat::Ident(ident), Some(init)) = (&declarator.name, &declarator.init) {
+            match &**init {
+                Expr::Arrow(arrow) => {
+                    self.check(&ident.id.sym, false, arrow.is_async, &*arrow.body)
+                }
+                Expr::Fn(fn_expr) => self.check(
+                    &ident.id.sym,
+                    false,
+                    fn_expr.function.is_async,
+                    &*fn_expr.function,
+                ),
+                _ => {}
+            }
+        }
+        declarator.visit_children_with(self);
+    }
+}
+
+fn contains_jsx<N: VisitWith<JsxFinder>>(node: &N) -> bool {
+    let mut finder = JsxFinder::default();
+    node.visit_with(&mut finder);
+    finder.found
+}
+
+#[derive(Default)]
+struct JsxFinder {
+    found: bool,
+}
+
+impl Visit for JsxFinder {
+    fn visit_jsx_element(&mut self, _: &JSXElement) {
+        self.found = true;
+    }
+
+    fn visit_jsx_fragment(&mut self, _: &JSXFragment) {
+        self.found = true;
+    }
+}
+
+#[turbo_tasks::value(shared)]
+struct ServerOnlyImportIssue {
+    path: Vc<FileSystemPath>,
+    /// The module marked with `"use client"` which imports `path`.
+    client_component: Vc<FileSystemPath>,
+    import: ServerOnlyImport,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for ServerOnlyImportIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Code(self.import.request.clone()),
+            StyledString::Text(" can't be imported from a client component".to_string()),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("module".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    async fn description(&self) -> Result<Vc<OptionStyledString>> {
+        let mut line = vec![StyledString::Text("This module imports ".to_string())];
+        if !self.import.names.is_empty() {
+            line.push(StyledString::Code(self.import.names.join(", ")));
+            line.push(StyledString::Text(" from ".to_string()));
+        }
+        line.push(StyledString::Code(self.import.request.clone()));
+        line.push(StyledString::Text(
+            match self.import.kind {
+                ServerOnlyKind::Module => ", which only works in server components.",
+                ServerOnlyKind::NodeBuiltin => {
+                    ", a Node.js builtin which isn't available in the browser."
+                }
+            }
+            .to_string(),
+        ));
+        line.push(StyledString::Text(
+            " It's part of the client component ".to_string(),
+        ));
+        line.push(StyledString::Code(
+            self.client_component.await?.path.clone(),
+        ));
+        line.push(StyledString::Text(
+            " marked with \"use client\". Use it in a server component instead and pass the \
+             result down as a prop."
+                .to_string(),
+        ));
+        Ok(Vc::cell(Some(StyledString::Line(line).cell())))
+    }
+}
+
+#[turbo_tasks::value(shared)]
+struct AsyncClientComponentIssue {
+    path: Vc<FileSystemPath>,
+    /// The module marked with `"use client"` which imports `path`.
+    client_component: Vc<FileSystemPath>,
+    component: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for AsyncClientComponentIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Text("The async component ".to_string()),
+            StyledString::Code(self.component.clone()),
+            StyledString::Text(" is rendered as a client component".to_string()),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("module".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    async fn description(&self) -> Result<Vc<OptionStyledString>> {
+        Ok(Vc::cell(Some(
+            StyledString::Line(vec![
+                StyledString::Text(
+                    "Only server components can be async, but this module is part of the client \
+                     component "
+                        .to_string(),
+                ),
+                StyledString::Code(self.client_component.await?.path.clone()),
+                StyledString::Text(
+                    " marked with \"use client\". Import it from a server component instead, or \
+                     pass it to the client component as `children`."
+                        .to_string(),
+                ),
+            ])
+            .cell(),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use turbo_tasks::Vc;
+
+    use super::{is_component_name, server_only_kind, validate_client_references, ServerOnlyKind};
+    use crate::test_utils::{client_module, fixture, fixture_root, issues, run, styled_text};
+
+    #[test]
+    fn test_server_only_kind() {
+        assert_eq!(
+            server_only_kind("next/headers"),
+            Some(ServerOnlyKind::Module)
+        );
+        assert_eq!(
+            server_only_kind("server-only"),
+            Some(ServerOnlyKind::Module)
+        );
+        assert_eq!(server_only_kind("fs"), Some(ServerOnlyKind::NodeBuiltin));
+        assert_eq!(
+            server_only_kind("node:fs/promises"),
+            Some(ServerOnlyKind::NodeBuiltin)
+        );
+        assert_eq!(
+            server_only_kind("child_process"),
+            Some(ServerOnlyKind::NodeBuiltin)
+        );
+    }
+
+    #[test]
+    fn test_client_safe_requests() {
+        // Polyfilled builtins and lookalike packages are fine.
+        assert_eq!(server_only_kind("path"), None);
+        assert_eq!(server_only_kind("node:buffer"), None);
+        assert_eq!(server_only_kind("fs-extra"), None);
+        assert_eq!(server_only_kind("next/navigation"), None);
+        assert_eq!(server_only_kind("./fs"), None);
+    }
+
+    #[test]
+    fn test_is_component_name() {
+        assert!(is_component_name("Profile"));
+        assert!(!is_component_name("fetchProfile"));
+        assert!(!is_component_name("_Profile"));
+    }
+
+    #[tokio::test]
+    async fn reports_next_headers_imported_from_a_client_component() {
+        let dir = fixture(&[
+            (
+                "counter.js",
+                indoc! {"
+                    'use client';
+                    import { theme } from './theme';
+                    export default function Counter() { return theme(); }
+                "},
+            ),
+            (
+                "theme.js",
+                indoc! {"
+                    import { cookies } from 'next/headers';
+                    export const theme = () => cookies().get('theme');
+                "},
+            ),
+            // Only imported by server components.
+            (
+                "page.js",
+                indoc! {"
+                    import { headers } from 'next/headers';
+                    export default function Page() { return headers().get('host'); }
+                "},
+            ),
+            ("node_modules/next/package.json", r#"{"name": "next"}"#),
+            (
+                "node_modules/next/headers.js",
+                "export function cookies() {}",
+            ),
+        ]);
+        let root = dir.path().to_owned();
+        let issues = run(async move {
+            let client_component = client_module(
+                fixture_root(&root),
+                "counter.js",
+                Vc::cell(Default::default()),
+            );
+            let validation = validate_client_references(Vc::cell(vec![client_component]));
+            validation.await?;
+            Ok(issues(validation)
+                .await?
+                .iter()
+                .map(|issue| {
+                    (
+                        issue.file_path.clone(),
+                        styled_text(&issue.title),
+                        issue
+                            .description
+                            .as_ref()
+                            .map(styled_text)
+                            .unwrap_or_default(),
+                    )
+                })
+                .filter(|(_, title, _)| title.contains("can't be imported"))
+                .collect::<Vec<_>>())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(issues.len(), 1, "{issues:?}");
+        let (file_path, title, description) = &issues[0];
+        assert!(file_path.ends_with("theme.js"), "{file_path}");
+        assert_eq!(
+            title,
+            "next/headers can't be imported from a client component"
+        );
+        assert!(
+            description.starts_with("This module imports cookies from next/headers"),
+            "{description}"
+        );
+        assert!(description.contains("counter.js"), "{description}");
+    }
+}
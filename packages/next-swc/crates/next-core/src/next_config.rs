@@ -41,7 +41,11 @@ use turbopack_binding::{
     },
 };
 
-use crate::{embed_js::next_asset, next_shared::transforms::ModularizeImportPackageConfig};
+use crate::{
+    embed_js::next_asset,
+    mode::NextMode,
+    next_shared::transforms::{modularize_imports::Transform, ModularizeImportPackageConfig},
+};
 
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -60,15 +64,16 @@ struct NextConfigAndCustomRoutes {
 #[serde(rename_all = "camelCase")]
 struct CustomRoutesRaw {
     rewrites: Rewrites,
+    headers: Vec<Header>,
 
     // unsupported
-    headers: Vec<Header>,
     redirects: Vec<Redirect>,
 }
 
 #[turbo_tasks::value]
 struct CustomRoutes {
     rewrites: Vc<Rewrites>,
+    headers: Vc<Headers>,
 }
 
 #[turbo_tasks::value(serialization = "custom", eq = "manual")]
@@ -88,7 +93,7 @@ pub struct NextConfig {
     pub dist_dir: Option<String>,
     sass_options: Option<serde_json::Value>,
     pub trailing_slash: Option<bool>,
-    pub asset_prefix: Option<String>,
+    pub asset_prefix: Option<AssetPrefix>,
     pub base_path: Option<String>,
     pub skip_middleware_url_normalize: Option<bool>,
     pub skip_trailing_slash_redirect: Option<bool>,
@@ -97,6 +102,13 @@ pub struct NextConfig {
     pub dev_indicators: Option<DevIndicatorsConfig>,
     pub output: Option<OutputType>,
     pub analytics_id: Option<String>,
+    pub powered_by_header: Option<bool>,
+    pub compress: Option<bool>,
+    pub generate_etags: Option<bool>,
+    /// The routes returned by the `generateRoutes` config function, from their
+    /// pathname to the path of their handler module, relative to the project.
+    /// The function is evaluated when loading the config.
+    pub generate_routes: Option<IndexMap<String, String>>,
 
     ///
     #[serde(rename = "_originalRedirects")]
@@ -110,18 +122,15 @@ pub struct NextConfig {
     // unsupported
     amp: AmpConfig,
     clean_dist_dir: bool,
-    compress: bool,
     eslint: EslintConfig,
     exclude_default_moment_locales: bool,
     // this can be a function in js land
     export_path_map: Option<serde_json::Value>,
-    // this is a function in js land
+    // this is a function in js land, it's evaluated when loading the config
     generate_build_id: Option<serde_json::Value>,
-    generate_etags: bool,
     http_agent_options: HttpAgentConfig,
     on_demand_entries: OnDemandEntriesConfig,
     output_file_tracing: bool,
-    powered_by_header: bool,
     production_browser_source_maps: bool,
     public_runtime_config: IndexMap<String, serde_json::Value>,
     server_runtime_config: IndexMap<String, serde_json::Value>,
@@ -246,6 +255,16 @@ pub struct Header {
     pub missing: Option<Vec<RouteHas>>,
 }
 
+/// The routes generated by the `generateRoutes` config function, from their
+/// normalized pathname to the path of their handler module.
+#[turbo_tasks::value(transparent)]
+pub struct GeneratedRoutes(IndexMap<String, String>);
+
+/// The `headers()` of `next.config.js`.
+#[turbo_tasks::value(transparent, eq = "manual")]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Headers(Vec<Header>);
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(rename_all = "camelCase")]
 pub enum RedirectStatus {
@@ -286,15 +305,55 @@ pub struct Rewrite {
     pub missing: Option<Vec<RouteHas>>,
 }
 
+/// The `rewrites()` of `next.config.js`, by the phase of routing they're
+/// applied in: before the pages and public files are checked, after them, or
+/// only when no page matched.
 #[turbo_tasks::value(eq = "manual")]
 #[derive(Clone, Debug, Default, PartialEq)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", from = "RewritesRaw")]
 pub struct Rewrites {
     pub before_files: Vec<Rewrite>,
     pub after_files: Vec<Rewrite>,
     pub fallback: Vec<Rewrite>,
 }
 
+/// `rewrites()` may also return a bare array, which is applied like
+/// `afterFiles`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RewritesRaw {
+    AfterFiles(Vec<Rewrite>),
+    #[serde(rename_all = "camelCase")]
+    Phases {
+        #[serde(default)]
+        before_files: Vec<Rewrite>,
+        #[serde(default)]
+        after_files: Vec<Rewrite>,
+        #[serde(default)]
+        fallback: Vec<Rewrite>,
+    },
+}
+
+impl From<RewritesRaw> for Rewrites {
+    fn from(raw: RewritesRaw) -> Self {
+        match raw {
+            RewritesRaw::AfterFiles(after_files) => Rewrites {
+                after_files,
+                ..Default::default()
+            },
+            RewritesRaw::Phases {
+                before_files,
+                after_files,
+                fallback,
+            } => Rewrites {
+                before_files,
+                after_files,
+                fallback,
+            },
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(rename_all = "camelCase")]
 pub struct TypeScriptConfig {
@@ -310,12 +369,17 @@ pub struct ImageConfig {
     pub image_sizes: Vec<u16>,
     pub path: String,
     pub loader: ImageLoader,
+    /// The module exporting the loader when `loader` is `custom`. It's
+    /// resolved to an absolute path by `next.config.js` loading, and empty
+    /// when unset.
+    #[serde(default)]
+    pub loader_file: String,
     pub domains: Vec<String>,
     pub disable_static_images: bool,
-    #[serde(rename(deserialize = "minimumCacheTTL"))]
+    #[serde(rename = "minimumCacheTTL")]
     pub minimum_cache_ttl: u32,
     pub formats: Vec<ImageFormat>,
-    #[serde(rename(deserialize = "dangerouslyAllowSVG"))]
+    #[serde(rename = "dangerouslyAllowSVG")]
     pub dangerously_allow_svg: bool,
     pub content_security_policy: String,
     pub remote_patterns: Vec<RemotePattern>,
@@ -330,6 +394,7 @@ impl Default for ImageConfig {
             image_sizes: vec![16, 32, 48, 64, 96, 128, 256, 384],
             path: "/_next/image".to_string(),
             loader: ImageLoader::Default,
+            loader_file: "".to_string(),
             domains: vec![],
             disable_static_images: false,
             minimum_cache_ttl: 60,
@@ -445,8 +510,33 @@ pub struct ExperimentalConfig {
     pub optimize_css: Option<serde_json::Value>,
     pub next_script_workers: Option<bool>,
     pub web_vitals_attribution: Option<Vec<String>>,
+    /// The names of the `<meta>` tags, like `traceparent`, which the server
+    /// renders with the active trace's metadata for the client to continue
+    /// the trace.
+    pub client_trace_metadata: Option<Vec<String>>,
+    /// Whether the classes of CSS modules can be imported as named exports,
+    /// e.g. `import { button } from './button.module.css'`, besides the
+    /// default export of all classes. Class names which aren't valid
+    /// identifiers are imported camelCased, e.g. `primaryButton` for
+    /// `primary-button`.
+    pub css_modules_named_exports: Option<bool>,
     pub server_actions: Option<ServerActionsOrLegacyBool>,
     pub sri: Option<SubResourceIntegrity>,
+    /// Generate Route types and enable type checking for Link and Router.push,
+    /// etc. This option requires `appDir` to be enabled first.
+    /// @see https://nextjs.org/docs/app/api-reference/next-config-js/typedRoutes
+    pub typed_routes: Option<bool>,
+    /// Default transforms which aren't applied, see [DISABLEABLE_TRANSFORMS].
+    pub disabled_transforms: Option<Vec<String>>,
+    /// Routes with at most this many bytes of CSS get it inlined into their
+    /// HTML instead of loading it with `<link>` tags.
+    pub inline_css_threshold: Option<u64>,
+    /// Edge functions larger than this many bytes get a warning, as edge hosts
+    /// cap the size of the functions they deploy.
+    pub edge_bundle_size_limit: Option<u64>,
+    /// Whether server chunks of production builds are minified. Defaults to
+    /// `true`.
+    pub server_minification: Option<bool>,
 
     // ---
     // UNSUPPORTED
@@ -489,18 +579,12 @@ pub struct ExperimentalConfig {
     ppr: Option<bool>,
     taint: Option<bool>,
     proxy_timeout: Option<f64>,
-    /// enables the minification of server code.
-    server_minification: Option<bool>,
     /// Enables source maps generation for the server production bundle.
     server_source_maps: Option<bool>,
     swc_minify: Option<bool>,
     swc_trace_profiling: Option<bool>,
     /// @internal Used by the Next.js internals only.
     trust_host_header: Option<bool>,
-    /// Generate Route types and enable type checking for Link and Router.push,
-    /// etc. This option requires `appDir` to be enabled first.
-    /// @see https://nextjs.org/docs/app/api-reference/next-config-js/typedRoutes
-    typed_routes: Option<bool>,
     url_imports: Option<serde_json::Value>,
     /// This option is to enable running the Webpack build in a worker thread
     /// (doesn't apply to Turbopack).
@@ -541,6 +625,34 @@ pub enum SizeLimit {
     WithUnit(String),
 }
 
+/// The `assetPrefix`, either for all modes or per mode, e.g. to serve assets
+/// from the dev server while developing and from a CDN in production.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
+#[serde(untagged)]
+pub enum AssetPrefix {
+    All(String),
+    PerMode {
+        development: Option<String>,
+        production: Option<String>,
+    },
+}
+
+impl AssetPrefix {
+    /// Returns the prefix configured for `mode`, if any.
+    pub fn for_mode(&self, mode: NextMode) -> Option<&str> {
+        match self {
+            AssetPrefix::All(prefix) => Some(prefix),
+            AssetPrefix::PerMode {
+                development,
+                production,
+            } => match mode {
+                NextMode::Development => development.as_deref(),
+                NextMode::Build => production.as_deref(),
+            },
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TraceRawVcs)]
 #[serde(rename_all = "kebab-case")]
 pub enum MiddlewarePrefetchType {
@@ -716,6 +828,16 @@ impl NextConfig {
         Ok(Vc::cell(self.await?.experimental.mdx_rs.unwrap_or(false)))
     }
 
+    #[turbo_tasks::function]
+    pub async fn css_modules_named_exports(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .css_modules_named_exports
+                .unwrap_or(false),
+        ))
+    }
+
     #[turbo_tasks::function]
     pub async fn sass_config(self: Vc<Self>) -> Result<Vc<JsonValue>> {
         Ok(Vc::cell(
@@ -742,25 +864,90 @@ impl NextConfig {
         ))
     }
 
-    /// Returns the final asset prefix. If an assetPrefix is set, it's used.
-    /// Otherwise, the basePath is used.
+    /// Returns whether responses should include the `X-Powered-By` header.
+    #[turbo_tasks::function]
+    pub async fn powered_by_header(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(self.await?.powered_by_header.unwrap_or(true)))
+    }
+
+    /// Returns whether responses should be gzip compressed.
     #[turbo_tasks::function]
-    pub async fn computed_asset_prefix(self: Vc<Self>) -> Result<Vc<Option<String>>> {
+    pub async fn compress(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(self.await?.compress.unwrap_or(true)))
+    }
+
+    /// Returns whether responses should include an `ETag` header.
+    #[turbo_tasks::function]
+    pub async fn generate_etags(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(self.await?.generate_etags.unwrap_or(true)))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn generated_routes(self: Vc<Self>) -> Result<Vc<GeneratedRoutes>> {
+        Ok(Vc::cell(generated_routes(
+            self.await?.generate_routes.as_ref(),
+        )))
+    }
+
+    /// Returns the final asset prefix in `mode`. If an assetPrefix is set for
+    /// the mode, it's used. Otherwise, the basePath is used.
+    #[turbo_tasks::function]
+    pub async fn computed_asset_prefix(
+        self: Vc<Self>,
+        mode: NextMode,
+    ) -> Result<Vc<Option<String>>> {
         let this = self.await?;
 
-        Ok(Vc::cell(Some(format!(
-            "{}/_next/",
-            if let Some(asset_prefix) = &this.asset_prefix {
-                asset_prefix
-            } else if let Some(base_path) = &this.base_path {
-                base_path
-            } else {
-                ""
-            }
-            .trim_end_matches('/')
+        Ok(Vc::cell(Some(computed_asset_prefix(
+            this.asset_prefix.as_ref(),
+            this.base_path.as_deref(),
+            mode,
         ))))
     }
 
+    #[turbo_tasks::function]
+    pub async fn web_vitals_attribution(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .web_vitals_attribution
+                .clone()
+                .unwrap_or_default(),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn client_trace_metadata(self: Vc<Self>) -> Result<Vc<Vec<String>>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .client_trace_metadata
+                .clone()
+                .unwrap_or_default(),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn typed_routes(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?.experimental.typed_routes.unwrap_or_default(),
+        ))
+    }
+
+    /// Returns the build id produced by the `generateBuildId` config function.
+    /// `None` means the default build id should be used, which is also the
+    /// case when `generateBuildId` returns `null`.
+    #[turbo_tasks::function]
+    pub async fn generate_build_id(self: Vc<Self>) -> Result<Vc<Option<String>>> {
+        Ok(Vc::cell(
+            self.await?
+                .generate_build_id
+                .as_ref()
+                .and_then(|build_id| build_id.as_str())
+                .map(|build_id| build_id.to_string()),
+        ))
+    }
+
     #[turbo_tasks::function]
     pub async fn enable_ppr(self: Vc<Self>) -> Result<Vc<bool>> {
         Ok(Vc::cell(self.await?.experimental.ppr.unwrap_or(false)))
@@ -771,6 +958,52 @@ impl NextConfig {
         Ok(Vc::cell(self.await?.experimental.taint.unwrap_or(false)))
     }
 
+    /// The tsconfig to compile with instead of the closest `tsconfig.json`,
+    /// relative to the project.
+    #[turbo_tasks::function]
+    pub async fn ts_config_path(self: Vc<Self>) -> Result<Vc<Option<String>>> {
+        Ok(Vc::cell(self.await?.typescript.ts_config_path.clone()))
+    }
+
+    /// Returns the config with `typescript.tsconfigPath` set to
+    /// `ts_config_path`.
+    #[turbo_tasks::function]
+    pub async fn with_ts_config_path(self: Vc<Self>, ts_config_path: String) -> Result<Vc<Self>> {
+        let mut config = self.await?.clone_value();
+        config.typescript.ts_config_path = Some(ts_config_path);
+        Ok(config.cell())
+    }
+
+    #[turbo_tasks::function]
+    pub async fn inline_css_threshold(self: Vc<Self>) -> Result<Vc<Option<u64>>> {
+        Ok(Vc::cell(self.await?.experimental.inline_css_threshold))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn server_minification(self: Vc<Self>) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?.experimental.server_minification.unwrap_or(true),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    pub async fn edge_bundle_size_limit(self: Vc<Self>) -> Result<Vc<Option<u64>>> {
+        Ok(Vc::cell(self.await?.experimental.edge_bundle_size_limit))
+    }
+
+    /// Returns whether the default transform `name` is disabled with
+    /// `experimental.disabledTransforms`.
+    #[turbo_tasks::function]
+    pub async fn is_transform_disabled(self: Vc<Self>, name: String) -> Result<Vc<bool>> {
+        Ok(Vc::cell(
+            self.await?
+                .experimental
+                .disabled_transforms
+                .as_ref()
+                .is_some_and(|disabled| disabled.contains(&name)),
+        ))
+    }
+
     #[turbo_tasks::function]
     pub async fn use_lightningcss(self: Vc<Self>) -> Result<Vc<bool>> {
         Ok(Vc::cell(
@@ -804,6 +1037,15 @@ pub async fn load_rewrites(execution_context: Vc<ExecutionContext>) -> Result<Vc
         .rewrites)
 }
 
+#[turbo_tasks::function]
+pub async fn load_headers(execution_context: Vc<ExecutionContext>) -> Result<Vc<Headers>> {
+    Ok(load_config_and_custom_routes(execution_context)
+        .await?
+        .custom_routes
+        .await?
+        .headers)
+}
+
 #[turbo_tasks::function]
 async fn load_config_and_custom_routes(
     execution_context: Vc<ExecutionContext>,
@@ -894,6 +1136,7 @@ async fn load_next_config_and_custom_routes_internal(
             config: NextConfig::default().cell(),
             custom_routes: CustomRoutes {
                 rewrites: Rewrites::default().cell(),
+                headers: Headers::default().cell(),
             }
             .cell(),
         }
@@ -901,16 +1144,32 @@ async fn load_next_config_and_custom_routes_internal(
     };
     let next_config_and_custom_routes: NextConfigAndCustomRoutesRaw =
         parse_json_with_source_context(val.to_str()?)?;
+    let config = next_config_and_custom_routes.config.cell();
+    validate_next_config(config, config_file.unwrap_or(project_path)).await?;
+
+    Ok(NextConfigAndCustomRoutes {
+        config,
+        custom_routes: CustomRoutes {
+            rewrites: next_config_and_custom_routes.custom_routes.rewrites.cell(),
+            headers: Vc::cell(next_config_and_custom_routes.custom_routes.headers),
+        }
+        .cell(),
+    }
+    .cell())
+}
 
-    if let Some(turbo) = next_config_and_custom_routes
-        .config
-        .experimental
-        .turbo
-        .as_ref()
-    {
+/// Emits the issues of the options in the config loaded from `path`.
+#[turbo_tasks::function]
+async fn validate_next_config(
+    config: Vc<NextConfig>,
+    path: Vc<FileSystemPath>,
+) -> Result<Vc<Completion>> {
+    let config = config.await?;
+
+    if let Some(turbo) = config.experimental.turbo.as_ref() {
         if turbo.loaders.is_some() {
             OutdatedConfigIssue {
-                path: config_file.unwrap_or(project_path),
+                path,
                 old_name: "experimental.turbo.loaders".to_string(),
                 new_name: "experimental.turbo.rules".to_string(),
                 description: indoc::indoc! { r#"
@@ -923,14 +1182,93 @@ async fn load_next_config_and_custom_routes_internal(
         }
     }
 
-    Ok(NextConfigAndCustomRoutes {
-        config: next_config_and_custom_routes.config.cell(),
-        custom_routes: CustomRoutes {
-            rewrites: next_config_and_custom_routes.custom_routes.rewrites.cell(),
+    for name in config.experimental.disabled_transforms.iter().flatten() {
+        let Some(description) = disabled_transform_warning(name, &config) else {
+            continue;
+        };
+        DisabledTransformIssue {
+            path,
+            name: name.clone(),
+            description,
         }
-        .cell(),
+        .cell()
+        .emit()
     }
-    .cell())
+
+    for (package, package_config) in config.modularize_imports.iter().flatten() {
+        if package_config.transform == Transform::None {
+            MissingModularizeImportsTransformIssue {
+                path,
+                package: package.clone(),
+            }
+            .cell()
+            .emit()
+        }
+    }
+
+    let images = &config.images;
+    if images.loader == ImageLoader::Custom && images.loader_file.is_empty() {
+        MissingImageLoaderFileIssue { path }.cell().emit()
+    }
+
+    Ok(Completion::new())
+}
+
+/// The default transforms which can be disabled with
+/// `experimental.disabledTransforms`.
+pub const DISABLEABLE_TRANSFORMS: &[&str] = &[
+    "styled-jsx",
+    "styled-components",
+    "emotion",
+    "relay",
+    "modularize-imports",
+    "mdx-frontmatter",
+];
+
+/// Returns why disabling the transform `name` is likely a mistake: either the
+/// transform doesn't exist, or the config configures it, so that part of the
+/// config is ignored.
+fn disabled_transform_warning(name: &str, config: &NextConfig) -> Option<String> {
+    if !DISABLEABLE_TRANSFORMS.contains(&name) {
+        return Some(format!(
+            "There is no default transform with this name. Transforms that can be disabled are: \
+             {}.",
+            DISABLEABLE_TRANSFORMS.join(", ")
+        ));
+    }
+
+    let compiler = config.compiler.as_ref();
+    let dependent_option = match name {
+        "styled-components" => compiler
+            .and_then(|compiler| compiler.styled_components.as_ref())
+            .is_some_and(|options| {
+                !matches!(
+                    options,
+                    StyledComponentsTransformOptionsOrBoolean::Boolean(false)
+                )
+            })
+            .then_some("compiler.styledComponents"),
+        "emotion" => compiler
+            .and_then(|compiler| compiler.emotion.as_ref())
+            .is_some_and(|options| {
+                !matches!(options, EmotionTransformOptionsOrBoolean::Boolean(false))
+            })
+            .then_some("compiler.emotion"),
+        "relay" => compiler
+            .is_some_and(|compiler| compiler.relay.is_some())
+            .then_some("compiler.relay"),
+        "modularize-imports" => config
+            .modularize_imports
+            .as_ref()
+            .is_some_and(|imports| !imports.is_empty())
+            .then_some("modularizeImports"),
+        _ => None,
+    }?;
+
+    Some(format!(
+        "`{dependent_option}` is configured, but has no effect because it's applied by this \
+         transform."
+    ))
 }
 
 #[turbo_tasks::function]
@@ -1011,3 +1349,370 @@ impl Issue for OutdatedConfigIssue {
         ))
     }
 }
+
+#[turbo_tasks::value]
+struct DisabledTransformIssue {
+    path: Vc<FileSystemPath>,
+    name: String,
+    description: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for DisabledTransformIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("config".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Text("Disabled transform ".to_string()),
+            StyledString::Code(self.name.clone()),
+            StyledString::Text(" in experimental.disabledTransforms".to_string()),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(self.description.to_string()).cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value]
+struct MissingModularizeImportsTransformIssue {
+    path: Vc<FileSystemPath>,
+    package: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for MissingModularizeImportsTransformIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("config".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Text("Missing transform for ".to_string()),
+            StyledString::Code(self.package.clone()),
+            StyledString::Text(" in modularizeImports".to_string()),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(
+                "Imports of this package are left as they are. Set `transform` to the path of its \
+                 members, e.g. `lodash/{{member}}`."
+                    .to_string(),
+            )
+            .cell(),
+        ))
+    }
+}
+
+#[turbo_tasks::value]
+struct MissingImageLoaderFileIssue {
+    path: Vc<FileSystemPath>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for MissingImageLoaderFileIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("config".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Code("images.loader".to_string()),
+            StyledString::Text(" is set to ".to_string()),
+            StyledString::Code("custom".to_string()),
+            StyledString::Text(" without an ".to_string()),
+            StyledString::Code("images.loaderFile".to_string()),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(
+                "Set `images.loaderFile` to the path of a module which default exports the loader \
+                 function, e.g. `./my/image/loader.js`."
+                    .to_string(),
+            )
+            .cell(),
+        ))
+    }
+}
+
+/// Normalizes the pathnames of the generated routes to start with a slash and
+/// not end with one, like the pathnames of file-based routes. Handler modules
+/// may be given as `./cms/handler.js`.
+fn generated_routes(routes: Option<&IndexMap<String, String>>) -> IndexMap<String, String> {
+    routes
+        .into_iter()
+        .flatten()
+        .map(|(pathname, module)| {
+            (
+                format!("/{}", pathname.trim_matches('/')),
+                module.trim_start_matches("./").to_string(),
+            )
+        })
+        .collect()
+}
+
+fn computed_asset_prefix(
+    asset_prefix: Option<&AssetPrefix>,
+    base_path: Option<&str>,
+    mode: NextMode,
+) -> String {
+    format!(
+        "{}/_next/",
+        asset_prefix
+            .and_then(|asset_prefix| asset_prefix.for_mode(mode))
+            .or(base_path)
+            .unwrap_or("")
+            .trim_end_matches('/')
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use super::{
+        computed_asset_prefix, disabled_transform_warning, generated_routes, validate_next_config,
+        AssetPrefix, CompilerConfig, ImageLoader, ModularizeImportPackageConfig, NextConfig,
+        Rewrites, StyledComponentsTransformOptionsOrBoolean, Transform,
+    };
+    use crate::{
+        mode::NextMode,
+        test_utils::{fixture, fixture_root, issues, run, styled_text},
+    };
+
+    #[test]
+    fn test_disabled_transform_warning() {
+        let mut config = NextConfig::default();
+        assert_eq!(disabled_transform_warning("styled-jsx", &config), None);
+        assert_eq!(
+            disabled_transform_warning("styled-components", &config),
+            None
+        );
+        assert!(disabled_transform_warning("styled-component", &config).is_some());
+
+        config.compiler = Some(CompilerConfig {
+            react_remove_properties: None,
+            relay: None,
+            emotion: None,
+            remove_console: None,
+            styled_components: Some(StyledComponentsTransformOptionsOrBoolean::Boolean(false)),
+        });
+        assert_eq!(
+            disabled_transform_warning("styled-components", &config),
+            None
+        );
+
+        config.compiler.as_mut().unwrap().styled_components =
+            Some(StyledComponentsTransformOptionsOrBoolean::Boolean(true));
+        assert!(disabled_transform_warning("styled-components", &config)
+            .unwrap()
+            .contains("`compiler.styledComponents`"));
+    }
+
+    #[test]
+    fn test_computed_asset_prefix() {
+        let asset_prefix: AssetPrefix = serde_json::from_str(
+            r#"{"development": "http://localhost:3001", "production": "https://cdn.example.com/"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            computed_asset_prefix(Some(&asset_prefix), None, NextMode::Build),
+            "https://cdn.example.com/_next/"
+        );
+        assert_eq!(
+            computed_asset_prefix(Some(&asset_prefix), None, NextMode::Development),
+            "http://localhost:3001/_next/"
+        );
+
+        let asset_prefix = AssetPrefix::All("/assets".to_string());
+        assert_eq!(
+            computed_asset_prefix(Some(&asset_prefix), Some("/docs"), NextMode::Development),
+            "/assets/_next/"
+        );
+
+        // The basePath applies to modes without a prefix.
+        let asset_prefix = AssetPrefix::PerMode {
+            development: None,
+            production: Some("https://cdn.example.com".to_string()),
+        };
+        assert_eq!(
+            computed_asset_prefix(Some(&asset_prefix), Some("/docs"), NextMode::Development),
+            "/docs/_next/"
+        );
+    }
+
+    #[test]
+    fn test_generated_routes() {
+        let generate_routes: IndexMap<String, String> = serde_json::from_str(
+            r#"{"/cms/[slug]": "cms/handler.js", "sitemap.xml/": "./cms/sitemap.js"}"#,
+        )
+        .unwrap();
+        let routes = generated_routes(Some(&generate_routes));
+        assert_eq!(
+            routes.into_iter().collect::<Vec<_>>(),
+            [
+                ("/cms/[slug]".to_string(), "cms/handler.js".to_string()),
+                ("/sitemap.xml".to_string(), "cms/sitemap.js".to_string()),
+            ]
+        );
+        assert!(generated_routes(None).is_empty());
+    }
+
+    #[test]
+    fn test_rewrites_phases() {
+        let rewrites: Rewrites = serde_json::from_str(
+            r#"{
+                "beforeFiles": [{"source": "/docs/:path*", "destination": "/help/:path*"}],
+                "afterFiles": [{"source": "/blog", "destination": "/news"}],
+                "fallback": [{"source": "/:path*", "destination": "https://legacy.example.com/:path*"}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(rewrites.before_files[0].source, "/docs/:path*");
+        assert_eq!(rewrites.after_files[0].source, "/blog");
+        assert_eq!(rewrites.fallback[0].source, "/:path*");
+        assert_eq!(
+            serde_json::to_value(&rewrites).unwrap(),
+            serde_json::json!({
+                "beforeFiles": [{"source": "/docs/:path*", "destination": "/help/:path*"}],
+                "afterFiles": [{"source": "/blog", "destination": "/news"}],
+                "fallback": [{"source": "/:path*", "destination": "https://legacy.example.com/:path*"}],
+            })
+        );
+
+        let rewrites: Rewrites =
+            serde_json::from_str(r#"[{"source": "/blog", "destination": "/news"}]"#).unwrap();
+        assert!(rewrites.before_files.is_empty());
+        assert_eq!(rewrites.after_files[0].destination, "/news");
+        assert!(rewrites.fallback.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_missing_modularize_imports_transform_issue() {
+        let dir = fixture(&[("next.config.js", "module.exports = {}")]);
+        let root = dir.path().to_owned();
+        let titles = run(async move {
+            let config = NextConfig {
+                modularize_imports: Some(IndexMap::from([
+                    (
+                        "lodash".to_string(),
+                        ModularizeImportPackageConfig::default(),
+                    ),
+                    (
+                        "date-fns".to_string(),
+                        ModularizeImportPackageConfig {
+                            transform: Transform::String("date-fns/{{member}}".to_string()),
+                            ..Default::default()
+                        },
+                    ),
+                ])),
+                ..Default::default()
+            };
+            let validated = validate_next_config(
+                config.cell(),
+                fixture_root(&root).join("next.config.js".to_string()),
+            );
+            validated.await?;
+            Ok(issues(validated)
+                .await?
+                .iter()
+                .map(|issue| styled_text(&issue.title))
+                .collect::<Vec<_>>())
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            titles,
+            ["Missing transform for lodash in modularizeImports"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_image_loader_file_issue() {
+        let dir = fixture(&[
+            ("next.config.js", "module.exports = {}"),
+            ("image-loader.js", "export default ({ src }) => src;"),
+        ]);
+        let root = dir.path().to_owned();
+        let titles = run(async move {
+            let mut titles = vec![];
+            for loader_file in ["", "./image-loader.js"] {
+                let mut config = NextConfig::default();
+                config.images.loader = ImageLoader::Custom;
+                config.images.loader_file = loader_file.to_string();
+                let validated = validate_next_config(
+                    config.cell(),
+                    fixture_root(&root).join("next.config.js".to_string()),
+                );
+                validated.await?;
+                titles.push(
+                    issues(validated)
+                        .await?
+                        .iter()
+                        .map(|issue| styled_text(&issue.title))
+                        .collect::<Vec<_>>(),
+                );
+            }
+            Ok(titles)
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            titles,
+            [
+                vec!["images.loader is set to custom without an images.loaderFile"],
+                vec![]
+            ]
+        );
+    }
+}
@@ -1,7 +1,9 @@
 pub(crate) mod dynamic_module;
 pub(crate) mod dynamic_transition;
+pub(crate) mod validate_dynamic;
 pub(crate) mod visit_dynamic;
 
 pub use dynamic_module::NextDynamicEntryModule;
 pub use dynamic_transition::NextDynamicTransition;
+pub use validate_dynamic::validate_dynamic_imports;
 pub use visit_dynamic::NextDynamicEntries;
@@ -0,0 +1,213 @@
+use anyhow::Result;
+use swc_core::ecma::{
+    ast::{CallExpr, Callee},
+    visit::{Visit, VisitWith},
+};
+use turbo_tasks::{TryJoinIterExt, Value, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::{
+        core::{
+            ident::AssetIdent,
+            issue::{Issue, IssueExt, IssueSeverity, OptionStyledString, StyledString},
+            module::{Module, Modules},
+            reference_type::EcmaScriptModulesReferenceSubType,
+            resolve::{origin::PlainResolveOrigin, parse::Request, pattern::Pattern},
+        },
+        ecmascript::resolve::esm_resolve,
+    },
+};
+
+use crate::module_analysis::{all_referenced_modules, string_literal, visit_ecmascript_module};
+
+/// Checks that every `import()` in the module graph of `entries` resolves at
+/// build time, instead of failing when the chunk is loaded at runtime.
+///
+/// Unresolvable imports are reported as errors. Imports with a computed
+/// specifier, e.g. a template literal with expressions, can't be checked and
+/// are reported as warnings. Modules in `node_modules` are skipped.
+#[turbo_tasks::function]
+pub async fn validate_dynamic_imports(entries: Vc<Modules>) -> Result<Vc<()>> {
+    let modules = all_referenced_modules(entries.await?.iter().copied()).await?;
+
+    modules
+        .into_iter()
+        .map(|module| async move {
+            if module.ident().path().await?.path.contains("node_modules/") {
+                return Ok(());
+            }
+            validate_module_dynamic_imports(module).await?;
+            Ok(())
+        })
+        .try_join()
+        .await?;
+
+    Ok(Default::default())
+}
+
+#[turbo_tasks::function]
+async fn validate_module_dynamic_imports(module: Vc<Box<dyn Module>>) -> Result<Vc<()>> {
+    let mut visitor = DynamicImportVisitor::default();
+    let Some(ecmascript_asset) = visit_ecmascript_module(module, &mut visitor).await? else {
+        return Ok(Default::default());
+    };
+
+    if visitor.has_computed_import {
+        DynamicImportIssue {
+            ident: module.ident(),
+            severity: IssueSeverity::Warning.cell(),
+            title: "Dynamic import with a computed specifier can't be validated".to_string(),
+            description: "Only `import()` calls with a string literal can be resolved at build \
+                          time. This import may still fail when it's loaded."
+                .to_string(),
+        }
+        .cell()
+        .emit();
+    }
+
+    let asset_context = ecmascript_asset.await?.asset_context;
+    for request in visitor.requests {
+        let resolved = esm_resolve(
+            Vc::upcast(PlainResolveOrigin::new(
+                asset_context,
+                module.ident().path(),
+            )),
+            Request::parse(Value::new(Pattern::Constant(request.clone()))),
+            Value::new(EcmaScriptModulesReferenceSubType::Undefined),
+            IssueSeverity::Error.cell(),
+            None,
+        )
+        .first_module()
+        .await?;
+
+        if resolved.is_none() {
+            DynamicImportIssue {
+                ident: module.ident(),
+                severity: IssueSeverity::Error.cell(),
+                title: format!("Dynamic import `{request}` can't be resolved"),
+                description: "The imported module doesn't exist, so loading it would fail at \
+                              runtime. Fix the path or remove the import."
+                    .to_string(),
+            }
+            .cell()
+            .emit();
+        }
+    }
+
+    Ok(Default::default())
+}
+
+#[derive(Default)]
+struct DynamicImportVisitor {
+    /// The literal specifiers of `import()` calls.
+    requests: Vec<String>,
+    has_computed_import: bool,
+}
+
+impl Visit for DynamicImportVisitor {
+    fn visit_call_expr(&mut self, call_expr: &CallExpr) {
+        if let Callee::Import(_) = call_expr.callee {
+            match call_expr
+                .args
+                .first()
+                .and_then(|arg| string_literal(&arg.expr))
+            {
+                Some(request) => self.requests.push(request),
+                None => self.has_computed_import = true,
+            }
+        }
+
+        call_expr.visit_children_with(self);
+    }
+}
+
+#[turbo_tasks::value(shared)]
+struct DynamicImportIssue {
+    ident: Vc<AssetIdent>,
+    severity: Vc<IssueSeverity>,
+    title: String,
+    description: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for DynamicImportIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        self.severity
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text(self.title.clone()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("resolve".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.ident.path()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(StyledString::Text(self.description.clone()).cell()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use turbo_tasks::Vc;
+    use turbopack_binding::turbopack::core::issue::IssueSeverity;
+
+    use super::validate_dynamic_imports;
+    use crate::test_utils::{client_module, fixture, fixture_root, issues, run, styled_text};
+
+    #[tokio::test]
+    async fn reports_dynamic_imports_which_dont_resolve() {
+        let dir = fixture(&[
+            (
+                "page.js",
+                indoc! {"
+                    const Missing = () => import('./missing');
+                    const Present = () => import('./present.js');
+                    const Locale = (lang) => import(`./locales/${lang}.js`);
+                "},
+            ),
+            ("present.js", "export default function Present() {}"),
+        ]);
+        let root = dir.path().to_owned();
+        let mut issues = run(async move {
+            let entry = client_module(fixture_root(&root), "page.js", Vc::cell(Default::default()));
+            let validation = validate_dynamic_imports(Vc::cell(vec![entry]));
+            validation.await?;
+            Ok(issues(validation)
+                .await?
+                .iter()
+                .map(|issue| (issue.severity, styled_text(&issue.title)))
+                .filter(|(_, title)| title.starts_with("Dynamic import"))
+                .collect::<Vec<_>>())
+        })
+        .await
+        .unwrap();
+        issues.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+        assert_eq!(
+            issues,
+            [
+                (
+                    IssueSeverity::Error,
+                    "Dynamic import `./missing` can't be resolved".to_string()
+                ),
+                // The computed specifier is only a warning.
+                (
+                    IssueSeverity::Warning,
+                    "Dynamic import with a computed specifier can't be validated".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -28,6 +28,7 @@ use crate::{
         ModuleFeatureReportResolvePlugin, NextSharedRuntimeResolvePlugin,
         UnsupportedModulesResolvePlugin,
     },
+    transform_options::get_tsconfig_path,
     util::foreign_code_context_condition,
 };
 
@@ -138,6 +139,7 @@ pub async fn get_edge_resolve_options_context(
 
     Ok(ResolveOptionsContext {
         enable_typescript: true,
+        tsconfig_path: get_tsconfig_path(project_path, next_config).await?,
         enable_react: true,
         enable_mjs_extension: true,
         enable_edge_node_externals: true,
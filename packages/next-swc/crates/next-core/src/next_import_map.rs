@@ -1,10 +1,13 @@
-use std::collections::{BTreeMap, HashMap};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
 
 use anyhow::{Context, Result};
 use indexmap::{indexmap, IndexMap};
 use turbo_tasks::{Value, Vc};
 use turbopack_binding::{
-    turbo::tasks_fs::{glob::Glob, FileSystem, FileSystemPath},
+    turbo::tasks_fs::{glob::Glob, DiskFileSystem, FileSystem, FileSystemPath},
     turbopack::{
         core::{
             reference_type::{CommonJsReferenceSubType, ReferenceType},
@@ -776,6 +779,22 @@ async fn insert_next_shared_aliases(
         package_root,
     );
 
+    let image_config = next_config.image_config().await?;
+    if !image_config.loader_file.is_empty() {
+        let loader_file =
+            image_loader_file_request(project_path, &image_config.loader_file).await?;
+        // `next/image` imports the loader from here, like the webpack aliases do.
+        for request in [
+            "next/dist/shared/lib/image-loader",
+            "next/dist/esm/shared/lib/image-loader",
+        ] {
+            import_map.insert_exact_alias(
+                request,
+                request_to_import_mapping(project_path, &loader_file),
+            );
+        }
+    }
+
     import_map.insert_alias(
         // Request path from js via next-font swc transform
         AliasPattern::exact("next/font/google/target.css"),
@@ -998,6 +1017,30 @@ fn insert_turbopack_dev_alias(import_map: &mut ImportMap) {
     );
 }
 
+/// Turns `images.loaderFile`, which is usually an absolute path, into a
+/// request relative to the project.
+async fn image_loader_file_request(
+    project_path: Vc<FileSystemPath>,
+    loader_file: &str,
+) -> Result<String> {
+    let path = Path::new(loader_file);
+    if path.is_relative() {
+        return Ok(format!("./{}", loader_file.trim_start_matches("./")));
+    }
+    if let Some(disk_fs) =
+        Vc::try_resolve_downcast_type::<DiskFileSystem>(project_path.fs()).await?
+    {
+        let project_sys_path = disk_fs.to_sys_path(project_path).await?;
+        if let Ok(relative) = path.strip_prefix(project_sys_path) {
+            return Ok(format!(
+                "./{}",
+                relative.to_string_lossy().replace('\\', "/")
+            ));
+        }
+    }
+    Ok(loader_file.to_string())
+}
+
 /// Creates a direct import mapping to the result of resolving a request
 /// in a context.
 fn request_to_import_mapping(context_path: Vc<FileSystemPath>, request: &str) -> Vc<ImportMapping> {
@@ -1009,3 +1052,35 @@ fn request_to_import_mapping(context_path: Vc<FileSystemPath>, request: &str) ->
 fn external_request_to_import_mapping(request: &str) -> Vc<ImportMapping> {
     ImportMapping::External(Some(request.to_string())).into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::image_loader_file_request;
+    use crate::test_utils::{fixture, fixture_root, run};
+
+    #[tokio::test]
+    async fn test_image_loader_file_request() {
+        let dir = fixture(&[("lib/image-loader.js", "export default ({ src }) => src;")]);
+        let root = dir.path().to_owned();
+        let requests = run(async move {
+            let project_path = fixture_root(&root);
+            let absolute = root.join("lib/image-loader.js");
+            Ok([
+                image_loader_file_request(project_path, absolute.to_str().unwrap()).await?,
+                image_loader_file_request(project_path, "./lib/image-loader.js").await?,
+                image_loader_file_request(project_path, "lib/image-loader.js").await?,
+            ])
+        })
+        .await
+        .unwrap();
+        // The loader is imported relative to the project.
+        assert_eq!(
+            requests,
+            [
+                "./lib/image-loader.js",
+                "./lib/image-loader.js",
+                "./lib/image-loader.js"
+            ]
+        );
+    }
+}
@@ -2,12 +2,19 @@
 
 pub(crate) mod client_reference_manifest;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use serde::{Deserialize, Serialize};
 use turbo_tasks::{trace::TraceRawVcs, TaskInput};
 
-use crate::next_config::Rewrites;
+use crate::{
+    next_app::{
+        app_boundaries::AppSegmentBoundaries, app_ppr_shell::AppPagePrerendering,
+        metadata::static_metadata::AppSegmentMetadata,
+    },
+    next_config::{HeaderValue, I18NConfig, ImageConfig, Rewrites},
+    util::NextRuntime,
+};
 
 #[derive(Serialize, Default, Debug)]
 pub struct PagesManifest {
@@ -15,6 +22,18 @@ pub struct PagesManifest {
     pub pages: HashMap<String, String>,
 }
 
+impl PagesManifest {
+    /// Resolves the paths of the pages, which are relative to the directory of
+    /// the manifest, against `base`, e.g. the absolute path a custom server
+    /// loads the pages from.
+    pub fn resolve_paths(&mut self, base: &str) {
+        let base = base.trim_end_matches(['/', '\\']);
+        for path in self.pages.values_mut() {
+            *path = format!("{base}/{path}");
+        }
+    }
+}
+
 #[derive(Serialize, Default, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct BuildManifest {
@@ -25,6 +44,31 @@ pub struct BuildManifest {
     pub root_main_files: Vec<String>,
     pub pages: HashMap<String, Vec<String>>,
     pub amp_first_pages: Vec<String>,
+    /// The chunks of the client runtime, in the order the HTML must load them
+    /// before any page chunk: the polyfills, then the chunks shared by all app
+    /// pages, i.e. the Turbopack runtime, React and the app bootstrap. Set by
+    /// [BuildManifest::compute_runtime_files].
+    pub runtime_files: Vec<String>,
+    /// The name of the [ClientRuntime] the `runtime_files` are of, e.g.
+    /// `production`. Only set by `next build`.
+    ///
+    /// [ClientRuntime]: crate::next_client::ClientRuntime
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_runtime: Option<String>,
+}
+
+impl BuildManifest {
+    /// Lists the `polyfill_files` and `root_main_files` in `runtime_files`,
+    /// once they're final.
+    pub fn compute_runtime_files(&mut self) {
+        let mut runtime_files: Vec<String> = vec![];
+        for file in self.polyfill_files.iter().chain(&self.root_main_files) {
+            if !runtime_files.contains(file) {
+                runtime_files.push(file.clone());
+            }
+        }
+        self.runtime_files = runtime_files;
+    }
 }
 
 #[derive(Serialize, Debug)]
@@ -109,7 +153,7 @@ pub struct InstrumentationDefinition {
     pub assets: Vec<AssetBinding>,
 }
 
-#[derive(Serialize, Default, Debug)]
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq, TraceRawVcs)]
 #[serde(rename_all = "camelCase")]
 pub struct AssetBinding {
     pub name: String,
@@ -154,6 +198,9 @@ pub struct NextFontManifest {
     pub pages_using_size_adjust: bool,
 }
 
+/// Maps the original name of each app page and route handler, e.g.
+/// `/blog/[slug]/page`, to the path of its server entry chunk, relative to the
+/// `server` directory.
 #[derive(Serialize, Default, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct AppPathsManifest {
@@ -289,10 +336,621 @@ pub struct FontManifestEntry {
     pub content: String,
 }
 
+/// The path prefix the data routes of pages are served under by default,
+/// followed by the build id.
+pub const DEFAULT_DATA_PATH_PREFIX: &str = "/_next/data";
+
+/// Settings read by the client runtime, derived from next.config.js.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientSettingsManifest {
+    /// The web vitals the client collects attribution data for.
+    pub web_vitals_attribution: Vec<String>,
+    /// The `<meta>` tags with trace metadata the client forwards, see
+    /// `experimental.clientTraceMetadata`.
+    pub client_trace_metadata: Vec<String>,
+}
+
+/// Settings read by the server runtime, derived from next.config.js.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerSettingsManifest {
+    /// Whether to send the `X-Powered-By` header.
+    pub powered_by_header: bool,
+    /// Whether to gzip compress responses.
+    pub compress: bool,
+    /// Whether to send `ETag` headers.
+    pub generate_etags: bool,
+}
+
+/// The image optimization settings read by the image optimizer and the custom
+/// loader, like the `images-manifest.json` of webpack builds.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ImagesManifest {
+    pub version: u32,
+    pub images: ImagesManifestConfig,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ImagesManifestConfig {
+    #[serde(flatten)]
+    pub config: ImageConfig,
+    /// The device sizes followed by the image sizes.
+    pub sizes: Vec<u16>,
+}
+
+impl ImagesManifest {
+    pub fn new(config: ImageConfig) -> Self {
+        let sizes = config
+            .device_sizes
+            .iter()
+            .chain(config.image_sizes.iter())
+            .copied()
+            .collect();
+        Self {
+            version: 1,
+            images: ImagesManifestConfig { config, sizes },
+        }
+    }
+}
+
+/// The `routes-manifest.json` written by `next build` before the Turbopack
+/// build runs. The build only adds header rules to it, and keeps the other
+/// fields as they are.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutesManifest {
+    pub version: u32,
+    #[serde(default)]
+    pub headers: Vec<RoutesManifestHeader>,
+    #[serde(flatten)]
+    pub rest: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Default for RoutesManifest {
+    fn default() -> Self {
+        Self {
+            version: 3,
+            headers: Default::default(),
+            rest: Default::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutesManifestHeader {
+    pub source: String,
+    pub headers: Vec<HeaderValue>,
+    pub regex: String,
+    #[serde(flatten)]
+    pub rest: serde_json::Map<String, serde_json::Value>,
+}
+
+impl RoutesManifest {
+    /// Adds a rule caching the files in `_next/static` forever, as their names
+    /// change with their content or the build id.
+    ///
+    /// The rule comes first, so that user `headers()` targeting these files
+    /// override it. It isn't added when a rule for the same source already sets
+    /// `Cache-Control`.
+    pub fn add_immutable_static_assets_header(&mut self, base_path: &str) {
+        let source = format!("{base_path}/_next/static/:path*");
+        if self.headers.iter().any(|header| {
+            header.source == source
+                && header
+                    .headers
+                    .iter()
+                    .any(|header| header.key.eq_ignore_ascii_case("cache-control"))
+        }) {
+            return;
+        }
+
+        // The regex `path-to-regexp` compiles the source to, as `next build`
+        // writes it.
+        let regex = format!(
+            "^{}/_next/static(?:/((?:[^/]+?)(?:/(?:[^/]+?))*))?(?:/)?$",
+            escape_regex(base_path)
+        );
+        self.headers.insert(
+            0,
+            RoutesManifestHeader {
+                source,
+                headers: vec![HeaderValue {
+                    key: "Cache-Control".to_string(),
+                    value: "public, max-age=31536000, immutable".to_string(),
+                }],
+                regex,
+                rest: Default::default(),
+            },
+        );
+    }
+
+    /// Moves the data routes of pages with `getStaticProps` or
+    /// `getServerSideProps` from [DEFAULT_DATA_PATH_PREFIX] to `prefix`, e.g.
+    /// `/proxy/data` for `/proxy/data/<buildId>/about.json`, by rewriting the
+    /// regexes `next build` wrote for them.
+    pub fn set_data_path_prefix(&mut self, prefix: &str) {
+        let Some(serde_json::Value::Array(data_routes)) = self.rest.get_mut("dataRoutes") else {
+            return;
+        };
+        let default_prefix = format!("^{DEFAULT_DATA_PATH_PREFIX}/");
+        let prefix = format!("^{}/", escape_regex(prefix));
+        for data_route in data_routes {
+            for key in ["dataRouteRegex", "namedDataRouteRegex"] {
+                let Some(serde_json::Value::String(regex)) = data_route.get_mut(key) else {
+                    continue;
+                };
+                if let Some(rest) = regex.strip_prefix(&default_prefix) {
+                    *regex = format!("{prefix}{rest}");
+                }
+            }
+        }
+    }
+}
+
+fn escape_regex(str: &str) -> String {
+    let mut escaped = String::with_capacity(str.len());
+    for char in str.chars() {
+        if ".+*?=^!:${}()[]|\\".contains(char) {
+            escaped.push('\\');
+        }
+        escaped.push(char);
+    }
+    escaped
+}
+
+/// The `metadata` exported by the layouts and pages of each app page, so it can
+/// be rendered without evaluating them when it's static.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AppMetadataManifest {
+    /// Keyed by the original name of the page. Segments are ordered from the
+    /// root layout down to the page, which is the order they're merged in.
+    pub pages: HashMap<String, Vec<AppSegmentMetadata>>,
+}
+
+/// How each app page can be prerendered, distinguishing pages that can be
+/// served as a static shell with dynamic holes from fully static and fully
+/// dynamic pages.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PprManifest {
+    /// Keyed by the original name of the page.
+    pub pages: HashMap<String, AppPagePrerendering>,
+}
+
+/// The `loading`, `error` and `not-found` files which apply to the segments of
+/// each app page, including the ones inherited from parent segments.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AppBoundariesManifest {
+    /// Keyed by the original name of the page. Segments are ordered from the
+    /// root segment down, parents before their children.
+    pub pages: HashMap<String, Vec<AppSegmentBoundaries>>,
+}
+
+/// The HTTP methods exported by each app route handler, so the server can
+/// respond to other methods with a 405.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AppRouteMethodsManifest {
+    /// Keyed by the original name of the route.
+    pub routes: BTreeMap<String, Vec<String>>,
+}
+
+/// The `Cache-Control` directives of app pages and route handlers, computed
+/// from their segment configs, for the server or a CDN to apply. Routes whose
+/// segments don't express caching intent are left out.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AppCacheControlManifest {
+    /// Keyed by the original name of the page or route.
+    pub routes: BTreeMap<String, String>,
+}
+
+/// Lists the environment variables inlined into client bundles, to audit them
+/// for accidentally exposed secrets.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientEnvManifest {
+    /// The paths of the modules referencing each inlined variable, keyed by
+    /// the variable name.
+    pub variables: BTreeMap<String, Vec<String>>,
+    /// Inlined variables without a public env prefix (`NEXT_PUBLIC_` by
+    /// default), apart from the ones defined by Next.js itself. These are
+    /// exposed because they are configured in `env`, which should be
+    /// double-checked.
+    pub non_public_variables: Vec<String>,
+    /// The paths of the modules accessing `process.env` dynamically, e.g.
+    /// `process.env[key]`. These can't be audited statically.
+    pub dynamic_access: Vec<String>,
+}
+
+/// The CSS files inlined into the HTML of each page instead of being loaded
+/// with `<link>` tags, see `experimental.inlineCssThreshold`. These files are
+/// removed from the page's entry in the build manifest.
+///
+/// App routes aren't supported yet, as they load their CSS through the client
+/// reference manifest.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineCssManifest {
+    pub pages: BTreeMap<String, Vec<String>>,
+}
+
+/// Per-function settings for the deploy layer, like the
+/// `functions-config-manifest.json` of webpack builds.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionsConfigManifest {
+    pub version: u32,
+    pub functions: BTreeMap<String, FunctionConfig>,
+}
+
+impl Default for FunctionsConfigManifest {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            functions: Default::default(),
+        }
+    }
+}
+
+impl FunctionsConfigManifest {
+    /// Adds the function of `route`, with the settings it doesn't configure
+    /// taken from the project's `defaults`.
+    pub fn add_function(
+        &mut self,
+        route: String,
+        config: FunctionConfig,
+        defaults: &FunctionConfig,
+    ) {
+        self.functions.insert(route, config.with_defaults(defaults));
+    }
+
+    /// Whether any function is configured beyond its runtime, which the deploy
+    /// layer infers otherwise.
+    pub fn has_function_settings(&self) -> bool {
+        self.functions
+            .values()
+            .any(|config| config.regions.is_some() || config.max_duration.is_some())
+    }
+}
+
+#[derive(Serialize, Default, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionConfig {
+    /// The runtime the function runs in, `nodejs` or `edge`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<NextRuntime>,
+    /// The regions to deploy the function to, from `preferredRegion`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub regions: Option<Vec<String>>,
+    /// The timeout of the function in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_duration: Option<u32>,
+}
+
+impl FunctionConfig {
+    /// Sets the unset values to the ones of `defaults`. The runtime is always
+    /// set, as functions run in Node.js unless configured otherwise.
+    pub fn with_defaults(self, defaults: &FunctionConfig) -> Self {
+        Self {
+            runtime: Some(self.runtime.or(defaults.runtime).unwrap_or_default()),
+            regions: self.regions.or_else(|| defaults.regions.clone()),
+            max_duration: self.max_duration.or(defaults.max_duration),
+        }
+    }
+}
+
+/// Maps the client files listed in the build manifests to their
+/// content-addressed copies, when the build is content addressed.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentAddressedManifest {
+    /// Keyed by the original path of the file, relative to `_next`.
+    pub files: BTreeMap<String, String>,
+}
+
+/// Lists every file written by the build, so that deploy tooling can upload
+/// only the files that changed.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployManifest {
+    pub files: Vec<DeployManifestFile>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployManifestFile {
+    /// The path of the file, relative to the dist directory.
+    pub path: String,
+    /// The hex-encoded hash of the file content.
+    pub hash: String,
+    /// The size of the file in bytes.
+    pub size: usize,
+}
+
+/// The resources of each route to hint with `<link rel="preload">` or
+/// `<link rel="modulepreload">` tags, so the HTML renderer can add them without
+/// classifying the files of the build manifests itself.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PreloadManifest {
+    /// Keyed by the pathname of pages and the original name of app pages.
+    pub pages: BTreeMap<String, Vec<PreloadHint>>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PreloadHint {
+    /// The path of the file, relative to `_next`.
+    pub file: String,
+    pub rel: PreloadRel,
+    /// The `as` attribute of the `<link>` tag.
+    #[serde(rename = "as")]
+    pub destination: PreloadDestination,
+    pub priority: PreloadPriority,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PreloadRel {
+    Preload,
+    /// For ES module scripts, which are also parsed ahead of time.
+    Modulepreload,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PreloadDestination {
+    Script,
+    Style,
+    /// Font preloads need a `crossorigin` attribute to be used.
+    Font,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PreloadPriority {
+    /// Needed to render the route, like its entry chunks, CSS and the fonts
+    /// marked for preloading.
+    High,
+    /// Loaded after the route is interactive, like the build manifest the
+    /// client router fetches.
+    Low,
+}
+
+impl PreloadManifest {
+    /// Adds the hints for a route from the files the build manifests list for
+    /// it. Chunks loaded by `import()` aren't listed there, so they're never
+    /// preloaded. Files which can't be preloaded, like source maps, are
+    /// skipped, and a file is only hinted once, with its highest priority.
+    pub fn add_route(
+        &mut self,
+        route: String,
+        entry_files: &[String],
+        font_files: &[String],
+        low_priority_files: &[String],
+    ) {
+        let mut hints: Vec<PreloadHint> = vec![];
+        let files = entry_files
+            .iter()
+            .chain(font_files)
+            .map(|file| (file, PreloadPriority::High))
+            .chain(
+                low_priority_files
+                    .iter()
+                    .map(|file| (file, PreloadPriority::Low)),
+            );
+        for (file, priority) in files {
+            if hints.iter().any(|hint| &hint.file == file) {
+                continue;
+            }
+            if let Some((rel, destination)) = preload_kind(file) {
+                hints.push(PreloadHint {
+                    file: file.clone(),
+                    rel,
+                    destination,
+                    priority,
+                });
+            }
+        }
+        self.pages.insert(route, hints);
+    }
+}
+
+fn preload_kind(file: &str) -> Option<(PreloadRel, PreloadDestination)> {
+    let (_, extension) = file.rsplit_once('.')?;
+    match extension {
+        "js" => Some((PreloadRel::Preload, PreloadDestination::Script)),
+        "mjs" => Some((PreloadRel::Modulepreload, PreloadDestination::Script)),
+        "css" => Some((PreloadRel::Preload, PreloadDestination::Style)),
+        "woff" | "woff2" | "eot" | "ttf" | "otf" => {
+            Some((PreloadRel::Preload, PreloadDestination::Font))
+        }
+        _ => None,
+    }
+}
+
+/// The resources each route renders into its `<head>`, in the order to render
+/// them in, so the HTML renderer can hand-tune what it emits. Render-blocking
+/// stylesheets come first, then the font preloads, the blocking and deferred
+/// scripts in the order they execute, and finally the async scripts.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadOrderManifest {
+    /// Keyed by the pathname of pages and the original name of app pages.
+    pub pages: BTreeMap<String, Vec<LoadOrderResource>>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadOrderResource {
+    /// The path of the file, relative to `_next`.
+    pub file: String,
+    pub kind: LoadOrderKind,
+    /// The `as` attribute of preloads.
+    #[serde(rename = "as", skip_serializing_if = "Option::is_none")]
+    pub destination: Option<PreloadDestination>,
+    pub loading: ResourceLoading,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LoadOrderKind {
+    /// A `<link rel="stylesheet">` tag.
+    Stylesheet,
+    /// A `<link rel="preload">` tag.
+    Preload,
+    /// A classic `<script>` tag.
+    Script,
+    /// A `<script type="module">` tag.
+    Module,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceLoading {
+    /// Blocks rendering, or parsing for scripts, until it's loaded.
+    Blocking,
+    /// Executed in order once the document is parsed.
+    Defer,
+    /// Executed as soon as it's loaded, in any order.
+    Async,
+}
+
+impl LoadOrderManifest {
+    /// Adds the resources of a route from the files the build manifests list
+    /// for it. Its entry scripts are loaded with `script_loading`, e.g. `defer`
+    /// for pages and `async` for app pages, and the `low_priority_files` are
+    /// always async. Like for the [PreloadManifest], files which can't be
+    /// loaded from the `<head>` are skipped and each file is only listed once.
+    pub fn add_route(
+        &mut self,
+        route: String,
+        entry_files: &[String],
+        font_files: &[String],
+        low_priority_files: &[String],
+        script_loading: ResourceLoading,
+    ) {
+        let mut resources: Vec<LoadOrderResource> = vec![];
+        let files = entry_files
+            .iter()
+            .chain(font_files)
+            .map(|file| (file, script_loading))
+            .chain(
+                low_priority_files
+                    .iter()
+                    .map(|file| (file, ResourceLoading::Async)),
+            );
+        for (file, script_loading) in files {
+            if resources.iter().any(|resource| &resource.file == file) {
+                continue;
+            }
+            let Some((_, destination)) = preload_kind(file) else {
+                continue;
+            };
+            let (kind, destination, loading) = match destination {
+                PreloadDestination::Style => {
+                    (LoadOrderKind::Stylesheet, None, ResourceLoading::Blocking)
+                }
+                PreloadDestination::Font => (
+                    LoadOrderKind::Preload,
+                    Some(PreloadDestination::Font),
+                    ResourceLoading::Async,
+                ),
+                // Module scripts are deferred unless they're async.
+                PreloadDestination::Script if file.ends_with(".mjs") => (
+                    LoadOrderKind::Module,
+                    None,
+                    script_loading.max(ResourceLoading::Defer),
+                ),
+                PreloadDestination::Script => (LoadOrderKind::Script, None, script_loading),
+            };
+            resources.push(LoadOrderResource {
+                file: file.clone(),
+                kind,
+                destination,
+                loading,
+            });
+        }
+        // Stable, so deferred scripts keep their execution order.
+        resources.sort_by_key(load_order_rank);
+        self.pages.insert(route, resources);
+    }
+}
+
+/// Stylesheets come before everything else, as they block rendering, and are
+/// followed by the preloads, so fonts download while the scripts are fetched.
+/// Scripts are ordered blocking, deferred, then async.
+fn load_order_rank(resource: &LoadOrderResource) -> (u8, ResourceLoading) {
+    match resource.kind {
+        LoadOrderKind::Stylesheet => (0, resource.loading),
+        LoadOrderKind::Preload => (1, resource.loading),
+        LoadOrderKind::Script | LoadOrderKind::Module => (2, resource.loading),
+    }
+}
+
+/// The pages available in each locale of the `i18n` config, so that the server
+/// can respond with a 404 for a page in a locale it doesn't exist in, instead
+/// of rendering it without translations.
+#[derive(Serialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LocaleRoutesManifest {
+    pub default_locale: String,
+    /// Keyed by the locale. The pathnames don't include the locale.
+    pub locales: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl LocaleRoutesManifest {
+    /// Computes the manifest from the pathnames of the pages. A page whose
+    /// first segment is a locale, like `/de/impressum`, is only available in
+    /// that locale, as `/impressum`. Other pages are available in all locales.
+    /// Pages which aren't localized, i.e. API routes and the special pages like
+    /// `/_app`, are skipped.
+    pub fn new<'a>(i18n: &I18NConfig, pages: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut locales: BTreeMap<String, BTreeSet<String>> = i18n
+            .locales
+            .iter()
+            .map(|locale| (locale.clone(), BTreeSet::new()))
+            .collect();
+
+        for page in pages {
+            if page.starts_with("/_") || page == "/api" || page.starts_with("/api/") {
+                continue;
+            }
+
+            let path = page.strip_prefix('/').unwrap_or(page);
+            let (first_segment, rest) = path.split_once('/').unwrap_or((path, ""));
+            if let Some(routes) = locales.get_mut(first_segment) {
+                routes.insert(format!("/{rest}"));
+            } else {
+                for routes in locales.values_mut() {
+                    routes.insert(page.to_string());
+                }
+            }
+        }
+
+        Self {
+            default_locale: i18n.default_locale.clone(),
+            locales,
+        }
+    }
+}
+
 #[derive(Serialize, Default, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct AppBuildManifest {
     pub pages: HashMap<String, Vec<String>>,
+    /// The pages served with a Content Security Policy restricting scripts,
+    /// whose inline scripts need a per-request nonce. Other pages skip the
+    /// nonce handling entirely.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub nonce_pages: Vec<String>,
 }
 
 // TODO(alexkirsz) Unify with the one for dev.
@@ -302,8 +960,473 @@ pub struct ClientBuildManifest<'a> {
     #[serde(rename = "__rewrites")]
     pub rewrites: &'a Rewrites,
 
+    /// The path prefix of the data routes, when it isn't
+    /// [DEFAULT_DATA_PATH_PREFIX].
+    #[serde(rename = "__dataPathPrefix", skip_serializing_if = "Option::is_none")]
+    pub data_path_prefix: Option<&'a str>,
+
     pub sorted_pages: &'a [String],
 
     #[serde(flatten)]
     pub pages: HashMap<String, Vec<&'a str>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AppBoundariesManifest, BuildManifest, FunctionConfig, FunctionsConfigManifest,
+        LoadOrderManifest, LocaleRoutesManifest, PagesManifest, PreloadDestination,
+        PreloadManifest, PreloadPriority, PreloadRel, ResourceLoading, RoutesManifest,
+    };
+    use crate::{
+        next_app::app_boundaries::AppSegmentBoundaries, next_config::I18NConfig, util::NextRuntime,
+    };
+
+    fn pages_manifest() -> PagesManifest {
+        PagesManifest {
+            pages: [
+                ("/".to_string(), "pages/index.js".to_string()),
+                (
+                    "/blog/[slug]".to_string(),
+                    "pages/blog/[slug].js".to_string(),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+
+    #[test]
+    fn test_pages_manifest_resolve_paths() {
+        let mut manifest = pages_manifest();
+        manifest.resolve_paths("/srv/app/.next/server/");
+        assert_eq!(manifest.pages["/"], "/srv/app/.next/server/pages/index.js");
+        assert_eq!(
+            manifest.pages["/blog/[slug]"],
+            "/srv/app/.next/server/pages/blog/[slug].js"
+        );
+    }
+
+    #[test]
+    fn test_build_manifest_runtime_files() {
+        let mut manifest = BuildManifest {
+            polyfill_files: vec!["static/chunks/polyfills.js".to_string()],
+            root_main_files: vec![
+                "static/chunks/turbopack-runtime.js".to_string(),
+                "static/chunks/framework.js".to_string(),
+                "static/chunks/main-app.js".to_string(),
+                "static/chunks/framework.js".to_string(),
+            ],
+            ..Default::default()
+        };
+        manifest.compute_runtime_files();
+        assert_eq!(
+            manifest.runtime_files,
+            [
+                "static/chunks/polyfills.js",
+                "static/chunks/turbopack-runtime.js",
+                "static/chunks/framework.js",
+                "static/chunks/main-app.js",
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&manifest).unwrap()["runtimeFiles"],
+            serde_json::json!(manifest.runtime_files)
+        );
+    }
+
+    #[test]
+    fn test_pages_manifest_relative_paths() {
+        let manifest = pages_manifest();
+        assert_eq!(
+            serde_json::to_value(&manifest).unwrap(),
+            serde_json::json!({
+                "/": "pages/index.js",
+                "/blog/[slug]": "pages/blog/[slug].js",
+            })
+        );
+    }
+
+    #[test]
+    fn test_immutable_static_assets_header() {
+        let mut manifest = RoutesManifest::default();
+        manifest.add_immutable_static_assets_header("");
+        manifest.add_immutable_static_assets_header("");
+
+        assert_eq!(manifest.headers.len(), 1);
+        let header = &manifest.headers[0];
+        assert_eq!(header.source, "/_next/static/:path*");
+        assert_eq!(header.headers[0].key, "Cache-Control");
+        assert_eq!(
+            header.headers[0].value,
+            "public, max-age=31536000, immutable"
+        );
+        assert_eq!(
+            header.regex,
+            "^/_next/static(?:/((?:[^/]+?)(?:/(?:[^/]+?))*))?(?:/)?$"
+        );
+    }
+
+    #[test]
+    fn test_immutable_static_assets_header_with_base_path() {
+        let mut manifest = RoutesManifest::default();
+        manifest.add_immutable_static_assets_header("/docs.v2");
+
+        assert_eq!(manifest.headers[0].source, "/docs.v2/_next/static/:path*");
+        assert!(manifest.headers[0]
+            .regex
+            .starts_with("^/docs\\.v2/_next/static"));
+    }
+
+    #[test]
+    fn test_immutable_static_assets_header_keeps_user_rule() {
+        let mut manifest: RoutesManifest = serde_json::from_str(
+            r#"{
+                "version": 3,
+                "basePath": "",
+                "headers": [{
+                    "source": "/_next/static/:path*",
+                    "headers": [{ "key": "cache-control", "value": "no-store" }],
+                    "regex": "^/_next/static(?:/((?:[^/]+?)(?:/(?:[^/]+?))*))?(?:/)?$"
+                }]
+            }"#,
+        )
+        .unwrap();
+        manifest.add_immutable_static_assets_header("");
+
+        assert_eq!(manifest.headers.len(), 1);
+        assert_eq!(manifest.headers[0].headers[0].value, "no-store");
+        assert_eq!(manifest.rest["basePath"], "");
+    }
+
+    #[test]
+    fn test_data_path_prefix() {
+        let mut manifest: RoutesManifest = serde_json::from_str(
+            r#"{
+                "version": 3,
+                "dataRoutes": [
+                    {
+                        "page": "/about",
+                        "dataRouteRegex": "^/_next/data/build\\-id/about.json$"
+                    },
+                    {
+                        "page": "/posts/[id]",
+                        "routeKeys": { "nxtPid": "nxtPid" },
+                        "dataRouteRegex": "^/_next/data/build\\-id/posts/([^/]+?)\\.json$",
+                        "namedDataRouteRegex": "^/_next/data/build\\-id/posts/(?<nxtPid>[^/]+?)\\.json$"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        manifest.set_data_path_prefix("/proxy/data.v2");
+
+        let data_routes = &manifest.rest["dataRoutes"];
+        assert_eq!(
+            data_routes[0]["dataRouteRegex"],
+            "^/proxy/data\\.v2/build\\-id/about.json$"
+        );
+        assert_eq!(
+            data_routes[1]["dataRouteRegex"],
+            "^/proxy/data\\.v2/build\\-id/posts/([^/]+?)\\.json$"
+        );
+        assert_eq!(
+            data_routes[1]["namedDataRouteRegex"],
+            "^/proxy/data\\.v2/build\\-id/posts/(?<nxtPid>[^/]+?)\\.json$"
+        );
+        assert_eq!(data_routes[1]["page"], "/posts/[id]");
+    }
+
+    #[test]
+    fn test_preload_manifest() {
+        let mut manifest = PreloadManifest::default();
+        manifest.add_route(
+            "/blog".to_string(),
+            &[
+                "static/chunks/pages/blog.js".to_string(),
+                "static/chunks/pages/blog.js.map".to_string(),
+                "static/chunks/pages/blog.css".to_string(),
+            ],
+            &["static/media/inter.p.woff2".to_string()],
+            &[
+                "static/build-id/_buildManifest.js".to_string(),
+                "static/chunks/pages/blog.js".to_string(),
+            ],
+        );
+
+        let hints = &manifest.pages["/blog"];
+        assert_eq!(hints.len(), 4);
+        assert_eq!(hints[0].file, "static/chunks/pages/blog.js");
+        assert_eq!(hints[0].rel, PreloadRel::Preload);
+        assert_eq!(hints[0].destination, PreloadDestination::Script);
+        assert_eq!(hints[0].priority, PreloadPriority::High);
+        assert_eq!(hints[1].destination, PreloadDestination::Style);
+        assert_eq!(hints[2].destination, PreloadDestination::Font);
+        assert_eq!(hints[2].priority, PreloadPriority::High);
+        assert_eq!(hints[3].file, "static/build-id/_buildManifest.js");
+        assert_eq!(hints[3].priority, PreloadPriority::Low);
+
+        // Chunks of `import()`s aren't listed for the route.
+        assert!(!hints
+            .iter()
+            .any(|hint| hint.file == "static/chunks/dynamic.js"));
+    }
+
+    #[test]
+    fn test_preload_manifest_module_scripts() {
+        let mut manifest = PreloadManifest::default();
+        manifest.add_route(
+            "/".to_string(),
+            &["static/chunks/main.mjs".to_string()],
+            &[],
+            &[],
+        );
+
+        let hint = &manifest.pages["/"][0];
+        assert_eq!(hint.rel, PreloadRel::Modulepreload);
+        assert_eq!(hint.destination, PreloadDestination::Script);
+        assert_eq!(
+            serde_json::to_value(hint).unwrap(),
+            serde_json::json!({
+                "file": "static/chunks/main.mjs",
+                "rel": "modulepreload",
+                "as": "script",
+                "priority": "high",
+            })
+        );
+    }
+
+    #[test]
+    fn test_load_order_manifest() {
+        let mut manifest = LoadOrderManifest::default();
+        manifest.add_route(
+            "/blog".to_string(),
+            &[
+                "static/chunks/webpack.js".to_string(),
+                "static/chunks/pages/blog.js".to_string(),
+                "static/chunks/pages/blog.js.map".to_string(),
+                "static/chunks/pages/blog.css".to_string(),
+                "static/chunks/main.mjs".to_string(),
+            ],
+            &["static/media/inter.p.woff2".to_string()],
+            &[
+                "static/build-id/_buildManifest.js".to_string(),
+                "static/chunks/pages/blog.js".to_string(),
+            ],
+            ResourceLoading::Defer,
+        );
+        assert_eq!(
+            serde_json::to_value(&manifest.pages["/blog"]).unwrap(),
+            serde_json::json!([
+                {
+                    "file": "static/chunks/pages/blog.css",
+                    "kind": "stylesheet",
+                    "loading": "blocking",
+                },
+                {
+                    "file": "static/media/inter.p.woff2",
+                    "kind": "preload",
+                    "as": "font",
+                    "loading": "async",
+                },
+                {
+                    "file": "static/chunks/webpack.js",
+                    "kind": "script",
+                    "loading": "defer",
+                },
+                {
+                    "file": "static/chunks/pages/blog.js",
+                    "kind": "script",
+                    "loading": "defer",
+                },
+                {
+                    "file": "static/chunks/main.mjs",
+                    "kind": "module",
+                    "loading": "defer",
+                },
+                {
+                    "file": "static/build-id/_buildManifest.js",
+                    "kind": "script",
+                    "loading": "async",
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_load_order_manifest_async_scripts() {
+        let mut manifest = LoadOrderManifest::default();
+        manifest.add_route(
+            "/page".to_string(),
+            &[
+                "static/chunks/main-app.js".to_string(),
+                "static/chunks/app/layout.css".to_string(),
+                "static/chunks/app/page.mjs".to_string(),
+            ],
+            &[],
+            &[],
+            ResourceLoading::Async,
+        );
+
+        let resources = &manifest.pages["/page"];
+        assert_eq!(resources[0].file, "static/chunks/app/layout.css");
+        assert_eq!(resources[0].loading, ResourceLoading::Blocking);
+        assert_eq!(resources[1].file, "static/chunks/main-app.js");
+        assert_eq!(resources[1].loading, ResourceLoading::Async);
+        assert_eq!(resources[2].file, "static/chunks/app/page.mjs");
+        assert_eq!(resources[2].loading, ResourceLoading::Async);
+    }
+
+    #[test]
+    fn test_locale_routes_manifest() {
+        let i18n = I18NConfig {
+            default_locale: "en".to_string(),
+            domains: None,
+            locale_detection: None,
+            locales: vec!["en".to_string(), "de".to_string(), "fr".to_string()],
+        };
+        let manifest = LocaleRoutesManifest::new(
+            &i18n,
+            [
+                "/",
+                "/_app",
+                "/api/hello",
+                "/about",
+                "/blog/[slug]",
+                "/de/impressum",
+                "/en/terms",
+                "/en",
+            ],
+        );
+
+        assert_eq!(manifest.default_locale, "en");
+        assert_eq!(
+            manifest.locales["en"].iter().collect::<Vec<_>>(),
+            ["/", "/about", "/blog/[slug]", "/terms"]
+        );
+        assert_eq!(
+            manifest.locales["de"].iter().collect::<Vec<_>>(),
+            ["/", "/about", "/blog/[slug]", "/impressum"]
+        );
+        assert_eq!(
+            manifest.locales["fr"].iter().collect::<Vec<_>>(),
+            ["/", "/about", "/blog/[slug]"]
+        );
+    }
+
+    #[test]
+    fn test_app_boundaries_manifest_inherits_boundaries() {
+        let root = AppSegmentBoundaries::nested(
+            None,
+            "/".to_string(),
+            None,
+            Some("app/error.tsx".to_string()),
+            Some("app/not-found.tsx".to_string()),
+        );
+        let dashboard = AppSegmentBoundaries::nested(
+            Some(&root),
+            "/dashboard".to_string(),
+            Some("app/dashboard/loading.tsx".to_string()),
+            None,
+            None,
+        );
+        let settings = AppSegmentBoundaries::nested(
+            Some(&dashboard),
+            "/dashboard/settings".to_string(),
+            None,
+            Some("app/dashboard/settings/error.tsx".to_string()),
+            None,
+        );
+        let manifest = AppBoundariesManifest {
+            pages: [(
+                "/dashboard/settings/page".to_string(),
+                vec![root, dashboard, settings],
+            )]
+            .into_iter()
+            .collect(),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&manifest).unwrap(),
+            serde_json::json!({
+                "pages": {
+                    "/dashboard/settings/page": [
+                        {
+                            "segment": "/",
+                            "loading": null,
+                            "error": { "path": "app/error.tsx", "inherited": false },
+                            "notFound": { "path": "app/not-found.tsx", "inherited": false },
+                        },
+                        {
+                            "segment": "/dashboard",
+                            "loading": { "path": "app/dashboard/loading.tsx", "inherited": false },
+                            "error": { "path": "app/error.tsx", "inherited": true },
+                            "notFound": { "path": "app/not-found.tsx", "inherited": true },
+                        },
+                        {
+                            "segment": "/dashboard/settings",
+                            "loading": { "path": "app/dashboard/loading.tsx", "inherited": true },
+                            "error": {
+                                "path": "app/dashboard/settings/error.tsx",
+                                "inherited": false,
+                            },
+                            "notFound": { "path": "app/not-found.tsx", "inherited": true },
+                        },
+                    ],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_functions_config_manifest_defaults() {
+        let defaults = FunctionConfig {
+            regions: Some(vec!["iad1".to_string()]),
+            max_duration: Some(10),
+            ..Default::default()
+        };
+        let mut manifest = FunctionsConfigManifest::default();
+        manifest.add_function("/".to_string(), FunctionConfig::default(), &defaults);
+        manifest.add_function(
+            "/api/edge".to_string(),
+            FunctionConfig {
+                runtime: Some(NextRuntime::Edge),
+                regions: Some(vec!["fra1".to_string(), "sfo1".to_string()]),
+                ..Default::default()
+            },
+            &defaults,
+        );
+        manifest.add_function(
+            "/blog/[slug]".to_string(),
+            FunctionConfig {
+                runtime: Some(NextRuntime::NodeJs),
+                max_duration: Some(60),
+                ..Default::default()
+            },
+            &defaults,
+        );
+        assert_eq!(
+            serde_json::to_value(&manifest).unwrap(),
+            serde_json::json!({
+                "version": 1,
+                "functions": {
+                    "/": {
+                        "runtime": "nodejs",
+                        "regions": ["iad1"],
+                        "maxDuration": 10,
+                    },
+                    "/api/edge": {
+                        "runtime": "edge",
+                        "regions": ["fra1", "sfo1"],
+                        "maxDuration": 10,
+                    },
+                    "/blog/[slug]": {
+                        "runtime": "nodejs",
+                        "regions": ["iad1"],
+                        "maxDuration": 60,
+                    },
+                },
+            })
+        );
+    }
+}
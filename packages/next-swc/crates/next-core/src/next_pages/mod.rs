@@ -1,3 +1,4 @@
 pub(crate) mod page_entry;
+pub(crate) mod page_exports;
 
 pub use page_entry::create_page_ssr_entry_module;
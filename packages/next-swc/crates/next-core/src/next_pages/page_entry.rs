@@ -14,15 +14,20 @@ use turbopack_binding::{
         core::{
             asset::{Asset, AssetContent},
             context::AssetContext,
+            issue::IssueSeverity,
             module::Module,
-            reference_type::{EntryReferenceSubType, ReferenceType},
+            reference_type::{
+                EcmaScriptModulesReferenceSubType, EntryReferenceSubType, ReferenceType,
+            },
+            resolve::{origin::PlainResolveOrigin, parse::Request, pattern::Pattern},
             source::Source,
             virtual_source::VirtualSource,
         },
-        ecmascript::{chunk::EcmascriptChunkPlaceable, utils::StringifyJs},
+        ecmascript::{chunk::EcmascriptChunkPlaceable, resolve::esm_resolve, utils::StringifyJs},
     },
 };
 
+use super::page_exports::{document_has_get_initial_props, validate_page_exports};
 use crate::{
     next_config::NextConfig,
     next_edge::entry::wrap_edge_entry,
@@ -49,6 +54,12 @@ pub async fn create_page_ssr_entry_module(
 
     let reference_type = reference_type.into_value();
 
+    validate_page_exports(
+        ssr_module,
+        reference_type == ReferenceType::Entry(EntryReferenceSubType::PagesApi),
+    )
+    .await?;
+
     let template_file = match (&reference_type, runtime) {
         (ReferenceType::Entry(EntryReferenceSubType::Page), _) => {
             // Load the Page entry file.
@@ -94,6 +105,48 @@ pub async fn create_page_ssr_entry_module(
     )
     .await?;
 
+    // A document with its own `getInitialProps` has to be called by the server
+    // render, so it's exposed and flagged on the entry. Otherwise the renderer
+    // can take the fast path.
+    if reference_type == ReferenceType::Entry(EntryReferenceSubType::Page) {
+        let document_module = esm_resolve(
+            Vc::upcast(PlainResolveOrigin::new(
+                ssr_module_context,
+                project_root.join("_".to_string()),
+            )),
+            Request::parse(Value::new(Pattern::Constant(
+                "@vercel/turbopack-next/pages/_document".to_string(),
+            ))),
+            Value::new(EcmaScriptModulesReferenceSubType::Undefined),
+            IssueSeverity::Error.cell(),
+            None,
+        )
+        .first_module()
+        .await?;
+
+        if let Some(document_module) = *document_module {
+            if *document_has_get_initial_props(document_module).await? {
+                let file = &*file_content_rope(source.content().file_content()).await?;
+
+                let mut result = RopeBuilder::default();
+                result += file;
+
+                writeln!(
+                    result,
+                    r#"export const documentGetInitialProps = Document.getInitialProps
+export const isAdvancedDocument = true"#
+                )?;
+
+                let file = File::from(result.build());
+
+                source = Vc::upcast(VirtualSource::new(
+                    source.ident().path(),
+                    AssetContent::file(file.into()),
+                ));
+            }
+        }
+    }
+
     // When we're building the instrumentation page (only when the
     // instrumentation file conflicts with a page also labeled
     // /instrumentation) hoist the `register` method.
@@ -0,0 +1,399 @@
+use anyhow::Result;
+use swc_core::ecma::ast::{
+    Class, ClassMember, Decl, DefaultDecl, ExportSpecifier, Expr, MemberProp, ModuleDecl,
+    ModuleExportName, ModuleItem, Pat, PatOrExpr, Program, PropName, Stmt,
+};
+use turbo_tasks::Vc;
+use turbopack_binding::{
+    turbo::tasks_fs::FileSystemPath,
+    turbopack::{
+        core::{
+            ident::AssetIdent,
+            issue::{Issue, IssueExt, IssueSeverity, OptionStyledString, StyledString},
+            module::Module,
+        },
+        ecmascript::{parse::ParseResult, EcmascriptModuleAsset},
+    },
+};
+
+/// Exports that Next.js only understands on pages, and which are meaningless
+/// (and likely a mistake) on API routes.
+const DATA_FETCHING_EXPORTS: &[&str] = &["getStaticProps", "getStaticPaths", "getServerSideProps"];
+
+/// The exports of a module, as far as they can be statically determined.
+#[derive(Default)]
+struct ModuleExports {
+    names: Vec<String>,
+    has_default: bool,
+    /// `export * from "..."` can contribute named exports we can't see.
+    has_star_reexport: bool,
+}
+
+fn export_name(name: &ModuleExportName) -> String {
+    match name {
+        ModuleExportName::Ident(ident) => ident.sym.to_string(),
+        ModuleExportName::Str(str) => str.value.to_string(),
+    }
+}
+
+fn collect_exports(body: &[ModuleItem]) -> ModuleExports {
+    let mut exports = ModuleExports::default();
+
+    for item in body {
+        let Some(decl) = item.as_module_decl() else {
+            continue;
+        };
+
+        match decl {
+            ModuleDecl::ExportDefaultDecl(_) | ModuleDecl::ExportDefaultExpr(_) => {
+                exports.has_default = true;
+            }
+            ModuleDecl::ExportDecl(export_decl) => match &export_decl.decl {
+                Decl::Fn(decl) => exports.names.push(decl.ident.sym.to_string()),
+                Decl::Class(decl) => exports.names.push(decl.ident.sym.to_string()),
+                Decl::Var(decl) => {
+                    for decl in &decl.decls {
+                        if let Pat::Ident(ident) = &decl.name {
+                            exports.names.push(ident.id.sym.to_string());
+                        }
+                    }
+                }
+                _ => {}
+            },
+            ModuleDecl::ExportNamed(named_export) => {
+                for specifier in &named_export.specifiers {
+                    let name = match specifier {
+                        ExportSpecifier::Named(specifier) => {
+                            export_name(specifier.exported.as_ref().unwrap_or(&specifier.orig))
+                        }
+                        ExportSpecifier::Default(specifier) => specifier.exported.sym.to_string(),
+                        ExportSpecifier::Namespace(specifier) => export_name(&specifier.name),
+                    };
+                    if name == "default" {
+                        exports.has_default = true;
+                    } else {
+                        exports.names.push(name);
+                    }
+                }
+            }
+            ModuleDecl::ExportAll(_) => {
+                exports.has_star_reexport = true;
+            }
+            _ => {}
+        }
+    }
+
+    exports
+}
+
+impl ModuleExports {
+    /// The data fetching methods among the exports, which are an error on API
+    /// routes.
+    fn data_fetching_exports(&self) -> impl Iterator<Item = &str> {
+        self.names
+            .iter()
+            .map(|name| name.as_str())
+            .filter(|name| DATA_FETCHING_EXPORTS.contains(name))
+    }
+
+    /// Whether a page is missing its default export. A star re-export may
+    /// provide it.
+    fn lacks_default(&self) -> bool {
+        !self.has_default && !self.has_star_reexport
+    }
+}
+
+/// Validates the exports of a pages router file: pages must export a
+/// default component, and data fetching methods can only be exported from
+/// pages, not from API routes.
+#[turbo_tasks::function]
+pub async fn validate_page_exports(
+    module: Vc<Box<dyn Module>>,
+    is_api_route: bool,
+) -> Result<Vc<()>> {
+    let Some(ecmascript_asset) =
+        Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await?
+    else {
+        return Ok(Default::default());
+    };
+
+    let ParseResult::Ok {
+        program: Program::Module(module_ast),
+        ..
+    } = &*ecmascript_asset.parse().await?
+    else {
+        return Ok(Default::default());
+    };
+
+    let exports = collect_exports(&module_ast.body);
+
+    if is_api_route {
+        for name in exports.data_fetching_exports() {
+            PageExportsIssue {
+                ident: module.ident(),
+                title: format!("`{name}` can not be exported from an API route"),
+                description: format!(
+                    "`{name}` is only supported on pages. API routes are not rendered, so it \
+                     would never be called. Move it to a page in the pages directory."
+                ),
+            }
+            .cell()
+            .emit();
+        }
+    } else if exports.lacks_default() {
+        PageExportsIssue {
+            ident: module.ident(),
+            title: "The default export is not a React Component".to_string(),
+            description: "Pages must export a React component as their default export, e.g. \
+                          `export default function Page() {}`."
+                .to_string(),
+        }
+        .cell()
+        .emit();
+    }
+
+    Ok(Default::default())
+}
+
+fn has_static_get_initial_props(class: &Class) -> bool {
+    class.body.iter().any(|member| {
+        let (key, is_static) = match member {
+            ClassMember::Method(method) => (&method.key, method.is_static),
+            ClassMember::ClassProp(prop) => (&prop.key, prop.is_static),
+            _ => return false,
+        };
+        is_static && matches!(key, PropName::Ident(ident) if &*ident.sym == "getInitialProps")
+    })
+}
+
+fn is_get_initial_props_assignment(expr: &Expr) -> bool {
+    let Expr::Assign(assign) = expr else {
+        return false;
+    };
+    let left = match &assign.left {
+        PatOrExpr::Expr(expr) => &**expr,
+        PatOrExpr::Pat(pat) => match &**pat {
+            Pat::Expr(expr) => &**expr,
+            _ => return false,
+        },
+    };
+    matches!(
+        left,
+        Expr::Member(member)
+            if matches!(&member.prop, MemberProp::Ident(ident) if &*ident.sym == "getInitialProps")
+    )
+}
+
+/// Whether a module defines a `getInitialProps`, either as a static member of
+/// a top-level class or by assigning `Component.getInitialProps`.
+///
+/// This doesn't check that it belongs to the default export, but a false
+/// positive only means the document takes the slower path.
+fn defines_get_initial_props(body: &[ModuleItem]) -> bool {
+    body.iter().any(|item| match item {
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(export)) => match &export.decl {
+            DefaultDecl::Class(class) => has_static_get_initial_props(&class.class),
+            _ => false,
+        },
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) => match &export.decl {
+            Decl::Class(decl) => has_static_get_initial_props(&decl.class),
+            _ => false,
+        },
+        ModuleItem::Stmt(Stmt::Decl(Decl::Class(decl))) => {
+            has_static_get_initial_props(&decl.class)
+        }
+        ModuleItem::Stmt(Stmt::Expr(stmt)) => is_get_initial_props_assignment(&stmt.expr),
+        _ => false,
+    })
+}
+
+/// Whether a custom `_document` defines its own `getInitialProps`, which
+/// makes it an "advanced" document the server render has to call. Documents
+/// that re-export or extend `next/document` without one take the fast path.
+#[turbo_tasks::function]
+pub async fn document_has_get_initial_props(module: Vc<Box<dyn Module>>) -> Result<Vc<bool>> {
+    let Some(ecmascript_asset) =
+        Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await?
+    else {
+        return Ok(Vc::cell(false));
+    };
+
+    let ParseResult::Ok {
+        program: Program::Module(module_ast),
+        ..
+    } = &*ecmascript_asset.parse().await?
+    else {
+        return Ok(Vc::cell(false));
+    };
+
+    Ok(Vc::cell(defines_get_initial_props(&module_ast.body)))
+}
+
+#[turbo_tasks::value(shared)]
+struct PageExportsIssue {
+    ident: Vc<AssetIdent>,
+    title: String,
+    description: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for PageExportsIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Text(self.title.clone()).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("pages".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.ident.path()
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(StyledString::Text(self.description.clone()).cell()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::{sync::Lrc, FileName, SourceMap},
+        ecma::{
+            ast::{EsVersion, ModuleItem},
+            parser::{parse_file_as_module, EsConfig, Syntax},
+        },
+    };
+
+    use super::{collect_exports, defines_get_initial_props};
+
+    fn parse(src: &str) -> Vec<ModuleItem> {
+        let cm = Lrc::<SourceMap>::default();
+        let fm = cm.new_source_file(FileName::Anon, src.to_string());
+        parse_file_as_module(
+            &fm,
+            Syntax::Es(EsConfig {
+                jsx: true,
+                ..Default::default()
+            }),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap()
+        .body
+    }
+
+    #[test]
+    fn test_defines_get_initial_props() {
+        assert!(defines_get_initial_props(&parse(
+            r#"
+            import Document from "next/document";
+            export default class MyDocument extends Document {
+                static async getInitialProps(ctx) {
+                    return Document.getInitialProps(ctx);
+                }
+            }
+            "#
+        )));
+        assert!(defines_get_initial_props(&parse(
+            r#"
+            class MyDocument extends Document {
+                static getInitialProps = Document.getInitialProps;
+            }
+            export default MyDocument;
+            "#
+        )));
+        assert!(defines_get_initial_props(&parse(
+            r#"
+            export default function MyDocument() {}
+            MyDocument.getInitialProps = async () => ({ html: "" });
+            "#
+        )));
+
+        // Documents without one take the fast path.
+        assert!(!defines_get_initial_props(&parse(
+            r#"export { default } from "next/document";"#
+        )));
+        assert!(!defines_get_initial_props(&parse(
+            r#"
+            import Document from "next/document";
+            export default class MyDocument extends Document {
+                getInitialProps() {}
+                render() {
+                    return <Html />;
+                }
+            }
+            "#
+        )));
+    }
+
+    #[test]
+    fn test_collect_exports() {
+        let exports = collect_exports(&parse(
+            r#"
+            export function getStaticProps() {}
+            export const revalidate = 60, { a } = {};
+            export class Model {}
+            export { helper as renamed };
+            export * as utils from "./utils";
+            export default function Page() {}
+            "#,
+        ));
+        assert_eq!(
+            exports.names,
+            [
+                "getStaticProps",
+                "revalidate",
+                "Model",
+                "renamed",
+                "quoted",
+                "utils"
+            ]
+        );
+        assert!(exports.has_default);
+        assert!(!exports.has_star_reexport);
+        assert!(!exports.lacks_default());
+
+        let exports = collect_exports(&parse(r#"export { Page as default };"#));
+        assert!(exports.names.is_empty());
+        assert!(exports.has_default);
+    }
+
+    #[test]
+    fn test_data_fetching_exports() {
+        // The issue for API routes is reported for each of these.
+        let exports = collect_exports(&parse(
+            r#"
+            export async function getStaticProps() {}
+            export const getServerSideProps = async () => {};
+            export const config = {};
+            export default function handler(req, res) {}
+            "#,
+        ));
+        assert_eq!(
+            exports.data_fetching_exports().collect::<Vec<_>>(),
+            ["getStaticProps", "getServerSideProps"]
+        );
+    }
+
+    #[test]
+    fn test_star_reexport() {
+        // The default export may come from the re-exported module.
+        let exports = collect_exports(&parse(r#"export * from "./page";"#));
+        assert!(exports.has_star_reexport);
+        assert!(!exports.has_default);
+        assert!(!exports.lacks_default());
+
+        assert!(collect_exports(&parse("export const a = 1;")).lacks_default());
+    }
+}
@@ -60,7 +60,7 @@ use crate::{
     },
     sass::maybe_add_sass_loader,
     transform_options::{
-        get_decorators_transform_options, get_jsx_transform_options,
+        get_decorators_transform_options, get_jsx_transform_options, get_tsconfig_path,
         get_typescript_transform_options,
     },
     util::{foreign_code_context_condition, load_next_js_templateon},
@@ -211,6 +211,7 @@ pub async fn get_server_resolve_options_context(
 
     Ok(ResolveOptionsContext {
         enable_typescript: true,
+        tsconfig_path: get_tsconfig_path(project_path, next_config).await?,
         enable_react: true,
         enable_mjs_extension: true,
         rules: vec![(
@@ -322,11 +323,12 @@ pub async fn get_server_module_options_context(
     // EcmascriptTransformPlugins for custom transforms
     let styled_components_transform_plugin =
         *get_styled_components_transform_plugin(next_config).await?;
-    let styled_jsx_transform_plugin = *get_styled_jsx_transform_plugin(use_lightningcss).await?;
+    let styled_jsx_transform_plugin =
+        *get_styled_jsx_transform_plugin(next_config, use_lightningcss).await?;
 
     // ModuleOptionsContext related options
-    let tsconfig = get_typescript_transform_options(project_path);
-    let decorators_options = get_decorators_transform_options(project_path);
+    let tsconfig = get_typescript_transform_options(project_path, next_config);
+    let decorators_options = get_decorators_transform_options(project_path, next_config);
     let enable_mdx_rs = if *next_config.mdx_rs().await? {
         Some(
             MdxTransformModuleOptions {
@@ -707,6 +709,21 @@ pub fn get_server_runtime_entries(
     Vc::cell(runtime_entries)
 }
 
+/// Whether server chunks are minified. Builds follow `minify_server` when
+/// it's set, e.g. from `BuildOptions::minify_server`, or else next.config.js's
+/// `experimental.serverMinification`. Development keeps the server code
+/// readable.
+pub fn server_minify(
+    mode: NextMode,
+    server_minification: bool,
+    minify_server: Option<bool>,
+) -> bool {
+    match mode {
+        NextMode::Development => false,
+        NextMode::Build => minify_server.unwrap_or(server_minification),
+    }
+}
+
 #[turbo_tasks::function]
 pub fn get_server_chunking_context(
     project_path: Vc<FileSystemPath>,
@@ -716,6 +733,7 @@ pub fn get_server_chunking_context(
     client_root: Vc<FileSystemPath>,
     asset_prefix: Vc<Option<String>>,
     environment: Vc<Environment>,
+    minify: bool,
 ) -> Vc<BuildChunkingContext> {
     // TODO(alexkirsz) This should return a trait that can be implemented by the
     // different server chunking contexts. OR the build chunking context should
@@ -729,6 +747,27 @@ pub fn get_server_chunking_context(
         environment,
     )
     .asset_prefix(asset_prefix)
-    .minify_type(MinifyType::NoMinify)
+    .minify_type(if minify {
+        MinifyType::Minify
+    } else {
+        MinifyType::NoMinify
+    })
     .build()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::server_minify;
+    use crate::mode::NextMode;
+
+    #[test]
+    fn test_server_minify() {
+        // `minify_server: false` keeps the server output readable, even though
+        // serverMinification is on.
+        assert!(!server_minify(NextMode::Build, true, Some(false)));
+        assert!(server_minify(NextMode::Build, false, Some(true)));
+        assert!(server_minify(NextMode::Build, true, None));
+        assert!(!server_minify(NextMode::Build, false, None));
+        assert!(!server_minify(NextMode::Development, true, None));
+    }
+}
@@ -4,5 +4,6 @@ pub(crate) mod transforms;
 
 pub use context::{
     get_server_chunking_context, get_server_compile_time_info, get_server_module_options_context,
-    get_server_resolve_options_context, get_server_runtime_entries, ServerContextType,
+    get_server_resolve_options_context, get_server_runtime_entries, server_minify,
+    ServerContextType,
 };
@@ -92,6 +92,14 @@ impl ResolvePlugin for ExternalCjsModulesResolvePlugin {
             return Ok(ResolveResultOption::none());
         }
 
+        // Workspace packages, e.g. `"@acme/ui": "workspace:*"`, are symlinked
+        // into `node_modules`, but live outside of it. They're bundled like the
+        // project's own code, whether they point to their source or their own
+        // build output, so that edits to them are picked up in watch mode.
+        if !*condition(self.root).matches(fs_path.realpath()).await? {
+            return Ok(ResolveResultOption::none());
+        }
+
         let raw_fs_path = &*fs_path.await?;
 
         let predicate = self.predicate.await?;
@@ -226,3 +234,141 @@ async fn packages_glob(packages: Vc<Vec<String>>) -> Result<Vc<OptionGlob>> {
             .await?,
     )))
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::time::Duration;
+
+    use turbo_tasks::{Value, Vc};
+    use turbopack_binding::{
+        turbo::tasks_fs::{DiskFileSystem, FileContent, FileSystem, FileSystemPath},
+        turbopack::core::{
+            reference_type::{CommonJsReferenceSubType, ReferenceType},
+            resolve::{parse::Request, pattern::Pattern, plugin::ResolvePlugin},
+        },
+    };
+
+    use super::{ExternalCjsModulesResolvePlugin, ExternalPredicate};
+    use crate::test_utils::{fixture, fixture_root, run};
+
+    /// A project depending on `"@acme/ui": "workspace:*"`, linked into
+    /// `node_modules` like package managers do, and on a regular package.
+    fn workspace_fixture() -> tempfile::TempDir {
+        let dir = fixture(&[
+            (
+                "package.json",
+                r#"{"dependencies": {"@acme/ui": "workspace:*", "lodash": "4.17.21"}}"#,
+            ),
+            ("app/page.js", "import '@acme/ui'; import 'lodash';"),
+            (
+                "packages/ui/package.json",
+                r#"{"name": "@acme/ui", "main": "dist/index.js"}"#,
+            ),
+            ("packages/ui/src/index.js", "module.exports = 'source';"),
+            ("packages/ui/dist/index.js", "module.exports = 'build';"),
+            (
+                "node_modules/lodash/package.json",
+                r#"{"main": "index.js"}"#,
+            ),
+            ("node_modules/lodash/index.js", "module.exports = {};"),
+        ]);
+        std::fs::create_dir_all(dir.path().join("node_modules/@acme")).unwrap();
+        std::os::unix::fs::symlink(
+            "../../packages/ui",
+            dir.path().join("node_modules/@acme/ui"),
+        )
+        .unwrap();
+        dir
+    }
+
+    async fn is_external(
+        project_path: Vc<FileSystemPath>,
+        fs_path: &str,
+        request: &str,
+    ) -> anyhow::Result<bool> {
+        let plugin = ExternalCjsModulesResolvePlugin::new(
+            project_path,
+            project_path.root(),
+            ExternalPredicate::AllExcept(Vc::cell(vec![])).cell(),
+            false,
+        );
+        Ok(plugin
+            .after_resolve(
+                project_path.join(fs_path.to_string()),
+                project_path.join("app".to_string()),
+                Value::new(ReferenceType::CommonJs(CommonJsReferenceSubType::Undefined)),
+                Request::parse(Value::new(Pattern::Constant(request.to_string()))),
+            )
+            .await?
+            .is_some())
+    }
+
+    #[tokio::test]
+    async fn bundles_workspace_packages() {
+        let dir = workspace_fixture();
+        let root = dir.path().to_owned();
+        let (source, build, package) = run(async move {
+            let project_path = fixture_root(&root);
+            Ok((
+                is_external(
+                    project_path,
+                    "node_modules/@acme/ui/src/index.js",
+                    "@acme/ui",
+                )
+                .await?,
+                // The entry of the package is its own build output.
+                is_external(
+                    project_path,
+                    "node_modules/@acme/ui/dist/index.js",
+                    "@acme/ui",
+                )
+                .await?,
+                is_external(project_path, "node_modules/lodash/index.js", "lodash").await?,
+            ))
+        })
+        .await
+        .unwrap();
+        assert!(
+            !source,
+            "the source of a workspace package should be bundled"
+        );
+        assert!(
+            !build,
+            "the build output of a workspace package should be bundled"
+        );
+        assert!(package, "a regular package should be external");
+    }
+
+    #[tokio::test]
+    async fn invalidates_workspace_packages_on_change() {
+        let dir = workspace_fixture();
+        let root = dir.path().to_owned();
+        let source_path = dir.path().join("packages/ui/src/index.js");
+        let content = run(async move {
+            let fs = DiskFileSystem::new("project".to_string(), root.to_str().unwrap().to_string());
+            fs.await?.start_watching_with_invalidation_reason()?;
+            // Resolving follows the link to the package in the workspace.
+            let path = fs
+                .root()
+                .join("node_modules/@acme/ui/src/index.js".to_string())
+                .realpath();
+            assert_eq!(path.await?.path, "packages/ui/src/index.js");
+            path.read().await?;
+
+            std::fs::write(&source_path, "module.exports = 'edited';")?;
+            for _ in 0..50 {
+                if let FileContent::Content(file) = &*path.read().strongly_consistent().await? {
+                    let content = file.content().to_str()?.into_owned();
+                    if content.contains("edited") {
+                        return Ok(content);
+                    }
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            anyhow::bail!("the edit of the workspace package wasn't picked up")
+        })
+        .await
+        .unwrap();
+        assert_eq!(content, "module.exports = 'edited';");
+    }
+}
@@ -9,6 +9,7 @@ use crate::{
     next_config::NextConfig,
     next_server::context::ServerContextType,
     next_shared::transforms::{
+        get_css_module_named_imports_rule, get_mdx_frontmatter_rule,
         get_next_dynamic_transform_rule, get_next_font_transform_rule, get_next_image_rule,
         get_next_modularize_imports_rule, get_next_pages_transforms_rule,
         get_server_actions_transform_rule, server_actions::ActionsTransform,
@@ -27,12 +28,27 @@ pub async fn get_next_server_transforms_rules(
     let modularize_imports_config = &next_config.await?.modularize_imports;
     let mdx_rs = *next_config.mdx_rs().await?;
     if let Some(modularize_imports_config) = modularize_imports_config {
-        rules.push(get_next_modularize_imports_rule(
-            modularize_imports_config,
-            mdx_rs,
-        ));
+        if !*next_config
+            .is_transform_disabled("modularize-imports".to_string())
+            .await?
+        {
+            rules.push(get_next_modularize_imports_rule(
+                modularize_imports_config,
+                mdx_rs,
+            ));
+        }
     }
     rules.push(get_next_font_transform_rule(mdx_rs));
+    if mdx_rs
+        && !*next_config
+            .is_transform_disabled("mdx-frontmatter".to_string())
+            .await?
+    {
+        rules.push(get_mdx_frontmatter_rule());
+    }
+    if *next_config.css_modules_named_exports().await? {
+        rules.push(get_css_module_named_imports_rule(mdx_rs));
+    }
 
     let (is_server_components, pages_dir) = match context_ty {
         ServerContextType::Pages { pages_dir } | ServerContextType::PagesApi { pages_dir } => {
@@ -0,0 +1,295 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::{
+        ast::{
+            BinExpr, BinaryOp, BindingIdent, ComputedPropName, Decl, Expr, Ident, ImportDecl,
+            ImportDefaultSpecifier, ImportSpecifier, Lit, MemberExpr, MemberProp, ModuleDecl,
+            ModuleExportName, ModuleItem, Pat, Program, Stmt, Str, VarDecl, VarDeclKind,
+            VarDeclarator,
+        },
+        visit::{VisitMut, VisitMutWith},
+    },
+};
+use turbo_tasks::Vc;
+use turbopack_binding::turbopack::{
+    ecmascript::{CustomTransformer, EcmascriptInputTransform, TransformContext},
+    turbopack::module_options::{ModuleRule, ModuleRuleEffect},
+};
+
+use super::module_rule_match_js_no_url;
+
+/// The extensions of CSS modules, including the Sass ones compiled to them.
+const CSS_MODULE_EXTENSIONS: &[&str] = &[".module.css", ".module.scss", ".module.sass"];
+
+/// Returns a rule which lets the classes of CSS modules be imported as named
+/// exports, e.g. `import { button } from './button.module.css'`, see
+/// [rewrite_css_module_named_imports].
+pub fn get_css_module_named_imports_rule(enable_mdx_rs: bool) -> ModuleRule {
+    let transformer =
+        EcmascriptInputTransform::Plugin(Vc::cell(Box::new(CssModuleNamedImportsTransformer) as _));
+    ModuleRule::new(
+        module_rule_match_js_no_url(enable_mdx_rs),
+        vec![ModuleRuleEffect::AddEcmascriptTransforms(Vc::cell(vec![
+            transformer,
+        ]))],
+    )
+}
+
+#[derive(Debug)]
+struct CssModuleNamedImportsTransformer;
+
+#[async_trait]
+impl CustomTransformer for CssModuleNamedImportsTransformer {
+    async fn transform(&self, program: &mut Program, _ctx: &TransformContext<'_>) -> Result<()> {
+        rewrite_css_module_named_imports(program);
+        Ok(())
+    }
+}
+
+/// Rewrites the named imports of CSS modules to reads of the classes object
+/// they export by default, as CSS modules only have a default export:
+///
+/// ```js
+/// import { button, primaryButton } from './button.module.css';
+/// // becomes
+/// import __next_css_module_0__ from './button.module.css';
+/// const button = __next_css_module_0__["button"];
+/// const primaryButton = __next_css_module_0__["primaryButton"] ?? __next_css_module_0__["primary-button"];
+/// ```
+///
+/// Class names which aren't valid identifiers can be imported camelCased, as
+/// above, or by their string name, e.g. `import { "primary-button" as
+/// primaryButton }`.
+pub fn rewrite_css_module_named_imports(program: &mut Program) {
+    program.visit_mut_with(&mut CssModuleNamedImports::default());
+}
+
+#[derive(Default)]
+struct CssModuleNamedImports {
+    /// The number of CSS modules imported without a default import so far,
+    /// to name the ones inserted for them.
+    inserted_default_imports: usize,
+}
+
+impl CssModuleNamedImports {
+    /// Replaces the named imports of `decl` with a default import, if it
+    /// doesn't have one, and returns the declarations of the classes they
+    /// imported.
+    fn rewrite(&mut self, decl: &mut ImportDecl) -> Vec<ModuleItem> {
+        if decl.type_only || !is_css_module(&decl.src.value) {
+            return vec![];
+        }
+
+        let mut named = vec![];
+        let mut default = None;
+        decl.specifiers.retain(|specifier| match specifier {
+            ImportSpecifier::Named(specifier) if !specifier.is_type_only => {
+                let class = match &specifier.imported {
+                    Some(ModuleExportName::Ident(ident)) => ident.sym.to_string(),
+                    Some(ModuleExportName::Str(str)) => str.value.to_string(),
+                    None => specifier.local.sym.to_string(),
+                };
+                named.push((specifier.local.clone(), class));
+                false
+            }
+            ImportSpecifier::Default(specifier) => {
+                default = Some(specifier.local.clone());
+                true
+            }
+            _ => true,
+        });
+        if named.is_empty() {
+            return vec![];
+        }
+
+        let classes = default.unwrap_or_else(|| {
+            let local = Ident::new(
+                format!("__next_css_module_{}__", self.inserted_default_imports).into(),
+                DUMMY_SP,
+            );
+            self.inserted_default_imports += 1;
+            decl.specifiers.insert(
+                0,
+                ImportSpecifier::Default(ImportDefaultSpecifier {
+                    span: DUMMY_SP,
+                    local: local.clone(),
+                }),
+            );
+            local
+        });
+
+        named
+            .into_iter()
+            .map(|(local, class)| {
+                let mut init = class_of(&classes, &class);
+                let kebab_cased = kebab_case(&class);
+                if kebab_cased != class {
+                    init = Expr::Bin(BinExpr {
+                        span: DUMMY_SP,
+                        op: BinaryOp::NullishCoalescing,
+                        left: Box::new(init),
+                        right: Box::new(class_of(&classes, &kebab_cased)),
+                    });
+                }
+                ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+                    span: DUMMY_SP,
+                    kind: VarDeclKind::Const,
+                    declare: false,
+                    decls: vec![VarDeclarator {
+                        span: DUMMY_SP,
+                        name: Pat::Ident(BindingIdent {
+                            id: local,
+                            type_ann: None,
+                        }),
+                        init: Some(Box::new(init)),
+                        definite: false,
+                    }],
+                }))))
+            })
+            .collect()
+    }
+}
+
+impl VisitMut for CssModuleNamedImports {
+    fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+        let mut rewritten = Vec::with_capacity(items.len());
+        for mut item in items.drain(..) {
+            let declarations = match &mut item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(decl)) => self.rewrite(decl),
+                _ => vec![],
+            };
+            rewritten.push(item);
+            rewritten.extend(declarations);
+        }
+        *items = rewritten;
+    }
+}
+
+fn is_css_module(request: &str) -> bool {
+    let path = request.split(['?', '#']).next().unwrap_or(request);
+    CSS_MODULE_EXTENSIONS
+        .iter()
+        .any(|extension| path.ends_with(extension))
+}
+
+/// `classes["class"]`.
+fn class_of(classes: &Ident, class: &str) -> Expr {
+    Expr::Member(MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(Expr::Ident(classes.clone())),
+        prop: MemberProp::Computed(ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Lit(Lit::Str(Str {
+                span: DUMMY_SP,
+                value: class.into(),
+                raw: None,
+            }))),
+        }),
+    })
+}
+
+/// Converts a camelCased name to the kebab-cased class name it stands for,
+/// e.g. `primaryButton` to `primary-button`.
+fn kebab_case(name: &str) -> String {
+    let mut kebab_cased = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            kebab_cased.push('-');
+            kebab_cased.push(c.to_ascii_lowercase());
+        } else {
+            kebab_cased.push(c);
+        }
+    }
+    kebab_cased
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::{sync::Lrc, FileName, SourceMap},
+        ecma::{
+            ast::{EsVersion, Program},
+            codegen::{text_writer::JsWriter, Emitter},
+            parser::{parse_file_as_module, Syntax},
+        },
+    };
+
+    use super::{kebab_case, rewrite_css_module_named_imports};
+
+    fn transform(src: &str) -> String {
+        let cm = Lrc::<SourceMap>::default();
+        let fm = cm.new_source_file(FileName::Anon, src.to_string());
+        let module = parse_file_as_module(
+            &fm,
+            Syntax::Es(Default::default()),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap();
+        let mut program = Program::Module(module);
+        rewrite_css_module_named_imports(&mut program);
+        let Program::Module(module) = program else {
+            unreachable!()
+        };
+
+        let mut buf = vec![];
+        Emitter {
+            cfg: Default::default(),
+            cm: cm.clone(),
+            comments: None,
+            wr: JsWriter::new(cm, "\n", &mut buf, None),
+        }
+        .emit_module(&module)
+        .unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn rewrites_named_imports_of_css_modules() {
+        assert_eq!(
+            transform(r#"import { button } from "./button.module.css";"#).trim(),
+            "import __next_css_module_0__ from \"./button.module.css\";\nconst button = \
+             __next_css_module_0__[\"button\"];"
+        );
+        assert_eq!(
+            transform(r#"import styles, { button as b } from "./button.module.scss";"#).trim(),
+            "import styles from \"./button.module.scss\";\nconst b = styles[\"button\"];"
+        );
+    }
+
+    #[test]
+    fn imports_invalid_identifiers_camel_cased_or_by_name() {
+        assert_eq!(
+            transform(
+                r#"import { primaryButton, "icon-button" as iconButton } from "./button.module.css";"#
+            )
+            .trim(),
+            "import __next_css_module_0__ from \"./button.module.css\";\nconst primaryButton = \
+             __next_css_module_0__[\"primaryButton\"] ?? \
+             __next_css_module_0__[\"primary-button\"];\nconst iconButton = \
+             __next_css_module_0__[\"icon-button\"];"
+        );
+    }
+
+    #[test]
+    fn keeps_other_imports() {
+        for src in [
+            r#"import { button } from "./button.css";"#,
+            r#"import { button } from "./button.js";"#,
+            r#"import styles from "./button.module.css";"#,
+            r#"import * as styles from "./button.module.css";"#,
+        ] {
+            assert_eq!(transform(src).trim(), src);
+        }
+    }
+
+    #[test]
+    fn test_kebab_case() {
+        assert_eq!(kebab_case("primaryButton"), "primary-button");
+        assert_eq!(kebab_case("button"), "button");
+        assert_eq!(kebab_case("button2"), "button2");
+    }
+}
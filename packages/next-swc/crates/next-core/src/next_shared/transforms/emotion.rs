@@ -11,6 +11,13 @@ use crate::next_config::{EmotionTransformOptionsOrBoolean, NextConfig};
 pub async fn get_emotion_transform_plugin(
     next_config: Vc<NextConfig>,
 ) -> Result<Vc<OptionTransformPlugin>> {
+    if *next_config
+        .is_transform_disabled("emotion".to_string())
+        .await?
+    {
+        return Ok(Vc::cell(None));
+    }
+
     let transform_plugin = next_config
         .await?
         .compiler
@@ -0,0 +1,161 @@
+use anyhow::{Context, Result};
+use turbo_tasks::Vc;
+use turbo_tasks_fs::{File, FileContent};
+use turbopack_binding::turbopack::{
+    core::{
+        asset::{Asset, AssetContent},
+        source::Source,
+        source_transform::SourceTransform,
+        virtual_source::VirtualSource,
+    },
+    ecmascript::utils::StringifyJs,
+    turbopack::module_options::{ModuleRule, ModuleRuleCondition, ModuleRuleEffect},
+};
+
+/// Returns a rule which exposes the YAML frontmatter of MDX files as their
+/// `frontmatter` and `metadata` exports.
+pub fn get_mdx_frontmatter_rule() -> ModuleRule {
+    ModuleRule::new(
+        ModuleRuleCondition::any(vec![
+            ModuleRuleCondition::ResourcePathEndsWith(".md".to_string()),
+            ModuleRuleCondition::ResourcePathEndsWith(".mdx".to_string()),
+        ]),
+        vec![ModuleRuleEffect::SourceTransforms(Vc::cell(vec![
+            Vc::upcast(MdxFrontmatterTransform::new()),
+        ]))],
+    )
+}
+
+/// Replaces the frontmatter of an MDX file with ESM exports, which the MDX
+/// compiler passes through.
+#[turbo_tasks::value]
+pub struct MdxFrontmatterTransform;
+
+#[turbo_tasks::value_impl]
+impl MdxFrontmatterTransform {
+    #[turbo_tasks::function]
+    pub fn new() -> Vc<Self> {
+        MdxFrontmatterTransform.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl SourceTransform for MdxFrontmatterTransform {
+    #[turbo_tasks::function]
+    async fn transform(&self, source: Vc<Box<dyn Source>>) -> Result<Vc<Box<dyn Source>>> {
+        let FileContent::Content(file) = &*source.content().file_content().await? else {
+            return Ok(source);
+        };
+        let path = source.ident().path();
+        let transformed = match frontmatter_to_exports(&file.content().to_str()?) {
+            Ok(Some(transformed)) => transformed,
+            Ok(None) => return Ok(source),
+            Err(err) => {
+                return Err(err.context(format!(
+                    "invalid frontmatter in {}",
+                    path.to_string().await?
+                )))
+            }
+        };
+
+        Ok(Vc::upcast(VirtualSource::new(
+            path,
+            AssetContent::file(File::from(transformed).into()),
+        )))
+    }
+}
+
+/// Splits `---` delimited frontmatter from the start of the content. Returns
+/// the frontmatter and the remaining content.
+fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Converts the frontmatter of an MDX file to a `frontmatter` export. It's
+/// also exported as the page's `metadata`, unless the file exports its own.
+///
+/// Returns `None` when the content has no frontmatter.
+fn frontmatter_to_exports(content: &str) -> Result<Option<String>> {
+    let Some((frontmatter, rest)) = split_frontmatter(content) else {
+        return Ok(None);
+    };
+
+    let frontmatter: serde_json::Value =
+        serde_yaml::from_str(frontmatter).context("frontmatter must be valid YAML")?;
+    let frontmatter = match frontmatter {
+        // Empty frontmatter
+        serde_json::Value::Null => serde_json::Value::Object(Default::default()),
+        serde_json::Value::Object(_) => frontmatter,
+        _ => anyhow::bail!("frontmatter must be a YAML mapping"),
+    };
+
+    let mut exports = format!(
+        "export const frontmatter = {};\n",
+        StringifyJs(&frontmatter)
+    );
+    // This is a heuristic, but a false positive only means the frontmatter
+    // isn't used as metadata.
+    if !rest.contains("export const metadata") {
+        exports.push_str("export const metadata = frontmatter;\n");
+    }
+
+    // MDX requires a blank line between ESM and markdown.
+    Ok(Some(format!("{exports}\n{rest}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{frontmatter_to_exports, split_frontmatter};
+
+    #[test]
+    fn test_split_frontmatter() {
+        assert_eq!(
+            split_frontmatter("---\ntitle: About\n---\n# About\n"),
+            Some(("title: About\n", "# About\n"))
+        );
+        assert_eq!(
+            split_frontmatter("---\r\ntitle: About\r\n---\r\n# About"),
+            Some(("title: About\r\n", "# About"))
+        );
+        assert_eq!(split_frontmatter("---\n---\n"), Some(("", "")));
+        assert_eq!(split_frontmatter("# About\n---\n"), None);
+        assert_eq!(split_frontmatter("---\ntitle: About\n"), None);
+    }
+
+    #[test]
+    fn test_frontmatter_to_exports() {
+        assert_eq!(
+            frontmatter_to_exports("---\ntags: [a, b]\n---\n# About\n").unwrap(),
+            Some(
+                "export const frontmatter = {\"tags\":[\"a\",\"b\"]};\nexport const metadata = \
+                 frontmatter;\n\n# About\n"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            frontmatter_to_exports(
+                "---\ntitle: About\n---\nexport const metadata = {}\n\n# About\n"
+            )
+            .unwrap(),
+            Some(
+                "export const frontmatter = {\"title\":\"About\"};\n\nexport const metadata = \
+                 {}\n\n# About\n"
+                    .to_string()
+            )
+        );
+        assert_eq!(frontmatter_to_exports("# About\n").unwrap(), None);
+        assert!(frontmatter_to_exports("---\n- a\n---\n").is_err());
+    }
+}
@@ -1,4 +1,6 @@
+pub(crate) mod css_module_named_imports;
 pub(crate) mod emotion;
+pub(crate) mod mdx_frontmatter;
 pub(crate) mod modularize_imports;
 pub(crate) mod next_dynamic;
 pub(crate) mod next_font;
@@ -9,6 +11,8 @@ pub(crate) mod styled_components;
 pub(crate) mod styled_jsx;
 pub(crate) mod swc_ecma_transform_plugins;
 
+pub use css_module_named_imports::get_css_module_named_imports_rule;
+pub use mdx_frontmatter::get_mdx_frontmatter_rule;
 pub use modularize_imports::{get_next_modularize_imports_rule, ModularizeImportPackageConfig};
 pub use next_dynamic::get_next_dynamic_transform_rule;
 pub use next_font::get_next_font_transform_rule;
@@ -67,23 +67,21 @@ impl ModularizeImportsTransformer {
         Self {
             packages: packages
                 .iter()
-                .map(|(k, v)| {
-                    (
+                .filter_map(|(k, v)| {
+                    let transform = match &v.transform {
+                        Transform::String(s) => modularize_imports::Transform::String(s.clone()),
+                        Transform::Vec(v) => modularize_imports::Transform::Vec(v.clone()),
+                        // Reported when loading the config.
+                        Transform::None => return None,
+                    };
+                    Some((
                         k.clone(),
                         PackageConfig {
-                            transform: match &v.transform {
-                                Transform::String(s) => {
-                                    modularize_imports::Transform::String(s.clone())
-                                }
-                                Transform::Vec(v) => modularize_imports::Transform::Vec(v.clone()),
-                                Transform::None => {
-                                    panic!("Missing transform value for package {}", k)
-                                }
-                            },
+                            transform,
                             prevent_full_import: v.prevent_full_import,
                             skip_default_conversion: v.skip_default_conversion,
                         },
-                    )
+                    ))
                 })
                 .collect(),
         }
@@ -103,3 +101,78 @@ impl CustomTransformer for ModularizeImportsTransformer {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+    use swc_core::{
+        common::{sync::Lrc, FileName, SourceMap},
+        ecma::{
+            ast::EsVersion,
+            codegen::{text_writer::JsWriter, Emitter},
+            parser::{parse_file_as_module, Syntax},
+            visit::FoldWith,
+        },
+    };
+    use turbopack_binding::swc::custom_transform::modularize_imports::{
+        modularize_imports, Config,
+    };
+
+    use super::{ModularizeImportPackageConfig, ModularizeImportsTransformer, Transform};
+
+    fn transform(packages: &IndexMap<String, ModularizeImportPackageConfig>, src: &str) -> String {
+        let cm = Lrc::<SourceMap>::default();
+        let fm = cm.new_source_file(FileName::Anon, src.to_string());
+        let module = parse_file_as_module(
+            &fm,
+            Syntax::Es(Default::default()),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap();
+        let transformer = ModularizeImportsTransformer::new(packages);
+        let module = module.fold_with(&mut modularize_imports(Config {
+            packages: transformer.packages,
+        }));
+
+        let mut buf = vec![];
+        Emitter {
+            cfg: Default::default(),
+            cm: cm.clone(),
+            comments: None,
+            wr: JsWriter::new(cm, "\n", &mut buf, None),
+        }
+        .emit_module(&module)
+        .unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn rewrites_named_imports_to_member_paths() {
+        let packages = IndexMap::from([(
+            "pkg".to_string(),
+            ModularizeImportPackageConfig {
+                transform: Transform::String("pkg/{{member}}".to_string()),
+                ..Default::default()
+            },
+        )]);
+        assert_eq!(
+            transform(&packages, r#"import { a } from "pkg";"#).trim(),
+            r#"import a from "pkg/a";"#
+        );
+    }
+
+    #[test]
+    fn skips_packages_without_transform() {
+        let packages =
+            IndexMap::from([("pkg".to_string(), ModularizeImportPackageConfig::default())]);
+        assert!(ModularizeImportsTransformer::new(&packages)
+            .packages
+            .is_empty());
+        assert_eq!(
+            transform(&packages, r#"import { a } from "pkg";"#).trim(),
+            r#"import { a } from "pkg";"#
+        );
+    }
+}
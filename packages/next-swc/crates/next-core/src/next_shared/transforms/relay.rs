@@ -11,6 +11,13 @@ use crate::next_config::NextConfig;
 pub async fn get_relay_transform_plugin(
     next_config: Vc<NextConfig>,
 ) -> Result<Vc<OptionTransformPlugin>> {
+    if *next_config
+        .is_transform_disabled("relay".to_string())
+        .await?
+    {
+        return Ok(Vc::cell(None));
+    }
+
     let transform_plugin = next_config
         .await?
         .compiler
@@ -13,6 +13,13 @@ use crate::next_config::{NextConfig, StyledComponentsTransformOptionsOrBoolean};
 pub async fn get_styled_components_transform_plugin(
     next_config: Vc<NextConfig>,
 ) -> Result<Vc<OptionTransformPlugin>> {
+    if *next_config
+        .is_transform_disabled("styled-components".to_string())
+        .await?
+    {
+        return Ok(Vc::cell(None));
+    }
+
     let transform_plugin = next_config
         .await?
         .compiler
@@ -5,11 +5,21 @@ use turbopack_binding::turbopack::{
     ecmascript_plugin::transform::styled_jsx::StyledJsxTransformer,
 };
 
+use crate::next_config::NextConfig;
+
 /// Returns a transform plugin for the relay graphql transform.
 #[turbo_tasks::function]
 pub async fn get_styled_jsx_transform_plugin(
+    next_config: Vc<NextConfig>,
     use_lightningcss: bool,
 ) -> Result<Vc<OptionTransformPlugin>> {
+    if *next_config
+        .is_transform_disabled("styled-jsx".to_string())
+        .await?
+    {
+        return Ok(Vc::cell(None));
+    }
+
     Ok(Vc::cell(Some(Vc::cell(
         Box::new(StyledJsxTransformer::new(use_lightningcss)) as _,
     ))))
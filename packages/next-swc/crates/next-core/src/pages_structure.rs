@@ -1,12 +1,15 @@
+use std::collections::{BTreeMap, HashMap};
+
 use anyhow::Result;
 use tracing::Instrument;
 use turbo_tasks::{Completion, ValueToString, Vc};
 use turbo_tasks_fs::FileSystemPathOption;
-use turbopack_binding::turbo::tasks_fs::{
-    DirectoryContent, DirectoryEntry, FileSystemEntryType, FileSystemPath,
+use turbopack_binding::{
+    turbo::tasks_fs::{DirectoryContent, DirectoryEntry, FileSystemPath},
+    turbopack::core::issue::{Issue, IssueExt, IssueSeverity, OptionStyledString, StyledString},
 };
 
-use crate::embed_js::next_js_file_path;
+use crate::{embed_js::next_js_file_path, util::find_source_dir};
 
 /// A final route in the pages directory.
 #[turbo_tasks::value]
@@ -121,6 +124,13 @@ impl PagesDirectoryStructure {
     }
 }
 
+/// Whether a pages router pathname belongs to `_app` or `_document`. These
+/// wrap every page via the page entries and must never become routes
+/// themselves, e.g. when a `pages/_app/index.js` would otherwise map to them.
+pub fn is_special_page(pathname: &str) -> bool {
+    matches!(pathname, "/_app" | "/_document")
+}
+
 /// Finds and returns the [PagesStructure] of the pages directory if existing.
 #[turbo_tasks::function]
 pub async fn find_pages_structure(
@@ -128,24 +138,10 @@ pub async fn find_pages_structure(
     next_router_root: Vc<FileSystemPath>,
     page_extensions: Vc<Vec<String>>,
 ) -> Result<Vc<PagesStructure>> {
-    let pages_root = project_root.join("pages".to_string());
-    let pages_root = Vc::<FileSystemPathOption>::cell(
-        if *pages_root.get_type().await? == FileSystemEntryType::Directory {
-            Some(pages_root)
-        } else {
-            let src_pages_root = project_root.join("src/pages".to_string());
-            if *src_pages_root.get_type().await? == FileSystemEntryType::Directory {
-                Some(src_pages_root)
-            } else {
-                // If neither pages nor src/pages exists, we still want to generate
-                // the pages structure, but with no pages and default values for
-                // _app, _document and _error.
-                None
-            }
-        },
-    )
-    .resolve()
-    .await?;
+    // If neither pages nor src/pages exists, we still want to generate the pages
+    // structure, but with no pages and default values for _app, _document and
+    // _error.
+    let pages_root = find_source_dir(project_root, "pages".to_string());
 
     Ok(get_pages_structure_for_root_directory(
         pages_root,
@@ -174,55 +170,11 @@ async fn get_pages_structure_for_root_directory(
 
         let dir_content = project_path.read_dir().await?;
         if let DirectoryContent::Entries(entries) = &*dir_content {
+            let mut files = vec![];
             for (name, entry) in entries.iter() {
                 match entry {
                     DirectoryEntry::File(file_project_path) => {
-                        let Some(basename) = page_basename(name, page_extensions_raw) else {
-                            continue;
-                        };
-                        match basename {
-                            "_app" => {
-                                let item_next_router_path =
-                                    next_router_path.join("_app".to_string());
-                                let _ = app_item.insert(PagesStructureItem::new(
-                                    *file_project_path,
-                                    item_next_router_path,
-                                    item_next_router_path,
-                                ));
-                            }
-                            "_document" => {
-                                let item_next_router_path =
-                                    next_router_path.join("_document".to_string());
-                                let _ = document_item.insert(PagesStructureItem::new(
-                                    *file_project_path,
-                                    item_next_router_path,
-                                    item_next_router_path,
-                                ));
-                            }
-                            "_error" => {
-                                let item_next_router_path =
-                                    next_router_path.join("_error".to_string());
-                                let _ = error_item.insert(PagesStructureItem::new(
-                                    *file_project_path,
-                                    item_next_router_path,
-                                    item_next_router_path,
-                                ));
-                            }
-                            basename => {
-                                let item_next_router_path =
-                                    next_router_path_for_basename(next_router_path, basename);
-                                let item_original_path =
-                                    next_router_path.join(basename.to_string());
-                                items.push((
-                                    basename,
-                                    PagesStructureItem::new(
-                                        *file_project_path,
-                                        item_next_router_path,
-                                        item_original_path,
-                                    ),
-                                ));
-                            }
-                        }
+                        files.push((name.as_str(), *file_project_path));
                     }
                     DirectoryEntry::Directory(dir_project_path) => match name.as_ref() {
                         "api" => {
@@ -248,6 +200,50 @@ async fn get_pages_structure_for_root_directory(
                     _ => {}
                 }
             }
+
+            for (basename, file_project_path) in
+                unique_page_files(*project_path, &files, page_extensions_raw)
+            {
+                match basename {
+                    "_app" => {
+                        let item_next_router_path = next_router_path.join("_app".to_string());
+                        let _ = app_item.insert(PagesStructureItem::new(
+                            file_project_path,
+                            item_next_router_path,
+                            item_next_router_path,
+                        ));
+                    }
+                    "_document" => {
+                        let item_next_router_path = next_router_path.join("_document".to_string());
+                        let _ = document_item.insert(PagesStructureItem::new(
+                            file_project_path,
+                            item_next_router_path,
+                            item_next_router_path,
+                        ));
+                    }
+                    "_error" => {
+                        let item_next_router_path = next_router_path.join("_error".to_string());
+                        let _ = error_item.insert(PagesStructureItem::new(
+                            file_project_path,
+                            item_next_router_path,
+                            item_next_router_path,
+                        ));
+                    }
+                    basename => {
+                        let item_next_router_path =
+                            next_router_path_for_basename(next_router_path, basename);
+                        let item_original_path = next_router_path.join(basename.to_string());
+                        items.push((
+                            basename,
+                            PagesStructureItem::new(
+                                file_project_path,
+                                item_next_router_path,
+                                item_original_path,
+                            ),
+                        ));
+                    }
+                }
+            }
         }
 
         // Ensure deterministic order since read_dir is not deterministic
@@ -331,25 +327,11 @@ async fn get_pages_structure_for_directory(
         let mut items = vec![];
         let dir_content = project_path.read_dir().await?;
         if let DirectoryContent::Entries(entries) = &*dir_content {
+            let mut files = vec![];
             for (name, entry) in entries.iter() {
                 match entry {
                     DirectoryEntry::File(file_project_path) => {
-                        let Some(basename) = page_basename(name, page_extensions_raw) else {
-                            continue;
-                        };
-                        let item_next_router_path = match basename {
-                            "index" => next_router_path,
-                            _ => next_router_path.join(basename.to_string()),
-                        };
-                        let item_original_name = next_router_path.join(basename.to_string());
-                        items.push((
-                            basename,
-                            PagesStructureItem::new(
-                                *file_project_path,
-                                item_next_router_path,
-                                item_original_name,
-                            ),
-                        ));
+                        files.push((name.as_str(), *file_project_path));
                     }
                     DirectoryEntry::Directory(dir_project_path) => {
                         children.push((
@@ -365,6 +347,24 @@ async fn get_pages_structure_for_directory(
                     _ => {}
                 }
             }
+
+            for (basename, file_project_path) in
+                unique_page_files(project_path, &files, page_extensions_raw)
+            {
+                let item_next_router_path = match basename {
+                    "index" => next_router_path,
+                    _ => next_router_path.join(basename.to_string()),
+                };
+                let item_original_name = next_router_path.join(basename.to_string());
+                items.push((
+                    basename,
+                    PagesStructureItem::new(
+                        file_project_path,
+                        item_next_router_path,
+                        item_original_name,
+                    ),
+                ));
+            }
         }
 
         // Ensure deterministic order since read_dir is not deterministic
@@ -385,11 +385,113 @@ async fn get_pages_structure_for_directory(
     .await
 }
 
-fn page_basename<'a>(name: &'a str, page_extensions: &'a [String]) -> Option<&'a str> {
-    page_extensions.iter().find_map(|allowed| {
-        name.strip_suffix(allowed)
-            .and_then(|name| name.strip_suffix('.'))
-    })
+/// Returns the basename of a page file and the index of its extension in
+/// `page_extensions`.
+fn page_basename<'a>(name: &'a str, page_extensions: &[String]) -> Option<(&'a str, usize)> {
+    page_extensions
+        .iter()
+        .enumerate()
+        .find_map(|(index, allowed)| {
+            name.strip_suffix(allowed.as_str())
+                .and_then(|name| name.strip_suffix('.'))
+                .map(|basename| (basename, index))
+        })
+}
+
+/// Groups the names of the page files of a directory by their basename. The
+/// files of a basename are ordered by the position of their extension in
+/// `page_extensions`.
+fn group_page_files<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    page_extensions: &[String],
+) -> BTreeMap<&'a str, Vec<&'a str>> {
+    let mut groups: BTreeMap<&str, Vec<(usize, &str)>> = BTreeMap::new();
+    for name in names {
+        if let Some((basename, index)) = page_basename(name, page_extensions) {
+            groups.entry(basename).or_default().push((index, name));
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(basename, mut files)| {
+            files.sort();
+            (basename, files.into_iter().map(|(_, name)| name).collect())
+        })
+        .collect()
+}
+
+/// Picks the file of each page among the `files` of the directory `dir`. When
+/// several files resolve to the same page, e.g. `about.tsx` and `about.js`,
+/// they're reported as a [DuplicatePageIssue] and the one whose extension
+/// comes first in `page_extensions` is used.
+fn unique_page_files<'a>(
+    dir: Vc<FileSystemPath>,
+    files: &[(&'a str, Vc<FileSystemPath>)],
+    page_extensions: &[String],
+) -> Vec<(&'a str, Vc<FileSystemPath>)> {
+    let paths: HashMap<&str, Vc<FileSystemPath>> = files.iter().copied().collect();
+    group_page_files(files.iter().map(|(name, _)| *name), page_extensions)
+        .into_iter()
+        .map(|(basename, names)| {
+            if names.len() > 1 {
+                DuplicatePageIssue {
+                    dir,
+                    files: names.iter().map(|name| name.to_string()).collect(),
+                }
+                .cell()
+                .emit();
+            }
+            (basename, paths[names[0]])
+        })
+        .collect()
+}
+
+#[turbo_tasks::value(shared)]
+struct DuplicatePageIssue {
+    dir: Vc<FileSystemPath>,
+    files: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for DuplicatePageIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        let mut title = vec![StyledString::Text("Duplicate page detected: ".to_string())];
+        for (index, file) in self.files.iter().enumerate() {
+            if index > 0 {
+                title.push(StyledString::Text(", ".to_string()));
+            }
+            title.push(StyledString::Code(file.clone()));
+        }
+        StyledString::Line(title).cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("routes".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.dir
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(format!(
+                "These files resolve to the same route, so only `{}` is used. Remove all but one \
+                 of them.",
+                self.files[0]
+            ))
+            .cell(),
+        ))
+    }
 }
 
 fn next_router_path_for_basename(
@@ -402,3 +504,30 @@ fn next_router_path_for_basename(
         next_router_path.join(basename.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::group_page_files;
+
+    #[test]
+    fn test_group_page_files_duplicates() {
+        let page_extensions = ["tsx", "ts", "jsx", "js"].map(|ext| ext.to_string());
+        let groups = group_page_files(
+            [
+                "about.js",
+                "index.tsx",
+                "about.tsx",
+                "styles.css",
+                "about.ts",
+            ],
+            &page_extensions,
+        );
+        assert_eq!(
+            groups.into_iter().collect::<Vec<_>>(),
+            [
+                ("about", vec!["about.tsx", "about.ts", "about.js"]),
+                ("index", vec!["index.tsx"]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbopack_binding::turbopack::turbopack::resolve_options_context::ResolveOptionsContext;
+
+/// Additional conditions to match the `exports` and `imports` of packages
+/// with, per layer. They're added to the conditions of the layer, e.g.
+/// `react-server` for server components, `node` for server rendering and
+/// `edge-light` for the edge runtime, and don't replace them.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Clone, Default)]
+pub struct ResolveConditions {
+    /// Conditions for client components and pages in the browser.
+    pub client: Vec<String>,
+    /// Conditions for server rendering of pages and client components.
+    pub ssr: Vec<String>,
+    /// Conditions for server components and route handlers.
+    pub rsc: Vec<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl ResolveConditions {
+    #[turbo_tasks::function]
+    pub fn client(&self) -> Vc<Vec<String>> {
+        Vc::cell(self.client.clone())
+    }
+
+    #[turbo_tasks::function]
+    pub fn ssr(&self) -> Vc<Vec<String>> {
+        Vc::cell(self.ssr.clone())
+    }
+
+    #[turbo_tasks::function]
+    pub fn rsc(&self) -> Vc<Vec<String>> {
+        Vc::cell(self.rsc.clone())
+    }
+}
+
+/// Adds the `conditions` to the custom conditions of
+/// `resolve_options_context`, after the ones of its layer, so a package
+/// exporting e.g. a `react-server` entry still resolves to it in server
+/// components.
+#[turbo_tasks::function]
+pub async fn with_resolve_conditions(
+    resolve_options_context: Vc<ResolveOptionsContext>,
+    conditions: Vc<Vec<String>>,
+) -> Result<Vc<ResolveOptionsContext>> {
+    let conditions_ref = conditions.await?;
+    if conditions_ref.is_empty() {
+        return Ok(resolve_options_context);
+    }
+
+    let mut context = resolve_options_context.await?.clone_value();
+    for condition in conditions_ref.iter() {
+        if !context.custom_conditions.contains(condition) {
+            context.custom_conditions.push(condition.clone());
+        }
+    }
+
+    // Dependencies in `node_modules` are resolved with the contexts of the
+    // rules, which have their own conditions.
+    for (_, rule_context) in context.rules.iter_mut() {
+        *rule_context = with_resolve_conditions(*rule_context, conditions);
+    }
+
+    Ok(context.cell())
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{Context, Result};
+    use turbo_tasks::{Value, Vc};
+    use turbopack_binding::{
+        turbo::tasks_fs::FileSystemPath,
+        turbopack::{
+            core::{
+                reference_type::{EcmaScriptModulesReferenceSubType, ReferenceType},
+                resolve::{parse::Request, pattern::Pattern, resolve},
+                source::Source,
+            },
+            dev::DevChunkingContext,
+            env::dotenv::load_env,
+            node::execution_context::ExecutionContext,
+            turbopack::{evaluate_context::node_build_environment, resolve_options},
+        },
+    };
+
+    use super::with_resolve_conditions;
+    use crate::{
+        mode::NextMode,
+        next_config::NextConfig,
+        next_server::{get_server_resolve_options_context, ServerContextType},
+        test_utils::{fixture, fixture_root, run},
+    };
+
+    /// Resolves the `dual` package from `app/page.js` in the server context
+    /// `ty`, with the additional `conditions`, and returns the path of the
+    /// file it resolves to.
+    async fn resolve_dual(
+        project_path: Vc<FileSystemPath>,
+        ty: ServerContextType,
+        conditions: &[&str],
+    ) -> Result<String> {
+        let node_root = project_path.join(".next".to_string());
+        let execution_context = ExecutionContext::new(
+            project_path,
+            Vc::upcast(
+                DevChunkingContext::builder(
+                    project_path,
+                    node_root,
+                    node_root.join("chunks".to_string()),
+                    node_root.join("assets".to_string()),
+                    node_build_environment(),
+                )
+                .build(),
+            ),
+            load_env(project_path),
+        );
+        let context = with_resolve_conditions(
+            get_server_resolve_options_context(
+                project_path,
+                Value::new(ty),
+                NextMode::Build,
+                NextConfig::default().cell(),
+                execution_context,
+            ),
+            Vc::cell(
+                conditions
+                    .iter()
+                    .map(|condition| condition.to_string())
+                    .collect(),
+            ),
+        );
+
+        let lookup_path = project_path.join("app".to_string());
+        let source = resolve(
+            lookup_path,
+            Value::new(ReferenceType::EcmaScriptModules(
+                EcmaScriptModulesReferenceSubType::Undefined,
+            )),
+            Request::parse(Value::new(Pattern::Constant("dual".to_string()))),
+            resolve_options(lookup_path, context),
+        )
+        .first_source()
+        .await?
+        .context("dual didn't resolve")?;
+        Ok(source.ident().path().await?.path.clone())
+    }
+
+    #[tokio::test]
+    async fn resolves_the_exports_of_each_layer() {
+        let dir = fixture(&[
+            ("app/page.js", "import dual from 'dual';"),
+            (
+                "node_modules/dual/package.json",
+                r#"{
+                    "name": "dual",
+                    "exports": {
+                        "worker": "./worker.js",
+                        "react-server": "./server.js",
+                        "default": "./index.js"
+                    }
+                }"#,
+            ),
+            ("node_modules/dual/worker.js", ""),
+            ("node_modules/dual/server.js", ""),
+            ("node_modules/dual/index.js", ""),
+        ]);
+        let root = dir.path().to_owned();
+        let (rsc, ssr, rsc_worker) = run(async move {
+            let project_path = fixture_root(&root);
+            let app_dir = project_path.join("app".to_string());
+            let rsc = || ServerContextType::AppRSC {
+                app_dir,
+                ecmascript_client_reference_transition_name: None,
+                client_transition: None,
+            };
+            Ok((
+                resolve_dual(project_path, rsc(), &[]).await?,
+                resolve_dual(project_path, ServerContextType::AppSSR { app_dir }, &[]).await?,
+                resolve_dual(project_path, rsc(), &["worker"]).await?,
+            ))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(rsc, "node_modules/dual/server.js");
+        assert_eq!(ssr, "node_modules/dual/index.js");
+        // `worker` is listed first, so it wins over `react-server` once added.
+        assert_eq!(rsc_worker, "node_modules/dual/worker.js");
+    }
+}
@@ -0,0 +1,114 @@
+/// A segment of a url, or of a `path-to-regexp` source matching urls.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UrlSegment<'a> {
+    /// e.g. `blog`
+    Static(&'a str),
+    /// e.g. `:slug`, matching a single url segment.
+    Dynamic,
+    /// e.g. `:path*` or `(.*)`, matching any number of url segments.
+    CatchAll,
+}
+
+impl<'a> UrlSegment<'a> {
+    /// Splits a url pathname, e.g. `/blog/hello`, into its segments.
+    pub fn from_pathname(pathname: &'a str) -> Vec<Self> {
+        pathname
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(UrlSegment::Static)
+            .collect()
+    }
+
+    /// Splits a `path-to-regexp` source, e.g. `/blog/:slug*`, into its
+    /// segments.
+    pub fn from_source(source: &'a str) -> Vec<Self> {
+        source
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| {
+                if segment.ends_with('*') || segment.ends_with('+') || segment.contains(".*") {
+                    UrlSegment::CatchAll
+                } else if segment.contains(':') || segment.contains('(') {
+                    UrlSegment::Dynamic
+                } else {
+                    UrlSegment::Static(segment)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Returns whether the app route, e.g. `/blog/[slug]`, matches the url
+/// segments. When the segments come from a source, this is whether the
+/// source could match some url of the route.
+pub fn route_matches(route: &str, url: &[UrlSegment]) -> bool {
+    let route_segments: Vec<_> = route
+        .split('/')
+        .filter(|segment| {
+            // Route groups and parallel route slots don't appear in urls.
+            !segment.is_empty() && !segment.starts_with('(') && !segment.starts_with('@')
+        })
+        .collect();
+    segments_match(&route_segments, url)
+}
+
+fn segments_match(route: &[&str], url: &[UrlSegment]) -> bool {
+    match (route.first(), url.first()) {
+        (None, None) => true,
+        // Segments matching any number of url segments can match whatever
+        // remains on the other side.
+        (Some(route_segment), _) if route_segment.starts_with("[[...") => true,
+        (Some(route_segment), Some(_)) if route_segment.starts_with("[...") => true,
+        (_, Some(UrlSegment::CatchAll)) => true,
+        (Some(route_segment), Some(url_segment)) => {
+            let is_dynamic = route_segment.starts_with('[') && route_segment.ends_with(']');
+            let matches = match url_segment {
+                UrlSegment::Static(url_segment) => is_dynamic || route_segment == url_segment,
+                _ => true,
+            };
+            matches && segments_match(&route[1..], &url[1..])
+        }
+        (None, Some(_)) | (Some(_), None) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{route_matches, UrlSegment};
+
+    fn matches_pathname(route: &str, pathname: &str) -> bool {
+        route_matches(route, &UrlSegment::from_pathname(pathname))
+    }
+
+    fn matches_source(route: &str, source: &str) -> bool {
+        route_matches(route, &UrlSegment::from_source(source))
+    }
+
+    #[test]
+    fn test_route_matches_pathname() {
+        assert!(matches_pathname("/", "/"));
+        assert!(matches_pathname("/blog", "/blog/"));
+        assert!(matches_pathname("/blog/[slug]", "/blog/hello"));
+        assert!(!matches_pathname("/blog/[slug]", "/blog"));
+        assert!(!matches_pathname("/blog/[slug]", "/blog/hello/world"));
+        assert!(matches_pathname("/docs/[...slug]", "/docs/a/b"));
+        assert!(!matches_pathname("/docs/[...slug]", "/docs"));
+        assert!(matches_pathname("/shop/[[...slug]]", "/shop"));
+        assert!(matches_pathname("/shop/[[...slug]]", "/shop/a/b"));
+        assert!(matches_pathname("/(marketing)/about", "/about"));
+        assert!(matches_pathname("/@modal/photos", "/photos"));
+        assert!(!matches_pathname("/about", "/contact"));
+    }
+
+    #[test]
+    fn test_route_matches_source() {
+        assert!(matches_source("/blog/[slug]", "/blog/:slug"));
+        assert!(matches_source("/blog/hello", "/blog/:slug"));
+        assert!(matches_source("/blog/[slug]", "/blog/hello"));
+        assert!(matches_source("/", "/:path*"));
+        assert!(matches_source("/admin/settings", "/admin/(.*)"));
+        assert!(matches_source("/docs/[...slug]", "/docs/a/b"));
+        assert!(!matches_source("/docs/[...slug]", "/docs"));
+        assert!(!matches_source("/admin", "/admin/:path"));
+    }
+}
@@ -0,0 +1,119 @@
+//! Helpers to run turbo-tasks functions against a fixture directory in unit
+//! tests. The crates built on next-core share them through the `test-utils`
+//! feature.
+
+use std::{
+    future::Future,
+    path::Path,
+    sync::{Arc, Once},
+};
+
+use anyhow::Result;
+use tempfile::TempDir;
+use turbo_tasks::{run_once, ReadRef, TurboTasks, Value, Vc};
+use turbopack_binding::{
+    turbo::{
+        tasks_env::EnvMap,
+        tasks_fs::{DiskFileSystem, FileSystem, FileSystemPath},
+        tasks_memory::MemoryBackend,
+    },
+    turbopack::{
+        core::{
+            context::AssetContext,
+            file_source::FileSource,
+            issue::{IssueDescriptionExt, PlainIssue, StyledString},
+            module::Module,
+            reference_type::{EntryReferenceSubType, ReferenceType},
+        },
+        turbopack::ModuleAssetContext,
+    },
+};
+
+use crate::next_client::get_client_compile_time_info;
+
+/// Runs `future` in a fresh turbo-tasks instance, after calling `register`
+/// once per process to register the functions of the crate under test.
+pub async fn run_with<T: Send + 'static>(
+    register: fn(),
+    future: impl Future<Output = Result<T>> + Send + 'static,
+) -> Result<T> {
+    run_once(turbo_tasks_with(register), future).await
+}
+
+/// A fresh turbo-tasks instance, after calling `register` once per process to
+/// register the functions of the crate under test.
+pub fn turbo_tasks_with(register: fn()) -> Arc<TurboTasks<MemoryBackend>> {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(register);
+
+    TurboTasks::new(MemoryBackend::new(usize::MAX))
+}
+
+/// Runs `future` in a fresh turbo-tasks instance.
+#[cfg(test)]
+pub(crate) async fn run<T: Send + 'static>(
+    future: impl Future<Output = Result<T>> + Send + 'static,
+) -> Result<T> {
+    run_with(crate::register, future).await
+}
+
+/// Writes `files` to a temporary directory. It's removed when the returned
+/// [TempDir] is dropped.
+pub fn fixture(files: &[(&str, &str)]) -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for (path, content) in files {
+        let path = dir.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+/// The root of a disk filesystem over `dir`. Must be called within
+/// [run_with].
+pub fn fixture_root(dir: &Path) -> Vc<FileSystemPath> {
+    DiskFileSystem::new("project".to_string(), dir.to_str().unwrap().to_string()).root()
+}
+
+/// The module of the file at `path` of `project_root`, processed as a client
+/// entry with the default module and resolve options, and `define_env`. Must
+/// be called within [run_with].
+pub fn client_module(
+    project_root: Vc<FileSystemPath>,
+    path: &str,
+    define_env: Vc<EnvMap>,
+) -> Vc<Box<dyn Module>> {
+    let context = ModuleAssetContext::new(
+        Vc::cell(Default::default()),
+        get_client_compile_time_info("defaults".to_string(), define_env),
+        Default::default(),
+        Default::default(),
+        Vc::cell("test".to_string()),
+    );
+    context
+        .process(
+            Vc::upcast(FileSource::new(project_root.join(path.to_string()))),
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+        )
+        .module()
+}
+
+/// The issues emitted while computing `source`.
+pub async fn issues<T: Send>(source: Vc<T>) -> Result<Vec<ReadRef<PlainIssue>>> {
+    source
+        .peek_issues_with_path()
+        .await?
+        .get_plain_issues()
+        .await
+}
+
+/// The text of a styled issue title or description.
+pub fn styled_text(styled: &StyledString) -> String {
+    match styled {
+        StyledString::Line(parts) => parts.iter().map(styled_text).collect(),
+        StyledString::Stack(parts) => parts.iter().map(styled_text).collect::<Vec<_>>().join("\n"),
+        StyledString::Text(string) | StyledString::Code(string) | StyledString::Strong(string) => {
+            string.clone()
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use turbo_tasks::Vc;
 use turbopack_binding::{
-    turbo::tasks_fs::{FileJsonContent, FileSystemPath},
+    turbo::tasks_fs::{FileJsonContent, FileSystemEntryType, FileSystemPath},
     turbopack::{
         core::{
             file_source::FileSource,
@@ -21,22 +21,49 @@ use turbopack_binding::{
 
 use crate::{mode::NextMode, next_config::NextConfig};
 
-async fn get_typescript_options(
+/// Finds the tsconfig of the project, which is next.config.js's
+/// `typescript.tsconfigPath` when it's set, or the closest `tsconfig.json`.
+async fn find_tsconfig(
     project_path: Vc<FileSystemPath>,
-) -> Option<Vec<(Vc<FileJsonContent>, Vc<Box<dyn Source>>)>> {
-    let tsconfig = find_context_file(project_path, tsconfig());
-    match *tsconfig.await.ok()? {
-        FindContextFileResult::Found(path, _) => Some(
-            read_tsconfigs(
-                path.read(),
-                Vc::upcast(FileSource::new(path)),
-                node_cjs_resolve_options(path.root()),
-            )
-            .await
-            .ok()?,
-        ),
-        FindContextFileResult::NotFound(_) => None,
+    next_config: Vc<NextConfig>,
+) -> Result<Option<Vc<FileSystemPath>>> {
+    if let Some(ts_config_path) = &*next_config.ts_config_path().await? {
+        let path = project_path.join(ts_config_path.clone());
+        return Ok(matches!(&*path.get_type().await?, FileSystemEntryType::File).then_some(path));
     }
+    Ok(match *find_context_file(project_path, tsconfig()).await? {
+        FindContextFileResult::Found(path, _) => Some(path),
+        FindContextFileResult::NotFound(_) => None,
+    })
+}
+
+/// The tsconfig the resolver applies `baseUrl` and `paths` from when
+/// next.config.js's `typescript.tsconfigPath` is set. Otherwise the resolver
+/// uses the closest `tsconfig.json`.
+pub(crate) async fn get_tsconfig_path(
+    project_path: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+) -> Result<Option<Vc<FileSystemPath>>> {
+    Ok(next_config
+        .ts_config_path()
+        .await?
+        .as_ref()
+        .map(|ts_config_path| project_path.join(ts_config_path.clone())))
+}
+
+/// Reads the project's tsconfig, followed by the configs it `extends`.
+pub(crate) async fn get_typescript_options(
+    project_path: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
+) -> Option<Vec<(Vc<FileJsonContent>, Vc<Box<dyn Source>>)>> {
+    let path = find_tsconfig(project_path, next_config).await.ok()??;
+    read_tsconfigs(
+        path.read(),
+        Vc::upcast(FileSource::new(path)),
+        node_cjs_resolve_options(path.root()),
+    )
+    .await
+    .ok()
 }
 
 /// Build the transform options for specifically for the typescript's runtime
@@ -44,8 +71,9 @@ async fn get_typescript_options(
 #[turbo_tasks::function]
 pub async fn get_typescript_transform_options(
     project_path: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
 ) -> Result<Vc<TypescriptTransformOptions>> {
-    let tsconfig = get_typescript_options(project_path).await;
+    let tsconfig = get_typescript_options(project_path, next_config).await;
 
     let use_define_for_class_fields = if let Some(tsconfig) = tsconfig {
         read_from_tsconfigs(&tsconfig, |json, _| {
@@ -69,8 +97,9 @@ pub async fn get_typescript_transform_options(
 #[turbo_tasks::function]
 pub async fn get_decorators_transform_options(
     project_path: Vc<FileSystemPath>,
+    next_config: Vc<NextConfig>,
 ) -> Result<Vc<DecoratorsOptions>> {
-    let tsconfig = get_typescript_options(project_path).await;
+    let tsconfig = get_typescript_options(project_path, next_config).await;
 
     let decorators_transform_options = if let Some(tsconfig) = tsconfig {
         read_from_tsconfigs(&tsconfig, |json, _| {
@@ -130,7 +159,7 @@ pub async fn get_jsx_transform_options(
     is_server_context: bool,
     next_config: Vc<NextConfig>,
 ) -> Result<Vc<JsxTransformOptions>> {
-    let tsconfig = get_typescript_options(project_path).await;
+    let tsconfig = get_typescript_options(project_path, next_config).await;
 
     let enable_react_refresh = if let Some(resolve_options_context) = resolve_options_context {
         assert_can_resolve_react_refresh(project_path, resolve_options_context)
@@ -187,3 +216,80 @@ pub async fn get_jsx_transform_options(
 
     Ok(react_transform_options.cell())
 }
+
+#[cfg(test)]
+mod tests {
+    use turbo_tasks::{Value, Vc};
+    use turbopack_binding::{
+        turbo::tasks_fs::FileSystemPath,
+        turbopack::{
+            core::{
+                reference_type::ReferenceType,
+                resolve::{options::ResolveOptions, parse::Request, pattern::Pattern, resolve},
+            },
+            turbopack::{resolve_options, resolve_options_context::ResolveOptionsContext},
+        },
+    };
+
+    use super::get_tsconfig_path;
+    use crate::{
+        next_config::NextConfig,
+        test_utils::{fixture, fixture_root, run},
+    };
+
+    async fn resolves(
+        lookup_path: Vc<FileSystemPath>,
+        options: Vc<ResolveOptions>,
+        request: &str,
+    ) -> anyhow::Result<bool> {
+        Ok(resolve(
+            lookup_path,
+            Value::new(ReferenceType::Undefined),
+            Request::parse(Value::new(Pattern::Constant(request.to_string()))),
+            options,
+        )
+        .first_source()
+        .await?
+        .is_some())
+    }
+
+    #[tokio::test]
+    async fn resolves_paths_of_custom_tsconfig_only() {
+        let dir = fixture(&[
+            (
+                "tsconfig.json",
+                r#"{"compilerOptions": {"paths": {"@default/*": ["./default/*"]}}}"#,
+            ),
+            (
+                "tsconfig.build.json",
+                r#"{"compilerOptions": {"paths": {"@build/*": ["./build/*"]}}}"#,
+            ),
+            ("default/a.ts", "export {}"),
+            ("build/a.ts", "export {}"),
+        ]);
+        let root = dir.path().to_owned();
+        let (build, default) = run(async move {
+            let project_path = fixture_root(&root);
+            let next_config = NextConfig::default()
+                .cell()
+                .with_ts_config_path("tsconfig.build.json".to_string());
+            let options = resolve_options(
+                project_path,
+                ResolveOptionsContext {
+                    enable_typescript: true,
+                    tsconfig_path: get_tsconfig_path(project_path, next_config).await?,
+                    ..Default::default()
+                }
+                .cell(),
+            );
+            Ok((
+                resolves(project_path, options, "@build/a").await?,
+                resolves(project_path, options, "@default/a").await?,
+            ))
+        })
+        .await
+        .unwrap();
+        assert!(build, "alias of tsconfig.build.json should resolve");
+        assert!(!default, "alias of tsconfig.json shouldn't resolve");
+    }
+}
@@ -0,0 +1,225 @@
+//! Rust port of the route types generated by `NextTypesPlugin` for
+//! `experimental.typedRoutes`.
+//!
+//! See `next/src/build/webpack/plugins/next-types-plugin`
+
+use indoc::formatdoc;
+
+/// Converts a route to the template literal type matching its pathnames.
+/// Returns whether the route is dynamic.
+fn route_type(route: &str) -> (bool, String) {
+    let mut is_dynamic = false;
+    let route = route
+        .split('/')
+        .map(|segment| {
+            if !segment.starts_with('[') || !segment.ends_with(']') {
+                return segment;
+            }
+            is_dynamic = true;
+            if segment.starts_with("[[...") && segment.ends_with("]]") {
+                // /[[...slug]]
+                "${OptionalCatchAllSlug<T>}"
+            } else if segment.starts_with("[...") {
+                // /[...slug]
+                "${CatchAllSlug<T>}"
+            } else {
+                // /[slug]
+                "${SafeSlug<T>}"
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    (is_dynamic, format!("\n    | `{route}`"))
+}
+
+/// Generates the `link.d.ts` declarations, which type the `href` of
+/// `next/link` and the `next/navigation` router methods with the given routes.
+///
+/// Internal routes (e.g. `/_app`) and API routes can't be navigated to and are
+/// left out.
+pub fn link_type_declarations(routes: &[String]) -> String {
+    let mut static_route_types = String::new();
+    let mut dynamic_route_types = String::new();
+
+    for route in routes {
+        if route.starts_with("/_") || route == "/api" || route.starts_with("/api/") {
+            continue;
+        }
+        match route_type(route) {
+            (true, route_type) => dynamic_route_types.push_str(&route_type),
+            (false, route_type) => static_route_types.push_str(&route_type),
+        }
+    }
+
+    // Without any routes, every string is allowed.
+    let route_impl = if static_route_types.is_empty() && dynamic_route_types.is_empty() {
+        "string".to_string()
+    } else {
+        "
+    | StaticRoutes
+    | SearchOrHash
+    | WithProtocol
+    | `${StaticRoutes}${SearchOrHash}`
+    | (T extends `${DynamicRoutes<infer _>}${Suffix}` ? T : never)
+    "
+        .to_string()
+    };
+
+    let static_route_types = if static_route_types.is_empty() {
+        "never".to_string()
+    } else {
+        static_route_types
+    };
+    let dynamic_route_types = if dynamic_route_types.is_empty() {
+        "never".to_string()
+    } else {
+        dynamic_route_types
+    };
+
+    formatdoc! {
+        r#"
+            // Type definitions for Next.js routes
+
+            /**
+             * Internal types used by the Next.js router and Link component.
+             * These types are not meant to be used directly.
+             * @internal
+             */
+            declare namespace __next_route_internal_types__ {{
+              type SearchOrHash = `?${{string}}` | `#${{string}}`
+              type WithProtocol = `${{string}}:${{string}}`
+
+              type Suffix = '' | SearchOrHash
+
+              type SafeSlug<S extends string> = S extends `${{string}}/${{string}}`
+                ? never
+                : S extends `${{string}}${{SearchOrHash}}`
+                ? never
+                : S extends ''
+                ? never
+                : S
+
+              type CatchAllSlug<S extends string> = S extends `${{string}}${{SearchOrHash}}`
+                ? never
+                : S extends ''
+                ? never
+                : S
+
+              type OptionalCatchAllSlug<S extends string> =
+                S extends `${{string}}${{SearchOrHash}}` ? never : S
+
+              type StaticRoutes = {static_route_types}
+              type DynamicRoutes<T extends string = string> = {dynamic_route_types}
+
+              type RouteImpl<T> = {route_impl}
+            }}
+
+            declare module 'next' {{
+              export {{ default }} from 'next/types/index.js'
+              export * from 'next/types/index.js'
+
+              export type Route<T extends string = string> =
+                __next_route_internal_types__.RouteImpl<T>
+            }}
+
+            declare module 'next/link' {{
+              import type {{ LinkProps as OriginalLinkProps }} from 'next/dist/client/link.js'
+              import type {{ AnchorHTMLAttributes, DetailedHTMLProps }} from 'react'
+              import type {{ UrlObject }} from 'url'
+
+              type LinkRestProps = Omit<
+                Omit<
+                  DetailedHTMLProps<
+                    AnchorHTMLAttributes<HTMLAnchorElement>,
+                    HTMLAnchorElement
+                  >,
+                  keyof OriginalLinkProps
+                > &
+                  OriginalLinkProps,
+                'href'
+              >
+
+              export type LinkProps<RouteInferType> = LinkRestProps & {{
+                /**
+                 * The path or URL to navigate to. This is the only required prop. It can also be an object.
+                 * @see https://nextjs.org/docs/api-reference/next/link
+                 */
+                href: __next_route_internal_types__.RouteImpl<RouteInferType> | UrlObject
+              }}
+
+              export default function Link<RouteType>(props: LinkProps<RouteType>): JSX.Element
+            }}
+
+            declare module 'next/navigation' {{
+              export * from 'next/dist/client/components/navigation.js'
+
+              import type {{ NavigateOptions, AppRouterInstance as OriginalAppRouterInstance }} from 'next/dist/shared/lib/app-router-context.shared-runtime.js'
+              interface AppRouterInstance extends OriginalAppRouterInstance {{
+                /**
+                 * Navigate to the provided href.
+                 * Pushes a new history entry.
+                 */
+                push<RouteType>(href: __next_route_internal_types__.RouteImpl<RouteType>, options?: NavigateOptions): void
+                /**
+                 * Navigate to the provided href.
+                 * Replaces the current history entry.
+                 */
+                replace<RouteType>(href: __next_route_internal_types__.RouteImpl<RouteType>, options?: NavigateOptions): void
+                /**
+                 * Prefetch the provided href.
+                 */
+                prefetch<RouteType>(href: __next_route_internal_types__.RouteImpl<RouteType>): void
+              }}
+
+              export declare function useRouter(): AppRouterInstance;
+            }}
+        "#
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{link_type_declarations, route_type};
+
+    #[test]
+    fn test_route_type() {
+        assert_eq!(
+            route_type("/about"),
+            (false, "\n    | `/about`".to_string())
+        );
+        assert_eq!(
+            route_type("/blog/[slug]"),
+            (true, "\n    | `/blog/${SafeSlug<T>}`".to_string())
+        );
+        assert_eq!(
+            route_type("/docs/[...slug]"),
+            (true, "\n    | `/docs/${CatchAllSlug<T>}`".to_string())
+        );
+        assert_eq!(
+            route_type("/shop/[[...slug]]"),
+            (
+                true,
+                "\n    | `/shop/${OptionalCatchAllSlug<T>}`".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_link_type_declarations() {
+        let declarations = link_type_declarations(&[
+            "/about".to_string(),
+            "/blog/[slug]".to_string(),
+            "/_app".to_string(),
+            "/api/hello".to_string(),
+        ]);
+        assert!(declarations.contains("type StaticRoutes = \n    | `/about`\n"));
+        assert!(declarations.contains(
+            "type DynamicRoutes<T extends string = string> = \n    | `/blog/${SafeSlug<T>}`\n"
+        ));
+        assert!(!declarations.contains("/_app"));
+        assert!(!declarations.contains("/api/hello"));
+
+        assert!(link_type_declarations(&[]).contains("type RouteImpl<T> = string\n"));
+    }
+}
@@ -4,12 +4,14 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use swc_core::{
     common::GLOBALS,
-    ecma::ast::{Expr, Lit, Program},
+    ecma::ast::{Expr, ExprOrSpread, Lit, Program},
 };
-use turbo_tasks::{trace::TraceRawVcs, TaskInput, ValueDefault, ValueToString, Vc};
-use turbo_tasks_fs::{rope::Rope, util::join_path, File};
+use turbo_tasks::{trace::TraceRawVcs, ReadRef, TaskInput, ValueDefault, ValueToString, Vc};
+use turbo_tasks_fs::{rope::Rope, util::join_path, File, FileSystemPathOption};
 use turbopack_binding::{
-    turbo::tasks_fs::{json::parse_json_rope_with_source_context, FileContent, FileSystemPath},
+    turbo::tasks_fs::{
+        json::parse_json_rope_with_source_context, FileContent, FileSystemEntryType, FileSystemPath,
+    },
     turbopack::{
         core::{
             asset::AssetContent,
@@ -22,9 +24,9 @@ use turbopack_binding::{
         },
         ecmascript::{
             analyzer::{JsValue, ObjectPart},
-            parse::ParseResult,
+            parse::{parse, ParseResult},
             utils::StringifyJs,
-            EcmascriptModuleAsset,
+            EcmascriptInputTransforms, EcmascriptModuleAsset, EcmascriptModuleAssetType,
         },
         turbopack::condition::ContextCondition,
     },
@@ -154,6 +156,14 @@ pub struct NextSourceConfig {
 
     /// Middleware router matchers
     pub matcher: Option<Vec<String>>,
+
+    /// The maximum duration of the function in seconds, from `export const
+    /// maxDuration`.
+    pub max_duration: Option<u32>,
+
+    /// The regions to deploy the function to, from `export const
+    /// preferredRegion`.
+    pub preferred_region: Option<Vec<String>>,
 }
 
 #[turbo_tasks::value_impl]
@@ -211,8 +221,33 @@ impl Issue for NextSourceConfigParsingIssue {
     }
 }
 
+/// Parses a JavaScript or TypeScript `source` without transforming it, e.g. to
+/// analyze the exports of a layout before it's processed as a module. Other
+/// files can't be analyzed and return nothing, as parsing them would emit an
+/// issue failing the build.
+pub async fn parse_js_source(source: Vc<Box<dyn Source>>) -> Result<Option<ReadRef<ParseResult>>> {
+    let path = source.ident().path().await?;
+    let module_type = if path.path.ends_with(".ts") || path.path.ends_with(".tsx") {
+        EcmascriptModuleAssetType::Typescript
+    } else if path.path.ends_with(".js") || path.path.ends_with(".jsx") {
+        EcmascriptModuleAssetType::Ecmascript
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(
+        parse(
+            source,
+            turbo_tasks::Value::new(module_type),
+            EcmascriptInputTransforms::empty(),
+        )
+        .await?,
+    ))
+}
+
 #[turbo_tasks::function]
 pub async fn parse_config_from_source(module: Vc<Box<dyn Module>>) -> Result<Vc<NextSourceConfig>> {
+    let mut config = NextSourceConfig::default();
     if let Some(ecmascript_asset) =
         Vc::try_resolve_downcast_type::<EcmascriptModuleAsset>(module).await?
     {
@@ -239,10 +274,12 @@ pub async fn parse_config_from_source(module: Vc<Box<dyn Module>>) -> Result<Vc<
                             .unwrap_or_default()
                         {
                             if let Some(init) = decl.init.as_ref() {
-                                return GLOBALS.set(globals, || {
+                                let parsed = GLOBALS.set(globals, || {
                                     let value = eval_context.eval(init);
-                                    Ok(parse_config_from_js_value(module, &value).cell())
+                                    parse_config_from_js_value(module, &value)
                                 });
+                                config.runtime = parsed.runtime;
+                                config.matcher = parsed.matcher;
                             } else {
                                 NextSourceConfigParsingIssue {
                                     ident: module.ident(),
@@ -276,8 +313,6 @@ pub async fn parse_config_from_source(module: Vc<Box<dyn Module>>) -> Result<Vc<
                                 // skipping eval and directly read the expr's value, as we know it
                                 // should be a const string
                                 if let Expr::Lit(Lit::Str(str_value)) = &**init {
-                                    let mut config = NextSourceConfig::default();
-
                                     let runtime = str_value.value.to_string();
                                     match runtime.as_str() {
                                         "edge" | "experimental-edge" => {
@@ -290,8 +325,6 @@ pub async fn parse_config_from_source(module: Vc<Box<dyn Module>>) -> Result<Vc<
                                             runtime_value_issue.emit();
                                         }
                                     }
-
-                                    return Ok(config.cell());
                                 } else {
                                     runtime_value_issue.emit();
                                 }
@@ -309,12 +342,82 @@ pub async fn parse_config_from_source(module: Vc<Box<dyn Module>>) -> Result<Vc<
                                 .emit()
                             }
                         }
+                        // Or, check if there is a route level `maxDuration`
+                        else if decl_ident
+                            .map(|ident| &*ident.sym == "maxDuration")
+                            .unwrap_or_default()
+                        {
+                            match decl.init.as_deref().and_then(parse_max_duration) {
+                                Some(max_duration) => config.max_duration = Some(max_duration),
+                                None => NextSourceConfigParsingIssue {
+                                    ident: module.ident(),
+                                    detail: StyledString::Text(
+                                        "The maxDuration property must be a non-negative integer \
+                                         number of seconds."
+                                            .to_string(),
+                                    )
+                                    .cell(),
+                                }
+                                .cell()
+                                .emit(),
+                            }
+                        }
+                        // Or, check if there is a route level `preferredRegion`
+                        else if decl_ident
+                            .map(|ident| &*ident.sym == "preferredRegion")
+                            .unwrap_or_default()
+                        {
+                            match decl.init.as_deref().and_then(parse_preferred_region) {
+                                Some(regions) => config.preferred_region = Some(regions),
+                                None => NextSourceConfigParsingIssue {
+                                    ident: module.ident(),
+                                    detail: StyledString::Text(
+                                        "The preferredRegion property must be a string or an \
+                                         array of strings."
+                                            .to_string(),
+                                    )
+                                    .cell(),
+                                }
+                                .cell()
+                                .emit(),
+                            }
+                        }
                     }
                 }
             }
         }
     }
-    Ok(Default::default())
+    Ok(config.cell())
+}
+
+/// Reads a `maxDuration` initializer, which must be a non-negative integer
+/// literal.
+fn parse_max_duration(init: &Expr) -> Option<u32> {
+    let Expr::Lit(Lit::Num(number)) = init else {
+        return None;
+    };
+    let value = number.value;
+    (value >= 0.0 && value.fract() == 0.0 && value <= u32::MAX as f64).then_some(value as u32)
+}
+
+/// Reads a `preferredRegion` initializer, which must be a string literal or
+/// an array of them.
+fn parse_preferred_region(init: &Expr) -> Option<Vec<String>> {
+    match init {
+        Expr::Lit(Lit::Str(region)) => Some(vec![region.value.to_string()]),
+        Expr::Array(array) => array
+            .elems
+            .iter()
+            .map(|elem| match elem {
+                Some(ExprOrSpread { spread: None, expr }) => match &**expr {
+                    Expr::Lit(Lit::Str(region)) => Some(region.value.to_string()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
 }
 
 fn parse_config_from_js_value(module: Vc<Box<dyn Module>>, value: &JsValue) -> NextSourceConfig {
@@ -739,3 +842,159 @@ pub async fn render_data(
     })?;
     Ok(Vc::cell(value))
 }
+
+/// Finds the `name` directory (e.g. `app` or `pages`) in the project root, or
+/// in `src` when the project root doesn't have it.
+///
+/// Having both is ambiguous and reported as an error. The directory in the
+/// project root is used in that case, like Next.js does.
+#[turbo_tasks::function]
+pub async fn find_source_dir(
+    project_path: Vc<FileSystemPath>,
+    name: String,
+) -> Result<Vc<FileSystemPathOption>> {
+    let root_dir = project_path.join(name.clone());
+    let src_dir = project_path.join(format!("src/{name}"));
+    let root_exists = *root_dir.get_type().await? == FileSystemEntryType::Directory;
+    let src_exists = *src_dir.get_type().await? == FileSystemEntryType::Directory;
+
+    if root_exists && src_exists {
+        AmbiguousSourceDirIssue {
+            project_path,
+            name: name.clone(),
+        }
+        .cell()
+        .emit();
+    }
+
+    let dir = if root_exists {
+        Some(root_dir.resolve().await?)
+    } else if src_exists {
+        Some(src_dir.resolve().await?)
+    } else {
+        None
+    };
+    Ok(Vc::cell(dir))
+}
+
+#[turbo_tasks::value(shared)]
+struct AmbiguousSourceDirIssue {
+    project_path: Vc<FileSystemPath>,
+    name: String,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for AmbiguousSourceDirIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> Vc<IssueSeverity> {
+        IssueSeverity::Error.into()
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> Vc<StyledString> {
+        StyledString::Line(vec![
+            StyledString::Text("Both ".to_string()),
+            StyledString::Code(self.name.clone()),
+            StyledString::Text(" and ".to_string()),
+            StyledString::Code(format!("src/{}", self.name)),
+            StyledString::Text(" directories exist".to_string()),
+        ])
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> Vc<String> {
+        Vc::cell("project".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn file_path(&self) -> Vc<FileSystemPath> {
+        self.project_path
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> Vc<OptionStyledString> {
+        Vc::cell(Some(
+            StyledString::Text(format!(
+                "Only the `{name}` directory in the project root is used, and `src/{name}` is \
+                 ignored. Move all routes into one of them.",
+                name = self.name
+            ))
+            .cell(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use turbo_tasks::Vc;
+
+    use super::find_source_dir;
+    use crate::{
+        app_structure::{find_app_dir, get_entrypoints},
+        test_utils::{fixture, fixture_root, issues, run, styled_text},
+    };
+
+    #[tokio::test]
+    async fn discovers_routes_in_src_app() {
+        let dir = fixture(&[
+            ("src/app/layout.js", "export default function Layout() {}"),
+            ("src/app/page.js", "export default function Page() {}"),
+            ("src/app/blog/page.js", "export default function Blog() {}"),
+            // The default not-found page comes from Next.js.
+            ("node_modules/next/package.json", r#"{"name": "next"}"#),
+        ]);
+        let root = dir.path().to_owned();
+        let (app_dir, routes, issue_count) = run(async move {
+            let project_path = fixture_root(&root);
+            let app_dir = find_app_dir(project_path);
+            let Some(app_dir_path) = *app_dir.await? else {
+                anyhow::bail!("the app directory wasn't found");
+            };
+            let entrypoints = get_entrypoints(app_dir_path, Vc::cell(vec!["js".to_string()]));
+            let mut routes: Vec<_> = entrypoints
+                .await?
+                .keys()
+                .map(|path| path.to_string())
+                .collect();
+            routes.sort();
+            Ok((
+                app_dir_path.await?.path.clone(),
+                routes,
+                issues(app_dir).await?.len(),
+            ))
+        })
+        .await
+        .unwrap();
+        assert_eq!(app_dir, "src/app");
+        assert_eq!(routes, ["/", "/_not-found", "/blog"]);
+        assert_eq!(issue_count, 0);
+    }
+
+    #[tokio::test]
+    async fn reports_app_and_src_app() {
+        let dir = fixture(&[
+            ("app/page.js", "export default function Page() {}"),
+            ("src/app/page.js", "export default function Page() {}"),
+        ]);
+        let root = dir.path().to_owned();
+        let (source_dir, titles) = run(async move {
+            let source_dir = find_source_dir(fixture_root(&root), "app".to_string());
+            let Some(source_dir_path) = *source_dir.await? else {
+                anyhow::bail!("the app directory wasn't found");
+            };
+            Ok((
+                source_dir_path.await?.path.clone(),
+                issues(source_dir)
+                    .await?
+                    .iter()
+                    .map(|issue| styled_text(&issue.title))
+                    .collect::<Vec<_>>(),
+            ))
+        })
+        .await
+        .unwrap();
+        assert_eq!(source_dir, "app");
+        assert_eq!(titles, ["Both app and src/app directories exist"]);
+    }
+}
@@ -0,0 +1,269 @@
+use anyhow::Result;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::{trace::TraceRawVcs, TaskInput, Vc};
+use turbopack_binding::{
+    turbo::tasks_fs::{FileContent, FileSystemPath},
+    turbopack::{
+        core::{
+            asset::AssetContent,
+            resolve::{
+                options::{ImportMap, ImportMapResult, ImportMapping, ImportMappingReplacement},
+                parse::Request,
+                ResolveResult,
+            },
+            virtual_source::VirtualSource,
+        },
+        turbopack::resolve_options_context::ResolveOptionsContext,
+    },
+};
+
+/// The JavaScript source of a module generated before the build.
+#[derive(Clone, Debug, PartialEq, Eq, TaskInput, TraceRawVcs, Serialize, Deserialize)]
+pub enum VirtualModuleSource {
+    /// The source itself. It only changes with the build options.
+    Source(String),
+    /// A file the generator writes the source to, relative to the project
+    /// directory. The module is invalidated whenever the file changes, e.g.
+    /// when the generator runs again because its inputs changed.
+    File(String),
+}
+
+/// The modules generated before the build, e.g. a registry of the routes,
+/// keyed by the specifier they're imported with, like `virtual:routes`.
+#[turbo_tasks::value(transparent)]
+pub struct VirtualModules(IndexMap<String, VirtualModuleSource>);
+
+#[turbo_tasks::value_impl]
+impl VirtualModules {
+    #[turbo_tasks::function]
+    pub fn empty() -> Vc<Self> {
+        Vc::cell(IndexMap::new())
+    }
+}
+
+/// Makes the `virtual_modules` resolvable in `resolve_options_context`. They're
+/// added to the fallback import map, so they're only consulted for specifiers
+/// that don't resolve otherwise and can't shadow a package.
+///
+/// A virtual module is an ordinary module at `__virtual__/` in the project
+/// root, so its own imports are resolved from there and it's part of the
+/// module graph. It changes whenever its source does, see
+/// [VirtualModuleSource].
+#[turbo_tasks::function]
+pub async fn with_virtual_modules(
+    resolve_options_context: Vc<ResolveOptionsContext>,
+    project_path: Vc<FileSystemPath>,
+    virtual_modules: Vc<VirtualModules>,
+) -> Result<Vc<ResolveOptionsContext>> {
+    let virtual_modules_ref = virtual_modules.await?;
+    if virtual_modules_ref.is_empty() {
+        return Ok(resolve_options_context);
+    }
+
+    let mut context = resolve_options_context.await?.clone_value();
+
+    let mut fallback_import_map = match context.fallback_import_map {
+        Some(import_map) => import_map.await?.clone_value(),
+        None => ImportMap::empty(),
+    };
+    for (specifier, source) in virtual_modules_ref.iter() {
+        fallback_import_map.insert_exact_alias(
+            specifier,
+            ImportMapping::Dynamic(Vc::upcast(VirtualModuleReplacer::new(
+                project_path,
+                specifier.clone(),
+                source.clone(),
+            )))
+            .into(),
+        );
+    }
+    context.fallback_import_map = Some(fallback_import_map.cell());
+
+    // Dependencies in `node_modules` are resolved with the contexts of the
+    // rules, and may import virtual modules too.
+    for (_, rule_context) in context.rules.iter_mut() {
+        *rule_context = with_virtual_modules(*rule_context, project_path, virtual_modules);
+    }
+
+    Ok(context.cell())
+}
+
+#[turbo_tasks::value(shared)]
+struct VirtualModuleReplacer {
+    project_path: Vc<FileSystemPath>,
+    specifier: String,
+    source: VirtualModuleSource,
+}
+
+#[turbo_tasks::value_impl]
+impl VirtualModuleReplacer {
+    #[turbo_tasks::function]
+    fn new(
+        project_path: Vc<FileSystemPath>,
+        specifier: String,
+        source: VirtualModuleSource,
+    ) -> Vc<Self> {
+        Self::cell(VirtualModuleReplacer {
+            project_path,
+            specifier,
+            source,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for VirtualModuleReplacer {
+    #[turbo_tasks::function]
+    fn replace(&self, _capture: String) -> Vc<ImportMapping> {
+        ImportMapping::Ignore.into()
+    }
+
+    #[turbo_tasks::function]
+    fn result(&self, _context: Vc<FileSystemPath>, _request: Vc<Request>) -> Vc<ImportMapResult> {
+        let source = VirtualSource::new(
+            self.project_path.join(format!(
+                "__virtual__/{}.js",
+                self.specifier.replace([':', '/', '\\'], "_")
+            )),
+            AssetContent::file(match &self.source {
+                VirtualModuleSource::Source(source) => {
+                    FileContent::Content(source.clone().into()).cell()
+                }
+                // Reading the file makes the module depend on it.
+                VirtualModuleSource::File(path) => self.project_path.join(path.clone()).read(),
+            }),
+        );
+        ImportMapResult::Result(ResolveResult::source(Vc::upcast(source)).into()).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use anyhow::{Context, Result};
+    use indexmap::indexmap;
+    use turbo_tasks::{Value, Vc};
+    use turbopack_binding::{
+        turbo::tasks_fs::{DiskFileSystem, FileContent, FileSystem, FileSystemPath},
+        turbopack::{
+            core::{
+                asset::Asset,
+                reference_type::{EcmaScriptModulesReferenceSubType, ReferenceType},
+                resolve::{parse::Request, pattern::Pattern, resolve},
+                source::Source,
+            },
+            turbopack::{resolve_options, resolve_options_context::ResolveOptionsContext},
+        },
+    };
+
+    use super::{with_virtual_modules, VirtualModuleSource, VirtualModules};
+    use crate::test_utils::{fixture, fixture_root, run};
+
+    fn routes_fixture() -> tempfile::TempDir {
+        fixture(&[
+            ("app/page.js", "import routes from 'virtual:routes';"),
+            ("generated/routes.js", "export default ['/'];"),
+        ])
+    }
+
+    /// Resolves `specifier` from `app/page.js` and returns the path and the
+    /// content of the module it resolves to.
+    async fn resolve_virtual_module(
+        project_path: Vc<FileSystemPath>,
+        specifier: &str,
+    ) -> Result<(String, String)> {
+        let virtual_modules = Vc::<VirtualModules>::cell(indexmap! {
+            "virtual:config".to_string() =>
+                VirtualModuleSource::Source("export default {};".to_string()),
+            "virtual:routes".to_string() =>
+                VirtualModuleSource::File("generated/routes.js".to_string()),
+        });
+        let context = with_virtual_modules(
+            ResolveOptionsContext::default().cell(),
+            project_path,
+            virtual_modules,
+        );
+        let lookup_path = project_path.join("app".to_string());
+        let source = resolve(
+            lookup_path,
+            Value::new(ReferenceType::EcmaScriptModules(
+                EcmaScriptModulesReferenceSubType::Undefined,
+            )),
+            Request::parse(Value::new(Pattern::Constant(specifier.to_string()))),
+            resolve_options(lookup_path, context),
+        )
+        .first_source()
+        .strongly_consistent()
+        .await?
+        .context("the virtual module didn't resolve")?;
+
+        let path = source.ident().path().await?.path.clone();
+        let FileContent::Content(file) = &*source
+            .content()
+            .file_content()
+            .strongly_consistent()
+            .await?
+        else {
+            anyhow::bail!("the virtual module has no content");
+        };
+        Ok((path, file.content().to_str()?.into_owned()))
+    }
+
+    #[tokio::test]
+    async fn resolves_virtual_modules() {
+        let dir = routes_fixture();
+        let root = dir.path().to_owned();
+        let (config, routes) = run(async move {
+            let project_path = fixture_root(&root);
+            Ok((
+                resolve_virtual_module(project_path, "virtual:config").await?,
+                resolve_virtual_module(project_path, "virtual:routes").await?,
+            ))
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            config,
+            (
+                "__virtual__/virtual_config.js".to_string(),
+                "export default {};".to_string()
+            )
+        );
+        assert_eq!(
+            routes,
+            (
+                "__virtual__/virtual_routes.js".to_string(),
+                "export default ['/'];".to_string()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn invalidates_generated_virtual_modules() {
+        let dir = routes_fixture();
+        let root = dir.path().to_owned();
+        let generated_path = dir.path().join("generated/routes.js");
+        let content = run(async move {
+            let fs = DiskFileSystem::new("project".to_string(), root.to_str().unwrap().to_string());
+            fs.await?.start_watching_with_invalidation_reason()?;
+            let project_path = fs.root();
+            resolve_virtual_module(project_path, "virtual:routes").await?;
+
+            // The generator runs again, e.g. because a route was added.
+            std::fs::write(&generated_path, "export default ['/', '/blog'];")?;
+            for _ in 0..50 {
+                let (_, content) = resolve_virtual_module(project_path, "virtual:routes").await?;
+                if content.contains("/blog") {
+                    return Ok(content);
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            anyhow::bail!("the regenerated virtual module wasn't picked up")
+        })
+        .await
+        .unwrap();
+        assert_eq!(content, "export default ['/', '/blog'];");
+    }
+}
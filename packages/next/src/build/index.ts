@@ -159,6 +159,7 @@ import { nodeFs } from '../server/lib/node-fs-methods'
 import { collectBuildTraces } from './collect-build-traces'
 import type { BuildTraceContext } from './webpack/plugins/next-trace-entrypoints-plugin'
 import { formatManifest } from './manifests/formatter/format-manifest'
+import { addPrerenderEtags } from './prerender-etags'
 import { getStartServerInfo, logStartInfo } from '../server/lib/app-info-log'
 import type { NextEnabledDirectories } from '../server/base-server'
 import { hasCustomExportOutput } from '../export/utils'
@@ -184,6 +185,10 @@ export interface SsgRoute
   srcRoute: string | null
   initialStatus?: number
   initialHeaders?: Record<string, string>
+  /** The ETag of the prerendered HTML, with `generateEtags`. */
+  initialEtag?: string
+  /** The ETag of the prerendered data, with `generateEtags`. */
+  initialDataEtag?: string
 }
 
 export interface DynamicSsgRoute
@@ -2871,6 +2876,14 @@ export default async function build(
             prefetchDataRouteRegex: undefined,
           }
         })
+
+        if (config.generateEtags) {
+          await addPrerenderEtags(
+            finalPrerenderRoutes,
+            path.join(distDir, SERVER_DIRECTORY)
+          )
+        }
+
         const prerenderManifest: Readonly<PrerenderManifest> = {
           version: 4,
           routes: finalPrerenderRoutes,
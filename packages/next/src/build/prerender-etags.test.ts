@@ -0,0 +1,46 @@
+import { mkdir, mkdtemp, writeFile } from 'fs/promises'
+import { join } from 'path'
+import { tmpdir } from 'os'
+import { generateETag } from '../server/lib/etag'
+import { addPrerenderEtags } from './prerender-etags'
+import type { SsgRoute } from './index'
+
+function ssgRoute(dataRoute: string | null): SsgRoute {
+  return {
+    initialRevalidateSeconds: false,
+    srcRoute: null,
+    dataRoute,
+    prefetchDataRoute: undefined,
+    experimentalPPR: undefined,
+  }
+}
+
+describe('addPrerenderEtags', () => {
+  it('records deterministic ETags of the prerendered files', async () => {
+    const serverDir = await mkdtemp(join(tmpdir(), 'prerender-etags'))
+    await mkdir(join(serverDir, 'pages'))
+    await mkdir(join(serverDir, 'app', 'blog'), { recursive: true })
+    await writeFile(join(serverDir, 'pages', 'index.html'), '<p>home</p>')
+    await writeFile(join(serverDir, 'pages', 'index.json'), '{"pageProps":{}}')
+    await writeFile(join(serverDir, 'app', 'blog', 'a.html'), '<p>a</p>')
+    await writeFile(join(serverDir, 'app', 'blog', 'a.rsc'), '0:["a"]')
+
+    const build = async () => {
+      const routes = {
+        '/': ssgRoute('/_next/data/build-id/index.json'),
+        '/blog/a': ssgRoute('/blog/a.rsc'),
+        '/api/feed': ssgRoute(null),
+      }
+      await addPrerenderEtags(routes, serverDir)
+      return routes
+    }
+
+    const routes = await build()
+    expect(routes['/'].initialEtag).toBe(generateETag('<p>home</p>'))
+    expect(routes['/'].initialDataEtag).toBe(generateETag('{"pageProps":{}}'))
+    expect(routes['/blog/a'].initialEtag).toBe(generateETag('<p>a</p>'))
+    expect(routes['/blog/a'].initialDataEtag).toBe(generateETag('0:["a"]'))
+    expect(routes['/api/feed'].initialEtag).toBeUndefined()
+    expect(await build()).toEqual(routes)
+  })
+})
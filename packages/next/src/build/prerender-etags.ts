@@ -0,0 +1,49 @@
+import path from 'path'
+import { promises as fs } from 'fs'
+import { RSC_SUFFIX } from '../lib/constants'
+import { generateETag } from '../server/lib/etag'
+import { normalizePagePath } from '../shared/lib/page-path/normalize-page-path'
+import type { SsgRoute } from './index'
+
+async function readEtag(filePath: string): Promise<string | undefined> {
+  try {
+    return generateETag(await fs.readFile(filePath, 'utf8'))
+  } catch (err: any) {
+    if (err.code === 'ENOENT') return undefined
+    throw err
+  }
+}
+
+/**
+ * Records the ETag of the prerendered HTML and data of each route, so the
+ * server can answer conditional requests for them without hashing the files
+ * again. Routes without prerendered HTML, like app route handlers, are left
+ * as is.
+ */
+export async function addPrerenderEtags(
+  routes: { [route: string]: SsgRoute },
+  serverDir: string
+): Promise<void> {
+  await Promise.all(
+    Object.entries(routes).map(async ([route, ssgRoute]) => {
+      // App routes have RSC payloads as their data, or no data for route
+      // handlers, pages have JSON.
+      const isAppRoute =
+        ssgRoute.dataRoute === null || ssgRoute.dataRoute.endsWith(RSC_SUFFIX)
+      const basePath = path.join(
+        serverDir,
+        isAppRoute ? 'app' : 'pages',
+        normalizePagePath(route)
+      )
+
+      const initialEtag = await readEtag(`${basePath}.html`)
+      if (!initialEtag) return
+      ssgRoute.initialEtag = initialEtag
+      if (ssgRoute.dataRoute !== null) {
+        ssgRoute.initialDataEtag = await readEtag(
+          `${basePath}${isAppRoute ? RSC_SUFFIX : '.json'}`
+        )
+      }
+    })
+  )
+}
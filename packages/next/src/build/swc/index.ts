@@ -7,9 +7,12 @@ import * as Log from '../output/log'
 import { getParserOptions } from './options'
 import { eventSwcLoadFailure } from '../../telemetry/events/swc-load-failure'
 import { patchIncorrectLockfile } from '../../lib/patch-incorrect-lockfile'
+import isError from '../../lib/is-error'
+import { RSC_HEADER } from '../../client/components/app-router-headers'
 import { downloadWasmSwc, downloadNativeNextSwc } from '../../lib/download-swc'
 import type { NextConfigComplete, TurboRule } from '../../server/config-shared'
 import { isDeepStrictEqual } from 'util'
+import { Readable } from 'stream'
 import { getDefineEnv } from '../webpack/plugins/define-env-plugin'
 import type { DefineEnvPluginOptions } from '../webpack/plugins/define-env-plugin'
 
@@ -379,6 +382,12 @@ export interface ProjectOptions {
    */
   projectPath: string
 
+  /**
+   * The directory containing the app/pages directories, relative to
+   * projectPath, when they aren't directly inside it.
+   */
+  routesDir?: string
+
   /**
    * The next.config.js contents.
    */
@@ -395,6 +404,12 @@ export interface ProjectOptions {
     compilerOptions: object
   }
 
+  /**
+   * The tsconfig to compile with, relative to projectPath, e.g.
+   * `tsconfig.build.json`. Overrides `typescript.tsconfigPath`.
+   */
+  tsconfigPath?: string
+
   /**
    * A map of environment variables to use when compiling code.
    */
@@ -411,6 +426,13 @@ export interface ProjectOptions {
    * The address of the dev server.
    */
   serverAddr: string
+
+  /**
+   * In-memory source files, from their path relative to rootPath to their
+   * content. They shadow the files at the same paths on disk, e.g. for tests
+   * defining a page in code.
+   */
+  overlayFiles?: Record<string, string>
 }
 
 type RustifiedEnv = { name: string; value: string }[]
@@ -516,6 +538,16 @@ export interface Issue {
   subIssues: Issue[]
 }
 
+export interface EndpointError {
+  issue: Issue
+  /**
+   * Where the error originates in its original source, with 1-indexed line
+   * and column. Its code frame is rendered like the one of a traced runtime
+   * frame, see `createOriginalSourceFrame` of the dev overlay.
+   */
+  sourceFrame?: TurbopackStackFrame
+}
+
 export interface Diagnostics {
   category: string
   name: string
@@ -536,6 +568,27 @@ export interface Instrumentation {
   edge: Endpoint
 }
 
+export interface MiddlewareChunks {
+  /** The JavaScript chunks, relative to the dist directory, in load order. */
+  files: string[]
+  /** The wasm files to bind as globals before running the middleware. */
+  wasm: { name: string; filePath: string }[]
+}
+
+export interface AppIcon {
+  kind: 'favicon' | 'icon' | 'apple-icon'
+  /** The url the HTML head references the icon with. */
+  url: string
+  /** The endpoint generating the icon, for dynamic icons like `app/icon.tsx`. */
+  endpoint?: Endpoint
+}
+
+export interface RouteSource {
+  pathname: string
+  /** The file serving the route, relative to the project path. */
+  filePath: string
+}
+
 export interface Entrypoints {
   routes: Map<string, Route>
   middleware?: Middleware
@@ -553,6 +606,23 @@ export interface HmrIdentifiers {
   identifiers: string[]
 }
 
+/**
+ * An event of `rscHmrEvents()`, with the new flight payload the client
+ * re-renders the page with, without a full reload.
+ */
+export interface RscRefresh {
+  /**
+   * The app page whose server components changed.
+   */
+  pathname: string
+  /**
+   * The new RSC payload of the page. Rendering it needs a request, so it's
+   * requested from the dev server at `serverAddr` with the `RSC: 1` header,
+   * like a router refresh.
+   */
+  flightPayload: string
+}
+
 interface TurbopackStackFrame {
   column: number | null
   file: string
@@ -566,10 +636,22 @@ export interface UpdateInfo {
   tasks: number
 }
 
+export interface CacheStats {
+  executedTasks: number
+  cachedTasks: number
+}
+
 export interface Project {
   update(options: Partial<ProjectOptions>): Promise<void>
   entrypointsSubscribe(): AsyncIterableIterator<TurbopackResult<Entrypoints>>
   hmrEvents(identifier: string): AsyncIterableIterator<TurbopackResult<Update>>
+  /**
+   * Yields the new RSC payload of the app page `pathname` when its server
+   * components change, see `RscRefresh`.
+   */
+  rscHmrEvents(
+    pathname: string
+  ): AsyncIterableIterator<TurbopackResult<RscRefresh>>
   hmrIdentifiersSubscribe(): AsyncIterableIterator<
     TurbopackResult<HmrIdentifiers>
   >
@@ -578,6 +660,13 @@ export interface Project {
     stackFrame: TurbopackStackFrame
   ): Promise<TurbopackStackFrame | null>
   updateInfoSubscribe(): AsyncIterableIterator<TurbopackResult<UpdateInfo>>
+  cacheStats(): Promise<CacheStats>
+  validate(): Promise<Issue[]>
+  middlewareChunks(): Promise<
+    TurbopackResult<{ middleware?: MiddlewareChunks | null }>
+  >
+  appIcons(): Promise<TurbopackResult<{ icons: AppIcon[] }>>
+  routeSources(): Promise<TurbopackResult<{ routes: RouteSource[] }>>
 }
 
 export type Route =
@@ -606,6 +695,25 @@ export type Route =
 export interface Endpoint {
   /** Write files for the endpoint to disk. */
   writeToDisk(): Promise<TurbopackResult<WrittenEndpoint>>
+  /**
+   * Like `writeToDisk()`, but re-runs the endpoint's own write task even if
+   * nothing changed. The invalidation is shallow: the chunking and the file
+   * writes it depends on are reused from the cache when their inputs are
+   * unchanged, so up to date files aren't rewritten.
+   */
+  writeToDiskUncached(): Promise<TurbopackResult<WrittenEndpoint>>
+  /**
+   * The files `writeToDisk()` writes, relative to the dist directory, without
+   * writing them.
+   */
+  chunks(): Promise<TurbopackResult<string[]>>
+  /**
+   * The concatenated server JavaScript of the endpoint, with the entry chunks
+   * last, without writing it to disk. The stream can be piped, e.g. to stdout.
+   */
+  serverOutput(): Promise<TurbopackResult<{ content: Readable }>>
+  /** The current compilation errors of the endpoint, for the error overlay. */
+  errors(): Promise<EndpointError[]>
   /**
    * Listen to client-side changes to the endpoint.
    * After clientChanged() has been awaited it will listen to changes.
@@ -636,6 +744,8 @@ interface EndpointConfig {
     | 'force-no-store'
   runtime?: 'nodejs' | 'edge'
   preferredRegion?: string
+  /** The timeout of the function in seconds, from `export const maxDuration`. */
+  maxDuration?: number
 }
 
 export type ServerPath = {
@@ -776,17 +886,44 @@ function bindingToApi(binding: any, _wasm: boolean) {
       jsConfig: options.jsConfig && JSON.stringify(options.jsConfig),
       env: options.env && rustifyEnv(options.env),
       defineEnv: options.defineEnv,
+      overlayFiles:
+        options.overlayFiles &&
+        Object.entries(options.overlayFiles).map(([path, content]) => ({
+          path,
+          content,
+        })),
+    }
+  }
+
+  /**
+   * Requests the RSC payload of the app page `pathname` from the dev server
+   * at `serverAddr`, like a router refresh.
+   */
+  async function fetchFlightPayload(serverAddr: string, pathname: string) {
+    const res = await fetch(`http://${serverAddr}${pathname}`, {
+      headers: { [RSC_HEADER]: '1' },
+    })
+    if (!res.ok) {
+      throw new Error(
+        `Failed to fetch the RSC payload of ${pathname}: ${res.status} ${res.statusText}`
+      )
     }
+    return res.text()
   }
 
   class ProjectImpl implements Project {
     private _nativeProject: { __napiType: 'Project' }
+    private _serverAddr: string
 
-    constructor(nativeProject: { __napiType: 'Project' }) {
+    constructor(nativeProject: { __napiType: 'Project' }, serverAddr: string) {
       this._nativeProject = nativeProject
+      this._serverAddr = serverAddr
     }
 
-    async update(options: ProjectOptions) {
+    async update(options: Partial<ProjectOptions>) {
+      if (options.serverAddr !== undefined) {
+        this._serverAddr = options.serverAddr
+      }
       await withErrorCause(async () =>
         binding.projectUpdate(
           this._nativeProject,
@@ -940,6 +1077,26 @@ function bindingToApi(binding: any, _wasm: boolean) {
       return subscription
     }
 
+    rscHmrEvents(pathname: string) {
+      const subscription = subscribe<
+        TurbopackResult<Omit<RscRefresh, 'flightPayload'>>
+      >(true, async (callback) =>
+        binding.projectRscHmrEvents(this._nativeProject, pathname, callback)
+      )
+      const serverAddr = () => this._serverAddr
+      return (async function* () {
+        for await (const event of subscription) {
+          yield {
+            ...event,
+            flightPayload: await fetchFlightPayload(
+              serverAddr(),
+              event.pathname
+            ),
+          }
+        }
+      })()
+    }
+
     hmrIdentifiersSubscribe() {
       const subscription = subscribe<TurbopackResult<HmrIdentifiers>>(
         false,
@@ -959,6 +1116,39 @@ function bindingToApi(binding: any, _wasm: boolean) {
       return binding.projectGetSourceForAsset(this._nativeProject, filePath)
     }
 
+    validate(): Promise<Issue[]> {
+      return binding.projectValidate(this._nativeProject)
+    }
+
+    middlewareChunks(): Promise<
+      TurbopackResult<{ middleware?: MiddlewareChunks | null }>
+    > {
+      return binding.projectMiddlewareChunks(this._nativeProject)
+    }
+
+    routeSources(): Promise<TurbopackResult<{ routes: RouteSource[] }>> {
+      return binding.projectRouteSources(this._nativeProject)
+    }
+
+    async appIcons(): Promise<TurbopackResult<{ icons: AppIcon[] }>> {
+      type NapiAppIcon = {
+        kind: AppIcon['kind']
+        url: string
+        endpoint?: { __napiType: 'Endpoint' }
+      }
+      const result = (await binding.projectAppIcons(
+        this._nativeProject
+      )) as TurbopackResult<{ icons: NapiAppIcon[] }>
+      return {
+        ...result,
+        icons: result.icons.map((icon) => ({
+          kind: icon.kind,
+          url: icon.url,
+          endpoint: icon.endpoint ? new EndpointImpl(icon.endpoint) : undefined,
+        })),
+      }
+    }
+
     updateInfoSubscribe() {
       const subscription = subscribe<TurbopackResult<UpdateInfo>>(
         true,
@@ -967,6 +1157,10 @@ function bindingToApi(binding: any, _wasm: boolean) {
       )
       return subscription
     }
+
+    cacheStats(): Promise<CacheStats> {
+      return binding.projectCacheStats(this._nativeProject)
+    }
   }
 
   class EndpointImpl implements Endpoint {
@@ -982,6 +1176,34 @@ function bindingToApi(binding: any, _wasm: boolean) {
       )
     }
 
+    async writeToDiskUncached(): Promise<TurbopackResult<WrittenEndpoint>> {
+      return await withErrorCause(() =>
+        binding.endpointWriteToDiskUncached(this._nativeEndpoint)
+      )
+    }
+
+    async chunks(): Promise<TurbopackResult<string[]>> {
+      return await withErrorCause(() =>
+        binding.endpointChunks(this._nativeEndpoint)
+      )
+    }
+
+    async serverOutput(): Promise<TurbopackResult<{ content: Readable }>> {
+      const { chunks, ...result } = await withErrorCause(
+        () =>
+          binding.endpointServerOutput(this._nativeEndpoint) as Promise<
+            TurbopackResult<{ chunks: Buffer[] }>
+          >
+      )
+      return { ...result, content: Readable.from(chunks) }
+    }
+
+    async errors(): Promise<EndpointError[]> {
+      return await withErrorCause(() =>
+        binding.endpointErrors(this._nativeEndpoint)
+      )
+    }
+
     async clientChanged(): Promise<AsyncIterableIterator<TurbopackResult<{}>>> {
       const clientSubscription = subscribe<TurbopackResult>(
         false,
@@ -1020,6 +1242,9 @@ function bindingToApi(binding: any, _wasm: boolean) {
     nextConfigSerializable.generateBuildId =
       await nextConfig.generateBuildId?.()
 
+    nextConfigSerializable.generateRoutes =
+      await nextConfig.generateRoutes?.()
+
     // TODO: these functions takes arguments, have to be supported in a different way
     nextConfigSerializable.exportPathMap = {}
     nextConfigSerializable.webpack = nextConfig.webpack && {}
@@ -1078,7 +1303,8 @@ function bindingToApi(binding: any, _wasm: boolean) {
       await binding.projectNew(
         await rustifyProjectOptions(options),
         turboEngineOptions || {}
-      )
+      ),
+      options.serverAddr
     )
   }
 
@@ -1321,11 +1547,49 @@ function loadNative(importPath?: string) {
       teardownHeapProfiler: bindings.teardownHeapProfiler,
       teardownCrashReporter: bindings.teardownCrashReporter,
       turbo: {
-        nextBuild: (options: unknown) => {
+        nextBuild: (
+          options: unknown,
+          logger?: (log: {
+            phase?: string
+            level: string
+            message: string
+          }) => void,
+          manifestTransform?: (name: string, json: string) => string
+        ) => {
           initHeapProfiler()
-          const ret = (customBindings ?? bindings).nextBuild(options)
+          // Errors thrown by the callbacks would terminate the process when
+          // thrown into the native binding, so they fail the build instead.
+          let loggerError: unknown
+          const ret: Promise<void> = (customBindings ?? bindings).nextBuild(
+            options,
+            logger &&
+              ((_err: null, log: Parameters<typeof logger>[0]) => {
+                if (loggerError !== undefined) return
+                try {
+                  logger(log)
+                } catch (err) {
+                  loggerError = err
+                }
+              }),
+            manifestTransform &&
+              ((_err: null, name: string, json: string) => {
+                try {
+                  const transformed = manifestTransform(name, json)
+                  if (typeof transformed !== 'string') {
+                    return { error: `returned a ${typeof transformed}` }
+                  }
+                  return { json: transformed }
+                } catch (err) {
+                  return { error: isError(err) ? err.message : String(err) }
+                }
+              })
+          )
 
-          return ret
+          return ret.then(() => {
+            if (loggerError !== undefined) {
+              throw loggerError
+            }
+          })
         },
         startTrace: (options = {}, turboTasks: unknown) => {
           initHeapProfiler()
@@ -1,5 +1,9 @@
 import type { MetadataRoute } from '../../../../lib/metadata/types/metadata-interface'
-import { resolveRobots, resolveSitemap } from './resolve-route-data'
+import {
+  resolveRobots,
+  resolveSitemap,
+  resolveSitemapLocaleAlternates,
+} from './resolve-route-data'
 
 describe('resolveRouteData', () => {
   describe('resolveRobots', () => {
@@ -99,5 +103,37 @@ describe('resolveRouteData', () => {
         "
       `)
     })
+
+    it('should resolve alternates for each i18n locale', () => {
+      const i18n = { locales: ['en', 'fr'], defaultLocale: 'en' }
+      const data = resolveSitemapLocaleAlternates(
+        [
+          { url: 'https://example.com/' },
+          { url: 'https://example.com/about' },
+          { url: 'https://example.com/pricing', locales: ['en'] },
+        ],
+        i18n
+      )
+
+      expect(data.map((item) => item.alternates?.languages)).toEqual([
+        { en: 'https://example.com/', fr: 'https://example.com/fr' },
+        {
+          en: 'https://example.com/about',
+          fr: 'https://example.com/fr/about',
+        },
+        { en: 'https://example.com/pricing' },
+      ])
+      expect(resolveSitemap(data.slice(1, 2))).toMatchInlineSnapshot(`
+        "<?xml version="1.0" encoding="UTF-8"?>
+        <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9" xmlns:xhtml="http://www.w3.org/1999/xhtml">
+        <url>
+        <loc>https://example.com/about</loc>
+        <xhtml:link rel="alternate" hreflang="en" href="https://example.com/about" />
+        <xhtml:link rel="alternate" hreflang="fr" href="https://example.com/fr/about" />
+        </url>
+        </urlset>
+        "
+      `)
+    })
   })
 })
@@ -44,14 +44,29 @@ export function resolveRobots(data: MetadataRoute.Robots): string {
 // TODO-METADATA: support multi sitemap files
 // convert sitemap data to xml string
 export function resolveSitemap(data: MetadataRoute.Sitemap): string {
+  const hasAlternates = data.some(
+    (item) => Object.keys(item.alternates?.languages ?? {}).length > 0
+  )
+
   let content = ''
   content += '<?xml version="1.0" encoding="UTF-8"?>\n'
-  content += '<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">\n'
+  content += '<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"'
+  if (hasAlternates) {
+    content += ' xmlns:xhtml="http://www.w3.org/1999/xhtml"'
+  }
+  content += '>\n'
 
   for (const item of data) {
     content += '<url>\n'
     content += `<loc>${item.url}</loc>\n`
 
+    const languages = item.alternates?.languages
+    if (languages) {
+      for (const language in languages) {
+        content += `<xhtml:link rel="alternate" hreflang="${language}" href="${languages[language]}" />\n`
+      }
+    }
+
     if (item.lastModified) {
       const serializedDate =
         item.lastModified instanceof Date
@@ -77,6 +92,32 @@ export function resolveSitemap(data: MetadataRoute.Sitemap): string {
   return content
 }
 
+// add alternate-language urls for the i18n locales to every sitemap entry
+// that doesn't specify its own alternates. Entries can limit the locales they
+// are available in with `locales`.
+export function resolveSitemapLocaleAlternates(
+  data: MetadataRoute.Sitemap,
+  i18n: { locales: string[]; defaultLocale: string }
+): MetadataRoute.Sitemap {
+  return data.map(({ locales = i18n.locales, ...item }) => {
+    if (item.alternates?.languages) {
+      return item
+    }
+
+    const url = new URL(item.url)
+    const pathname = url.pathname === '/' ? '' : url.pathname
+    const languages: Record<string, string> = {}
+    for (const locale of locales) {
+      const localeUrl = new URL(url)
+      localeUrl.pathname =
+        locale === i18n.defaultLocale ? url.pathname : `/${locale}${pathname}`
+      languages[locale] = localeUrl.toString()
+    }
+
+    return { ...item, alternates: { ...item.alternates, languages } }
+  })
+}
+
 export function resolveManifest(data: MetadataRoute.Manifest): string {
   return JSON.stringify(data)
 }
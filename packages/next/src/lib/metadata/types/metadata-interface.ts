@@ -580,6 +580,14 @@ type SitemapFile = Array<{
     | 'yearly'
     | 'never'
   priority?: number
+  alternates?: {
+    languages?: Record<string, string>
+  }
+  /**
+   * The i18n locales this url is available in, used to generate `alternates`
+   * when i18n is configured. Defaults to all locales.
+   */
+  locales?: string[]
 }>
 
 type ResolvingMetadata = Promise<ResolvedMetadata>
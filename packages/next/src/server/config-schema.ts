@@ -317,8 +317,13 @@ export const configSchema: zod.ZodType<NextConfig> = z.lazy(() =>
             ])
           )
           .optional(),
+        clientTraceMetadata: z.array(z.string()).optional(),
+        cssModulesNamedExports: z.boolean().optional(),
         mdxRs: z.boolean().optional(),
         typedRoutes: z.boolean().optional(),
+        disabledTransforms: z.array(z.string()).optional(),
+        inlineCssThreshold: z.number().int().nonnegative().optional(),
+        edgeBundleSizeLimit: z.number().int().positive().optional(),
         webpackBuildWorker: z.boolean().optional(),
         turbo: z
           .object({
@@ -396,6 +401,16 @@ export const configSchema: zod.ZodType<NextConfig> = z.lazy(() =>
         ])
       )
       .optional(),
+    generateRoutes: z
+      .function()
+      .args()
+      .returns(
+        z.union([
+          z.record(z.string(), z.string()),
+          z.promise(z.record(z.string(), z.string())),
+        ])
+      )
+      .optional(),
     generateEtags: z.boolean().optional(),
     headers: z
       .function()
@@ -241,6 +241,19 @@ export interface ExperimentalConfig {
 
   webVitalsAttribution?: Array<(typeof WEB_VITALS)[number]>
 
+  /**
+   * The names of the `<meta>` tags with trace metadata, like `traceparent`,
+   * which are propagated from the server to the client.
+   */
+  clientTraceMetadata?: string[]
+
+  /**
+   * Whether the classes of CSS modules can be imported as named exports, like
+   * `import { button } from './button.module.css'`. Class names which aren't
+   * valid identifiers are imported camelCased. Only supported by Turbopack.
+   */
+  cssModulesNamedExports?: boolean
+
   /**
    * Automatically apply the "modularizeImports" optimization to imports of the specified packages.
    */
@@ -282,6 +295,25 @@ export interface ExperimentalConfig {
    */
   typedRoutes?: boolean
 
+  /**
+   * Default transforms Turbopack shouldn't apply, e.g. to use a Babel plugin
+   * instead. One of `styled-jsx`, `styled-components`, `emotion`, `relay`,
+   * `modularize-imports` or `mdx-frontmatter`.
+   */
+  disabledTransforms?: string[]
+
+  /**
+   * Routes with at most this many bytes of CSS get it inlined into their HTML
+   * instead of loading it with `<link>` tags.
+   */
+  inlineCssThreshold?: number
+
+  /**
+   * Edge functions larger than this many bytes get a build warning, as edge
+   * hosts cap the size of the functions they deploy.
+   */
+  edgeBundleSizeLimit?: number
+
   /**
    * Run the Webpack build in a separate process to optimize memory usage during build.
    * Valid values are:
@@ -483,6 +515,15 @@ export interface NextConfig extends Record<string, any> {
    */
   generateBuildId?: () => string | null | Promise<string | null>
 
+  /**
+   * Returns additional routes, from their pathname to the path of their
+   * handler module relative to the project, which are served like API routes.
+   * A generated route conflicting with a file-based route is an error.
+   */
+  generateRoutes?: () =>
+    | Record<string, string>
+    | Promise<Record<string, string>>
+
   /** @see [Disabling ETag Configuration](https://nextjs.org/docs/api-reference/next.config.js/disabling-etag-generation) */
   generateEtags?: boolean
 
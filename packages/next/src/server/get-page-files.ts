@@ -12,6 +12,8 @@ export type BuildManifest = {
     [page: string]: readonly string[]
   }
   ampFirstPages: readonly string[]
+  /** The client runtime chunks, in load order. Only written by Turbopack. */
+  runtimeFiles?: readonly string[]
 }
 
 export function getPageFiles(
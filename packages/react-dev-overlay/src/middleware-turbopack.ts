@@ -38,6 +38,14 @@ async function batchedTraceSource(
     return
   }
 
+  return withSource(project, sourceFrame, frame.methodName)
+}
+
+async function withSource(
+  project: Project,
+  sourceFrame: TurbopackStackFrame,
+  methodName: string | null
+) {
   let source
   // Don't show code frames for node_modules. These can also often be large bundled files.
   if (!sourceFrame.file.includes('node_modules')) {
@@ -60,7 +68,7 @@ async function batchedTraceSource(
       file: sourceFrame.file,
       lineNumber: sourceFrame.line,
       column: sourceFrame.column,
-      methodName: sourceFrame.methodName ?? frame.methodName ?? '<unknown>',
+      methodName: sourceFrame.methodName ?? methodName ?? '<unknown>',
       arguments: [],
     },
     source: source ?? null,
@@ -76,6 +84,25 @@ export async function createOriginalStackFrame(
     return null
   }
 
+  return toOriginalStackFrameResponse(traced)
+}
+
+/**
+ * Like `createOriginalStackFrame`, for a frame that already points into the
+ * original source, e.g. the source frame of a compilation error.
+ */
+export async function createOriginalSourceFrame(
+  project: Project,
+  frame: TurbopackStackFrame
+): Promise<OriginalStackFrameResponse> {
+  return toOriginalStackFrameResponse(
+    await withSource(project, frame, frame.methodName)
+  )
+}
+
+function toOriginalStackFrameResponse(
+  traced: Awaited<ReturnType<typeof withSource>>
+): OriginalStackFrameResponse {
   return {
     originalStackFrame: traced.frame,
     originalCodeFrame:
@@ -13,6 +13,8 @@ import {
   UpdateInfo,
 } from 'next/src/build/swc'
 import loadConfig from 'next/src/server/config'
+import http from 'http'
+import type { AddressInfo } from 'net'
 import path from 'path'
 
 function normalizePath(path: string) {
@@ -147,6 +149,7 @@ describe('next.rs api', () => {
           'pages/index.js': pagesIndexCode('hello world'),
           'lib/props.js': 'export default {}',
           'pages/page-nodejs.js': 'export default () => <div>hello world</div>',
+          'pages/blog/[slug].js': 'export default () => <div>hello world</div>',
           'pages/page-edge.js':
             'export default () => <div>hello world</div>\nexport const config = { runtime: "experimental-edge" }',
           'pages/api/nodejs.js':
@@ -227,6 +230,7 @@ describe('next.rs api', () => {
       '/app',
       '/app-edge',
       '/app-nodejs',
+      '/blog/[slug]',
       '/page-edge',
       '/page-nodejs',
       '/route-edge',
@@ -239,6 +243,21 @@ describe('next.rs api', () => {
     entrypointsSubscribtion.return()
   })
 
+  it('should map routes to their source files', async () => {
+    const { routes } = await project.routeSources()
+    const sources = Object.fromEntries(
+      routes.map(({ pathname, filePath }) => [pathname, filePath])
+    )
+    expect(sources).toMatchObject({
+      '/': 'pages/index.js',
+      '/page-nodejs': 'pages/page-nodejs.js',
+      '/blog/[slug]': 'pages/blog/[slug].js',
+      '/api/nodejs': 'pages/api/nodejs.js',
+      '/app': 'app/app/page.ts',
+      '/route-nodejs': 'app/route-nodejs/route.ts',
+    })
+  })
+
   const routes = [
     {
       name: 'root page',
@@ -585,6 +604,54 @@ describe('next.rs api', () => {
       })
   }
 
+  it('should carry the new RSC payload in RSC HMR events', async () => {
+    // Stands in for the dev server rendering the RSC payload.
+    const server = http.createServer((req, res) => {
+      if (req.headers['rsc'] !== '1') {
+        res.statusCode = 400
+        res.end()
+        return
+      }
+      res.setHeader('Content-Type', 'text/x-component')
+      res.end(`0:["${req.url}"]`)
+    })
+    await new Promise<void>((resolve) =>
+      server.listen(0, '127.0.0.1', resolve)
+    )
+    const { port } = server.address() as AddressInfo
+    await project.update({ serverAddr: `127.0.0.1:${port}` })
+
+    const file = 'app/app/page.ts'
+    const oldContent = await next.readFile(file)
+    try {
+      const entrypointsSubscribtion = project.entrypointsSubscribe()
+      const entrypoints: TurbopackResult<Entrypoints> = (
+        await entrypointsSubscribtion.next()
+      ).value
+      const route = entrypoints.routes.get('/app')
+      entrypointsSubscribtion.return()
+      if (route.type !== 'app-page') throw new Error('unknown route type')
+      await route.htmlEndpoint.writeToDisk()
+
+      const rscEvents = project.rscHmrEvents('/app')
+      // The first event is the current state of the page.
+      await rscEvents.next()
+      await next.patchFile(file, appPageCode('hello world2'))
+      const { value, done } = await rscEvents.next()
+      expect(done).toBe(false)
+      expect(value).toMatchObject({
+        pathname: '/app',
+        flightPayload: '0:["/app"]',
+        issues: [],
+      })
+      rscEvents.return()
+    } finally {
+      await next.patchFile(file, oldContent)
+      await project.update({ serverAddr: '127.0.0.1:3000' })
+      server.close()
+    }
+  })
+
   it.skip('should allow to make many HMR updates', async () => {
     console.log('start')
     await new Promise((r) => setTimeout(r, 1000))
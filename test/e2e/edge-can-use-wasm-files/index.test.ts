@@ -119,6 +119,56 @@ describe('middleware can use wasm files', () => {
   }
 })
 
+describe('middleware can import a wasm file from several modules', () => {
+  let next: NextInstance
+
+  beforeAll(async () => {
+    const config = baseNextConfig()
+    config.files['src/add-two.js'] = `
+      import wasm from './add.wasm?module'
+      import { increment } from './add.js'
+      const instance$ = WebAssembly.instantiate(wasm);
+
+      export async function incrementTwice(a) {
+        const { exports } = await instance$;
+        return exports.add_one(await increment(a));
+      }
+    `
+    config.files['middleware.js'] = `
+      import { incrementTwice } from './src/add-two.js'
+      export default async function middleware(request) {
+        const input = Number(request.nextUrl.searchParams.get('input')) || 1;
+        const value = await incrementTwice(input);
+        return new Response(null, { headers: { data: JSON.stringify({ input, value }) } });
+      }
+    `
+    next = await createNext(config)
+  })
+  afterAll(() => next.destroy())
+
+  it('uses the wasm file', async () => {
+    const response = await fetchViaHTTP(next.url, '/', { input: 3 })
+    expect(extractJSON(response)).toEqual({
+      input: 3,
+      value: 5,
+    })
+  })
+
+  if (!(global as any).isNextDeploy) {
+    it('emits the wasm file and binds it once', async () => {
+      const manifest = await fs.readJSON(
+        path.join(next.testDir, '.next/server/middleware-manifest.json')
+      )
+      const { wasm } = manifest.middleware['/']
+      expect(wasm).toHaveLength(1)
+      expect(wasm[0].name).toMatch(/^wasm_/)
+      expect(
+        await fs.pathExists(path.join(next.testDir, '.next', wasm[0].filePath))
+      ).toBe(true)
+    })
+  }
+})
+
 describe('middleware can use wasm files with the experimental modes on', () => {
   let next: NextInstance
 
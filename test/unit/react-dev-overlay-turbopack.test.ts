@@ -0,0 +1,32 @@
+/* eslint-env jest */
+import { createOriginalSourceFrame } from 'next/dist/compiled/@next/react-dev-overlay/dist/middleware-turbopack'
+
+describe('createOriginalSourceFrame', () => {
+  it('renders the code frame of an error in a shared module', async () => {
+    const project = {
+      getSourceForAsset: async (filePath: string) =>
+        filePath === 'lib/shared.js'
+          ? 'export function a() {}\nexport const b = ;\n'
+          : null,
+      traceSource: async () => {
+        throw new Error('source frames are not traced')
+      },
+    }
+
+    const { originalStackFrame, originalCodeFrame } =
+      await createOriginalSourceFrame(project, {
+        file: 'lib/shared.js',
+        line: 2,
+        column: 18,
+        methodName: null,
+        isServer: false,
+      })
+
+    expect(originalStackFrame).toMatchObject({
+      file: 'lib/shared.js',
+      lineNumber: 2,
+      column: 18,
+    })
+    expect(originalCodeFrame).toContain('export const b = ;')
+  })
+})